@@ -0,0 +1,391 @@
+use crate::{
+    circuit::Circuit,
+    dimacs::tokens,
+    parser::{numbered, ParseError},
+    types::{Clause, Formula, VarPool, Variable},
+};
+
+/// Parse a combinational and-inverter graph in the ASCII `aag` or binary `aig` AIGER format,
+/// converting it to a circuit in CNF.
+/// Inputs and outputs are named by the symbol table, or `i0`, `i1`, ... and `o0`, `o1`, ... otherwise,
+/// and the and gates are given auxiliary variables. Circuits with latches are not supported.
+///
+/// ## Examples
+/// ```plaintext
+/// aag 3 2 0 1 1
+/// 2
+/// 4
+/// 6
+/// 6 2 5
+/// i0 rain
+/// i1 roof
+/// o0 wet
+/// ```
+pub fn parse_aiger(input: &[u8], pool: &mut VarPool) -> Result<Circuit, ParseError> {
+    let mut reader = Reader { input, position: 0 };
+    let (offset, line) = reader.line(&["'aag'", "'aig'"])?;
+    let mut header = tokens(line, offset);
+    let binary = match header.next() {
+        Some((_, "aag")) => false,
+        Some((_, "aig")) => true,
+        Some((offset, token)) => return Err(ParseError::new(offset, token, &["'aag'", "'aig'"])),
+        None => return Err(ParseError::new(offset, "", &["'aag'", "'aig'"])),
+    };
+    let mut counts = Vec::new();
+    for (offset, token) in header {
+        match token.parse::<u64>() {
+            Ok(count) => counts.push((offset, token, count)),
+            Err(_) => return Err(ParseError::new(offset, token, &["a number"])),
+        }
+    }
+    if counts.len() < 5 {
+        return Err(ParseError::new(offset + line.len(), "", &["a number"]));
+    }
+    // Latches, and the bad state, constraint, justice and fairness properties of AIGER 1.9
+    for (offset, token, count) in [&counts[2..3], &counts[5..]].concat() {
+        if count > 0 {
+            return Err(ParseError::new(
+                offset,
+                token,
+                &["0 for a combinational circuit"],
+            ));
+        }
+    }
+    let [max, inputs, _, outputs, ands] = [0, 1, 2, 3, 4].map(|i| counts[i].2);
+    let bound = format!("a literal up to {}", 2 * max + 1);
+    let literal = |(offset, number): (usize, u64)| match number {
+        number if number > 2 * max + 1 => Err(ParseError::new(
+            offset,
+            &number.to_string(),
+            &[bound.as_str()],
+        )),
+        number => Ok((offset, number)),
+    };
+
+    let mut input_literals = Vec::new();
+    for i in 0..inputs {
+        let (offset, number) = if binary {
+            (reader.position, 2 * (i + 1))
+        } else {
+            literal(reader.numbers(1, "an input literal")?[0])?
+        };
+        if number < 2 || number % 2 == 1 {
+            return Err(ParseError::new(
+                offset,
+                &number.to_string(),
+                &["an even literal"],
+            ));
+        }
+        input_literals.push((offset, number));
+    }
+    let mut output_literals = Vec::new();
+    for _ in 0..outputs {
+        output_literals.push(literal(reader.numbers(1, "an output literal")?[0])?);
+    }
+    let mut gates = Vec::new();
+    for i in 0..ands {
+        let gate = if binary {
+            // The left side is implicit, and the right sides are deltas below it
+            let lhs = 2 * (inputs + i + 1);
+            let (offset, delta) = reader.varint()?;
+            let rhs0 = lhs.checked_sub(delta).map(|rhs0| (offset, rhs0));
+            let (offset, delta) = reader.varint()?;
+            let rhs1 =
+                rhs0.and_then(|(_, rhs0)| rhs0.checked_sub(delta).map(|rhs1| (offset, rhs1)));
+            match (rhs0, rhs1) {
+                (Some(rhs0), Some(rhs1)) => [literal((offset, lhs))?, rhs0, rhs1],
+                _ => {
+                    return Err(ParseError::new(
+                        offset,
+                        &delta.to_string(),
+                        &["a smaller delta"],
+                    ))
+                }
+            }
+        } else {
+            let numbers = reader.numbers(3, "an and gate literal")?;
+            [
+                literal(numbers[0])?,
+                literal(numbers[1])?,
+                literal(numbers[2])?,
+            ]
+        };
+        if gate[0].1 < 2 || gate[0].1 % 2 == 1 {
+            return Err(ParseError::new(
+                gate[0].0,
+                &gate[0].1.to_string(),
+                &["an even literal"],
+            ));
+        }
+        gates.push(gate);
+    }
+
+    // The symbol table, up to the comments
+    let mut input_names = vec![None; input_literals.len()];
+    let mut output_names = vec![None; output_literals.len()];
+    while reader.position < input.len() {
+        let (offset, line) = reader.line(&["a symbol"])?;
+        if line == "c" {
+            break;
+        }
+        let (position, name) = line.split_once(' ').unwrap_or((line, ""));
+        let names = match position.chars().next() {
+            Some('i') => &mut input_names,
+            Some('o') => &mut output_names,
+            _ => {
+                return Err(ParseError::new(
+                    offset,
+                    position,
+                    &["a symbol like i0 or o0"],
+                ))
+            }
+        };
+        match position[1..].parse::<usize>() {
+            Ok(index) if index < names.len() && !name.is_empty() => {
+                names[index] = Some(name.to_string())
+            }
+            _ => {
+                return Err(ParseError::new(
+                    offset,
+                    position,
+                    &["a symbol like i0 or o0"],
+                ))
+            }
+        }
+    }
+
+    // Named variables first, so the auxiliary variables of the gates are above them
+    let input_names = input_names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| name.unwrap_or_else(|| format!("i{}", i)))
+        .collect::<Vec<_>>();
+    let output_names = output_names
+        .into_iter()
+        .enumerate()
+        .map(|(i, name)| name.unwrap_or_else(|| format!("o{}", i)))
+        .collect::<Vec<_>>();
+    for name in input_names.iter().chain(&output_names) {
+        if let Some(id) = numbered(name) {
+            pool.reserve(id);
+        }
+    }
+    let mut name_id = |name: &str| numbered(name).unwrap_or_else(|| pool.id(name));
+    let mut ids = vec![None; max as usize + 1];
+    let mut circuit = Circuit {
+        formula: Formula::new(),
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+    };
+    for ((offset, number), name) in input_literals.iter().zip(&input_names) {
+        let id = name_id(name);
+        if ids[(number / 2) as usize].replace(id).is_some() {
+            return Err(ParseError::new(
+                *offset,
+                &number.to_string(),
+                &["a new variable"],
+            ));
+        }
+        circuit.inputs.push(id);
+    }
+    for name in &output_names {
+        circuit.outputs.push(name_id(name));
+    }
+    for [(offset, number), ..] in &gates {
+        if ids[(number / 2) as usize].replace(pool.fresh()).is_some() {
+            return Err(ParseError::new(
+                *offset,
+                &number.to_string(),
+                &["a new variable"],
+            ));
+        }
+    }
+
+    let mut truth = None;
+    let mut variable = |(offset, number): (usize, u64)| {
+        let id = match number / 2 {
+            // The constant false, as the negation of a variable defined to be true
+            0 => *truth.get_or_insert_with(|| pool.fresh()),
+            index => match ids[index as usize] {
+                Some(id) => id,
+                None => {
+                    return Err(ParseError::new(
+                        offset,
+                        &number.to_string(),
+                        &["a defined literal"],
+                    ))
+                }
+            },
+        };
+        Ok(if (number % 2 == 1) == (number / 2 == 0) {
+            Variable::Positive(id)
+        } else {
+            Variable::Negative(id)
+        })
+    };
+    let mut clauses = Vec::new();
+    for [lhs, rhs0, rhs1] in &gates {
+        let (gate, a, b) = (variable(*lhs)?, variable(*rhs0)?, variable(*rhs1)?);
+        clauses.push(vec![gate.negated(), a]);
+        clauses.push(vec![gate.negated(), b]);
+        clauses.push(vec![gate, a.negated(), b.negated()]);
+    }
+    for (literal, id) in output_literals.iter().zip(&circuit.outputs) {
+        let (output, value) = (Variable::Positive(*id), variable(*literal)?);
+        clauses.push(vec![output.negated(), value]);
+        clauses.push(vec![output, value.negated()]);
+    }
+    if let Some(truth) = truth {
+        circuit.formula.add(Clause(vec![Variable::Positive(truth)]));
+    }
+    for clause in clauses {
+        circuit.formula.add(Clause(clause));
+    }
+    Ok(circuit)
+}
+
+/// A position in an AIGER file, which mixes lines of text with binary encoded gates.
+struct Reader<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Take the next line as text, with its byte offset.
+    fn line(&mut self, expected: &[&str]) -> Result<(usize, &'a str), ParseError> {
+        let start = self.position;
+        if start >= self.input.len() {
+            return Err(ParseError::new(start, "", expected));
+        }
+        let end = self.input[start..]
+            .iter()
+            .position(|byte| *byte == b'\n')
+            .map_or(self.input.len(), |end| start + end);
+        self.position = end + 1;
+        match std::str::from_utf8(&self.input[start..end]) {
+            Ok(line) => Ok((start, line.trim_end_matches('\r'))),
+            Err(error) => Err(ParseError::new(
+                start + error.valid_up_to(),
+                "byte",
+                &["text"],
+            )),
+        }
+    }
+
+    /// Take a line of exactly `count` numbers, with their byte offsets.
+    fn numbers(&mut self, count: usize, expected: &str) -> Result<Vec<(usize, u64)>, ParseError> {
+        let (offset, line) = self.line(&[expected])?;
+        let mut numbers = Vec::new();
+        for (offset, token) in tokens(line, offset) {
+            if numbers.len() == count {
+                return Err(ParseError::new(offset, token, &["the end of the line"]));
+            }
+            match token.parse::<u64>() {
+                Ok(number) => numbers.push((offset, number)),
+                Err(_) => return Err(ParseError::new(offset, token, &[expected])),
+            }
+        }
+        if numbers.len() < count {
+            return Err(ParseError::new(offset + line.len(), "", &[expected]));
+        }
+        Ok(numbers)
+    }
+
+    /// Take a number encoded in groups of seven bits, lowest first, with the high bit set on all but the last byte.
+    fn varint(&mut self) -> Result<(usize, u64), ParseError> {
+        let start = self.position;
+        let mut number = 0;
+        for shift in (0..64).step_by(7) {
+            let Some(byte) = self.input.get(self.position) else {
+                return Err(ParseError::new(self.position, "", &["a binary and gate"]));
+            };
+            self.position += 1;
+            number |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok((start, number));
+            }
+        }
+        Err(ParseError::new(start, "byte", &["a smaller delta"]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        solver::{solve_all, solve_assuming},
+        solvers::Dfs,
+        types::Certificate,
+    };
+
+    use super::*;
+
+    // A half adder with an unnamed carry output
+    const HALF_ADDER: &str =
+        "aag 7 2 0 2 3\n2\n4\n6\n12\n6 13 15\n12 2 4\n14 3 5\ni0 a\ni1 b\no0 sum\nc\nhalf adder\n";
+
+    #[test]
+    fn test_parse_aiger() {
+        let mut pool = VarPool::new();
+        let circuit = parse_aiger(HALF_ADDER.as_bytes(), &mut pool).unwrap();
+        assert_eq!(circuit.inputs, [1, 2]);
+        assert_eq!(circuit.outputs, [3, 4]);
+        assert_eq!(pool.name(3), Some("sum"));
+        assert_eq!(pool.name(4), Some("o1"));
+        let solutions = solve_all(&circuit.formula, &Dfs::default());
+        assert_eq!(solutions.len(), 4);
+        for solution in solutions {
+            let (a, b) = (solution.get(1), solution.get(2));
+            assert_eq!(solution.get(3), a != b);
+            assert_eq!(solution.get(4), a && b);
+        }
+        let Certificate::Satisfiable(solution) =
+            solve_assuming(&circuit.any_output(), &[], &Dfs::default())
+        else {
+            panic!("Some input sets an output of a half adder");
+        };
+        assert!(solution.get(1) || solution.get(2));
+    }
+
+    #[test]
+    fn test_parse_binary_aiger() {
+        // The same half adder, with the gates encoded as deltas and a constant false output
+        let mut input = b"aig 5 2 0 3 3\n10\n6\n0\n".to_vec();
+        input.extend([2, 2, 3, 2, 1, 2]);
+        input.extend(b"i0 a\ni1 b\no0 sum\n");
+        let mut pool = VarPool::new();
+        let binary = parse_aiger(&input, &mut pool).unwrap();
+        assert_eq!(binary.formula.len(), 16);
+        let solutions = solve_all(&binary.formula, &Dfs::default());
+        assert_eq!(solutions.len(), 4);
+        assert!(solutions
+            .iter()
+            .all(|solution| !solution.get(binary.outputs[2])));
+        let ascii = parse_aiger(HALF_ADDER.as_bytes(), &mut pool).unwrap();
+        assert_eq!(ascii.inputs, binary.inputs);
+        assert_eq!(
+            solve_assuming(&ascii.miter(&binary, &mut pool), &[], &Dfs::default()),
+            Certificate::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn test_aiger_errors() {
+        let mut pool = VarPool::new();
+        let error = parse_aiger(b"aag 1 0 1 0 0\n2 3\n", &mut pool).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected 1, expected 0 for a combinational circuit"
+        );
+        let error = parse_aiger(b"aag 1 1 0 1 0\n2\n4\n", &mut pool).unwrap_err();
+        assert_eq!(error.span, 16..17);
+        let error = parse_aiger(b"aag 2 1 0 1 0\n2\n4\n", &mut pool).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected 4, expected a defined literal"
+        );
+        let error = parse_aiger(b"aag 1 1 0 0 0\n2\nx0 a\n", &mut pool).unwrap_err();
+        assert_eq!(error.span, 16..18);
+        let error = parse_aiger(b"aig 1 1 0 0 1\n", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "Missing a binary and gate");
+    }
+}
@@ -0,0 +1,291 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    circuit::Circuit,
+    dimacs::tokens,
+    expr::Expr,
+    parser::{numbered, ParseError},
+    types::{Formula, VarPool},
+};
+
+/// A logic function given by a `.names` table, whose last signal is the output.
+struct Table<'a> {
+    signals: Vec<(usize, &'a str)>,
+    /// The cubes of the inputs, like `1-0`, for which the output has the value of the table.
+    cubes: Vec<&'a str>,
+    /// The output value of the rows, with no rows meaning the constant false.
+    value: Option<bool>,
+}
+
+/// Parse the first model of a combinational netlist in the Berkeley Logic Interchange Format,
+/// converting it to a circuit in CNF.
+/// Every `.names` table defines its output signal as the disjunction of its cubes,
+/// or the negation of it if the rows have the output value 0.
+/// The primary inputs and outputs are named variables, and the internal signals are auxiliary variables.
+/// Lines are continued by a trailing `\`, and `#` starts a comment.
+///
+/// ## Examples
+/// ```plaintext
+/// .model half_adder
+/// .inputs a b
+/// .outputs sum carry
+/// .names a b sum
+/// 10 1
+/// 01 1
+/// .names a b carry
+/// 11 1
+/// .end
+/// ```
+pub fn parse_blif(input: &str, pool: &mut VarPool) -> Result<Circuit, ParseError> {
+    let mut inputs = Vec::new();
+    let mut outputs = Vec::new();
+    let mut tables: Vec<Table> = Vec::new();
+    let mut in_table = false;
+    for (end, line) in logical_lines(input) {
+        let (offset, first) = line[0];
+        match first {
+            ".model" => in_table = false,
+            ".inputs" => {
+                inputs.extend(&line[1..]);
+                in_table = false;
+            }
+            ".outputs" => {
+                outputs.extend(&line[1..]);
+                in_table = false;
+            }
+            ".names" if line.len() < 2 => return Err(ParseError::new(end, "", &["a signal"])),
+            ".names" => {
+                tables.push(Table {
+                    signals: line[1..].to_vec(),
+                    cubes: Vec::new(),
+                    value: None,
+                });
+                in_table = true;
+            }
+            ".end" => break,
+            _ if first.starts_with('.') => {
+                return Err(ParseError::new(
+                    offset,
+                    first,
+                    &["'.inputs'", "'.outputs'", "'.names'", "'.end'"],
+                ))
+            }
+            _ => {
+                let Some(table) = tables.last_mut().filter(|_| in_table) else {
+                    return Err(ParseError::new(offset, first, &["a directive like .names"]));
+                };
+                table_row(table, &line, end)?;
+            }
+        }
+    }
+
+    // Named variables first, so the auxiliary variables of the internal signals are above them
+    for (_, name) in inputs.iter().chain(&outputs) {
+        if let Some(id) = numbered(name) {
+            pool.reserve(id);
+        }
+    }
+    let mut circuit = Circuit {
+        formula: Formula::new(),
+        inputs: Vec::new(),
+        outputs: Vec::new(),
+    };
+    let mut ids = HashMap::new();
+    for (_, name) in &inputs {
+        let id = *ids
+            .entry(*name)
+            .or_insert_with(|| numbered(name).unwrap_or_else(|| pool.id(name)));
+        circuit.inputs.push(id);
+    }
+    for (_, name) in &outputs {
+        let id = *ids
+            .entry(*name)
+            .or_insert_with(|| numbered(name).unwrap_or_else(|| pool.id(name)));
+        circuit.outputs.push(id);
+    }
+    // Signals may be used before the table defining them
+    let mut driven = inputs.iter().map(|(_, name)| *name).collect::<HashSet<_>>();
+    for table in &tables {
+        let (offset, output) = *table.signals.last().unwrap();
+        if !driven.insert(output) {
+            return Err(ParseError::new(
+                offset,
+                output,
+                &["a signal without a driver"],
+            ));
+        }
+        ids.entry(output).or_insert_with(|| pool.fresh());
+    }
+    for (offset, name) in inputs.iter().chain(&outputs).chain(
+        tables
+            .iter()
+            .flat_map(|table| &table.signals[..table.signals.len() - 1]),
+    ) {
+        if !driven.contains(name) {
+            return Err(ParseError::new(*offset, name, &["a driven signal"]));
+        }
+    }
+
+    let mut clauses = Vec::new();
+    for table in &tables {
+        let (output, signals) = table.signals.split_last().unwrap();
+        let cover = Expr::any(table.cubes.iter().map(|cube| {
+            Expr::all(
+                cube.chars()
+                    .zip(signals)
+                    .filter_map(|(c, (_, name))| match c {
+                        '1' => Some(Expr::var(ids[name])),
+                        '0' => Some(!Expr::var(ids[name])),
+                        _ => None,
+                    }),
+            )
+        }));
+        let cover = if table.value == Some(false) {
+            !cover
+        } else {
+            cover
+        };
+        let definition = Expr::var(ids[output.1]).iff(cover);
+        clauses.extend(definition.to_cnf(pool).0);
+    }
+    circuit.formula = Formula::from(clauses);
+    Ok(circuit)
+}
+
+/// Add a row of a `.names` table, with a cube of its inputs and the output value.
+fn table_row<'a>(
+    table: &mut Table<'a>,
+    line: &[(usize, &'a str)],
+    end: usize,
+) -> Result<(), ParseError> {
+    let width = table.signals.len() - 1;
+    let (cube, value) = match (width, line) {
+        (0, [value]) => (None, *value),
+        (0, [_, (offset, token), ..]) | (_, [_, _, (offset, token), ..]) => {
+            return Err(ParseError::new(*offset, token, &["the end of the line"]))
+        }
+        (_, [(offset, cube), value]) => {
+            let valid = cube.len() == width && cube.chars().all(|c| "01-".contains(c));
+            if !valid {
+                let expected = format!("a cube of {} inputs like 1-0", width);
+                return Err(ParseError::new(*offset, cube, &[expected.as_str()]));
+            }
+            (Some(*cube), *value)
+        }
+        (_, [_]) => return Err(ParseError::new(end, "", &["an output value"])),
+        (_, []) => unreachable!("Lines have at least one token"),
+    };
+    let expected: &[&str] = match table.value {
+        Some(true) => &["'1'"],
+        Some(false) => &["'0'"],
+        None => &["'0'", "'1'"],
+    };
+    let value = match value {
+        (_, "1") if table.value != Some(false) => true,
+        (_, "0") if table.value != Some(true) => false,
+        (offset, token) => return Err(ParseError::new(offset, token, expected)),
+    };
+    table.value = Some(value);
+    table.cubes.push(cube.unwrap_or(""));
+    Ok(())
+}
+
+/// Split the input into lines of tokens with their byte offsets, joining lines continued by `\`
+/// and leaving out comments and empty lines. Each line is given with the offset of its end.
+fn logical_lines(input: &str) -> Vec<(usize, Vec<(usize, &str)>)> {
+    let mut lines = Vec::new();
+    let mut current = Vec::new();
+    let mut start = 0;
+    for line in input.split('\n') {
+        let offset = start;
+        start += line.len() + 1;
+        let line = line.split('#').next().unwrap().trim_end();
+        let (line, continued) = match line.strip_suffix('\\') {
+            Some(line) => (line, true),
+            None => (line, false),
+        };
+        current.extend(tokens(line, offset));
+        if !continued && !current.is_empty() {
+            lines.push((offset + line.len(), std::mem::take(&mut current)));
+        }
+    }
+    if !current.is_empty() {
+        lines.push((input.len(), current));
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{solver::solve_all, solvers::Dfs};
+
+    use super::*;
+
+    #[test]
+    fn test_parse_blif() {
+        let input = "
+            .model full_adder # with the sum through an internal signal
+            .inputs a b \\
+                    cin
+            .outputs sum cout
+            .names a b half
+            10 1
+            01 1
+            .names half cin sum
+            00 0
+            11 0
+            .names a b cin cout
+            11- 1
+            1-1 1
+            -11 1
+            .end
+            .model ignored
+        ";
+        let mut pool = VarPool::new();
+        let circuit = parse_blif(input, &mut pool).unwrap();
+        assert_eq!(circuit.inputs, [1, 2, 3]);
+        assert_eq!(circuit.outputs, [4, 5]);
+        let solutions = solve_all(&circuit.formula, &Dfs::default());
+        assert_eq!(solutions.len(), 8);
+        for solution in solutions {
+            let values = circuit.decode(&solution, &pool);
+            let count = values[..3].iter().filter(|(_, value)| *value).count();
+            assert_eq!(values[3], ("sum".to_string(), count % 2 == 1));
+            assert_eq!(values[4], ("cout".to_string(), count >= 2));
+        }
+    }
+
+    #[test]
+    fn test_blif_constants() {
+        let input = ".inputs a\n.outputs one zero\n.names one\n1\n.names a zero\n";
+        let mut pool = VarPool::new();
+        let circuit = parse_blif(input, &mut pool).unwrap();
+        // The input is not used by any output
+        let solutions = solve_all(&circuit.formula, &Dfs::default());
+        assert_eq!(solutions.len(), 1);
+        let values = circuit.decode(&solutions[0], &pool);
+        assert_eq!(
+            values[1..],
+            [("one".to_string(), true), ("zero".to_string(), false)]
+        );
+    }
+
+    #[test]
+    fn test_blif_errors() {
+        let mut pool = VarPool::new();
+        let error =
+            parse_blif(".inputs a\n.outputs f\n.names a g f\n11 1\n", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "Unexpected g, expected a driven signal");
+        let error = parse_blif(".inputs a b\n.names a b f\n1 1\n", &mut pool).unwrap_err();
+        assert_eq!(error.span, 25..26);
+        let error = parse_blif(".names a f\n1 1\n0 0\n", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "Unexpected 0, expected '1'");
+        let error = parse_blif(".inputs a\n.latch a b\n", &mut pool).unwrap_err();
+        assert_eq!(error.span, 10..16);
+        let error = parse_blif(".inputs a\n.names a a\n1 1\n", &mut pool).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected a, expected a signal without a driver"
+        );
+    }
+}
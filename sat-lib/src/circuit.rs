@@ -0,0 +1,100 @@
+use crate::types::{Clause, Formula, Literal, Solution, VarMap, VarPool, Variable};
+
+/// A combinational circuit converted to CNF, where every gate is an auxiliary variable
+/// defined to be equivalent to its output by the Tseitin transformation.
+///
+/// The inputs and outputs are named variables, so the models of the formula
+/// are exactly the input values with the output values they give.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Circuit {
+    /// The clauses defining the gates and outputs in terms of the inputs.
+    pub formula: Formula,
+    pub inputs: Vec<Literal>,
+    pub outputs: Vec<Literal>,
+}
+
+impl Circuit {
+    /// Get the formula of the circuit requiring some output to be true,
+    /// which is satisfiable by the inputs for which the circuit can output true.
+    pub fn any_output(&self) -> Formula {
+        let mut formula = self.formula.clone();
+        formula.add(Clause(
+            self.outputs
+                .iter()
+                .map(|id| Variable::Positive(*id))
+                .collect(),
+        ));
+        formula
+    }
+
+    /// Get the miter of two circuits over the same inputs, comparing their outputs in order.
+    /// The formula is satisfiable by the inputs for which some pair of outputs differs,
+    /// and unsatisfiable if the circuits are equivalent.
+    /// Outputs of the other circuit with the same name as one of this circuit are renamed to fresh variables.
+    pub fn miter(&self, other: &Circuit, pool: &mut VarPool) -> Formula {
+        let mut renaming = VarMap::default();
+        for id in &other.outputs {
+            if self.outputs.contains(id) {
+                renaming.insert(*id, pool.fresh());
+            }
+        }
+        let mut formula = self.formula.and(&other.formula.rename(&renaming));
+        let mut differences = Vec::new();
+        for (a, b) in self.outputs.iter().zip(&other.outputs) {
+            let (a, b) = (
+                Variable::Positive(*a),
+                renaming.rename(Variable::Positive(*b)),
+            );
+            // The difference only needs to imply that the outputs differ
+            let difference = Variable::Positive(pool.fresh());
+            formula.add(Clause(vec![difference.negated(), a, b]));
+            formula.add(Clause(vec![difference.negated(), a.negated(), b.negated()]));
+            differences.push(difference);
+        }
+        formula.add(Clause(differences));
+        formula
+    }
+
+    /// Get the values of the inputs and then the outputs in a model of the circuit, by their names.
+    /// Numbered variables without a name are called `x1`, `x2`, ...
+    /// Inputs that no output depends on are not in the formula, and are given as false.
+    pub fn decode(&self, solution: &Solution, pool: &VarPool) -> Vec<(String, bool)> {
+        self.inputs
+            .iter()
+            .chain(&self.outputs)
+            .map(|id| {
+                let name = pool.name(*id).map_or(format!("x{}", id), str::to_string);
+                (name, solution.0.get(id).copied().unwrap_or(false))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{expr::Expr, solver::solve_all, solvers::Dfs};
+
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let mut pool = VarPool::new();
+        let (a, b, and) = (pool.id("a"), pool.id("b"), pool.id("and"));
+        let circuit = Circuit {
+            formula: Expr::var(and)
+                .iff(Expr::var(a).and(Expr::var(b)))
+                .to_cnf(&mut pool),
+            inputs: vec![a, b],
+            outputs: vec![and],
+        };
+        let solution = &solve_all(&circuit.any_output(), &Dfs::default())[0];
+        assert_eq!(
+            circuit.decode(solution, &pool),
+            [
+                ("a".to_string(), true),
+                ("b".to_string(), true),
+                ("and".to_string(), true)
+            ]
+        );
+    }
+}
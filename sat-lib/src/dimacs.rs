@@ -0,0 +1,843 @@
+use std::{
+    collections::VecDeque,
+    error::Error,
+    fmt::{self, Display, Formatter, Write},
+    io::{self, BufRead},
+};
+
+use crate::{
+    parser::ParseError,
+    solver::{solve_assuming, solve_optimize_limited},
+    solvers::{Budget, Solver},
+    types::{Certificate, Clause, Formula, Literal, Solution, VarPool, Variable, MAX_LITERAL},
+};
+
+/// A formula read from the DIMACS CNF format, with the number of variables declared by its header.
+/// The declared variables that are in no clause can have either value in a model.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Dimacs {
+    pub formula: Formula,
+    pub declared: Literal,
+}
+
+impl Dimacs {
+    /// Get the variables from 1 up to the declared number, and any higher ones in the clauses.
+    pub fn variables(&self) -> Vec<Literal> {
+        let mut variables = (1..=self.declared).collect::<Vec<_>>();
+        variables.extend(
+            self.formula
+                .literals()
+                .into_iter()
+                .filter(|id| *id > self.declared),
+        );
+        variables
+    }
+}
+
+/// Parse a formula in the DIMACS CNF format of the SAT competitions.
+/// A `p cnf` header with the number of variables and clauses comes before the clauses,
+/// which are signed variable numbers ended by 0 and can span lines. Lines starting with `c` are comments.
+///
+/// ## Examples
+/// ```plaintext
+/// c (x1 OR -x2) AND x3
+/// p cnf 3 2
+/// 1 -2 0
+/// 3 0
+/// ```
+pub fn parse_dimacs(input: &str) -> Result<Dimacs, ParseError> {
+    let mut formula = Formula::new();
+    let mut clause = Vec::new();
+    let mut header = None;
+    for (offset, line) in lines(input) {
+        if line.starts_with('%') {
+            // The end of the clauses in SATLIB benchmarks
+            break;
+        }
+        if line.starts_with('p') {
+            header = Some(declared(line, offset)?);
+            continue;
+        }
+        for (offset, token) in tokens(line, offset) {
+            if header.is_none() {
+                return Err(ParseError::new(offset, token, &["p cnf"]));
+            }
+            match literal(token, offset)? {
+                Some(variable) => clause.push(variable),
+                None => formula.add(Clause(std::mem::take(&mut clause))),
+            }
+        }
+    }
+    // The last clause may lack its 0
+    if !clause.is_empty() {
+        formula.add(Clause(clause));
+    }
+    Ok(Dimacs {
+        formula,
+        declared: header.unwrap_or(0),
+    })
+}
+
+/// Parse a `p cnf` header into the number of variables it declares, which must be identifiers.
+fn declared(line: &str, offset: usize) -> Result<Literal, ParseError> {
+    let counts = header_counts(line, offset, "cnf", 2)?;
+    Literal::try_from(counts[0])
+        .ok()
+        .filter(|variables| *variables <= MAX_LITERAL)
+        .ok_or_else(|| {
+            let (offset, token) = tokens(line, offset).nth(2).expect("The count was parsed");
+            ParseError::new(offset, token, &["a smaller number of variables"])
+        })
+}
+
+/// Write a formula in the DIMACS CNF format, see [`parse_dimacs`].
+pub fn write_dimacs(formula: &Formula) -> String {
+    let variables = formula.literals().last().copied().unwrap_or(0);
+    let mut output = format!("p cnf {} {}\n", variables, formula.len());
+    for clause in formula.iter() {
+        write_clause(&mut output, clause);
+    }
+    output
+}
+
+/// Write the result of solving a formula in the output format of the SAT competitions,
+/// with an `s` line for the status, and for a solution `v` lines of its literals ending with `0`.
+///
+/// ## Examples
+/// ```plaintext
+/// s SATISFIABLE
+/// v 1 -2 3 0
+/// ```
+pub fn write_result(certificate: &Certificate) -> String {
+    let solution = match certificate {
+        Certificate::Satisfiable(solution) => solution,
+        Certificate::Unsatisfiable => return "s UNSATISFIABLE\n".to_string(),
+        Certificate::Indeterminate => return "s UNKNOWN\n".to_string(),
+    };
+    let mut output = "s SATISFIABLE\n".to_string();
+    let mut line = "v".to_string();
+    let literals = solution.literals().into_iter().map(|id| {
+        if solution.get(id) {
+            id.to_string()
+        } else {
+            format!("-{}", id)
+        }
+    });
+    // Lines are kept short for tools reading them, with the terminating 0 on the last one
+    for literal in literals.chain(["0".to_string()]) {
+        if line.len() + 1 + literal.len() > 80 {
+            output.push_str(&line);
+            output.push('\n');
+            line = "v".to_string();
+        }
+        line.push(' ');
+        line.push_str(&literal);
+    }
+    output.push_str(&line);
+    output.push('\n');
+    output
+}
+
+/// Get the exit code the SAT competitions use for a result,
+/// 10 if the formula is satisfiable, 20 if it is unsatisfiable and 0 if it is unknown.
+pub fn exit_code(certificate: &Certificate) -> i32 {
+    match certificate {
+        Certificate::Satisfiable(_) => 10,
+        Certificate::Unsatisfiable => 20,
+        Certificate::Indeterminate => 0,
+    }
+}
+
+/// The number of clauses between the progress reports of [`read_dimacs`].
+pub const PROGRESS_INTERVAL: usize = 100_000;
+
+/// An error reading a DIMACS file from a stream.
+#[derive(Debug)]
+pub enum ReadError {
+    Io(io::Error),
+    /// A parse error on a line numbered from 1, with its span in bytes from the start of the stream.
+    Parse {
+        line: usize,
+        error: ParseError,
+    },
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReadError::Io(error) => write!(f, "{}", error),
+            ReadError::Parse { line, error } => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+impl Error for ReadError {}
+
+impl From<io::Error> for ReadError {
+    fn from(error: io::Error) -> Self {
+        ReadError::Io(error)
+    }
+}
+
+/// A streaming parser of the clauses of a DIMACS CNF file, see [`parse_dimacs`].
+/// The input is read one line at a time, so only the current line is held in memory,
+/// and the clauses are given one by one as they are completed.
+pub struct DimacsReader<R> {
+    reader: R,
+    line: String,
+    /// The number of lines read, and the byte offset after them.
+    lines: usize,
+    offset: usize,
+    /// The number of variables and clauses in the header, once it is read.
+    header: Option<(u64, u64)>,
+    declared: Literal,
+    clause: Vec<Variable>,
+    /// The clauses completed on the current line, which can hold more than one.
+    completed: VecDeque<Clause>,
+    done: bool,
+}
+
+impl<R: BufRead> DimacsReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line: String::new(),
+            lines: 0,
+            offset: 0,
+            header: None,
+            declared: 0,
+            clause: Vec::new(),
+            completed: VecDeque::new(),
+            done: false,
+        }
+    }
+
+    /// Get the number of variables and clauses declared by the header, if it has been read.
+    pub fn header(&self) -> Option<(u64, u64)> {
+        self.header
+    }
+
+    /// Read the next line that is not a comment, completing the clauses on it.
+    /// Returns `false` at the end of the input.
+    fn read_line(&mut self) -> Result<bool, ReadError> {
+        self.line.clear();
+        let length = self.reader.read_line(&mut self.line)?;
+        let offset = self.offset;
+        self.offset += length;
+        self.lines += 1;
+        let (offset, line) = match lines(&self.line).next() {
+            Some((start, line)) => (offset + start, line),
+            None => return Ok(length > 0),
+        };
+        let error = |error| ReadError::Parse {
+            line: self.lines,
+            error,
+        };
+        if line.starts_with('%') {
+            // The end of the clauses in SATLIB benchmarks
+            return Ok(false);
+        }
+        if line.starts_with('p') {
+            let counts = header_counts(line, offset, "cnf", 2).map_err(error)?;
+            self.declared = declared(line, offset).map_err(error)?;
+            self.header = Some((counts[0], counts[1]));
+            return Ok(true);
+        }
+        for (offset, token) in tokens(line, offset) {
+            if self.header.is_none() {
+                return Err(error(ParseError::new(offset, token, &["p cnf"])));
+            }
+            match literal(token, offset).map_err(error)? {
+                Some(variable) => self.clause.push(variable),
+                None => self
+                    .completed
+                    .push_back(Clause(std::mem::take(&mut self.clause))),
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Iterator for DimacsReader<R> {
+    type Item = Result<Clause, ReadError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(clause) = self.completed.pop_front() {
+                return Some(Ok(clause));
+            }
+            if self.done {
+                return None;
+            }
+            match self.read_line() {
+                Ok(true) => (),
+                Ok(false) => {
+                    self.done = true;
+                    // The last clause may lack its 0
+                    if !self.clause.is_empty() {
+                        return Some(Ok(Clause(std::mem::take(&mut self.clause))));
+                    }
+                }
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+    }
+}
+
+/// Read a formula in the DIMACS CNF format from a stream, adding the clauses to it as they are parsed,
+/// without holding the text of the file in memory. The progress is reported with the number of clauses read
+/// and the number declared by the header, every [`PROGRESS_INTERVAL`] clauses and at the end.
+pub fn read_dimacs<R: BufRead>(
+    reader: R,
+    mut progress: impl FnMut(usize, u64),
+) -> Result<Dimacs, ReadError> {
+    let mut reader = DimacsReader::new(reader);
+    let mut formula = Formula::new();
+    let mut count = 0;
+    while let Some(clause) = reader.next() {
+        formula.add(clause?);
+        count += 1;
+        if count % PROGRESS_INTERVAL == 0 {
+            progress(count, reader.header().map_or(0, |(_, clauses)| clauses));
+        }
+    }
+    progress(count, reader.header().map_or(0, |(_, clauses)| clauses));
+    Ok(Dimacs {
+        formula,
+        declared: reader.declared,
+    })
+}
+
+/// A weighted MaxSAT problem, where every hard clause must be satisfied
+/// and the total weight of falsified soft clauses is minimized.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Wcnf {
+    pub hard: Formula,
+    /// The soft clauses with their weights.
+    pub soft: Vec<(u64, Clause)>,
+}
+
+/// The version of the WCNF format to write.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum WcnfFormat {
+    /// The format before 2022, with a `p wcnf` header and hard clauses weighted by its top weight.
+    Old,
+    /// The format of the MaxSAT Evaluations since 2022, without header and with `h` for hard clauses.
+    #[default]
+    New,
+}
+
+impl Wcnf {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Find a model of the hard clauses minimizing the total weight of falsified soft clauses,
+    /// returning it with that weight, or `None` if the hard clauses are unsatisfiable.
+    /// Fails if the total weight of the soft clauses is above `i64::MAX`, which the optimizer can't sum.
+    ///
+    /// Each soft clause is relaxed by a new variable that is true when the clause may be falsified,
+    /// and the weighted sum of those variables is minimized by [`solve_optimize`](crate::solver::solve_optimize).
+    pub fn solve(&self) -> Result<Option<(Solution, u64)>, WeightOverflow> {
+        self.solve_limited(&Budget::default())
+    }
+
+    /// Solve like [`solve`](Self::solve), giving up when the budget runs out with the best model found by then,
+    /// see [`solve_optimize_limited`].
+    pub fn solve_limited(
+        &self,
+        budget: &Budget,
+    ) -> Result<Option<(Solution, u64)>, WeightOverflow> {
+        // Every weight fits in an i64 if their sum does, and then no cost overflows
+        i64::try_from(self.total_weight()?).map_err(|_| WeightOverflow)?;
+        let mut pool = VarPool::new();
+        let mut variables = self.hard.literals();
+        for (_, clause) in &self.soft {
+            variables.extend(clause.literals());
+        }
+        variables.sort();
+        variables.dedup();
+        if let Some(last) = variables.last() {
+            pool.reserve(*last);
+        }
+        let mut formula = self.hard.clone();
+        let mut objective = Vec::new();
+        for (weight, clause) in &self.soft {
+            let relaxation = pool.fresh();
+            let mut relaxed = clause.clone();
+            relaxed.0.push(Variable::Positive(relaxation));
+            formula.add(relaxed);
+            let weight = i64::try_from(*weight).map_err(|_| WeightOverflow)?;
+            objective.push((relaxation, weight));
+        }
+        let Some((solution, cost)) = solve_optimize_limited(&formula, &objective, budget) else {
+            return Ok(None);
+        };
+        let cost = u64::try_from(cost).expect("Weights are not negative");
+        Ok(Some((solution.project(&variables), cost)))
+    }
+
+    /// Get the total weight of the soft clauses.
+    pub fn total_weight(&self) -> Result<u64, WeightOverflow> {
+        self.soft
+            .iter()
+            .try_fold(0u64, |total, (weight, _)| total.checked_add(*weight))
+            .ok_or(WeightOverflow)
+    }
+}
+
+/// The weights of a [`Wcnf`] sum to more than fits in an integer.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct WeightOverflow;
+
+impl Display for WeightOverflow {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "The total weight of the soft clauses is too large")
+    }
+}
+
+impl Error for WeightOverflow {}
+
+/// Parse a weighted MaxSAT problem in either WCNF format, see [`WcnfFormat`].
+/// Every clause is on its own line, starting with its weight, or `h` for hard clauses in the new format.
+/// In the old format, clauses with at least the top weight of the header are hard.
+///
+/// ## Examples
+/// ```plaintext
+/// c new format                  c old format
+/// h 1 2 0                       p wcnf 2 3 10
+/// 3 -1 0                        10 1 2 0
+/// 5 -2 0                        3 -1 0
+///                               5 -2 0
+/// ```
+pub fn parse_wcnf(input: &str) -> Result<Wcnf, ParseError> {
+    let mut wcnf = Wcnf::new();
+    // The top weight of the old format, or no weight if every clause is soft
+    let mut top = None;
+    // The total weight of the soft clauses, which must fit in an i64 to be solved
+    let mut total = 0i64;
+    for (offset, line) in lines(input) {
+        if line.starts_with('p') {
+            let counts = header_counts(line, offset, "wcnf", 2)?;
+            top = counts.get(2).copied().or(Some(u64::MAX));
+            continue;
+        }
+        let mut tokens = tokens(line, offset);
+        let Some((offset, weight)) = tokens.next() else {
+            continue;
+        };
+        let weight = match weight {
+            "h" if top.is_none() => None,
+            weight => match weight.parse::<u64>() {
+                Ok(weight) if top.is_some_and(|top| weight >= top) => None,
+                Ok(number) => {
+                    total = i64::try_from(number)
+                        .ok()
+                        .and_then(|number| total.checked_add(number))
+                        .ok_or_else(|| ParseError::new(offset, weight, &["a smaller weight"]))?;
+                    Some(number)
+                }
+                Err(_) => return Err(ParseError::new(offset, weight, &["a weight", "h"])),
+            },
+        };
+        let clause = clause_line(tokens, offset + line.len())?;
+        match weight {
+            Some(weight) => wcnf.soft.push((weight, Clause(clause))),
+            None => wcnf.hard.add(Clause(clause)),
+        }
+    }
+    Ok(wcnf)
+}
+
+/// Write a weighted MaxSAT problem in a WCNF format.
+/// The top weight of the old format is one more than the total weight of the soft clauses,
+/// so it fails if that is above `u64::MAX`.
+pub fn write_wcnf(wcnf: &Wcnf, format: WcnfFormat) -> Result<String, WeightOverflow> {
+    let mut output = String::new();
+    let hard = match format {
+        WcnfFormat::Old => {
+            let top = wcnf.total_weight()?.checked_add(1).ok_or(WeightOverflow)?;
+            let variables = wcnf
+                .hard
+                .literals()
+                .into_iter()
+                .chain(wcnf.soft.iter().flat_map(|(_, clause)| clause.literals()))
+                .max()
+                .unwrap_or(0);
+            let clauses = wcnf.hard.len() + wcnf.soft.len();
+            writeln!(output, "p wcnf {} {} {}", variables, clauses, top).unwrap();
+            top.to_string()
+        }
+        WcnfFormat::New => "h".to_string(),
+    };
+    for clause in wcnf.hard.iter() {
+        write!(output, "{} ", hard).unwrap();
+        write_clause(&mut output, clause);
+    }
+    for (weight, clause) in &wcnf.soft {
+        write!(output, "{} ", weight).unwrap();
+        write_clause(&mut output, clause);
+    }
+    Ok(output)
+}
+
+/// A step of an incremental SAT problem, replayed in order by [`replay_icnf`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum IcnfStep {
+    /// Add a clause to the formula for every later solve.
+    Clause(Clause),
+    /// Solve the clauses added so far under assumptions that only hold for this solve.
+    Solve(Vec<Variable>),
+}
+
+/// Parse an incremental SAT problem in the ICNF format of incremental benchmark traces.
+/// After a `p inccnf` header, every line is a clause ended by 0,
+/// or a line of assumptions starting with `a` that solves the clauses so far.
+///
+/// ## Examples
+/// ```plaintext
+/// p inccnf
+/// 1 2 0
+/// a -1 0
+/// -2 0
+/// a 0
+/// ```
+pub fn parse_icnf(input: &str) -> Result<Vec<IcnfStep>, ParseError> {
+    let mut steps = Vec::new();
+    let mut header = false;
+    for (offset, line) in lines(input) {
+        if line.starts_with('p') {
+            header_counts(line, offset, "inccnf", 0)?;
+            header = true;
+            continue;
+        }
+        if !header {
+            let (offset, token) = tokens(line, offset).next().unwrap();
+            return Err(ParseError::new(offset, token, &["p inccnf"]));
+        }
+        let mut tokens = tokens(line, offset).peekable();
+        let assume = tokens.next_if(|(_, token)| *token == "a").is_some();
+        let variables = clause_line(tokens, offset + line.len())?;
+        steps.push(if assume {
+            IcnfStep::Solve(variables)
+        } else {
+            IcnfStep::Clause(Clause(variables))
+        });
+    }
+    Ok(steps)
+}
+
+/// Write an incremental SAT problem in the ICNF format, see [`parse_icnf`].
+pub fn write_icnf(steps: &[IcnfStep]) -> String {
+    let mut output = "p inccnf\n".to_string();
+    for step in steps {
+        match step {
+            IcnfStep::Clause(clause) => write_clause(&mut output, clause),
+            IcnfStep::Solve(assumptions) => {
+                output.push_str("a ");
+                write_clause(&mut output, &Clause(assumptions.clone()));
+            }
+        }
+    }
+    output
+}
+
+/// Replay an incremental SAT problem with [`solve_assuming`],
+/// returning the certificate of every solve in order.
+pub fn replay_icnf<S: Solver + ?Sized>(steps: &[IcnfStep], solver: &S) -> Vec<Certificate> {
+    let mut formula = Formula::new();
+    let mut certificates = Vec::new();
+    for step in steps {
+        match step {
+            IcnfStep::Clause(clause) => formula.add(clause.clone()),
+            IcnfStep::Solve(assumptions) => {
+                certificates.push(solve_assuming(&formula, assumptions, solver));
+            }
+        }
+    }
+    certificates
+}
+
+/// Get the lines that are not comments or empty, trimmed, with their byte offsets.
+pub(crate) fn lines(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .split('\n')
+        .scan(0, |start, line| {
+            let offset = *start;
+            *start += line.len() + 1;
+            let trimmed = line.trim_start();
+            Some((offset + line.len() - trimmed.len(), trimmed.trim_end()))
+        })
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('c'))
+}
+
+/// Split a line at whitespace, keeping the byte offset of every token.
+pub(crate) fn tokens(line: &str, offset: usize) -> impl Iterator<Item = (usize, &str)> {
+    line.split_whitespace().map(move |token| {
+        (
+            offset + token.as_ptr() as usize - line.as_ptr() as usize,
+            token,
+        )
+    })
+}
+
+/// Parse a `p <format>` header line with at least `counts` numbers.
+pub(crate) fn header_counts(
+    line: &str,
+    offset: usize,
+    format: &str,
+    counts: usize,
+) -> Result<Vec<u64>, ParseError> {
+    let mut tokens = tokens(line, offset).skip(1);
+    match tokens.next() {
+        Some((_, token)) if token == format => (),
+        Some((offset, token)) => return Err(ParseError::new(offset, token, &[format])),
+        None => return Err(ParseError::new(offset + line.len(), "", &[format])),
+    }
+    let mut numbers = Vec::new();
+    for (offset, token) in tokens {
+        match token.parse() {
+            Ok(number) => numbers.push(number),
+            Err(_) => return Err(ParseError::new(offset, token, &["a number"])),
+        }
+    }
+    if numbers.len() < counts {
+        return Err(ParseError::new(offset + line.len(), "", &["a number"]));
+    }
+    Ok(numbers)
+}
+
+/// Parse the rest of a line as signed variable numbers ended by 0, where `end` is the offset of the line end.
+pub(crate) fn clause_line<'a>(
+    tokens: impl Iterator<Item = (usize, &'a str)>,
+    end: usize,
+) -> Result<Vec<Variable>, ParseError> {
+    let mut variables = Vec::new();
+    let mut ended = false;
+    for (offset, token) in tokens {
+        if ended {
+            return Err(ParseError::new(offset, token, &["the end of the line"]));
+        }
+        match literal(token, offset)? {
+            Some(variable) => variables.push(variable),
+            None => ended = true,
+        }
+    }
+    if !ended {
+        return Err(ParseError::new(end, "", &["0"]));
+    }
+    Ok(variables)
+}
+
+/// Parse a signed variable number, or `None` for the 0 ending a clause.
+pub(crate) fn literal(token: &str, offset: usize) -> Result<Option<Variable>, ParseError> {
+    match token.parse::<i64>() {
+        Ok(0) => Ok(None),
+        Ok(number) => match Literal::try_from(number.unsigned_abs())
+            .ok()
+            .filter(|id| *id <= MAX_LITERAL)
+        {
+            Some(id) if number > 0 => Ok(Some(Variable::Positive(id))),
+            Some(id) => Ok(Some(Variable::Negative(id))),
+            None => Err(ParseError::new(offset, token, &["a smaller variable"])),
+        },
+        Err(_) => Err(ParseError::new(offset, token, &["a literal like -3", "0"])),
+    }
+}
+
+/// Write the signed variable numbers of a clause ended by 0 on a line.
+pub(crate) fn write_clause(output: &mut String, clause: &Clause) {
+    for variable in clause.iter() {
+        match variable {
+            Variable::Positive(id) => write!(output, "{} ", id).unwrap(),
+            Variable::Negative(id) => write!(output, "-{} ", id).unwrap(),
+        }
+    }
+    output.push_str("0\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solvers::Dfs;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_dimacs() {
+        let input = "c (x1 OR -x2) AND x3\np cnf 3 3\n1 -2 0\n  3\n0 -1 2\n%\n0\n";
+        let formula = parse_dimacs(input).unwrap().formula;
+        assert_eq!(formula.to_string(), "(X1 or -X2) and (X3) and (-X1 or X2)");
+        assert_eq!(
+            parse_dimacs(&write_dimacs(&formula)).map(|dimacs| dimacs.formula),
+            Ok(formula)
+        );
+        assert_eq!(
+            write_dimacs(&parse_dimacs("p cnf 2 1\n0\n").unwrap().formula),
+            "p cnf 0 1\n0\n"
+        );
+        // x2 and x4 are declared without being in a clause
+        let dimacs = parse_dimacs("p cnf 4 2\n1 0\n-3 5 0\n").unwrap();
+        assert_eq!(dimacs.declared, 4);
+        assert_eq!(dimacs.variables(), [1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_parse_dimacs_error() {
+        let error = parse_dimacs("p cnf 3 1\n1 x2 0\n").unwrap_err();
+        assert_eq!(error.span, 12..14);
+        assert_eq!(
+            error.to_string(),
+            "Unexpected x2, expected a literal like -3 or 0"
+        );
+        let error = parse_dimacs("1 2 0\n").unwrap_err();
+        assert_eq!(error.to_string(), "Unexpected 1, expected p cnf");
+        let error = parse_dimacs("p dnf 1 1\n").unwrap_err();
+        assert_eq!((error.span, error.token.as_str()), (2..5, "dnf"));
+        let error = parse_dimacs("p cnf 1\n").unwrap_err();
+        assert_eq!(error.to_string(), "Missing a number");
+        let error = parse_dimacs("p cnf 2147483647 1\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected 2147483647, expected a smaller number of variables"
+        );
+        let error = parse_dimacs("p cnf 1 1\n-2147483647 0\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected -2147483647, expected a smaller variable"
+        );
+    }
+
+    #[test]
+    fn test_write_result() {
+        let solution: Solution = [(1, true), (2, false), (3, true)][..].into();
+        let certificate = Certificate::Satisfiable(solution);
+        assert_eq!(write_result(&certificate), "s SATISFIABLE\nv 1 -2 3 0\n");
+        assert_eq!(exit_code(&certificate), 10);
+        assert_eq!(
+            write_result(&Certificate::Unsatisfiable),
+            "s UNSATISFIABLE\n"
+        );
+        assert_eq!(exit_code(&Certificate::Unsatisfiable), 20);
+        assert_eq!(exit_code(&Certificate::Indeterminate), 0);
+        // Long solutions are split over several lines
+        let solution: Solution = (1..=30).map(|id| (id, true)).collect::<Vec<_>>()[..].into();
+        let output = write_result(&Certificate::Satisfiable(solution));
+        let lines = output.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1..]
+            .iter()
+            .all(|line| line.starts_with("v ") && line.len() <= 80));
+        assert!(lines[2].ends_with(" 30 0"));
+    }
+
+    #[test]
+    fn test_read_dimacs() {
+        let input = "c (x1 OR -x2) AND x3\np cnf 3 3\n1 -2 0\n  3\n0 -1 2 0 -3\n%\n0\n";
+        let mut reports = Vec::new();
+        let dimacs =
+            read_dimacs(input.as_bytes(), |read, total| reports.push((read, total))).unwrap();
+        assert_eq!(dimacs.declared, 3);
+        assert_eq!(
+            dimacs.formula.to_string(),
+            "(X1 or -X2) and (X3) and (-X1 or X2) and (-X3)"
+        );
+        assert_eq!(reports, [(4, 3)]);
+        let mut reader = DimacsReader::new("p cnf 2 2\n1 0 2 0\n".as_bytes());
+        assert_eq!(reader.header(), None);
+        assert!(matches!(reader.next(), Some(Ok(clause)) if clause.literals() == [1]));
+        assert_eq!(reader.header(), Some((2, 2)));
+        assert!(matches!(reader.next(), Some(Ok(clause)) if clause.literals() == [2]));
+        assert!(reader.next().is_none());
+        let error = read_dimacs("p cnf 1 1\n\n1 x 0\n".as_bytes(), |_, _| ()).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3: Unexpected x, expected a literal like -3 or 0"
+        );
+        let ReadError::Parse { error, .. } = error else {
+            panic!("The error is in the input");
+        };
+        assert_eq!(error.span, 13..14);
+    }
+
+    #[test]
+    fn test_parse_wcnf() {
+        let new = "c comment\nh 1 2 0\n3 -1 0\n5 -2 0\n";
+        let old = "p wcnf 2 3 10\n10 1 2 0\n3 -1 0\n5 -2 0\n";
+        let wcnf = parse_wcnf(new).unwrap();
+        assert_eq!(wcnf.hard.to_string(), "(X1 or X2)");
+        assert_eq!(
+            wcnf.soft,
+            [
+                (3, Clause(vec![Variable::Negative(1)])),
+                (5, Clause(vec![Variable::Negative(2)]))
+            ]
+        );
+        assert_eq!(parse_wcnf(old), Ok(wcnf.clone()));
+        assert_eq!(
+            write_wcnf(&wcnf, WcnfFormat::New).unwrap(),
+            new.replace("c comment\n", "")
+        );
+        assert_eq!(
+            write_wcnf(&wcnf, WcnfFormat::Old).unwrap(),
+            old.replace(" 10", " 9").replace("10 ", "9 ")
+        );
+        // Without a top weight, every clause of the old format is soft
+        let wcnf = parse_wcnf("p wcnf 1 1\n4 1 0\n").unwrap();
+        assert_eq!(wcnf.soft.len(), 1);
+        let error = parse_wcnf("h 1 2\n").unwrap_err();
+        assert_eq!(error.span, 5..5);
+        assert_eq!(error.to_string(), "Missing 0");
+        // The soft weights must sum to at most i64::MAX
+        let error = parse_wcnf("9223372036854775807 1 0\n1 2 0\n").unwrap_err();
+        assert_eq!(error.span, 24..25);
+        let error = parse_wcnf("9223372036854775808 1 0\n").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected 9223372036854775808, expected a smaller weight"
+        );
+        // Weights set directly are checked when solving and writing
+        let wcnf = Wcnf {
+            hard: Formula::new(),
+            soft: vec![
+                (u64::MAX, Clause(vec![Variable::Positive(1)])),
+                (1, Clause(vec![Variable::Negative(1)])),
+            ],
+        };
+        assert_eq!(write_wcnf(&wcnf, WcnfFormat::Old), Err(WeightOverflow));
+        assert!(write_wcnf(&wcnf, WcnfFormat::New).is_ok());
+        assert_eq!(wcnf.solve(), Err(WeightOverflow));
+    }
+
+    #[test]
+    fn test_wcnf_solve() {
+        let wcnf = parse_wcnf("h 1 2 0\n3 -1 0\n5 -2 0\n2 -1 -2 0\n").unwrap();
+        let (solution, cost) = wcnf.solve().unwrap().unwrap();
+        assert_eq!(cost, 3);
+        assert_eq!(solution.to_string(), "X1 = T, X2 = F");
+        let wcnf = parse_wcnf("h 1 0\nh -1 0\n").unwrap();
+        assert_eq!(wcnf.solve(), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_icnf() {
+        let input = "c trace\np inccnf\n1 2 0\na -1 0\n-2 0\na 0\na -1 0\n";
+        let steps = parse_icnf(input).unwrap();
+        assert_eq!(steps.len(), 5);
+        assert_eq!(steps[1], IcnfStep::Solve(vec![Variable::Negative(1)]));
+        assert_eq!(write_icnf(&steps), input.replace("c trace\n", ""));
+        let certificates = replay_icnf(&steps, &Dfs::default());
+        let Certificate::Satisfiable(solution) = &certificates[0] else {
+            panic!("Expected a solution");
+        };
+        assert!(!solution.get(1) && solution.get(2));
+        assert!(matches!(&certificates[1], Certificate::Satisfiable(solution) if solution.get(1)));
+        assert_eq!(certificates[2], Certificate::Unsatisfiable);
+        let error = parse_icnf("p inccnf\na 1 2\n").unwrap_err();
+        assert_eq!(error.to_string(), "Missing 0");
+        let error = parse_icnf("1 0\n").unwrap_err();
+        assert_eq!(error.to_string(), "Unexpected 1, expected p inccnf");
+    }
+}
@@ -0,0 +1,793 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    solver::solve_assuming,
+    solvers::Solver,
+    types::{Certificate, Clause, Formula, Literal, Solution, VarPool, Variable},
+};
+
+/// The encoding used by [`at_most_one`], trading clauses for auxiliary variables.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Strategy {
+    /// A binary clause for every pair, `O(n^2)` clauses and no auxiliary variables.
+    Pairwise,
+    /// Commander variables for groups of the given size, with at most one commander recursively.
+    Commander(usize),
+    /// The given number of groups, each with pairwise clauses and a binary encoded group index.
+    Bimander(usize),
+}
+
+/// Encode that at most one of the variables is true.
+///
+/// ## Examples
+/// ```plaintext
+/// at most one of x1, x2, x3 pairwise
+/// (-x1 OR -x2) AND (-x1 OR -x3) AND (-x2 OR -x3)
+/// ```
+pub fn at_most_one(lits: &[Variable], strategy: Strategy, pool: &mut VarPool) -> Vec<Clause> {
+    for lit in lits {
+        pool.reserve(lit.id());
+    }
+    let mut clauses = Vec::new();
+    match strategy {
+        Strategy::Pairwise => pairwise(lits, &mut clauses),
+        Strategy::Commander(size) => commander(lits, size.max(2), pool, &mut clauses),
+        Strategy::Bimander(groups) => bimander(lits, groups.max(1), pool, &mut clauses),
+    }
+    clauses
+}
+
+fn pairwise(lits: &[Variable], clauses: &mut Vec<Clause>) {
+    for (i, a) in lits.iter().enumerate() {
+        for b in &lits[i + 1..] {
+            clauses.push(Clause(vec![a.negated(), b.negated()]));
+        }
+    }
+}
+
+/// The commander encoding of Klieber and Kwon (2007).
+fn commander(lits: &[Variable], size: usize, pool: &mut VarPool, clauses: &mut Vec<Clause>) {
+    if lits.len() <= size {
+        return pairwise(lits, clauses);
+    }
+    let mut commanders = Vec::new();
+    for group in lits.chunks(size) {
+        let c = Variable::Positive(pool.fresh());
+        pairwise(group, clauses);
+        // The commander is true exactly when one in its group is
+        let mut some = vec![c.negated()];
+        some.extend(group);
+        clauses.push(Clause(some));
+        for lit in group {
+            clauses.push(Clause(vec![lit.negated(), c]));
+        }
+        commanders.push(c);
+    }
+    commander(&commanders, size, pool, clauses);
+}
+
+/// The bimander encoding of Nguyen and Mai (2015).
+fn bimander(lits: &[Variable], groups: usize, pool: &mut VarPool, clauses: &mut Vec<Clause>) {
+    let size = lits.len().div_ceil(groups).max(1);
+    let count = lits.len().div_ceil(size);
+    let bits = (0..usize::BITS - count.saturating_sub(1).leading_zeros())
+        .map(|_| pool.fresh())
+        .collect::<Vec<_>>();
+    for (i, group) in lits.chunks(size).enumerate() {
+        pairwise(group, clauses);
+        // Every true variable forces the bits to the index of its group
+        for lit in group {
+            for (j, &bit) in bits.iter().enumerate() {
+                let bit = if (i >> j) & 1 == 1 {
+                    Variable::Positive(bit)
+                } else {
+                    Variable::Negative(bit)
+                };
+                clauses.push(Clause(vec![lit.negated(), bit]));
+            }
+        }
+    }
+}
+
+/// Encode that at most `k` of the variables are true with the sequential counter of Sinz (2005).
+/// Auxiliary register variables from the pool count the true variables so far, with `s(i, j)`
+/// true when at least `j` of the first `i` variables are true, giving `O(n k)` clauses and variables.
+///
+/// ## Examples
+/// ```plaintext
+/// at most 1 of x1, x2, x3
+/// (-x1 OR s1) AND (-x2 OR s2) AND (-s1 OR s2) AND (-x2 OR -s1) AND (-x3 OR -s2)
+/// ```
+pub fn at_most_k_seq(lits: &[Variable], k: usize, pool: &mut VarPool) -> Vec<Clause> {
+    let n = lits.len();
+    if k >= n {
+        return Vec::new();
+    }
+    if k == 0 {
+        return lits.iter().map(|lit| Clause(vec![lit.negated()])).collect();
+    }
+    for lit in lits {
+        pool.reserve(lit.id());
+    }
+    // The registers s(i, j) for the first n - 1 variables
+    let s = (0..n - 1)
+        .map(|_| (0..k).map(|_| pool.fresh()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let pos = |i: usize, j: usize| Variable::Positive(s[i][j]);
+    let neg = |i: usize, j: usize| Variable::Negative(s[i][j]);
+    let mut clauses = vec![Clause(vec![lits[0].negated(), pos(0, 0)])];
+    for j in 1..k {
+        clauses.push(Clause(vec![neg(0, j)]));
+    }
+    for (i, lit) in lits.iter().enumerate().take(n - 1).skip(1) {
+        clauses.push(Clause(vec![lit.negated(), pos(i, 0)]));
+        clauses.push(Clause(vec![neg(i - 1, 0), pos(i, 0)]));
+        for j in 1..k {
+            clauses.push(Clause(vec![lit.negated(), neg(i - 1, j - 1), pos(i, j)]));
+            clauses.push(Clause(vec![neg(i - 1, j), pos(i, j)]));
+        }
+        clauses.push(Clause(vec![lit.negated(), neg(i - 1, k - 1)]));
+    }
+    clauses.push(Clause(vec![lits[n - 1].negated(), neg(n - 2, k - 1)]));
+    clauses
+}
+
+/// The totalizer encoding of Bailleux and Boufkhad (2003) counts the true variables in unary,
+/// with a binary tree of auxiliary variables from the pool where each node has an output per count.
+/// Output `j` of the root is true whenever at least `j + 1` of the variables are true.
+///
+/// Bounds are set by unit clauses on the outputs, so they can be tightened incrementally
+/// without re-encoding, like when core-guided MaxSAT lowers the allowed cost.
+///
+/// ## Examples
+/// ```plaintext
+/// x1, x2 with outputs o1, o2
+/// (-x1 OR o1) AND (-x2 OR o1) AND (-x1 OR -x2 OR o2)
+/// at most 1: -o2
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Totalizer {
+    outputs: Vec<Variable>,
+    clauses: Vec<Clause>,
+}
+
+impl Totalizer {
+    pub fn new(lits: &[Variable], pool: &mut VarPool) -> Self {
+        for lit in lits {
+            pool.reserve(lit.id());
+        }
+        let mut clauses = Vec::new();
+        let outputs = Self::node(lits, pool, &mut clauses);
+        Self { outputs, clauses }
+    }
+
+    /// Build the subtree counting the variables, returning its outputs.
+    fn node(lits: &[Variable], pool: &mut VarPool, clauses: &mut Vec<Clause>) -> Vec<Variable> {
+        if lits.len() <= 1 {
+            return lits.to_vec();
+        }
+        let (left, right) = lits.split_at(lits.len() / 2);
+        let a = Self::node(left, pool, clauses);
+        let b = Self::node(right, pool, clauses);
+        let outputs = (0..lits.len())
+            .map(|_| Variable::Positive(pool.fresh()))
+            .collect::<Vec<_>>();
+        // At least i on the left and j on the right means at least i + j in total
+        for i in 0..=a.len() {
+            for j in 0..=b.len() {
+                if i + j == 0 {
+                    continue;
+                }
+                let mut clause = Vec::new();
+                if i > 0 {
+                    clause.push(a[i - 1].negated());
+                }
+                if j > 0 {
+                    clause.push(b[j - 1].negated());
+                }
+                clause.push(outputs[i + j - 1]);
+                clauses.push(Clause(clause));
+            }
+        }
+        outputs
+    }
+
+    /// Get the clauses defining the outputs.
+    pub fn clauses(&self) -> &[Clause] {
+        &self.clauses
+    }
+
+    /// Get the outputs, where output `j` is true whenever at least `j + 1` variables are true.
+    pub fn outputs(&self) -> &[Variable] {
+        &self.outputs
+    }
+
+    /// Get the unit clause limiting the count to at most `k`, or `None` if it can't be exceeded.
+    pub fn at_most(&self, k: usize) -> Option<Clause> {
+        self.outputs
+            .get(k)
+            .map(|output| Clause(vec![output.negated()]))
+    }
+}
+
+/// Encode that at most `k` of the variables are true with a [`Totalizer`].
+pub fn at_most_k_totalizer(lits: &[Variable], k: usize, pool: &mut VarPool) -> Vec<Clause> {
+    let totalizer = Totalizer::new(lits, pool);
+    let mut clauses = totalizer.clauses().to_vec();
+    clauses.extend(totalizer.at_most(k));
+    clauses
+}
+
+/// Encode that at least `k` of the variables are true, as at most `n - k` of them being false.
+pub fn at_least_k(lits: &[Variable], k: usize, pool: &mut VarPool) -> Vec<Clause> {
+    between(lits, k, lits.len(), pool)
+}
+
+/// Encode that exactly `k` of the variables are true.
+///
+/// ## Examples
+/// ```plaintext
+/// exactly 1 of x1, x2
+/// (-x1 OR -x2) AND (x1 OR x2)
+/// ```
+pub fn exactly_k(lits: &[Variable], k: usize, pool: &mut VarPool) -> Vec<Clause> {
+    between(lits, k, k, pool)
+}
+
+/// Encode that between `lo` and `hi` of the variables are true, inclusive.
+/// An impossible range gives the empty clause.
+pub fn between(lits: &[Variable], lo: usize, hi: usize, pool: &mut VarPool) -> Vec<Clause> {
+    if lo > hi || lo > lits.len() {
+        return vec![Clause(Vec::new())];
+    }
+    let negated = lits.iter().map(|lit| lit.negated()).collect::<Vec<_>>();
+    let mut clauses = at_most_k_seq(lits, hi, pool);
+    clauses.extend(at_most_k_seq(&negated, lits.len() - lo, pool));
+    clauses
+}
+
+/// The comparison of a [`PbConstraint`] between its weighted sum and its bound.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Comparison {
+    AtLeast,
+    AtMost,
+    Equal,
+}
+
+/// A linear pseudo-Boolean constraint, comparing the sum of the weights of the true literals to a bound.
+/// Weights and the bound may be negative.
+///
+/// ## Examples
+/// ```plaintext
+/// 3 x1 + 2 x2 - x3 >= 2
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PbConstraint {
+    pub terms: Vec<(i64, Variable)>,
+    pub comparison: Comparison,
+    pub bound: i64,
+}
+
+impl PbConstraint {
+    /// Check if the constraint holds in a solution.
+    pub fn satisfied(&self, solution: &Solution) -> bool {
+        let sum = self
+            .terms
+            .iter()
+            .filter(|(_, lit)| solution.get(lit.id()) == lit.is_positive())
+            .map(|(weight, _)| weight)
+            .sum::<i64>();
+        match self.comparison {
+            Comparison::AtLeast => sum >= self.bound,
+            Comparison::AtMost => sum <= self.bound,
+            Comparison::Equal => sum == self.bound,
+        }
+    }
+
+    /// Encode the constraint in CNF by a reduced decision diagram over its literals,
+    /// with an auxiliary variable for every node that is not a literal or a constant.
+    /// An impossible constraint gives the empty clause, and a trivial one no clauses.
+    pub fn to_cnf(&self, pool: &mut VarPool) -> Vec<Clause> {
+        for (_, lit) in &self.terms {
+            pool.reserve(lit.id());
+        }
+        let negated = self
+            .terms
+            .iter()
+            .map(|(weight, lit)| (-weight, *lit))
+            .collect::<Vec<_>>();
+        match self.comparison {
+            Comparison::AtLeast => pb_at_least(&self.terms, self.bound, pool),
+            Comparison::AtMost => pb_at_least(&negated, -self.bound, pool),
+            Comparison::Equal => {
+                let mut clauses = pb_at_least(&self.terms, self.bound, pool);
+                clauses.extend(pb_at_least(&negated, -self.bound, pool));
+                clauses
+            }
+        }
+    }
+}
+
+/// A node of the decision diagram of a pseudo-Boolean constraint.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Node {
+    True,
+    False,
+    Literal(Variable),
+}
+
+/// Encode that the weighted sum of the true literals is at least the bound.
+fn pb_at_least(terms: &[(i64, Variable)], bound: i64, pool: &mut VarPool) -> Vec<Clause> {
+    // A negative weight counts when the literal is false, so it is moved to the negated literal
+    let mut bound = bound;
+    let mut positive = Vec::new();
+    for (weight, lit) in terms {
+        if *weight < 0 {
+            bound = bound.saturating_sub(*weight);
+            positive.push((-weight, lit.negated()));
+        } else if *weight > 0 {
+            positive.push((*weight, *lit));
+        }
+    }
+    // Deciding the largest weights first keeps the diagram small
+    positive.sort_by_key(|(weight, _)| std::cmp::Reverse(*weight));
+    let mut remaining = vec![0i64; positive.len() + 1];
+    for i in (0..positive.len()).rev() {
+        remaining[i] = remaining[i + 1].saturating_add(positive[i].0);
+    }
+    let mut diagram = Diagram {
+        terms: &positive,
+        remaining: &remaining,
+        nodes: HashMap::new(),
+        clauses: Vec::new(),
+    };
+    match diagram.node(0, bound, pool) {
+        Node::True => Vec::new(),
+        Node::False => vec![Clause(Vec::new())],
+        Node::Literal(root) => {
+            let mut clauses = diagram.clauses;
+            clauses.push(Clause(vec![root]));
+            clauses
+        }
+    }
+}
+
+/// The decision diagram of a pseudo-Boolean constraint with positive weights, sorted by weight.
+struct Diagram<'a> {
+    terms: &'a [(i64, Variable)],
+    /// The sum of the weights from each term to the end.
+    remaining: &'a [i64],
+    nodes: HashMap<(usize, i64), Node>,
+    clauses: Vec<Clause>,
+}
+
+impl Diagram<'_> {
+    /// Get the node for the terms from `i` reaching a sum of at least `bound`,
+    /// adding clauses so that the node implies it.
+    fn node(&mut self, i: usize, bound: i64, pool: &mut VarPool) -> Node {
+        if bound <= 0 {
+            return Node::True;
+        }
+        if self.remaining[i] < bound {
+            return Node::False;
+        }
+        if let Some(node) = self.nodes.get(&(i, bound)) {
+            return *node;
+        }
+        let (weight, lit) = self.terms[i];
+        let high = self.node(i + 1, bound - weight, pool);
+        let low = self.node(i + 1, bound, pool);
+        let node = match (high, low) {
+            // Reaching the bound without the literal means reaching it with the literal too
+            (_, Node::True) => Node::True,
+            (Node::False, _) => Node::False,
+            (Node::True, Node::False) => Node::Literal(lit),
+            _ => {
+                let node = Variable::Positive(pool.fresh());
+                let mut unless_low = vec![node.negated(), lit];
+                if let Node::Literal(low) = low {
+                    unless_low.push(low);
+                }
+                self.clauses.push(Clause(unless_low));
+                if let Node::Literal(high) = high {
+                    self.clauses.push(Clause(vec![node.negated(), high]));
+                }
+                Node::Literal(node)
+            }
+        };
+        self.nodes.insert((i, bound), node);
+        node
+    }
+}
+
+/// A Sudoku grid of rows of cells, with the digits 1 to 9 or `None` for empty cells.
+pub type Grid = [[Option<u8>; 9]; 9];
+
+/// Get the variable of a Sudoku cell having a digit, for rows and columns from 0 to 8 and digits from 1 to 9.
+pub fn sudoku_var(row: usize, col: usize, digit: u8) -> Literal {
+    (row * 81 + col * 9 + digit as usize) as Literal
+}
+
+/// Encode a Sudoku puzzle, where every cell has exactly one digit, every row, column and box
+/// has each digit exactly once, and the given digits are unit clauses.
+/// The variables are numbered by [`sudoku_var`], and models are decoded by [`sudoku_decode`].
+///
+/// ## Panics
+/// If a cell has a digit outside 1 to 9.
+///
+/// ## Examples
+/// ```plaintext
+/// cell (0, 0) has a digit:   (x1 OR x2 OR ... OR x9)
+/// cell (0, 0) has one digit: (-x1 OR -x2) AND (-x1 OR -x3) AND ...
+/// cell (0, 0) is given 5:    (x5)
+/// ```
+pub fn sudoku(grid: Grid) -> Formula {
+    let mut pool = VarPool::new();
+    pool.reserve(sudoku_var(8, 8, 9));
+    let mut formula = Formula::new();
+    let mut exactly_one = |cells: &mut dyn Iterator<Item = (usize, usize, u8)>| {
+        let lits = cells
+            .map(|(row, col, digit)| Variable::Positive(sudoku_var(row, col, digit)))
+            .collect::<Vec<_>>();
+        formula.add(Clause(lits.clone()));
+        for clause in at_most_one(&lits, Strategy::Pairwise, &mut pool) {
+            formula.add(clause);
+        }
+    };
+    for i in 0..9 {
+        for j in 0..9 {
+            exactly_one(&mut (1..=9).map(|digit| (i, j, digit)));
+        }
+    }
+    for digit in 1..=9 {
+        for i in 0..9 {
+            exactly_one(&mut (0..9).map(|col| (i, col, digit)));
+            exactly_one(&mut (0..9).map(|row| (row, i, digit)));
+            exactly_one(&mut (0..9).map(|k| (i / 3 * 3 + k / 3, i % 3 * 3 + k % 3, digit)));
+        }
+    }
+    for (row, cells) in grid.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            if let Some(digit) = *cell {
+                assert!(
+                    (1..=9).contains(&digit),
+                    "Sudoku digit {} is not 1 to 9",
+                    digit
+                );
+                formula.add(Clause(vec![Variable::Positive(sudoku_var(
+                    row, col, digit,
+                ))]));
+            }
+        }
+    }
+    formula
+}
+
+/// Get the solved grid from a model of a formula encoded by [`sudoku`].
+pub fn sudoku_decode(solution: &Solution) -> [[u8; 9]; 9] {
+    let mut grid = [[0; 9]; 9];
+    for (row, cells) in grid.iter_mut().enumerate() {
+        for (col, cell) in cells.iter_mut().enumerate() {
+            *cell = (1..=9)
+                .find(|digit| solution.get(sudoku_var(row, col, *digit)))
+                .unwrap_or(0);
+        }
+    }
+    grid
+}
+
+/// Encode that at least `k` vertices of a graph are chosen, where every two chosen vertices are adjacent.
+/// The vertices are numbered from 0, and vertex `v` is chosen by the variable `v + 1`.
+///
+/// ## Examples
+/// ```plaintext
+/// a clique of 2 in the path 0 - 1 - 2
+/// (-x1 OR -x3) AND at least 2 of x1, x2, x3
+/// ```
+pub fn clique(
+    vertices: usize,
+    edges: &[(usize, usize)],
+    k: usize,
+    pool: &mut VarPool,
+) -> Vec<Clause> {
+    let adjacent = adjacency(edges);
+    choose_vertices(vertices, |u, v| !adjacent.contains(&(u, v)), k, pool)
+}
+
+/// Encode that at least `k` vertices of a graph are chosen, where no two chosen vertices are adjacent.
+/// The vertices are numbered like in [`clique`].
+pub fn independent_set(
+    vertices: usize,
+    edges: &[(usize, usize)],
+    k: usize,
+    pool: &mut VarPool,
+) -> Vec<Clause> {
+    let adjacent = adjacency(edges);
+    choose_vertices(vertices, |u, v| adjacent.contains(&(u, v)), k, pool)
+}
+
+/// Find a largest clique of a graph by solving for ever larger cliques until there are none.
+/// If the solver gives up, the largest clique found so far is returned.
+pub fn max_clique<S: Solver + ?Sized>(
+    vertices: usize,
+    edges: &[(usize, usize)],
+    solver: &S,
+) -> Vec<usize> {
+    maximize(vertices, solver, |k, pool| clique(vertices, edges, k, pool))
+}
+
+/// Find a largest independent set of a graph like [`max_clique`].
+pub fn max_independent_set<S: Solver + ?Sized>(
+    vertices: usize,
+    edges: &[(usize, usize)],
+    solver: &S,
+) -> Vec<usize> {
+    maximize(vertices, solver, |k, pool| {
+        independent_set(vertices, edges, k, pool)
+    })
+}
+
+/// Get both directions of every edge.
+fn adjacency(edges: &[(usize, usize)]) -> HashSet<(usize, usize)> {
+    edges.iter().flat_map(|&(u, v)| [(u, v), (v, u)]).collect()
+}
+
+/// Encode choosing at least `k` vertices, where no two conflicting vertices are both chosen.
+fn choose_vertices(
+    vertices: usize,
+    conflict: impl Fn(usize, usize) -> bool,
+    k: usize,
+    pool: &mut VarPool,
+) -> Vec<Clause> {
+    let var = |v: usize| Variable::Positive(v as Literal + 1);
+    let mut clauses = Vec::new();
+    for u in 0..vertices {
+        for v in u + 1..vertices {
+            if conflict(u, v) {
+                clauses.push(Clause(vec![var(u).negated(), var(v).negated()]));
+            }
+        }
+    }
+    let lits = (0..vertices).map(var).collect::<Vec<_>>();
+    clauses.extend(at_least_k(&lits, k, pool));
+    clauses
+}
+
+/// Find the largest set of vertices chosen by an encoding, asking for one more vertex than the last model had.
+fn maximize<S: Solver + ?Sized>(
+    vertices: usize,
+    solver: &S,
+    encode: impl Fn(usize, &mut VarPool) -> Vec<Clause>,
+) -> Vec<usize> {
+    let mut best = Vec::new();
+    while best.len() < vertices {
+        let mut formula = Formula::new();
+        encode(best.len() + 1, &mut VarPool::new())
+            .into_iter()
+            .for_each(|clause| formula.add(clause));
+        match solve_assuming(&formula, &[], solver) {
+            Certificate::Satisfiable(model) => {
+                best = (0..vertices)
+                    .filter(|v| model.get(*v as Literal + 1))
+                    .collect();
+            }
+            _ => break,
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        solver::{count_models_projected, solve_propagated},
+        solvers::Dfs,
+        types::Certificate,
+    };
+
+    use super::*;
+
+    /// Count the assignments to `n` variables satisfying the clauses, projecting out auxiliary variables.
+    /// The model counter counts the unconstrained variables too, without enumerating the models.
+    fn count(n: u32, clauses: Vec<Clause>) -> usize {
+        let projection = (1..=n).collect::<Vec<_>>();
+        count_models_projected(&Formula::from(clauses), &projection) as usize
+    }
+
+    /// The number of ways to choose at most `k` of `n` items.
+    fn at_most(n: usize, k: usize) -> usize {
+        (0..=k.min(n)).map(|i| choose(n, i)).sum()
+    }
+
+    fn choose(n: usize, k: usize) -> usize {
+        (0..k).fold(1, |c, i| c * (n - i) / (i + 1))
+    }
+
+    #[test]
+    fn test_at_most_k_seq() {
+        let lits = (1..=5).map(Variable::Positive).collect::<Vec<_>>();
+        for k in 0..=5 {
+            let clauses = at_most_k_seq(&lits, k, &mut VarPool::new());
+            assert_eq!(count(5, clauses), at_most(5, k), "k = {}", k);
+        }
+        // Negative literals count when their variable is false
+        let lits = [Variable::Negative(1), Variable::Positive(2)];
+        let clauses = at_most_k_seq(&lits, 1, &mut VarPool::new());
+        assert_eq!(count(2, clauses), 3);
+    }
+
+    #[test]
+    fn test_at_most_k_totalizer() {
+        let lits = (1..=6).map(Variable::Positive).collect::<Vec<_>>();
+        for k in 0..=6 {
+            let clauses = at_most_k_totalizer(&lits, k, &mut VarPool::new());
+            assert_eq!(count(6, clauses), at_most(6, k), "k = {}", k);
+        }
+        let lits = [Variable::Negative(1), Variable::Positive(2)];
+        let clauses = at_most_k_totalizer(&lits, 1, &mut VarPool::new());
+        assert_eq!(count(2, clauses), 3);
+    }
+
+    #[test]
+    fn test_totalizer_tightening() {
+        let lits = (1..=4).map(Variable::Positive).collect::<Vec<_>>();
+        let totalizer = Totalizer::new(&lits, &mut VarPool::new());
+        assert_eq!(totalizer.outputs().len(), 4);
+        let mut clauses = totalizer.clauses().to_vec();
+        for k in (0..4).rev() {
+            clauses.push(totalizer.at_most(k).unwrap());
+            assert_eq!(count(4, clauses.clone()), at_most(4, k), "k = {}", k);
+        }
+        assert_eq!(totalizer.at_most(4), None);
+    }
+
+    #[test]
+    fn test_at_most_one() {
+        let lits = (1..=7).map(Variable::Positive).collect::<Vec<_>>();
+        for strategy in [
+            Strategy::Pairwise,
+            Strategy::Commander(2),
+            Strategy::Commander(3),
+            Strategy::Bimander(1),
+            Strategy::Bimander(3),
+            Strategy::Bimander(7),
+        ] {
+            let clauses = at_most_one(&lits, strategy, &mut VarPool::new());
+            assert_eq!(count(7, clauses), 8, "{:?}", strategy);
+        }
+        let clauses = at_most_one(&lits[..1], Strategy::Commander(2), &mut VarPool::new());
+        assert!(clauses.is_empty());
+    }
+
+    #[test]
+    fn test_pb_constraint() {
+        // 3 x1 + 2 x2 - x3 + 2 -x4
+        let terms = vec![
+            (3, Variable::Positive(1)),
+            (2, Variable::Positive(2)),
+            (-1, Variable::Positive(3)),
+            (2, Variable::Negative(4)),
+        ];
+        let sum = |m: u32| {
+            [3, 2, -1, 2][..]
+                .iter()
+                .enumerate()
+                .fold(0, |sum, (i, weight)| {
+                    let value = m >> i & 1 == 1;
+                    if value == (i != 3) {
+                        sum + weight
+                    } else {
+                        sum
+                    }
+                })
+        };
+        for comparison in [Comparison::AtLeast, Comparison::AtMost, Comparison::Equal] {
+            for bound in -2..=8 {
+                let constraint = PbConstraint {
+                    terms: terms.clone(),
+                    comparison,
+                    bound,
+                };
+                let expected = (0..16u32)
+                    .filter(|m| match comparison {
+                        Comparison::AtLeast => sum(*m) >= bound,
+                        Comparison::AtMost => sum(*m) <= bound,
+                        Comparison::Equal => sum(*m) == bound,
+                    })
+                    .count();
+                let clauses = constraint.to_cnf(&mut VarPool::new());
+                assert_eq!(count(4, clauses), expected, "{:?} {}", comparison, bound);
+            }
+        }
+        let constraint = PbConstraint {
+            terms,
+            comparison: Comparison::Equal,
+            bound: 4,
+        };
+        let formula = Formula::from(constraint.to_cnf(&mut VarPool::new()));
+        let Certificate::Satisfiable(solution) = solve_propagated(&formula, &Dfs::default()) else {
+            panic!("3 + 2 - 1 = 4");
+        };
+        assert!(constraint.satisfied(&solution));
+    }
+
+    #[test]
+    fn test_between() {
+        let lits = (1..=3).map(Variable::Positive).collect::<Vec<_>>();
+        for lo in 0..=4 {
+            for hi in 0..=4 {
+                let clauses = between(&lits, lo, hi, &mut VarPool::new());
+                // Brute force over all assignments
+                let expected = (0..8u32)
+                    .filter(|m| (lo..=hi).contains(&(m.count_ones() as usize)))
+                    .count();
+                assert_eq!(count(3, clauses), expected, "{}..={}", lo, hi);
+            }
+        }
+        for k in 0..=3 {
+            let clauses = exactly_k(&lits, k, &mut VarPool::new());
+            assert_eq!(count(3, clauses), choose(3, k), "k = {}", k);
+            let clauses = at_least_k(&lits, k, &mut VarPool::new());
+            assert_eq!(count(3, clauses), 8 - at_most(3, k) + choose(3, k));
+        }
+    }
+
+    #[test]
+    fn test_sudoku() {
+        let puzzle = [
+            "53..7....",
+            "6..195...",
+            ".98....6.",
+            "8...6...3",
+            "4..8.3..1",
+            "7...2...6",
+            ".6....28.",
+            "...419..5",
+            "....8..79",
+        ];
+        let mut grid: Grid = [[None; 9]; 9];
+        for (row, line) in puzzle.iter().enumerate() {
+            for (col, c) in line.chars().enumerate() {
+                grid[row][col] = c.to_digit(10).map(|digit| digit as u8);
+            }
+        }
+        let formula = sudoku(grid);
+        let Certificate::Satisfiable(solution) = solve_propagated(&formula, &Dfs::default()) else {
+            panic!("Sudoku has no solution");
+        };
+        let solved = sudoku_decode(&solution);
+        assert_eq!(solved[0], [5, 3, 4, 6, 7, 8, 9, 1, 2]);
+        assert_eq!(solved[8], [3, 4, 5, 2, 8, 6, 1, 7, 9]);
+        for i in 0..9 {
+            let mut row = solved[i].to_vec();
+            let mut col = solved.iter().map(|row| row[i]).collect::<Vec<_>>();
+            row.sort();
+            col.sort();
+            assert_eq!(row, (1..=9).collect::<Vec<_>>());
+            assert_eq!(col, (1..=9).collect::<Vec<_>>());
+        }
+        // Two equal digits in a row have no solution
+        grid[0][2] = Some(5);
+        let formula = sudoku(grid);
+        assert_eq!(
+            solve_propagated(&formula, &Dfs::default()),
+            Certificate::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn test_max_clique() {
+        // A triangle 0 - 1 - 2 with a tail 2 - 3 - 4
+        let edges = [(0, 1), (1, 2), (2, 0), (2, 3), (3, 4)];
+        assert_eq!(max_clique(5, &edges, &Dfs::default()), [0, 1, 2]);
+        let set = max_independent_set(5, &edges, &Dfs::default());
+        assert_eq!(set.len(), 2);
+        for (u, v) in edges {
+            assert!(!(set.contains(&u) && set.contains(&v)), "{:?}", set);
+        }
+        assert_eq!(max_clique(0, &[], &Dfs::default()), Vec::<usize>::new());
+        // There are 3 ways to choose a clique of 2 in the path 0 - 1 - 2 - 3
+        let clauses = clique(4, &[(0, 1), (1, 2), (2, 3)], 2, &mut VarPool::new());
+        assert_eq!(count(4, clauses), 3);
+    }
+}
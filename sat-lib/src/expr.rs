@@ -0,0 +1,725 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{BitAnd, BitOr, BitXor, Not},
+};
+
+use crate::types::{Clause, Formula, Literal, Solution, VarPool, Variable};
+
+/// A propositional expression with any nesting of connectives, not limited to CNF.
+/// Expressions are built from variables with the methods or the operators `!`, `&`, `|` and `^`,
+/// and flattened as they are built, so `a & b & c` is a single conjunction.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 -> x2) AND (x2 <-> -x3)
+/// ```
+/// Built as:
+/// ```rust,ignore
+/// Expr::var(1).implies(Expr::var(2)) & Expr::var(2).iff(!Expr::var(3))
+/// ```
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum Expr {
+    Const(bool),
+    Var(Literal),
+    Not(Box<Expr>),
+    /// The conjunction of all operands, true if there are none.
+    And(Vec<Expr>),
+    /// The disjunction of all operands, false if there are none.
+    Or(Vec<Expr>),
+    Implies(Box<Expr>, Box<Expr>),
+    Iff(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    pub fn var(id: Literal) -> Self {
+        Expr::Var(id)
+    }
+
+    pub fn constant(value: bool) -> Self {
+        Expr::Const(value)
+    }
+
+    pub fn and(self, other: Expr) -> Self {
+        Expr::all([self, other])
+    }
+
+    pub fn or(self, other: Expr) -> Self {
+        Expr::any([self, other])
+    }
+
+    pub fn implies(self, other: Expr) -> Self {
+        Expr::Implies(Box::new(self), Box::new(other))
+    }
+
+    pub fn iff(self, other: Expr) -> Self {
+        Expr::Iff(Box::new(self), Box::new(other))
+    }
+
+    pub fn xor(self, other: Expr) -> Self {
+        Expr::Xor(Box::new(self), Box::new(other))
+    }
+
+    /// Get the conjunction of the expressions, merging nested conjunctions into it.
+    pub fn all(operands: impl IntoIterator<Item = Expr>) -> Self {
+        let mut flat = Vec::new();
+        for operand in operands {
+            match operand {
+                Expr::And(operands) => flat.extend(operands),
+                operand => flat.push(operand),
+            }
+        }
+        Expr::And(flat)
+    }
+
+    /// Get the disjunction of the expressions, merging nested disjunctions into it.
+    pub fn any(operands: impl IntoIterator<Item = Expr>) -> Self {
+        let mut flat = Vec::new();
+        for operand in operands {
+            match operand {
+                Expr::Or(operands) => flat.extend(operands),
+                operand => flat.push(operand),
+            }
+        }
+        Expr::Or(flat)
+    }
+
+    /// Get all variables in the expression, sorted and without duplicates.
+    pub fn literals(&self) -> Vec<Literal> {
+        let mut variables = Vec::new();
+        self.collect(&mut variables);
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+
+    fn collect(&self, variables: &mut Vec<Literal>) {
+        match self {
+            Expr::Const(_) => (),
+            Expr::Var(id) => variables.push(*id),
+            Expr::Not(operand) => operand.collect(variables),
+            Expr::And(operands) | Expr::Or(operands) => operands
+                .iter()
+                .for_each(|operand| operand.collect(variables)),
+            Expr::Implies(a, b) | Expr::Iff(a, b) | Expr::Xor(a, b) => {
+                a.collect(variables);
+                b.collect(variables);
+            }
+        }
+    }
+
+    /// Replace every occurrence of a variable by an expression.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR x2) AND -x1 with x1 = x3 AND x4
+    /// ((x3 AND x4) OR x2) AND -(x3 AND x4)
+    /// ```
+    pub fn substitute(&self, id: Literal, expr: &Expr) -> Expr {
+        let substitute = |operand: &Expr| Box::new(operand.substitute(id, expr));
+        match self {
+            Expr::Var(x) if *x == id => expr.clone(),
+            Expr::Const(_) | Expr::Var(_) => self.clone(),
+            Expr::Not(operand) => Expr::Not(substitute(operand)),
+            Expr::And(operands) => Expr::And(
+                operands
+                    .iter()
+                    .map(|operand| operand.substitute(id, expr))
+                    .collect(),
+            ),
+            Expr::Or(operands) => Expr::Or(
+                operands
+                    .iter()
+                    .map(|operand| operand.substitute(id, expr))
+                    .collect(),
+            ),
+            Expr::Implies(a, b) => Expr::Implies(substitute(a), substitute(b)),
+            Expr::Iff(a, b) => Expr::Iff(substitute(a), substitute(b)),
+            Expr::Xor(a, b) => Expr::Xor(substitute(a), substitute(b)),
+        }
+    }
+
+    /// Convert the expression to negation normal form, where only variables are negated
+    /// and the only connectives are conjunction and disjunction.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// -(x1 -> (x2 <-> x3))
+    /// x1 AND (x2 OR x3) AND (-x2 OR -x3)
+    /// ```
+    pub fn to_nnf(&self) -> Expr {
+        self.nnf(false)
+    }
+
+    /// Simplify away the constants in the expression, like `x1 AND F` to `F` and `x1 -> F` to `-x1`.
+    /// The result is a constant, or an expression without constants.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR T) AND (x2 XOR T) AND (F -> x3)
+    /// -x2
+    /// ```
+    pub fn fold_constants(&self) -> Expr {
+        // Negating a constant flips it instead of wrapping it
+        let negate = |expr: Expr| match expr {
+            Expr::Const(value) => Expr::Const(!value),
+            expr => !expr,
+        };
+        match self {
+            Expr::Const(_) | Expr::Var(_) => self.clone(),
+            Expr::Not(operand) => negate(operand.fold_constants()),
+            Expr::And(operands) | Expr::Or(operands) => {
+                // The absorbing constant decides the connective, and the neutral one is left out
+                let absorbing = matches!(self, Expr::Or(_));
+                let mut folded = Vec::new();
+                for operand in operands.iter().map(Expr::fold_constants) {
+                    match operand {
+                        Expr::Const(value) if value == absorbing => return operand,
+                        Expr::Const(_) => (),
+                        operand => folded.push(operand),
+                    }
+                }
+                match (folded.pop(), folded.is_empty()) {
+                    (None, _) => Expr::Const(!absorbing),
+                    (Some(operand), true) => operand,
+                    (Some(operand), false) if absorbing => {
+                        Expr::any(folded.into_iter().chain([operand]))
+                    }
+                    (Some(operand), false) => Expr::all(folded.into_iter().chain([operand])),
+                }
+            }
+            Expr::Implies(a, b) => match (a.fold_constants(), b.fold_constants()) {
+                (Expr::Const(false), _) | (_, Expr::Const(true)) => Expr::Const(true),
+                (Expr::Const(true), b) => b,
+                (a, Expr::Const(false)) => negate(a),
+                (a, b) => a.implies(b),
+            },
+            Expr::Iff(a, b) => match (a.fold_constants(), b.fold_constants()) {
+                (Expr::Const(value), other) | (other, Expr::Const(value)) if value => other,
+                (Expr::Const(_), other) | (other, Expr::Const(_)) => negate(other),
+                (a, b) => a.iff(b),
+            },
+            Expr::Xor(a, b) => match (a.fold_constants(), b.fold_constants()) {
+                (Expr::Const(value), other) | (other, Expr::Const(value)) if value => negate(other),
+                (Expr::Const(_), other) | (other, Expr::Const(_)) => other,
+                (a, b) => a.xor(b),
+            },
+        }
+    }
+
+    /// Get the negation normal form of the expression, or of its negation.
+    fn nnf(&self, negated: bool) -> Expr {
+        match (self, negated) {
+            (Expr::Const(value), _) => Expr::Const(*value != negated),
+            (Expr::Var(id), false) => Expr::Var(*id),
+            (Expr::Var(id), true) => !Expr::Var(*id),
+            (Expr::Not(operand), _) => operand.nnf(!negated),
+            (Expr::And(operands), false) | (Expr::Or(operands), true) => {
+                Expr::all(operands.iter().map(|e| e.nnf(negated)))
+            }
+            (Expr::Or(operands), false) | (Expr::And(operands), true) => {
+                Expr::any(operands.iter().map(|e| e.nnf(negated)))
+            }
+            // a -> b is -a OR b
+            (Expr::Implies(a, b), false) => a.nnf(true).or(b.nnf(false)),
+            (Expr::Implies(a, b), true) => a.nnf(false).and(b.nnf(true)),
+            // a <-> b is (-a OR b) AND (a OR -b), and its negation is a XOR b
+            (Expr::Iff(a, b), false) | (Expr::Xor(a, b), true) => {
+                let either = a.nnf(true).or(b.nnf(false));
+                either.and(a.nnf(false).or(b.nnf(true)))
+            }
+            // a XOR b is (a OR b) AND (-a OR -b)
+            (Expr::Xor(a, b), false) | (Expr::Iff(a, b), true) => {
+                let either = a.nnf(false).or(b.nnf(false));
+                either.and(a.nnf(true).or(b.nnf(true)))
+            }
+        }
+    }
+
+    /// Convert the expression to a logically equivalent formula in CNF, without auxiliary variables,
+    /// by distributing disjunctions over conjunctions in its negation normal form.
+    /// Clauses are normalized and tautologies dropped.
+    ///
+    /// The formula can grow exponentially, like for `(x1 AND x2) OR (x3 AND x4) OR ...`,
+    /// see [`Expr::to_cnf`] for choosing the conversion by size.
+    pub fn to_cnf_exact(&self) -> Formula {
+        let mut formula = Formula::new();
+        for clause in distribute(&self.to_nnf()) {
+            formula.add_normalized(Clause(clause));
+        }
+        formula
+    }
+
+    /// Convert the expression to CNF, exactly by [`Expr::to_cnf_exact`] if the result has at most
+    /// [`EXACT_CNF_LIMIT`] clauses, and by [`Expr::to_cnf_tseitin`] otherwise.
+    /// Constants are folded first, see [`Expr::fold_constants`], so a false expression is a single empty clause.
+    /// An expression already in CNF keeps its clauses as they are.
+    pub fn to_cnf(&self, pool: &mut VarPool) -> Formula {
+        let expr = self.fold_constants();
+        if expr.is_cnf() {
+            expr.to_cnf_tseitin(pool)
+        } else if exact_size(&expr.to_nnf()) <= EXACT_CNF_LIMIT {
+            expr.to_cnf_exact()
+        } else {
+            expr.to_cnf_tseitin(pool)
+        }
+    }
+
+    /// Convert the expression to an equisatisfiable formula in CNF by the Tseitin transformation.
+    /// Each nested connective gets an auxiliary variable from the pool, defined to be equivalent to it,
+    /// so the formula grows linearly with the expression.
+    /// The top level conjunction and disjunctions of literals become clauses directly,
+    /// so an expression already in CNF gets no auxiliary variables.
+    ///
+    /// The auxiliary variables are marked in the pool, and the models of the formula
+    /// projected onto the variables of the expression are exactly the models of the expression.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// x1 OR (x2 AND x3)
+    /// (x1 OR x4) AND (-x4 OR x2) AND (-x4 OR x3) AND (x4 OR -x2 OR -x3)
+    /// ```
+    pub fn to_cnf_tseitin(&self, pool: &mut VarPool) -> Formula {
+        for id in self.literals() {
+            pool.reserve(id);
+        }
+        let mut tseitin = Tseitin {
+            pool,
+            formula: Formula::new(),
+            truth: None,
+        };
+        let conjuncts = match self {
+            Expr::And(operands) => operands.iter().collect(),
+            expr => vec![expr],
+        };
+        for conjunct in conjuncts {
+            let clause = match conjunct {
+                Expr::Or(operands) => operands.iter().map(|e| tseitin.encode(e)).collect(),
+                expr => vec![tseitin.encode(expr)],
+            };
+            tseitin.formula.add(Clause(clause));
+        }
+        tseitin.formula
+    }
+
+    /// Check if the expression is a conjunction of disjunctions of literals, or one of them alone.
+    pub fn is_cnf(&self) -> bool {
+        let literal = |expr: &Expr| match expr {
+            Expr::Var(_) => true,
+            Expr::Not(operand) => matches!(operand.as_ref(), Expr::Var(_)),
+            _ => false,
+        };
+        let clause = |expr: &Expr| match expr {
+            Expr::Or(operands) => operands.iter().all(literal),
+            expr => literal(expr),
+        };
+        match self {
+            Expr::And(operands) => operands.iter().all(clause),
+            expr => clause(expr),
+        }
+    }
+
+    /// Evaluate the expression under a solution assigning all of its variables.
+    pub fn eval(&self, solution: &Solution) -> bool {
+        match self {
+            Expr::Const(value) => *value,
+            Expr::Var(id) => solution.get(*id),
+            Expr::Not(operand) => !operand.eval(solution),
+            Expr::And(operands) => operands.iter().all(|operand| operand.eval(solution)),
+            Expr::Or(operands) => operands.iter().any(|operand| operand.eval(solution)),
+            Expr::Implies(a, b) => !a.eval(solution) || b.eval(solution),
+            Expr::Iff(a, b) => a.eval(solution) == b.eval(solution),
+            Expr::Xor(a, b) => a.eval(solution) != b.eval(solution),
+        }
+    }
+}
+
+/// The largest number of clauses for which [`Expr::to_cnf`] distributes instead of using auxiliary variables.
+pub const EXACT_CNF_LIMIT: usize = 64;
+
+/// Get the clauses of an expression in negation normal form by distribution.
+fn distribute(expr: &Expr) -> Vec<Vec<Variable>> {
+    match expr {
+        Expr::Const(true) => Vec::new(),
+        Expr::Const(false) => vec![Vec::new()],
+        Expr::Var(id) => vec![vec![Variable::Positive(*id)]],
+        Expr::Not(operand) => match operand.as_ref() {
+            Expr::Var(id) => vec![vec![Variable::Negative(*id)]],
+            _ => unreachable!("Only variables are negated in negation normal form"),
+        },
+        Expr::And(operands) => operands.iter().flat_map(distribute).collect(),
+        Expr::Or(operands) => {
+            // Every combination of one clause from each operand
+            let mut clauses = vec![Vec::new()];
+            for operand in operands {
+                let operand = distribute(operand);
+                clauses = clauses
+                    .iter()
+                    .flat_map(|clause| {
+                        operand
+                            .iter()
+                            .map(move |other| [clause.as_slice(), other].concat())
+                    })
+                    .collect();
+            }
+            clauses
+        }
+        _ => unreachable!("Only conjunctions and disjunctions are left in negation normal form"),
+    }
+}
+
+/// Count the clauses [`distribute`] would create, saturating instead of overflowing.
+fn exact_size(expr: &Expr) -> usize {
+    match expr {
+        Expr::Const(true) => 0,
+        Expr::And(operands) => operands
+            .iter()
+            .fold(0, |size, e| size.saturating_add(exact_size(e))),
+        Expr::Or(operands) => operands
+            .iter()
+            .fold(1, |size, e| size.saturating_mul(exact_size(e))),
+        _ => 1,
+    }
+}
+
+/// The state of a Tseitin transformation, adding the definitions of auxiliary variables to the formula.
+struct Tseitin<'a> {
+    pool: &'a mut VarPool,
+    formula: Formula,
+    /// A variable defined to be true, shared by all constants.
+    truth: Option<Literal>,
+}
+
+impl Tseitin<'_> {
+    /// Get a variable equivalent to the expression, defining auxiliary variables as needed.
+    fn encode(&mut self, expr: &Expr) -> Variable {
+        match expr {
+            Expr::Var(id) => Variable::Positive(*id),
+            Expr::Not(operand) => self.encode(operand).negated(),
+            Expr::Const(value) => {
+                let truth = match self.truth {
+                    Some(truth) => truth,
+                    None => {
+                        let truth = self.pool.fresh();
+                        self.formula.add(Clause(vec![Variable::Positive(truth)]));
+                        self.truth = Some(truth);
+                        truth
+                    }
+                };
+                if *value {
+                    Variable::Positive(truth)
+                } else {
+                    Variable::Negative(truth)
+                }
+            }
+            Expr::And(operands) => {
+                let operands = operands.iter().map(|e| self.encode(e)).collect();
+                self.and(operands)
+            }
+            Expr::Or(operands) => {
+                // a OR b is -(-a AND -b)
+                let operands = operands.iter().map(|e| self.encode(e).negated()).collect();
+                self.and(operands).negated()
+            }
+            Expr::Implies(a, b) => {
+                let operands = vec![self.encode(a), self.encode(b).negated()];
+                self.and(operands).negated()
+            }
+            Expr::Iff(a, b) => {
+                let (a, b) = (self.encode(a), self.encode(b));
+                self.xor(a, b).negated()
+            }
+            Expr::Xor(a, b) => {
+                let (a, b) = (self.encode(a), self.encode(b));
+                self.xor(a, b)
+            }
+        }
+    }
+
+    /// Define a variable equivalent to the conjunction of the operands.
+    fn and(&mut self, operands: Vec<Variable>) -> Variable {
+        let y = self.pool.fresh();
+        let mut definition = vec![Variable::Positive(y)];
+        for operand in operands {
+            self.formula
+                .add(Clause(vec![Variable::Negative(y), operand]));
+            definition.push(operand.negated());
+        }
+        self.formula.add(Clause(definition));
+        Variable::Positive(y)
+    }
+
+    /// Define a variable equivalent to the exclusive or of the operands.
+    fn xor(&mut self, a: Variable, b: Variable) -> Variable {
+        let y = self.pool.fresh();
+        let (pos, neg) = (Variable::Positive(y), Variable::Negative(y));
+        self.formula.add(Clause(vec![neg, a, b]));
+        self.formula
+            .add(Clause(vec![neg, a.negated(), b.negated()]));
+        self.formula.add(Clause(vec![pos, a.negated(), b]));
+        self.formula.add(Clause(vec![pos, a, b.negated()]));
+        pos
+    }
+}
+
+impl Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr::Not(Box::new(self))
+    }
+}
+
+impl BitAnd for Expr {
+    type Output = Expr;
+
+    fn bitand(self, other: Expr) -> Expr {
+        self.and(other)
+    }
+}
+
+impl BitOr for Expr {
+    type Output = Expr;
+
+    fn bitor(self, other: Expr) -> Expr {
+        self.or(other)
+    }
+}
+
+impl BitXor for Expr {
+    type Output = Expr;
+
+    fn bitxor(self, other: Expr) -> Expr {
+        self.xor(other)
+    }
+}
+
+impl From<Variable> for Expr {
+    fn from(variable: Variable) -> Self {
+        match variable {
+            Variable::Positive(id) => Expr::Var(id),
+            Variable::Negative(id) => !Expr::Var(id),
+        }
+    }
+}
+
+/// Create the conjunction of the disjunctions of the clauses.
+impl From<&Formula> for Expr {
+    fn from(formula: &Formula) -> Self {
+        Expr::And(
+            formula
+                .iter()
+                .map(|clause| Expr::Or(clause.iter().map(|v| Expr::from(*v)).collect()))
+                .collect(),
+        )
+    }
+}
+
+/// Plain text in the normal print style, with parentheses around every nested connective.
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Const(true) => write!(f, "T"),
+            Expr::Const(false) => write!(f, "F"),
+            Expr::And(operands) if operands.is_empty() => write!(f, "T"),
+            Expr::Or(operands) if operands.is_empty() => write!(f, "F"),
+            Expr::Var(id) => write!(f, "X{}", id),
+            Expr::Not(operand) => {
+                write!(f, "-")?;
+                write_operand(f, operand)
+            }
+            Expr::And(operands) => write_operands(f, operands, "and"),
+            Expr::Or(operands) => write_operands(f, operands, "or"),
+            Expr::Implies(a, b) => write_operands(f, [a.as_ref(), b.as_ref()], "->"),
+            Expr::Iff(a, b) => write_operands(f, [a.as_ref(), b.as_ref()], "<->"),
+            Expr::Xor(a, b) => write_operands(f, [a.as_ref(), b.as_ref()], "xor"),
+        }
+    }
+}
+
+/// Write an operand of a connective, in parentheses unless it is a variable, constant or negation.
+fn write_operand(f: &mut Formatter<'_>, operand: &Expr) -> fmt::Result {
+    match operand {
+        Expr::Const(_) | Expr::Var(_) | Expr::Not(_) => write!(f, "{}", operand),
+        _ => write!(f, "({})", operand),
+    }
+}
+
+fn write_operands<'a>(
+    f: &mut Formatter<'_>,
+    operands: impl IntoIterator<Item = &'a Expr>,
+    connective: &str,
+) -> fmt::Result {
+    for (i, operand) in operands.into_iter().enumerate() {
+        if i > 0 {
+            write!(f, " {} ", connective)?;
+        }
+        write_operand(f, operand)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expr_builder() {
+        let expr = Expr::var(1) & Expr::var(2) & !Expr::var(3);
+        assert_eq!(
+            expr,
+            Expr::And(vec![
+                Expr::Var(1),
+                Expr::Var(2),
+                Expr::Not(Box::new(Expr::Var(3)))
+            ])
+        );
+        let expr = Expr::var(1).implies(Expr::var(2)) | (Expr::var(3) ^ Expr::constant(true));
+        assert_eq!(expr.literals(), [1, 2, 3]);
+        assert_eq!(expr.to_string(), "(X1 -> X2) or (X3 xor T)");
+    }
+
+    #[test]
+    fn test_expr_eval() {
+        let expr = Expr::var(1).implies(Expr::var(2)).iff(!Expr::var(3));
+        assert!(expr.eval(&[(1, true), (2, false), (3, true)][..].into()));
+        assert!(!expr.eval(&[(1, true), (2, true), (3, true)][..].into()));
+        assert!(Expr::all([]).eval(&Solution::new()));
+        assert!(!Expr::any([]).eval(&Solution::new()));
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![Variable::Positive(2)],
+        ]
+        .into();
+        let expr = Expr::from(&formula);
+        assert_eq!(expr.to_string(), "(X1 or -X2) and (X2)");
+        let solution = [(1, true), (2, true)][..].into();
+        assert_eq!(expr.eval(&solution), solution.satisfy(&formula));
+    }
+
+    #[test]
+    fn test_tseitin() {
+        let mut pool = VarPool::new();
+        let expr = Expr::var(1) | (Expr::var(2) & Expr::var(3));
+        let formula = expr.to_cnf_tseitin(&mut pool);
+        let expected: Formula = vec![
+            vec![Variable::Negative(4), Variable::Positive(2)],
+            vec![Variable::Negative(4), Variable::Positive(3)],
+            vec![
+                Variable::Positive(4),
+                Variable::Negative(2),
+                Variable::Negative(3),
+            ],
+            vec![Variable::Positive(1), Variable::Positive(4)],
+        ]
+        .into();
+        assert_eq!(formula, expected);
+        assert!(pool.is_auxiliary(4));
+        assert!(!pool.is_auxiliary(1));
+        // A formula in CNF is kept as it is
+        let cnf: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![Variable::Positive(2)],
+        ]
+        .into();
+        assert_eq!(Expr::from(&cnf).to_cnf_tseitin(&mut pool), cnf);
+    }
+
+    #[test]
+    fn test_tseitin_models() {
+        let solver = crate::solvers::Dfs::default();
+        let expr = (Expr::var(1) ^ Expr::var(2)).implies(Expr::var(3).iff(Expr::constant(false)))
+            & !(Expr::var(1) & Expr::var(3));
+        let formula = expr.to_cnf_tseitin(&mut VarPool::new());
+        let projection = expr.literals();
+        let models = crate::solver::solve_all_projected(&formula, &projection, &solver);
+        // Every assignment of the expression's variables satisfies it exactly when it is a projected model
+        for bits in 0..8u32 {
+            let solution: Solution =
+                [(1, bits & 1 != 0), (2, bits & 2 != 0), (3, bits & 4 != 0)][..].into();
+            assert_eq!(expr.eval(&solution), models.contains(&solution));
+        }
+    }
+
+    #[test]
+    fn test_nnf() {
+        let expr = !Expr::var(1).implies(Expr::var(2).iff(Expr::var(3)));
+        let nnf = Expr::var(1) & (Expr::var(2) | Expr::var(3)) & (!Expr::var(2) | !Expr::var(3));
+        assert_eq!(expr.to_nnf(), nnf);
+        assert_eq!((!!Expr::constant(true)).to_nnf(), Expr::constant(true));
+    }
+
+    #[test]
+    fn test_cnf_exact() {
+        let expr = (Expr::var(1) & Expr::var(2)) | (Expr::var(1) ^ Expr::var(3));
+        let formula = expr.to_cnf_exact();
+        assert!(formula.literals().iter().all(|id| *id <= 3));
+        for bits in 0..8u32 {
+            let solution: Solution =
+                [(1, bits & 1 != 0), (2, bits & 2 != 0), (3, bits & 4 != 0)][..].into();
+            assert_eq!(expr.eval(&solution), solution.satisfy(&formula));
+        }
+        assert_eq!(Expr::constant(false).to_cnf_exact(), vec![vec![]].into());
+        assert!((Expr::var(1) | Expr::constant(true))
+            .to_cnf_exact()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_cnf_threshold() {
+        // (x1 AND x2) OR (x3 AND x4) OR ... has 2^n clauses when distributed
+        let pairs =
+            |n: Literal| Expr::any((0..n).map(|i| Expr::var(2 * i + 1) & Expr::var(2 * i + 2)));
+        let mut pool = VarPool::new();
+        let small = pairs(6).to_cnf(&mut pool);
+        assert_eq!(small.len(), EXACT_CNF_LIMIT);
+        assert_eq!(small.literals().len(), 12);
+        let large = pairs(7).to_cnf(&mut pool);
+        assert!(large.len() < EXACT_CNF_LIMIT);
+        assert!(large.literals().iter().any(|id| pool.is_auxiliary(*id)));
+        // Clauses in CNF are neither normalized nor sorted
+        let cnf = (Expr::var(2) | Expr::var(1) | Expr::var(2)) & !Expr::var(3);
+        assert!(cnf.is_cnf());
+        assert!(!(!!Expr::var(3)).is_cnf());
+        assert_eq!(
+            cnf.to_cnf(&mut pool).to_string(),
+            "(X2 or X1 or X2) and (-X3)"
+        );
+    }
+
+    #[test]
+    fn test_fold_constants() {
+        let (t, f) = (Expr::constant(true), Expr::constant(false));
+        let expr = (Expr::var(1) | t.clone())
+            & (Expr::var(2) ^ t.clone())
+            & f.clone().implies(Expr::var(3));
+        assert_eq!(expr.fold_constants(), !Expr::var(2));
+        assert_eq!((Expr::var(1) & f.clone()).fold_constants(), f);
+        assert_eq!(
+            Expr::var(1).implies(f.clone()).fold_constants(),
+            !Expr::var(1)
+        );
+        assert_eq!(f.clone().iff(Expr::var(1)).fold_constants(), !Expr::var(1));
+        assert_eq!(
+            (!(Expr::var(1) | f.clone())).fold_constants(),
+            !Expr::var(1)
+        );
+        let expr = Expr::var(1) | Expr::var(2) | f.clone();
+        assert_eq!(expr.fold_constants(), Expr::var(1) | Expr::var(2));
+        let mut pool = VarPool::new();
+        assert_eq!((Expr::var(1) & f).to_cnf(&mut pool), vec![vec![]].into());
+        assert!((Expr::var(1) | t).to_cnf(&mut pool).is_empty());
+    }
+
+    #[test]
+    fn test_substitute() {
+        let expr = (Expr::var(1) | Expr::var(2)) & !Expr::var(1);
+        let substituted = expr.substitute(1, &(Expr::var(3) & Expr::var(4)));
+        assert_eq!(
+            substituted,
+            ((Expr::var(3) & Expr::var(4)) | Expr::var(2)) & !(Expr::var(3) & Expr::var(4))
+        );
+        assert_eq!(substituted.literals(), [2, 3, 4]);
+        assert_eq!(expr.substitute(5, &Expr::var(6)), expr);
+    }
+}
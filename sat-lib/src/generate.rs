@@ -0,0 +1,71 @@
+use crate::{
+    rng::Rng,
+    types::{Clause, Formula, Literal, Variable},
+};
+
+/// Generate a uniform random k-SAT formula, where each clause has `k` distinct variables
+/// of `x1..=xvars`, each negated with probability 1/2. The same seed gives the same formula.
+///
+/// Random 3-SAT goes from mostly satisfiable to mostly unsatisfiable around a clause to variable
+/// ratio of 4.26, where the instances are also the hardest to solve.
+///
+/// ## Panics
+/// If `k` is larger than the number of variables.
+///
+/// ## Examples
+/// ```plaintext
+/// random_ksat(4, 2, 3, seed)
+/// (x1 OR -x3 OR x4) AND (-x2 OR -x1 OR -x4)
+/// ```
+pub fn random_ksat(vars: u32, clauses: usize, k: usize, seed: u64) -> Formula {
+    assert!(
+        k <= vars as usize,
+        "k = {} is more than {} variables",
+        k,
+        vars
+    );
+    let mut rng = Rng::new(seed);
+    let mut formula = Formula::new();
+    for _ in 0..clauses {
+        let mut ids: Vec<Literal> = Vec::with_capacity(k);
+        while ids.len() < k {
+            let id = rng.below(vars as usize) as Literal + 1;
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        let clause = ids
+            .into_iter()
+            .map(|id| {
+                if rng.bool() {
+                    Variable::Positive(id)
+                } else {
+                    Variable::Negative(id)
+                }
+            })
+            .collect();
+        formula.add(Clause(clause));
+    }
+    formula
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_ksat() {
+        let formula = random_ksat(10, 42, 3, 7);
+        assert_eq!(formula.0.len(), 42);
+        for clause in &formula.0 {
+            let mut ids = clause.0.iter().map(|lit| lit.id()).collect::<Vec<_>>();
+            ids.sort();
+            ids.dedup();
+            assert_eq!(ids.len(), 3);
+            assert!(ids.iter().all(|id| (1..=10).contains(id)));
+        }
+        assert_eq!(formula, random_ksat(10, 42, 3, 7));
+        assert_ne!(formula, random_ksat(10, 42, 3, 8));
+        assert!(random_ksat(0, 0, 0, 0).0.is_empty());
+    }
+}
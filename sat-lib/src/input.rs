@@ -0,0 +1,115 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Read},
+    path::Path,
+};
+
+/// The first bytes of a gzip stream.
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+/// The first bytes of an xz stream.
+const XZ_MAGIC: &[u8] = &[0xfd, b'7', b'z', b'X', b'Z', 0x00];
+
+/// Open a file for reading, decompressing it on the fly if it is compressed, see [`decompress`].
+pub fn open(path: impl AsRef<Path>) -> io::Result<Box<dyn BufRead>> {
+    decompress(BufReader::new(File::open(path)?))
+}
+
+/// Read a whole file, decompressing it if it is compressed.
+pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    open(path)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+/// Read a whole text file, decompressing it if it is compressed.
+pub fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let mut text = String::new();
+    open(path)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+/// Decompress a stream on the fly if it starts like a gzip or xz stream, like SAT competition benchmarks in
+/// `.cnf.gz` and `.cnf.xz` files, and pass other streams through as they are.
+/// Decompression needs the `gzip` or `xz` feature, and fails as unsupported without it.
+pub fn decompress<R: BufRead + 'static>(mut reader: R) -> io::Result<Box<dyn BufRead>> {
+    let start = reader.fill_buf()?;
+    if start.starts_with(GZIP_MAGIC) {
+        #[cfg(feature = "gzip")]
+        return Ok(Box::new(BufReader::new(
+            flate2::bufread::MultiGzDecoder::new(reader),
+        )));
+        #[cfg(not(feature = "gzip"))]
+        return Err(unsupported("gzip"));
+    }
+    if start.starts_with(XZ_MAGIC) {
+        #[cfg(feature = "xz")]
+        return Ok(Box::new(BufReader::new(
+            xz2::bufread::XzDecoder::new_multi_decoder(reader),
+        )));
+        #[cfg(not(feature = "xz"))]
+        return Err(unsupported("xz"));
+    }
+    Ok(Box::new(reader))
+}
+
+/// The error for a compression format whose feature is disabled.
+#[cfg(not(all(feature = "gzip", feature = "xz")))]
+fn unsupported(format: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{} compressed input needs the {} feature", format, format),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decompress_plain() {
+        let mut text = String::new();
+        decompress(&b"p cnf 1 1\n1 0\n"[..])
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "p cnf 1 1\n1 0\n");
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_decompress_gzip() {
+        use std::io::Write;
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(b"p cnf 1 1\n1 0\n").unwrap();
+        let compressed = encoder.finish().unwrap();
+        let mut text = String::new();
+        decompress(io::Cursor::new(compressed))
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "p cnf 1 1\n1 0\n");
+    }
+
+    #[cfg(feature = "xz")]
+    #[test]
+    fn test_decompress_xz() {
+        let compressed = xz2::read::XzEncoder::new(&b"p cnf 1 1\n1 0\n"[..], 6);
+        let mut bytes = Vec::new();
+        BufReader::new(compressed).read_to_end(&mut bytes).unwrap();
+        let mut text = String::new();
+        decompress(io::Cursor::new(bytes))
+            .unwrap()
+            .read_to_string(&mut text)
+            .unwrap();
+        assert_eq!(text, "p cnf 1 1\n1 0\n");
+    }
+
+    #[cfg(not(feature = "gzip"))]
+    #[test]
+    fn test_decompress_unsupported() {
+        let error = decompress(&[0x1f, 0x8b, 0x08][..]).err().unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+    }
+}
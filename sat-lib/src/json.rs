@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+
+use serde::{de::Error as _, Deserialize, Serialize};
+use serde_json::Error;
+
+use crate::{
+    parser::{numbered, parse_literal},
+    types::{Certificate, Clause, Formula, Literal, Solution, VarPool, Variable, MAX_LITERAL},
+};
+
+/// A literal in JSON, either a signed variable number or a variable name negated by a leading `-`.
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum JsonLiteral {
+    Number(i64),
+    Name(String),
+}
+
+/// The JSON form of a certificate like [`JsonModels`], with at most one model and its don't-care variables.
+#[derive(Deserialize, Serialize)]
+struct JsonCertificate {
+    status: Status,
+    #[serde(default)]
+    models: Vec<BTreeMap<String, bool>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    dont_care: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+enum Status {
+    #[serde(rename = "sat")]
+    Satisfiable,
+    #[serde(rename = "unsat")]
+    Unsatisfiable,
+    #[serde(rename = "unknown")]
+    Unknown,
+}
+
+/// Parse a formula from JSON, as a list of clauses that are lists of literals.
+/// Literals are signed variable numbers, or names negated by a leading `-`,
+/// which are looked up in the symbol table and added to it if they are not in it yet.
+///
+/// ## Examples
+/// ```plaintext
+/// [[1, -2], ["rain", "-wet"], ["x3"]]
+/// ```
+pub fn parse_json(input: &str, pool: &mut VarPool) -> Result<Formula, Error> {
+    let clauses: Vec<Vec<JsonLiteral>> = serde_json::from_str(input)?;
+    // Reserve the numbered variables first, so names are given identifiers after them
+    for literal in clauses.iter().flatten() {
+        let id = match literal {
+            JsonLiteral::Number(number) => Literal::try_from(number.unsigned_abs())
+                .ok()
+                .filter(|id| *id <= MAX_LITERAL),
+            JsonLiteral::Name(name) => numbered(name.trim_start_matches('-')),
+        };
+        if let Some(id) = id {
+            pool.reserve(id);
+        }
+    }
+    let mut formula = Formula::new();
+    for clause in clauses {
+        let variables = clause
+            .iter()
+            .map(|literal| variable(literal, pool))
+            .collect::<Result<_, _>>()?;
+        formula.add(Clause(variables));
+    }
+    Ok(formula)
+}
+
+/// Write a formula as JSON, see [`parse_json`], with named variables by their names.
+pub fn to_json(formula: &Formula, pool: &VarPool) -> String {
+    let clauses = formula
+        .iter()
+        .map(|clause| {
+            clause
+                .iter()
+                .map(|variable| match (pool.name(variable.id()), variable) {
+                    (Some(name), Variable::Positive(_)) => JsonLiteral::Name(name.to_string()),
+                    (Some(name), Variable::Negative(_)) => JsonLiteral::Name(format!("-{}", name)),
+                    (None, Variable::Positive(id)) => JsonLiteral::Number(i64::from(*id)),
+                    (None, Variable::Negative(id)) => JsonLiteral::Number(-i64::from(*id)),
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    serde_json::to_string(&clauses).unwrap()
+}
+
+/// Parse the result of solving a formula from JSON, see [`solution_to_json`].
+/// Variables are looked up like in [`parse_json`].
+pub fn parse_json_solution(input: &str, pool: &mut VarPool) -> Result<Certificate, Error> {
+    let certificate: JsonCertificate = serde_json::from_str(input)?;
+    match certificate.status {
+        Status::Satisfiable => (),
+        Status::Unsatisfiable => return Ok(Certificate::Unsatisfiable),
+        Status::Unknown => return Ok(Certificate::Indeterminate),
+    }
+    let [model] = &certificate.models[..] else {
+        return Err(Error::custom("a sat status needs one model"));
+    };
+    let mut solution = Solution::new();
+    for (key, value) in model {
+        solution.set(key_id(key, pool)?, *value);
+    }
+    for key in &certificate.dont_care {
+        let id = key_id(key, pool)?;
+        if !solution.0.contains_key(&id) {
+            return Err(Error::custom(format!(
+                "don't-care variable {} has no value",
+                key
+            )));
+        }
+        solution.1.insert(id);
+    }
+    Ok(Certificate::Satisfiable(solution))
+}
+
+/// Write the result of solving a formula as JSON like [`solutions_to_json`], with the status `sat`, `unsat` or `unknown`,
+/// and for a solution a single model and the don't-care variables, leaving out the auxiliary variables of the pool.
+///
+/// ## Examples
+/// ```plaintext
+/// {"status":"sat","models":[{"1":true,"rain":false}],"dont_care":["1"]}
+/// ```
+pub fn solution_to_json(certificate: &Certificate, pool: &VarPool) -> String {
+    let key = |id: Literal| pool.name(id).map_or(id.to_string(), str::to_string);
+    let mut json = JsonCertificate {
+        status: match certificate {
+            Certificate::Satisfiable(_) => Status::Satisfiable,
+            Certificate::Unsatisfiable => Status::Unsatisfiable,
+            Certificate::Indeterminate => Status::Unknown,
+        },
+        models: Vec::new(),
+        dont_care: Vec::new(),
+    };
+    if let Certificate::Satisfiable(solution) = certificate {
+        let literals = solution
+            .literals()
+            .into_iter()
+            .filter(|id| !pool.is_auxiliary(*id));
+        json.models.push(
+            literals
+                .clone()
+                .map(|id| (key(id), solution.get(id)))
+                .collect(),
+        );
+        json.dont_care = literals
+            .filter(|id| solution.is_dont_care(*id))
+            .map(key)
+            .collect();
+    }
+    serde_json::to_string(&json).unwrap()
+}
+
+/// The JSON form of all solutions of a formula, for scripts reading the output of the shell.
+#[derive(Serialize)]
+struct JsonModels {
+    /// `sat`, `unsat` or `unknown`
+    status: &'static str,
+    models: Vec<BTreeMap<String, bool>>,
+    /// Whether there are more solutions than the models, when they are limited
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    more: bool,
+}
+
+/// Write the solutions found for a formula as JSON, with the status `sat` if there are any,
+/// and otherwise `unsat` if the search was complete or `unknown` if not.
+/// Variables are by name or number, leaving out the auxiliary variables of the pool.
+///
+/// ## Examples
+/// ```plaintext
+/// {"status":"sat","models":[{"1":true,"rain":false},{"1":false,"rain":true}]}
+/// ```
+pub fn solutions_to_json(solutions: &[Solution], complete: bool, pool: &VarPool) -> String {
+    limited_solutions_to_json(solutions, complete, false, pool)
+}
+
+/// Write the solutions like [`solutions_to_json`], marking with `"more":true` if there are more than them,
+/// like the first solutions found by [`crate::solver::solve_n`].
+pub fn limited_solutions_to_json(
+    solutions: &[Solution],
+    complete: bool,
+    more: bool,
+    pool: &VarPool,
+) -> String {
+    let status = match (solutions.is_empty(), complete) {
+        (false, _) => "sat",
+        (true, true) => "unsat",
+        (true, false) => "unknown",
+    };
+    let models = solutions
+        .iter()
+        .map(|solution| {
+            solution
+                .literals()
+                .into_iter()
+                .filter(|id| !pool.is_auxiliary(*id))
+                .map(|id| {
+                    let key = pool.name(id).map_or(id.to_string(), str::to_string);
+                    (key, solution.get(id))
+                })
+                .collect()
+        })
+        .collect();
+    serde_json::to_string(&JsonModels {
+        status,
+        models,
+        more,
+    })
+    .unwrap()
+}
+
+/// Get the variable of a JSON literal, checking names like the formula parser does.
+fn variable(literal: &JsonLiteral, pool: &mut VarPool) -> Result<Variable, Error> {
+    let (positive, id) = match literal {
+        JsonLiteral::Number(0) => return Err(Error::custom("0 is not a literal")),
+        JsonLiteral::Number(number) => match Literal::try_from(number.unsigned_abs()) {
+            Ok(id) if id <= MAX_LITERAL => (*number > 0, id),
+            _ => return Err(Error::custom(format!("literal {} is too large", number))),
+        },
+        JsonLiteral::Name(name) => match name.strip_prefix('-') {
+            Some(name) => (false, key_id(name, pool)?),
+            None => (true, key_id(name, pool)?),
+        },
+    };
+    Ok(if positive {
+        Variable::Positive(id)
+    } else {
+        Variable::Negative(id)
+    })
+}
+
+/// Get the identifier of a variable written as a number or a name.
+fn key_id(key: &str, pool: &mut VarPool) -> Result<Literal, Error> {
+    if let Ok(id) = key.parse::<Literal>() {
+        if id > MAX_LITERAL {
+            return Err(Error::custom(format!("variable {} is too large", id)));
+        }
+        pool.reserve(id);
+        return Ok(id);
+    }
+    parse_literal(key, 0, pool)
+        .map_err(|error| Error::custom(format!("invalid variable \"{}\": {}", key, error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_json() {
+        let mut pool = VarPool::new();
+        let formula = parse_json(r#"[[1, -2], ["rain", "-wet"], ["x3"], []]"#, &mut pool).unwrap();
+        assert_eq!(
+            formula.to_string(),
+            "(X1 or -X2) and (X4 or -X5) and (X3) and ()"
+        );
+        assert_eq!(pool.name(4), Some("rain"));
+        let json = to_json(&formula, &pool);
+        assert_eq!(json, r#"[[1,-2],["rain","-wet"],[3],[]]"#);
+        assert_eq!(parse_json(&json, &mut pool).unwrap(), formula);
+        let error = parse_json("[[1, 0]]", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "0 is not a literal");
+        let error = parse_json("[[-2147483647]]", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "literal -2147483647 is too large");
+        let error = parse_json(r#"[["2147483647"]]"#, &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "variable 2147483647 is too large");
+        assert!(parse_json("[[2147483646]]", &mut pool).is_ok());
+        let error = parse_json(r#"[["2x"]]"#, &mut pool).unwrap_err();
+        assert!(error.to_string().starts_with("invalid variable \"2x\""));
+        assert!(parse_json("[[1, 2]", &mut pool).unwrap_err().is_eof());
+    }
+
+    #[test]
+    fn test_solution_json() {
+        let mut pool = VarPool::new();
+        pool.reserve(1);
+        let rain = pool.id("rain");
+        let mut solution: Solution = [(1, true), (rain, false)][..].into();
+        solution.1.insert(1);
+        let certificate = Certificate::Satisfiable(solution);
+        let json = solution_to_json(&certificate, &pool);
+        assert_eq!(
+            json,
+            r#"{"status":"sat","models":[{"1":true,"rain":false}],"dont_care":["1"]}"#
+        );
+        assert_eq!(parse_json_solution(&json, &mut pool).unwrap(), certificate);
+        let json = solution_to_json(&Certificate::Unsatisfiable, &pool);
+        assert_eq!(json, r#"{"status":"unsat","models":[]}"#);
+        assert_eq!(
+            parse_json_solution(&json, &mut pool).unwrap(),
+            Certificate::Unsatisfiable
+        );
+        let error = parse_json_solution(
+            r#"{"status":"sat","models":[{"1":true}],"dont_care":["2"]}"#,
+            &mut pool,
+        );
+        assert!(error.is_err());
+        let error = parse_json_solution(r#"{"status":"sat","models":[]}"#, &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "a sat status needs one model");
+    }
+
+    #[test]
+    fn test_solutions_json() {
+        let mut pool = VarPool::new();
+        let rain = pool.id("rain");
+        let auxiliary = pool.fresh();
+        let solutions: Vec<Solution> = vec![
+            [(rain, true), (auxiliary, true)][..].into(),
+            [(rain, false), (auxiliary, true)][..].into(),
+        ];
+        assert_eq!(
+            solutions_to_json(&solutions, true, &pool),
+            r#"{"status":"sat","models":[{"rain":true},{"rain":false}]}"#
+        );
+        assert_eq!(
+            solutions_to_json(&[], true, &pool),
+            r#"{"status":"unsat","models":[]}"#
+        );
+        assert_eq!(
+            solutions_to_json(&[], false, &pool),
+            r#"{"status":"unknown","models":[]}"#
+        );
+        assert_eq!(
+            limited_solutions_to_json(&solutions[..1], true, true, &pool),
+            r#"{"status":"sat","models":[{"rain":true}],"more":true}"#
+        );
+    }
+}
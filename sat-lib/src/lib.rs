@@ -1,5 +1,26 @@
+pub mod aiger;
+pub mod blif;
+pub mod circuit;
+pub mod dimacs;
+pub mod encode;
+pub mod expr;
+pub mod generate;
+pub mod input;
+#[cfg(feature = "json")]
+pub mod json;
+pub mod opb;
 pub mod parser;
+pub mod preprocessors;
 pub mod printer;
+pub mod qbf;
+pub mod report;
+mod rng;
+pub mod smtlib;
 pub mod solver;
 pub mod solvers;
+#[cfg(any(feature = "proptest", feature = "arbitrary"))]
+pub mod strategies;
+pub mod tptp;
+pub mod trail;
+pub mod truth_table;
 pub mod types;
@@ -0,0 +1,310 @@
+use std::fmt::Write;
+
+use crate::{
+    encode::{Comparison, PbConstraint},
+    parser::{numbered, ParseError},
+    solver::solve_optimize_limited,
+    solvers::Budget,
+    types::{Formula, Literal, Solution, VarPool, Variable},
+};
+
+/// A pseudo-Boolean problem of linear constraints, with an optional objective to minimize.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Opb {
+    /// The weighted literals of the objective, empty for a decision problem.
+    pub objective: Vec<(i64, Variable)>,
+    pub constraints: Vec<PbConstraint>,
+}
+
+impl Opb {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the variables of the objective and the constraints, sorted.
+    pub fn literals(&self) -> Vec<Literal> {
+        let mut variables = self
+            .objective
+            .iter()
+            .chain(
+                self.constraints
+                    .iter()
+                    .flat_map(|constraint| &constraint.terms),
+            )
+            .map(|(_, lit)| lit.id())
+            .collect::<Vec<_>>();
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+
+    /// Encode the constraints in CNF, see [`PbConstraint::to_cnf`].
+    pub fn to_formula(&self, pool: &mut VarPool) -> Formula {
+        if let Some(last) = self.literals().last() {
+            pool.reserve(*last);
+        }
+        let mut formula = Formula::new();
+        for constraint in &self.constraints {
+            for clause in constraint.to_cnf(pool) {
+                formula.add(clause);
+            }
+        }
+        formula
+    }
+
+    /// Find a model of the constraints minimizing the objective, returning it with the value of the objective,
+    /// or `None` if the constraints are unsatisfiable.
+    ///
+    /// A negated literal `w ~x` of the objective is minimized as the constant `w` plus `-w x`,
+    /// so the weights can be given to [`solve_optimize`](crate::solver::solve_optimize).
+    pub fn solve(&self) -> Option<(Solution, i64)> {
+        self.solve_limited(&Budget::default())
+    }
+
+    /// Solve like [`solve`](Self::solve), giving up when the budget runs out with the best model found by then,
+    /// see [`solve_optimize_limited`].
+    pub fn solve_limited(&self, budget: &Budget) -> Option<(Solution, i64)> {
+        let formula = self.to_formula(&mut VarPool::new());
+        let mut offset = 0;
+        let objective = self
+            .objective
+            .iter()
+            .map(|(weight, lit)| {
+                if lit.is_positive() {
+                    (lit.id(), *weight)
+                } else {
+                    offset += weight;
+                    (lit.id(), -weight)
+                }
+            })
+            .collect::<Vec<_>>();
+        let (solution, cost) = solve_optimize_limited(&formula, &objective, budget)?;
+        Some((solution.project(&self.literals()), cost + offset))
+    }
+}
+
+/// Parse a pseudo-Boolean problem in the OPB format of the pseudo-Boolean competitions.
+/// An optional objective `min: ...;` comes first, followed by constraints ending in `;`
+/// that compare a sum of weighted literals to a bound by `>=`, `<=` or `=`.
+/// Literals are variables like `x3`, negated by `~`, and lines starting with `*` are comments.
+/// Products of literals, as in non-linear OPB problems, are not supported.
+///
+/// ## Examples
+/// ```plaintext
+/// * #variable= 3 #constraint= 2
+/// min: +2 x1 +1 x2 +3 x3 ;
+/// +1 x1 +1 x2 +1 x3 >= 2 ;
+/// +1 x1 +1 ~x3 = 1 ;
+/// ```
+pub fn parse_opb(input: &str) -> Result<Opb, ParseError> {
+    let mut tokens = tokenize(input).into_iter().peekable();
+    let end = input.len();
+    let mut opb = Opb::new();
+    if tokens.next_if(|(_, token)| *token == "min:").is_some() {
+        opb.objective = terms(&mut tokens, end)?;
+        match tokens.next() {
+            Some((_, ";")) => (),
+            Some((offset, token)) => return Err(ParseError::new(offset, token, &["';'"])),
+            None => return Err(ParseError::new(end, "", &["';'"])),
+        }
+    }
+    while tokens.peek().is_some() {
+        let terms = terms(&mut tokens, end)?;
+        let comparison = match tokens.next() {
+            Some((_, ">=")) => Comparison::AtLeast,
+            Some((_, "<=")) => Comparison::AtMost,
+            Some((_, "=")) => Comparison::Equal,
+            Some((offset, token)) => {
+                return Err(ParseError::new(offset, token, &["'>='", "'<='", "'='"]))
+            }
+            None => return Err(ParseError::new(end, "", &["'>='", "'<='", "'='"])),
+        };
+        let bound = match tokens.next() {
+            Some((offset, token)) => integer(offset, token, "a bound")?,
+            None => return Err(ParseError::new(end, "", &["a bound"])),
+        };
+        match tokens.next() {
+            Some((_, ";")) => (),
+            Some((offset, token)) => return Err(ParseError::new(offset, token, &["';'"])),
+            None => return Err(ParseError::new(end, "", &["';'"])),
+        }
+        opb.constraints.push(PbConstraint {
+            terms,
+            comparison,
+            bound,
+        });
+    }
+    Ok(opb)
+}
+
+/// Write a pseudo-Boolean problem in the OPB format, with a header counting the variables and constraints.
+/// Constraints using `<=` are written with `>=` by negating both sides, as the format requires.
+pub fn write_opb(opb: &Opb) -> String {
+    let variables = opb.literals().last().copied().unwrap_or(0);
+    let mut output = format!(
+        "* #variable= {} #constraint= {}\n",
+        variables,
+        opb.constraints.len()
+    );
+    if !opb.objective.is_empty() {
+        writeln!(output, "min: {};", write_terms(&opb.objective)).unwrap();
+    }
+    for constraint in &opb.constraints {
+        let (terms, relation, bound) = match constraint.comparison {
+            Comparison::AtLeast => (constraint.terms.clone(), ">=", constraint.bound),
+            Comparison::Equal => (constraint.terms.clone(), "=", constraint.bound),
+            Comparison::AtMost => (
+                constraint
+                    .terms
+                    .iter()
+                    .map(|(weight, lit)| (-weight, *lit))
+                    .collect(),
+                ">=",
+                -constraint.bound,
+            ),
+        };
+        writeln!(output, "{}{} {} ;", write_terms(&terms), relation, bound).unwrap();
+    }
+    output
+}
+
+fn write_terms(terms: &[(i64, Variable)]) -> String {
+    terms
+        .iter()
+        .map(|(weight, lit)| {
+            let sign = if lit.is_positive() { "" } else { "~" };
+            format!("{:+} {}x{} ", weight, sign, lit.id())
+        })
+        .collect()
+}
+
+/// Take weighted literals up to the next token that is not a weight.
+fn terms(
+    tokens: &mut std::iter::Peekable<std::vec::IntoIter<(usize, &str)>>,
+    end: usize,
+) -> Result<Vec<(i64, Variable)>, ParseError> {
+    let mut terms = Vec::new();
+    while let Some((offset, token)) = tokens.next_if(|(_, token)| {
+        token.starts_with(|c: char| c.is_ascii_digit() || c == '+' || c == '-')
+    }) {
+        let weight = integer(offset, token, "a weight like +2")?;
+        let lit = match tokens.next() {
+            Some((offset, token)) => {
+                let (negated, name) = match token.strip_prefix('~') {
+                    Some(name) => (true, name),
+                    None => (false, token),
+                };
+                match numbered(name).filter(|_| name.starts_with('x')) {
+                    Some(id) if id > 0 && negated => Variable::Negative(id),
+                    Some(id) if id > 0 => Variable::Positive(id),
+                    _ => {
+                        return Err(ParseError::new(
+                            offset,
+                            token,
+                            &["a literal like x1 or ~x1"],
+                        ))
+                    }
+                }
+            }
+            None => return Err(ParseError::new(end, "", &["a literal like x1 or ~x1"])),
+        };
+        // A product of literals in non-linear problems
+        if let Some((offset, token)) = tokens.next_if(|(_, token)| token.starts_with(['x', '~'])) {
+            return Err(ParseError::new(offset, token, &["a linear term"]));
+        }
+        terms.push((weight, lit));
+    }
+    Ok(terms)
+}
+
+fn integer(offset: usize, token: &str, expected: &str) -> Result<i64, ParseError> {
+    token
+        .parse()
+        .map_err(|_| ParseError::new(offset, token, &[expected]))
+}
+
+/// Split a problem into tokens with their byte offsets, skipping whitespace and comment lines.
+/// The relations and `;` are tokens of their own, even without whitespace around them.
+fn tokenize(input: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    for line in input.split('\n') {
+        let offset = start;
+        start += line.len() + 1;
+        if line.starts_with('*') {
+            continue;
+        }
+        let mut rest = line;
+        while let Some(skipped) = rest.find(|c: char| !c.is_whitespace()) {
+            rest = &rest[skipped..];
+            let position = offset + line.len() - rest.len();
+            let length = if rest.starts_with(">=") || rest.starts_with("<=") {
+                2
+            } else if rest.starts_with([';', '=']) {
+                1
+            } else {
+                rest.find(|c: char| c.is_whitespace() || ";=<>".contains(c))
+                    .unwrap_or(rest.len())
+                    .max(1)
+            };
+            tokens.push((position, &rest[..length]));
+            rest = &rest[length..];
+        }
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_opb() {
+        let input = "
+* #variable= 3 #constraint= 3
+* Pick two, but not both x1 and x3
+min: +2 x1 +1 x2 +3 ~x3 ;
++1 x1 +1 x2 +1 x3 >= 2 ;
+1 x1 +1 x3 <= 1;
+-1 x2 = -1 ;
+";
+        let opb = parse_opb(input).unwrap();
+        assert_eq!(opb.constraints.len(), 3);
+        assert_eq!(opb.constraints[1].comparison, Comparison::AtMost);
+        assert_eq!(opb.objective[2], (3, Variable::Negative(3)));
+        let (solution, cost) = opb.solve().unwrap();
+        assert_eq!(cost, 1);
+        assert!((solution.get(1), solution.get(2), solution.get(3)) == (false, true, true));
+        let output = write_opb(&opb);
+        assert_eq!(
+            output,
+            "* #variable= 3 #constraint= 3\nmin: +2 x1 +1 x2 +3 ~x3 ;\n\
+             +1 x1 +1 x2 +1 x3 >= 2 ;\n-1 x1 -1 x3 >= -1 ;\n-1 x2 = -1 ;\n"
+        );
+        let written = parse_opb(&output).unwrap();
+        assert_eq!(written.solve().map(|(_, cost)| cost), Some(1));
+    }
+
+    #[test]
+    fn test_opb_unsatisfiable() {
+        let opb = parse_opb("+1 x1 +1 x2 >= 3 ;").unwrap();
+        assert_eq!(opb.solve(), None);
+        let formula = opb.to_formula(&mut VarPool::new());
+        assert_eq!(formula.to_string(), "()");
+    }
+
+    #[test]
+    fn test_opb_errors() {
+        let error = parse_opb("+1 x1 x2 >= 1 ;").unwrap_err();
+        assert_eq!(error.to_string(), "Unexpected x2, expected a linear term");
+        let error = parse_opb("+1 y1 >= 1 ;").unwrap_err();
+        assert_eq!(error.span, 3..5);
+        let error = parse_opb("+1 x1 > 1 ;").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected >, expected '>=', '<=' or '='"
+        );
+        let error = parse_opb("+1 x1 >= 1").unwrap_err();
+        assert_eq!(error.to_string(), "Missing ';'");
+    }
+}
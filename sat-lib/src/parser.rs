@@ -1,6 +1,19 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Display, Formatter},
+    ops::Range,
+};
+
 use regex::Regex;
 
-use crate::types::{Clause, Formula, Variable};
+use crate::{
+    encode::Grid,
+    expr::Expr,
+    qbf::Qbf,
+    truth_table::TruthTable,
+    types::{Formula, Literal, VarPool, Variable, MAX_LITERAL},
+};
 
 /// Parse a string into a formula.
 ///
@@ -9,59 +22,769 @@ use crate::types::{Clause, Formula, Variable};
 /// (x1 OR x2) AND (-x2 OR x3) AND (x1 OR -x3)
 /// ```
 /// Into:
-/// ```rust
+/// ```rust,ignore
 /// vec![
 ///    vec![Variable::Positive(1), Variable::Positive(2)],
 ///    vec![Variable::Negative(2), Variable::Positive(3)],
 ///    vec![Variable::Positive(1), Variable::Negative(3)],
 /// ]
 /// ```
-pub fn parse(input: &str) -> Option<Formula> {
-    let mut formula = Formula::new();
-    let and = Regex::new(r"and|&").unwrap();
-    let or = Regex::new(r"or|\|").unwrap();
-    for clause in and.split(&input.to_lowercase()) {
-        let mut variables = Vec::new();
-        let clause = clause.trim().trim_start_matches('(').trim_end_matches(')');
-        for variable in or.split(clause) {
-            let variable = variable.trim();
-            if variable.starts_with('-') {
-                variables.push(Variable::Negative(parse_literal(
-                    variable.trim_start_matches("-"),
-                )?));
-            } else if variable.starts_with('!') {
-                variables.push(Variable::Negative(parse_literal(
-                    variable.trim_start_matches("!"),
-                )?));
-            } else {
-                variables.push(Variable::Positive(parse_literal(variable)?));
-            }
+pub fn parse(input: &str) -> Result<Formula, ParseError> {
+    parse_with(input, &mut VarPool::new())
+}
+
+/// Parse a string into a formula, looking up named variables in the symbol table,
+/// and adding the names that are not in it yet.
+/// Names start with a letter or `_` and are case-sensitive, unlike the keywords and numbered variables.
+/// A numbered variable whose number was given to a name earlier is mapped onto a new variable named like it.
+///
+/// ## Examples
+/// ```plaintext
+/// (rain OR sprinkler) AND (-rain OR wet)
+/// ```
+pub fn parse_with(input: &str, pool: &mut VarPool) -> Result<Formula, ParseError> {
+    parse_at(input, 0, pool)
+}
+
+/// Parse a formula found at the given byte offset of the full input, so errors point into it.
+fn parse_at(input: &str, offset: usize, pool: &mut VarPool) -> Result<Formula, ParseError> {
+    Ok(parse_expr_at(input, offset, pool)?.to_cnf(pool))
+}
+
+/// Parse a string into an expression with any nesting of connectives, looking up named variables
+/// in the symbol table like [`parse_with`].
+///
+/// From the tightest binding to the loosest, the connectives are negation `-`, `!`, `~`, `not` or `¬`,
+/// `and`, `&&`, `&` or `∧`, `xor`, `^` or `⊕`, `or`, `||`, `|` or `∨`, `->` or `→` and `<->` or `↔`,
+/// where `->` groups to the right and the others to the left.
+/// The constants are `true`, `1` or `⊤` and `false`, `0` or `⊥`, which are folded when converting to CNF.
+/// Expressions can span lines, and comments start with `#` or `//` and run to the end of the line.
+///
+/// ## Examples
+/// ```plaintext
+/// ((x1 OR x2) AND x3) OR x4
+/// x1 -> x2 -> x3 <-> -x4 XOR x5
+/// ```
+/// Into:
+/// ```plaintext
+/// ((X1 or X2) and X3) or X4
+/// (X1 -> (X2 -> X3)) <-> (-X4 xor X5)
+/// ```
+pub fn parse_expr(input: &str, pool: &mut VarPool) -> Result<Expr, ParseError> {
+    parse_expr_at(input, 0, pool)
+}
+
+fn parse_expr_at(input: &str, offset: usize, pool: &mut VarPool) -> Result<Expr, ParseError> {
+    parse_expr_recovering(input, offset, pool, &HashMap::new())
+        .map_err(|mut errors| errors.swap_remove(0))
+}
+
+/// Parse an expression, recovering from errors to find all of them.
+/// Names of the bindings stand for their expressions, and are not variables of the pool.
+fn parse_expr_recovering(
+    input: &str,
+    offset: usize,
+    pool: &mut VarPool,
+    bindings: &HashMap<String, Expr>,
+) -> Result<Expr, Vec<ParseError>> {
+    let tokens = tokenize(input, offset);
+    // Reserve the numbered variables first, so names are given identifiers after them
+    for (_, text, token) in &tokens {
+        if let (Token::Word, Some(id)) = (token, numbered(text)) {
+            pool.reserve(id);
+        }
+    }
+    let mut parser = ExprParser {
+        tokens,
+        position: 0,
+        end: offset + input.len(),
+        pool,
+        bindings,
+        errors: Vec::new(),
+    };
+    let expr = parser.top(false);
+    if parser.errors.is_empty() {
+        Ok(expr)
+    } else {
+        Err(parser.errors)
+    }
+}
+
+/// Parse a string into a formula like [`parse_with`], but recover from errors to report all of them
+/// in the order of the input instead of only the first.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 OR 2x) AND (? OR x2
+/// ```
+/// Gives errors for `2x`, `?` and the missing `)`.
+pub fn parse_all_errors(input: &str, pool: &mut VarPool) -> Result<Formula, Vec<ParseError>> {
+    Ok(parse_expr_all_errors(input, pool)?.to_cnf(pool))
+}
+
+/// Parse a string into an expression like [`parse_expr`], but recover from errors to report all of them
+/// like [`parse_all_errors`].
+pub fn parse_expr_all_errors(input: &str, pool: &mut VarPool) -> Result<Expr, Vec<ParseError>> {
+    parse_expr_recovering(input, 0, pool, &HashMap::new())
+}
+
+/// Parse an expression like [`parse_expr_all_errors`], where the names of formulas defined by
+/// [`parse_binding`] stand for them.
+///
+/// ## Examples
+/// ```plaintext
+/// f1 = x1 OR x2
+/// f1 AND -x3
+/// ```
+/// Gives `(x1 OR x2) AND -x3`.
+pub fn parse_expr_bound(
+    input: &str,
+    pool: &mut VarPool,
+    bindings: &HashMap<String, Expr>,
+) -> Result<Expr, Vec<ParseError>> {
+    parse_expr_recovering(input, 0, pool, bindings)
+}
+
+/// Parse the definition of a named formula, giving the name and the expression it stands for.
+/// The name is like the names of variables, but not a numbered variable or a keyword,
+/// and is not added to the pool. The expression can use earlier bindings like [`parse_expr_bound`].
+///
+/// ## Examples
+/// ```plaintext
+/// f1 = (x1 OR x2) AND -x3
+/// ```
+pub fn parse_binding<'a>(
+    input: &'a str,
+    pool: &mut VarPool,
+    bindings: &HashMap<String, Expr>,
+) -> Result<(&'a str, Expr), ParseError> {
+    let mut tokens = tokenize(input, 0).into_iter();
+    let name = match tokens.next() {
+        Some((_, name, Token::Word)) if digits(name).is_none() => name,
+        Some((offset, token, _)) => {
+            return Err(ParseError::new(offset, token, &["a name like f1"]))
+        }
+        None => return Err(ParseError::new(input.len(), "", &["a name like f1"])),
+    };
+    let offset = match tokens.next() {
+        Some((offset, "=", _)) => offset + 1,
+        Some((offset, token, _)) => return Err(ParseError::new(offset, token, &["'='"])),
+        None => return Err(ParseError::new(input.len(), "", &["'='"])),
+    };
+    let expr = parse_expr_recovering(&input[offset..], offset, pool, bindings)
+        .map_err(|mut errors| errors.swap_remove(0))?;
+    Ok((name, expr))
+}
+
+/// Parse a quantified formula with universal variables followed by existential variables.
+/// Variables of the formula that are not quantified are existential.
+///
+/// ## Examples
+/// ```plaintext
+/// forall x1 x2 exists x3: (x1 OR x3) AND (x2 OR -x3)
+/// ```
+pub fn parse_qbf(input: &str, pool: &mut VarPool) -> Result<Qbf, ParseError> {
+    let (offset, input) = trim((0, input), char::is_whitespace);
+    let Some(prefix) = input
+        .get(..6)
+        .filter(|prefix| prefix.eq_ignore_ascii_case("forall"))
+    else {
+        let token = input.split_whitespace().next().unwrap_or_default();
+        return Err(ParseError::new(offset, token, &["forall"]));
+    };
+    let offset = offset + prefix.len();
+    let input = &input[prefix.len()..];
+    let Some((quantifiers, matrix)) = input.split_once(':') else {
+        return Err(ParseError::new(offset + input.len(), "", &["':'"]));
+    };
+    let exists = Regex::new(r"(?i)\bexists\b").unwrap();
+    let (universal, existential) = match exists.find(quantifiers) {
+        Some(m) => (
+            (offset, &quantifiers[..m.start()]),
+            (offset + m.end(), &quantifiers[m.end()..]),
+        ),
+        None => ((offset, quantifiers), (offset + quantifiers.len(), "")),
+    };
+    let separator = Regex::new(r"[\s,]").unwrap();
+    let mut parse_variables = |(offset, variables): (usize, &str)| {
+        split(&separator, variables, offset)
+            .into_iter()
+            .filter(|(_, variable)| !variable.is_empty())
+            .map(|(offset, variable)| parse_literal(variable, offset, pool))
+            .collect::<Result<Vec<_>, _>>()
+    };
+    let universal = parse_variables(universal)?;
+    let mut existential = parse_variables(existential)?;
+    let matrix = parse_at(matrix, offset + quantifiers.len() + 1, pool)?;
+    for id in matrix.literals() {
+        if !universal.contains(&id) && !existential.contains(&id) {
+            existential.push(id);
         }
-        formula.add(Clause(variables));
     }
-    Some(formula)
+    Ok(Qbf {
+        universal,
+        existential,
+        matrix,
+    })
 }
 
-fn parse_literal(literal: &str) -> Option<u32> {
-    if literal.trim().is_empty() {
-        eprintln!("Missing variable!");
-        return None;
+/// Parse a linear objective over variables into a list of weighted literals.
+///
+/// ## Examples
+/// ```plaintext
+/// 2 x1 + x2 - 3*x3
+/// ```
+/// Into:
+/// ```rust,ignore
+/// vec![(1, 2), (2, 1), (3, -3)]
+/// ```
+pub fn parse_objective(input: &str, pool: &mut VarPool) -> Result<Vec<(Literal, i64)>, ParseError> {
+    // Split before each sign, keeping minus signs with their term
+    let mut terms = Vec::new();
+    let mut start = 0;
+    for (i, c) in input.char_indices() {
+        if c == '+' || c == '-' {
+            terms.push((start, &input[start..i]));
+            start = if c == '+' { i + 1 } else { i };
+        }
     }
-    if let Some(num) = literal.trim().strip_prefix("x") {
-        if let Ok(num) = num.parse() {
-            Some(num)
+    terms.push((start, &input[start..]));
+    let mut objective = Vec::new();
+    for term in terms {
+        let (offset, term) = trim(term, char::is_whitespace);
+        if term.is_empty() {
+            continue;
+        }
+        let (negative, (offset, term)) = match term.strip_prefix('-') {
+            Some(term) => (true, trim((offset + 1, term), char::is_whitespace)),
+            None => (false, (offset, term)),
+        };
+        let index = term
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(term.len());
+        let (weight, variable) = term.split_at(index);
+        let variable = trim((offset + index, variable), |c| {
+            c.is_whitespace() || c == '*'
+        });
+        let weight: i64 = if weight.is_empty() {
+            1
+        } else if let Ok(weight) = weight.parse() {
+            weight
         } else {
-            eprintln!("Invalid variable: {}, expected a number", literal);
-            None
+            return Err(ParseError::new(offset, weight, &["a smaller weight"]));
+        };
+        let weight = if negative { -weight } else { weight };
+        objective.push((parse_literal(variable.1, variable.0, pool)?, weight));
+    }
+    if objective.is_empty() {
+        return Err(ParseError::new(input.len(), "", VARIABLE));
+    }
+    Ok(objective)
+}
+
+/// Parse a Sudoku puzzle of 81 cells row by row, with digits for given cells and `.` or `0` for empty cells.
+/// Whitespace between cells is ignored.
+///
+/// ## Examples
+/// ```plaintext
+/// 53..7.... 6..195... .98....6. 8...6...3 4..8.3..1 7...2...6 .6....28. ...419..5 ....8..79
+/// ```
+pub fn parse_sudoku(input: &str) -> Result<Grid, ParseError> {
+    let mut grid = [[None; 9]; 9];
+    let mut cells = 0;
+    for (offset, c) in input.char_indices() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let token = &input[offset..offset + c.len_utf8()];
+        let cell = match c {
+            '.' | '0' => None,
+            '1'..='9' => c.to_digit(10).map(|digit| digit as u8),
+            _ => return Err(ParseError::new(offset, token, &["a digit", "'.'"])),
+        };
+        if cells == 81 {
+            return Err(ParseError::new(offset, token, &["the end of the puzzle"]));
+        }
+        grid[cells / 9][cells % 9] = cell;
+        cells += 1;
+    }
+    if cells < 81 {
+        let missing = format!("{} more cells", 81 - cells);
+        return Err(ParseError::new(input.len(), "", &[&missing]));
+    }
+    Ok(grid)
+}
+
+/// Parse a truth table of a Boolean function, with its variables before a `:`
+/// and then its value in each row as `0` or `1`, see [`TruthTable`].
+///
+/// ## Examples
+/// ```plaintext
+/// x1 x2 x3 : 10110100
+/// ```
+pub fn parse_truth_table(input: &str, pool: &mut VarPool) -> Result<TruthTable, ParseError> {
+    let Some((header, rows)) = input.split_once(':') else {
+        return Err(ParseError::new(input.len(), "", &["':'"]));
+    };
+    let separator = Regex::new(r"[\s,]").unwrap();
+    let mut variables = Vec::new();
+    for (offset, variable) in split(&separator, header, 0) {
+        if variable.is_empty() {
+            continue;
+        }
+        let id = parse_literal(variable, offset, pool)?;
+        if variables.contains(&id) {
+            return Err(ParseError::new(offset, variable, &["a new variable"]));
+        }
+        variables.push(id);
+    }
+    let rows_offset = header.len() + 1;
+    let count = 1usize
+        .checked_shl(variables.len() as u32)
+        .unwrap_or(usize::MAX);
+    let mut values = Vec::new();
+    for (offset, c) in rows.char_indices() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let offset = rows_offset + offset;
+        let token = &input[offset..offset + c.len_utf8()];
+        let value = match c {
+            '0' => false,
+            '1' => true,
+            _ => return Err(ParseError::new(offset, token, &["'0'", "'1'"])),
+        };
+        if values.len() == count {
+            return Err(ParseError::new(offset, token, &["the end of the table"]));
+        }
+        values.push(value);
+    }
+    if values.len() < count {
+        let missing = match count - values.len() {
+            1 => "1 more value".to_string(),
+            missing => format!("{} more values", missing),
+        };
+        return Err(ParseError::new(input.len(), "", &[&missing]));
+    }
+    Ok(TruthTable { variables, values })
+}
+
+/// Parse a list of variables separated by whitespace or commas, like the variables to show in solutions.
+///
+/// ## Examples
+/// ```plaintext
+/// x1, x40 rain
+/// ```
+pub fn parse_variables(input: &str, pool: &mut VarPool) -> Result<Vec<Literal>, ParseError> {
+    let separator = Regex::new(r"[\s,]").unwrap();
+    let mut variables = Vec::new();
+    for (offset, variable) in split(&separator, input, 0) {
+        if !variable.is_empty() {
+            variables.push(parse_literal(variable, offset, pool)?);
         }
+    }
+    if variables.is_empty() {
+        return Err(ParseError::new(input.len(), "", VARIABLE));
+    }
+    Ok(variables)
+}
+
+/// Parse a list of variables that may be negated, separated by whitespace or commas, like assumptions to solve under.
+///
+/// ## Examples
+/// ```plaintext
+/// x3 -x5, !rain
+/// ```
+pub fn parse_assumptions(input: &str, pool: &mut VarPool) -> Result<Vec<Variable>, ParseError> {
+    let separator = Regex::new(r"[\s,]").unwrap();
+    let mut assumptions = Vec::new();
+    for (offset, assumption) in split(&separator, input, 0) {
+        if assumption.is_empty() {
+            continue;
+        }
+        let variable = assumption.trim_start_matches(['-', '!', '~', '¬']);
+        let negations = assumption.chars().count() - variable.chars().count();
+        let id = parse_literal(variable, offset + assumption.len() - variable.len(), pool)?;
+        assumptions.push(if negations % 2 == 1 {
+            Variable::Negative(id)
+        } else {
+            Variable::Positive(id)
+        });
+    }
+    if assumptions.is_empty() {
+        return Err(ParseError::new(input.len(), "", VARIABLE));
+    }
+    Ok(assumptions)
+}
+
+/// What is expected where a variable is missing or invalid.
+const VARIABLE: &[&str] = &["xN", "a name"];
+
+/// What is expected between the operands of an expression.
+const OPERATOR: &[&str] = &["and", "or", "xor", "->", "<->"];
+
+/// An error found while parsing, pointing at the offending token of the input.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    /// The byte range of the token in the input, empty if something is missing.
+    pub span: Range<usize>,
+    pub token: String,
+    /// The alternatives that would have been accepted instead of the token.
+    pub expected: Vec<String>,
+}
+
+impl ParseError {
+    pub(crate) fn new(offset: usize, token: &str, expected: &[&str]) -> Self {
+        Self {
+            span: offset..offset + token.len(),
+            token: token.to_string(),
+            expected: expected.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    /// Get the line and column of the start of the error in the input, both counted from 1,
+    /// where columns count characters.
+    pub fn line_column(&self, input: &str) -> (usize, usize) {
+        let before = &input[..self.span.start];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        (
+            before.matches('\n').count() + 1,
+            before[line_start..].chars().count() + 1,
+        )
+    }
+
+    /// Show the line of the input with the error, with the offending region underlined by carets.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// 2 | 1 x2 0
+    ///   |   ^^
+    /// ```
+    pub fn snippet(&self, input: &str) -> String {
+        let (line, column) = self.line_column(input);
+        let text = input.lines().nth(line - 1).unwrap_or_default();
+        let width = input[self.span.clone()].chars().count().max(1);
+        let margin = " ".repeat(line.to_string().len());
+        format!(
+            "{} | {}\n{} | {}{}\n",
+            line,
+            text,
+            margin,
+            " ".repeat(column - 1),
+            "^".repeat(width)
+        )
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let expected = match self.expected.split_last() {
+            Some((last, [])) => last.clone(),
+            Some((last, rest)) => format!("{} or {}", rest.join(", "), last),
+            None => "nothing".to_string(),
+        };
+        if self.token.is_empty() {
+            write!(f, "Missing {}", expected)
+        } else {
+            write!(f, "Unexpected {}, expected {}", self.token, expected)
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// The kinds of tokens in an expression.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Not,
+    And,
+    Or,
+    Xor,
+    Implies,
+    Iff,
+    Const(bool),
+    /// A variable, or something invalid made of the characters of names.
+    Word,
+    /// A character that can't start any token.
+    Unknown,
+}
+
+/// Split an expression into tokens, keeping the byte offset and text of every token.
+fn tokenize(input: &str, offset: usize) -> Vec<(usize, &str, Token)> {
+    const SYMBOLS: &[(&str, Token)] = &[
+        ("<->", Token::Iff),
+        ("->", Token::Implies),
+        ("(", Token::Open),
+        (")", Token::Close),
+        ("-", Token::Not),
+        ("!", Token::Not),
+        ("&&", Token::And),
+        ("||", Token::Or),
+        ("&", Token::And),
+        ("|", Token::Or),
+        ("^", Token::Xor),
+        ("~", Token::Not),
+        ("¬", Token::Not),
+        ("∧", Token::And),
+        ("∨", Token::Or),
+        ("⊕", Token::Xor),
+        ("→", Token::Implies),
+        ("↔", Token::Iff),
+        ("⊤", Token::Const(true)),
+        ("⊥", Token::Const(false)),
+    ];
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while let Some(c) = input[start..].chars().next() {
+        let rest = &input[start..];
+        let length = if c.is_whitespace() {
+            c.len_utf8()
+        } else if c == '#' || rest.starts_with("//") {
+            // Comments run to the end of the line
+            rest.find('\n').unwrap_or(rest.len())
+        } else if let Some((symbol, token)) = SYMBOLS.iter().find(|(s, _)| rest.starts_with(s)) {
+            tokens.push((offset + start, *symbol, *token));
+            symbol.len()
+        } else if c.is_alphanumeric() || c == '_' {
+            let word = rest
+                .find(|c: char| !c.is_alphanumeric() && c != '_')
+                .map_or(rest, |end| &rest[..end]);
+            let token = match word.to_lowercase().as_str() {
+                "and" => Token::And,
+                "or" => Token::Or,
+                "xor" => Token::Xor,
+                "not" => Token::Not,
+                "true" | "1" => Token::Const(true),
+                "false" | "0" => Token::Const(false),
+                _ => Token::Word,
+            };
+            tokens.push((offset + start, word, token));
+            word.len()
+        } else {
+            tokens.push((offset + start, &rest[..c.len_utf8()], Token::Unknown));
+            c.len_utf8()
+        };
+        start += length;
+    }
+    tokens
+}
+
+/// A Pratt parser building an expression from its tokens.
+struct ExprParser<'a, 'p> {
+    tokens: Vec<(usize, &'a str, Token)>,
+    position: usize,
+    /// The byte offset of the end of the input, where missing tokens are reported.
+    end: usize,
+    pool: &'p mut VarPool,
+    /// The named formulas, replacing their names where they are used.
+    bindings: &'p HashMap<String, Expr>,
+    /// The errors found so far, in the order of the input.
+    errors: Vec<ParseError>,
+}
+
+impl<'a> ExprParser<'a, '_> {
+    fn peek(&self) -> Option<(usize, &'a str, Token)> {
+        self.tokens.get(self.position).copied()
+    }
+
+    /// Record an error and get a placeholder for the operand it was found in, to continue parsing.
+    /// An error at the same place as the last one is left out, when recovery reaches it again.
+    fn fail(&mut self, error: ParseError) -> Expr {
+        if self
+            .errors
+            .last()
+            .is_none_or(|last| last.span != error.span)
+        {
+            self.errors.push(error);
+        }
+        Expr::Const(true)
+    }
+
+    /// Parse an expression whose connectives bind at least as tightly as the given power.
+    fn expr(&mut self, power: u8) -> Expr {
+        let operand = self.operand();
+        self.connectives(operand, power)
+    }
+
+    /// Parse the connectives following an operand that bind at least as tightly as the given power.
+    fn connectives(&mut self, mut expr: Expr, power: u8) -> Expr {
+        while let Some((_, _, token)) = self.peek() {
+            // The binding power on each side, where a higher right power groups to the left
+            let (left, right) = match token {
+                Token::Iff => (1, 2),
+                Token::Implies => (3, 3),
+                Token::Or => (5, 6),
+                Token::Xor => (7, 8),
+                Token::And => (9, 10),
+                _ => break,
+            };
+            if left < power {
+                break;
+            }
+            self.position += 1;
+            let other = self.expr(right);
+            expr = match token {
+                Token::Iff => expr.iff(other),
+                Token::Implies => expr.implies(other),
+                Token::Or => expr.or(other),
+                Token::Xor => expr.xor(other),
+                _ => expr.and(other),
+            };
+        }
+        expr
+    }
+
+    /// Parse a whole expression up to the end of the input, or a closing parenthesis if nested.
+    /// After something that isn't a connective, the rest is parsed as if it was conjoined to find more errors.
+    fn top(&mut self, nested: bool) -> Expr {
+        let mut expr = self.expr(0);
+        while let Some((offset, text, token)) = self.peek() {
+            match token {
+                Token::Close if nested => break,
+                Token::Close => {
+                    self.fail(ParseError::new(offset, text, OPERATOR));
+                    self.position += 1;
+                }
+                _ if nested => {
+                    self.fail(ParseError::new(
+                        offset,
+                        text,
+                        &[OPERATOR, &["')'"]].concat(),
+                    ));
+                }
+                _ => {
+                    self.fail(ParseError::new(offset, text, OPERATOR));
+                }
+            }
+            expr = match self.peek() {
+                Some((_, _, Token::And | Token::Or | Token::Xor | Token::Implies | Token::Iff)) => {
+                    self.connectives(expr, 0)
+                }
+                _ => expr.and(self.expr(0)),
+            };
+        }
+        expr
+    }
+
+    /// Parse a variable, a negated operand or an expression in parentheses.
+    fn operand(&mut self) -> Expr {
+        let Some((offset, text, token)) = self.peek() else {
+            return self.fail(ParseError::new(self.end, "", VARIABLE));
+        };
+        match token {
+            // Left for the enclosing parentheses
+            Token::Close => return self.fail(ParseError::new(offset, text, VARIABLE)),
+            // Skipped to continue with the next operand
+            Token::And | Token::Or | Token::Xor | Token::Implies | Token::Iff => {
+                self.fail(ParseError::new(offset, text, VARIABLE));
+                self.position += 1;
+                return self.operand();
+            }
+            _ => (),
+        }
+        self.position += 1;
+        match token {
+            Token::Not => !self.operand(),
+            Token::Open => {
+                let expr = self.top(true);
+                if self.peek().is_some() {
+                    self.position += 1;
+                } else {
+                    self.fail(ParseError::new(self.end, "", &["')'"]));
+                }
+                expr
+            }
+            Token::Const(value) => Expr::Const(value),
+            Token::Unknown => self.fail(ParseError::new(offset, text, VARIABLE)),
+            Token::Word if self.bindings.contains_key(text) => self.bindings[text].clone(),
+            _ => match parse_literal(text, offset, self.pool) {
+                Ok(id) => Expr::Var(id),
+                Err(error) => self.fail(error),
+            },
+        }
+    }
+}
+
+/// Split the input at each match of the separator, keeping the byte offset of every part.
+fn split<'a>(separator: &Regex, input: &'a str, offset: usize) -> Vec<(usize, &'a str)> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for m in separator.find_iter(input) {
+        parts.push((offset + start, &input[start..m.start()]));
+        start = m.end();
+    }
+    parts.push((offset + start, &input[start..]));
+    parts
+}
+
+/// Trim the matching characters from both ends of a part, moving its offset past the removed start.
+fn trim(part: (usize, &str), pattern: impl Fn(char) -> bool) -> (usize, &str) {
+    let (offset, part) = part;
+    let start = part.trim_start_matches(&pattern);
+    (
+        offset + part.len() - start.len(),
+        start.trim_end_matches(&pattern),
+    )
+}
+
+/// Parse a numbered variable like `x3`, or `𝑋₃` as printed in the mathematical style, into its identifier.
+/// Numbers above [`MAX_LITERAL`] are not identifiers.
+pub(crate) fn numbered(literal: &str) -> Option<Literal> {
+    digits(literal)?
+        .parse()
+        .ok()
+        .filter(|id| *id <= MAX_LITERAL)
+}
+
+/// Get the digits of a numbered variable, however large the number is.
+fn digits(literal: &str) -> Option<String> {
+    let number = literal.trim().strip_prefix(['x', 'X', '𝑥', '𝑋'])?;
+    let digits = number
+        .chars()
+        .map(|c| match c {
+            '₀'..='₉' => char::from_digit(c as u32 - '₀' as u32, 10),
+            c => Some(c),
+        })
+        .collect::<Option<String>>()?;
+    Some(digits).filter(|digits| !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Parse a variable found at the given byte offset of the input.
+pub(crate) fn parse_literal(
+    literal: &str,
+    offset: usize,
+    pool: &mut VarPool,
+) -> Result<Literal, ParseError> {
+    let (offset, literal) = trim((offset, literal), char::is_whitespace);
+    if literal.is_empty() {
+        return Err(ParseError::new(offset, "", VARIABLE));
+    }
+    if digits(literal).is_some() && numbered(literal).is_none() {
+        let expected = format!("xN up to x{}", MAX_LITERAL);
+        Err(ParseError::new(offset, literal, &[&expected]))
+    } else if let Some(id) = numbered(literal) {
+        // A number already given to a name is mapped onto a new identifier, named like the variable
+        let name = format!("x{}", id);
+        match pool.get(&name) {
+            Some(mapped) => Ok(mapped),
+            None if pool.reserve(id) => Ok(id),
+            None => Ok(pool.id(&name)),
+        }
+    } else if literal.starts_with(|c: char| c.is_alphabetic() || c == '_')
+        && literal.chars().all(|c| c.is_alphanumeric() || c == '_')
+    {
+        Ok(pool.id(literal))
     } else {
-        eprintln!("Invalid variable: {}, expected xN", literal);
-        None
+        Err(ParseError::new(offset, literal, VARIABLE))
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::types::Variable;
+
     use super::*;
 
     #[test]
@@ -73,13 +796,269 @@ mod tests {
             vec![Variable::Positive(1), Variable::Negative(3)],
         ]
         .into();
-        assert_eq!(parse(input), Some(expected));
+        assert_eq!(parse(input), Ok(expected));
+    }
+
+    #[test]
+    fn test_parse_names() {
+        let mut pool = VarPool::new();
+        let input = "(rain OR sprinkler) AND (-rain OR wet) AND (x1 OR -wet)";
+        let expected: Formula = vec![
+            vec![Variable::Positive(2), Variable::Positive(3)],
+            vec![Variable::Negative(2), Variable::Positive(4)],
+            vec![Variable::Positive(1), Variable::Negative(4)],
+        ]
+        .into();
+        assert_eq!(parse_with(input, &mut pool), Ok(expected));
+        assert_eq!(pool.name(3), Some("sprinkler"));
+        // Names containing the operators are not split
+        let formula = parse_with("candy or orange", &mut pool).unwrap();
+        assert_eq!(formula.literals(), [5, 6]);
+        // Names keep their spelling, and differ by case
+        let formula = parse_with("Rain or -rain or Wet_2", &mut pool).unwrap();
+        assert_eq!(formula.literals(), [2, 7, 8]);
+        assert_eq!(pool.name(7), Some("Rain"));
+        assert_eq!(pool.get("Wet_2"), Some(8));
+    }
+
+    #[test]
+    fn test_parse_expr() {
+        let mut pool = VarPool::new();
+        let expr = parse_expr("((x1 or x2) and x3) or x4", &mut pool).unwrap();
+        assert_eq!(
+            expr,
+            ((Expr::var(1) | Expr::var(2)) & Expr::var(3)) | Expr::var(4)
+        );
+        let expr = parse_expr("x1 -> x2 -> x3 <-> -x4 XOR x5", &mut pool).unwrap();
+        assert_eq!(expr.to_string(), "(X1 -> (X2 -> X3)) <-> (-X4 xor X5)");
+        let expr = parse_expr("!x1 & x2 | x3 ^ x4 & -(x5)", &mut pool).unwrap();
+        assert_eq!(expr.to_string(), "(-X1 and X2) or (X3 xor (X4 and -X5))");
+        let expr = parse_expr("!(x1 && x2) || x3 && !x4", &mut pool).unwrap();
+        assert_eq!(expr.to_string(), "-(X1 and X2) or (X3 and -X4)");
+        let expr = parse_expr("~x1 ∧ ¬(𝑋₂ ∨ x3) → not x4 ⊕ ⊤ ↔ ⊥", &mut pool).unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "((-X1 and -(X2 or X3)) -> (-X4 xor T)) <-> F"
+        );
+        let formula = parse("(𝑋₁ ∨ ¬𝑋₂) ∧ (𝑋₁₂)").unwrap();
+        assert_eq!(formula.to_string(), "(X1 or -X2) and (X12)");
+        let expr = parse_expr("x1 or x2 or (x3 or x4)", &mut pool).unwrap();
+        assert_eq!(expr.to_string(), "X1 or X2 or X3 or X4");
+        // Formulas not in CNF are converted to an equivalent CNF
+        let formula = parse("((x1 or x2) and x3) or x4").unwrap();
+        assert_eq!(formula.to_string(), "(X1 or X2 or X4) and (X3 or X4)");
+        let formula = parse("x1 <-> x2").unwrap();
+        assert_eq!(formula.to_string(), "(-X1 or X2) and (X1 or -X2)");
+        let input = "# Rain makes the grass wet\n(-rain or wet) // unless covered\n  and rain";
+        let expr = parse_expr(input, &mut pool).unwrap();
+        assert_eq!(expr.to_string(), "(-X6 or X7) and X6");
+        let formula = parse("x1 and FALSE").unwrap();
+        assert_eq!(formula.to_string(), "()");
+        let formula = parse("(x1 or 1) and (x2 -> 0) and (true <-> x3)").unwrap();
+        assert_eq!(formula.to_string(), "(-X2) and (X3)");
     }
 
     #[test]
     fn test_parse_literal() {
-        assert_eq!(parse_literal("x1"), Some(1));
-        assert_eq!(parse_literal("x2"), Some(2));
-        assert_eq!(parse_literal("x3"), Some(3));
+        let mut pool = VarPool::new();
+        assert_eq!(parse_literal("x1", 0, &mut pool), Ok(1));
+        assert_eq!(parse_literal("x2", 0, &mut pool), Ok(2));
+        assert_eq!(parse_literal("x3", 0, &mut pool), Ok(3));
+        assert_eq!(parse_literal("rain", 0, &mut pool), Ok(4));
+        // x4 belongs to rain, so it is mapped onto a new variable named after it
+        assert_eq!(parse_literal("x4", 0, &mut pool), Ok(5));
+        assert_eq!(parse_literal("X₄", 0, &mut pool), Ok(5));
+        assert_eq!(pool.name(5), Some("x4"));
+        assert_eq!(parse_literal("x5", 0, &mut pool), Ok(6));
+        assert!(parse_literal("2x", 0, &mut pool).is_err());
+        let error = parse_literal("x3000000000", 0, &mut pool).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected x3000000000, expected xN up to x2147483646"
+        );
+        assert_eq!(parse_literal("x2147483646", 0, &mut pool), Ok(MAX_LITERAL));
+    }
+
+    #[test]
+    fn test_parse_objective() {
+        assert_eq!(
+            parse_objective("2 x1 + x2 - 3*x3", &mut VarPool::new()),
+            Ok(vec![(1, 2), (2, 1), (3, -3)])
+        );
+        assert_eq!(
+            parse_objective("-x1", &mut VarPool::new()),
+            Ok(vec![(1, -1)])
+        );
+        assert!(parse_objective("2 + x1", &mut VarPool::new()).is_err());
+    }
+
+    #[test]
+    fn test_parse_qbf() {
+        let expected = Qbf {
+            universal: vec![1, 2],
+            existential: vec![3],
+            matrix: vec![
+                vec![Variable::Positive(1), Variable::Positive(3)],
+                vec![Variable::Positive(2), Variable::Negative(3)],
+            ]
+            .into(),
+        };
+        let input = "forall x1 x2 exists x3: (x1 OR x3) AND (x2 OR -x3)";
+        assert_eq!(parse_qbf(input, &mut VarPool::new()), Ok(expected.clone()));
+        let input = "forall x1, x2: (x1 OR x3) AND (x2 OR -x3)";
+        assert_eq!(parse_qbf(input, &mut VarPool::new()), Ok(expected));
+    }
+
+    #[test]
+    fn test_parse_error() {
+        let error = parse("(x1 OR x2) AND (-x2 OR 2x)").unwrap_err();
+        assert_eq!(error.span, 23..25);
+        assert_eq!(error.token, "2x");
+        assert_eq!(error.to_string(), "Unexpected 2x, expected xN or a name");
+        let error = parse("x1 OR").unwrap_err();
+        assert_eq!(error.span, 5..5);
+        assert_eq!(error.to_string(), "Missing xN or a name");
+        let error = parse("(x1 OR (x2 AND x3)").unwrap_err();
+        assert_eq!(error.span, 18..18);
+        assert_eq!(error.to_string(), "Missing ')'");
+        let error = parse("(x1 x2)").unwrap_err();
+        assert_eq!(error.span, 4..6);
+        assert_eq!(error.expected.last().unwrap(), "')'");
+        let error = parse("x1) or x2").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected ), expected and, or, xor, -> or <->"
+        );
+        let error = parse("x1 and ? x2").unwrap_err();
+        assert_eq!((error.span, error.token.as_str()), (7..8, "?"));
+        let error = parse_qbf("forall x1 (x1 OR x2)", &mut VarPool::new()).unwrap_err();
+        assert_eq!(error.span, 20..20);
+        assert_eq!(error.expected, ["':'"]);
+        let error = parse_qbf("forall x1: x1 OR -2", &mut VarPool::new()).unwrap_err();
+        assert_eq!(error.span, 18..19);
+        let error = parse_objective("2 x1 + 3 *?", &mut VarPool::new()).unwrap_err();
+        assert_eq!((error.span, error.token.as_str()), (10..11, "?"));
+        let error = parse_sudoku("53..7x").unwrap_err();
+        assert_eq!(error.to_string(), "Unexpected x, expected a digit or '.'");
+        let error = parse_sudoku("53..7").unwrap_err();
+        assert_eq!(error.to_string(), "Missing 76 more cells");
+    }
+
+    #[test]
+    fn test_parse_all_errors() {
+        let mut pool = VarPool::new();
+        let errors = parse_all_errors("(x1 OR 2x) AND (? OR x2", &mut pool).unwrap_err();
+        let spans = errors.iter().map(|e| e.span.clone()).collect::<Vec<_>>();
+        assert_eq!(spans, [7..9, 16..17, 23..23]);
+        assert_eq!(errors[2].to_string(), "Missing ')'");
+        let errors = parse_all_errors("x1 x2) or and x3 ?", &mut pool).unwrap_err();
+        let tokens = errors.iter().map(|e| e.token.as_str()).collect::<Vec<_>>();
+        assert_eq!(tokens, ["x2", ")", "and", "?"]);
+        assert_eq!(
+            parse_all_errors("x1 -> (x2 or x3)", &mut pool),
+            Ok(parse("x1 -> (x2 or x3)").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_parse_error_snippet() {
+        let input = "p cnf 2 1\n1 x2 0\n";
+        let error = crate::dimacs::parse_dimacs(input).unwrap_err();
+        assert_eq!(error.line_column(input), (2, 3));
+        assert_eq!(error.snippet(input), "2 | 1 x2 0\n  |   ^^\n");
+        let input = "¬𝑋₁ ∧ ?";
+        let error = parse(input).unwrap_err();
+        assert_eq!(error.line_column(input), (1, 7));
+        assert_eq!(error.snippet(input), "1 | ¬𝑋₁ ∧ ?\n  |       ^\n");
+    }
+
+    #[test]
+    fn test_parse_sudoku() {
+        let puzzle = "53..7.... 6..195... .98....6. 8...6...3 4..8.3..1 7...2...6 .6....28. ...419..5 ....8..79";
+        let grid = parse_sudoku(puzzle).unwrap();
+        assert_eq!(grid[0][..3], [Some(5), Some(3), None]);
+        assert_eq!(grid[8][8], Some(9));
+        assert_eq!(parse_sudoku(&puzzle.replace('.', "0")), Ok(grid));
+        let error = parse_sudoku(&format!("{}1", puzzle)).unwrap_err();
+        assert_eq!(error.span, 89..90);
+    }
+
+    #[test]
+    fn test_parse_truth_table() {
+        let mut pool = VarPool::new();
+        let table = parse_truth_table("x1 x2 rain : 1011 0100", &mut pool).unwrap();
+        assert_eq!(table.variables, [1, 2, 3]);
+        assert_eq!(
+            table.values,
+            [true, false, true, true, false, true, false, false]
+        );
+        let error = parse_truth_table("x1 x2 : 0110 1", &mut pool).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected 1, expected the end of the table"
+        );
+        let error = parse_truth_table("x1 x2 : 01", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "Missing 2 more values");
+        let error = parse_truth_table("x1 x1 : 0110", &mut pool).unwrap_err();
+        assert_eq!(error.span, 3..5);
+        let error = parse_truth_table("x1 : 0x", &mut pool).unwrap_err();
+        assert_eq!(error.span, 6..7);
+    }
+
+    #[test]
+    fn test_parse_variables() {
+        let mut pool = VarPool::new();
+        pool.reserve(1);
+        assert_eq!(
+            parse_variables("x1, rain  x40", &mut pool),
+            Ok(vec![1, 2, 40])
+        );
+        let error = parse_variables(" , ", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "Missing xN or a name");
+        let error = parse_variables("x1 -x2", &mut pool).unwrap_err();
+        assert_eq!(error.span, 3..6);
+    }
+
+    #[test]
+    fn test_parse_assumptions() {
+        let mut pool = VarPool::new();
+        assert_eq!(
+            parse_assumptions("x3 -x5, !rain ~~x1", &mut pool),
+            Ok(vec![
+                Variable::Positive(3),
+                Variable::Negative(5),
+                Variable::Negative(6),
+                Variable::Positive(1)
+            ])
+        );
+        let error = parse_assumptions("x1 -", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "Missing xN or a name");
+        let error = parse_assumptions("x1 -2x", &mut pool).unwrap_err();
+        assert_eq!(error.span, 4..6);
+    }
+
+    #[test]
+    fn test_parse_binding() {
+        let mut pool = VarPool::new();
+        let mut bindings = HashMap::new();
+        let (name, expr) =
+            parse_binding("f1 = (x1 or x2) and -rain", &mut pool, &bindings).unwrap();
+        assert_eq!(name, "f1");
+        assert_eq!(expr.to_string(), "(X1 or X2) and -X3");
+        assert_eq!(pool.get("f1"), None);
+        bindings.insert(name.to_string(), expr);
+        let (_, expr) = parse_binding("f2 = f1 or x4", &mut pool, &bindings).unwrap();
+        assert_eq!(expr.to_string(), "((X1 or X2) and -X3) or X4");
+        let expr = parse_expr_bound("-f1 and x5", &mut pool, &bindings).unwrap();
+        assert_eq!(expr.to_string(), "-((X1 or X2) and -X3) and X5");
+        // The names of bindings take no identifiers
+        assert_eq!(pool.get("f1"), None);
+        assert_eq!(pool.get("f2"), None);
+        let error = parse_binding("x1 = x2", &mut pool, &bindings).unwrap_err();
+        assert_eq!(error.to_string(), "Unexpected x1, expected a name like f1");
+        let error = parse_binding("f2 x1", &mut pool, &bindings).unwrap_err();
+        assert_eq!(error.span, 3..5);
+        let error = parse_binding("f2 = x1 or", &mut pool, &bindings).unwrap_err();
+        assert_eq!(error.span, 10..10);
     }
 }
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+
+use crate::types::{Clause, Formula, Literal, Variable};
+
+use super::{Preprocessor, Reconstruction};
+
+/// Bounded variable elimination (as in SatELite) removes a variable `x` by replacing all clauses
+/// containing `x` or `-x` with their pairwise resolvents on `x`, as long as that does not increase the number of clauses.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 OR x2) AND (-x1 OR x3) AND (x2 OR x4)
+/// eliminate x1
+/// (x2 OR x3) AND (x2 OR x4)
+/// ```
+pub struct VariableElimination {
+    /// Variables occurring in more clauses than this, with either sign, are not eliminated.
+    pub max_occurrences: usize,
+}
+
+impl Default for VariableElimination {
+    fn default() -> Self {
+        Self {
+            max_occurrences: 16,
+        }
+    }
+}
+
+impl Preprocessor for VariableElimination {
+    fn preprocess(&self, formula: &mut Formula, reconstruction: &mut Reconstruction) {
+        // Tautologies are always satisfied, and would be counted on one side of their variable only
+        formula.0.retain(|clause| !clause.is_tautology());
+        formula.recount();
+        // Try the variables that are cheapest to eliminate first
+        let mut candidates = formula.literals();
+        candidates.sort_by_key(|id| {
+            let (positive, negative) = occurrences(formula, *id);
+            positive.len() * negative.len()
+        });
+        for id in candidates {
+            let (positive, negative) = occurrences(formula, id);
+            if positive.len() > self.max_occurrences || negative.len() > self.max_occurrences {
+                continue;
+            }
+            let mut resolvents = Vec::new();
+            for p in &positive {
+                for n in &negative {
+                    if let Some(resolvent) = resolve(&formula.0[*p], &formula.0[*n], id) {
+                        resolvents.push(resolvent);
+                    }
+                }
+            }
+            if resolvents.len() > positive.len() + negative.len() {
+                continue;
+            }
+            let removed = positive.iter().chain(&negative).collect::<HashSet<_>>();
+            let mut clauses = Vec::new();
+            for (i, clause) in formula.0.drain(..).enumerate() {
+                if !removed.contains(&i) {
+                    clauses.push(clause);
+                } else if clause
+                    .iter()
+                    .any(|variable| *variable == Variable::Positive(id))
+                {
+                    reconstruction.push(Variable::Positive(id), clause);
+                } else {
+                    reconstruction.push(Variable::Negative(id), clause);
+                }
+            }
+            clauses.extend(resolvents);
+            *formula = Formula::from(clauses);
+        }
+    }
+}
+
+/// Get the indices of the clauses containing a variable positively and negatively.
+fn occurrences(formula: &Formula, id: Literal) -> (Vec<usize>, Vec<usize>) {
+    let mut positive = Vec::new();
+    let mut negative = Vec::new();
+    for (i, clause) in formula.iter().enumerate() {
+        if clause
+            .iter()
+            .any(|variable| *variable == Variable::Positive(id))
+        {
+            positive.push(i);
+        } else if clause
+            .iter()
+            .any(|variable| *variable == Variable::Negative(id))
+        {
+            negative.push(i);
+        }
+    }
+    (positive, negative)
+}
+
+/// Resolve two clauses on a variable, returning `None` if the resolvent is a tautology.
+/// Only the pivot literals are removed, `x` from the positive clause and `-x` from the negative one.
+fn resolve(positive: &Clause, negative: &Clause, id: Literal) -> Option<Clause> {
+    let mut variables = Vec::new();
+    let positive = positive
+        .iter()
+        .filter(|variable| **variable != Variable::Positive(id));
+    let negative = negative
+        .iter()
+        .filter(|variable| **variable != Variable::Negative(id));
+    for variable in positive.chain(negative) {
+        if variables.contains(variable) {
+            continue;
+        }
+        if variables.contains(&variable.negated()) {
+            return None;
+        }
+        variables.push(*variable);
+    }
+    Some(Clause(variables))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        preprocessors::preprocess,
+        solver::solve_all,
+        solvers::Dfs,
+        types::{Clause, Solution},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_variable_elimination() {
+        // (x1 OR x2) AND (-x1 OR x3) AND (-x2 OR -x3 OR x4) AND (-x4 OR x1)
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+            vec![
+                Variable::Negative(2),
+                Variable::Negative(3),
+                Variable::Positive(4),
+            ],
+            vec![Variable::Negative(4), Variable::Positive(1)],
+        ]
+        .into();
+        let mut eliminated = formula.clone();
+        let reconstruction = preprocess(&mut eliminated, &[&VariableElimination::default()]);
+        assert!(eliminated.literals().len() < formula.literals().len());
+        let solutions = solve_all(&eliminated, &Dfs::default());
+        assert!(!solutions.is_empty());
+        for mut solution in solutions {
+            reconstruction.extend(&mut solution);
+            assert_eq!(solution.literals(), formula.literals());
+            assert!(solution.satisfy(&formula));
+        }
+    }
+
+    #[test]
+    fn test_variable_elimination_unsat() {
+        // (x1 OR x2) AND (x1 OR -x2) AND (-x1 OR x2) AND (-x1 OR -x2)
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Negative(2)],
+        ]
+        .into();
+        preprocess(&mut formula, &[&VariableElimination::default()]);
+        assert!(formula.iter().any(Clause::is_empty));
+        assert!(!Solution::new().satisfy(&formula));
+    }
+
+    #[test]
+    fn test_variable_elimination_tautology() {
+        // (x1 OR -x1) AND (-x1 OR x2) AND -x2 is satisfiable by x1 = x2 = false
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(1)],
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Negative(2)],
+        ]
+        .into();
+        let mut eliminated = formula.clone();
+        let reconstruction = preprocess(&mut eliminated, &[&VariableElimination::default()]);
+        assert!(!eliminated.iter().any(Clause::is_empty));
+        let mut solutions = solve_all(&eliminated, &Dfs::default());
+        assert!(!solutions.is_empty());
+        for solution in &mut solutions {
+            reconstruction.extend(solution);
+            assert!(solution.satisfy(&formula));
+        }
+    }
+
+    #[test]
+    fn test_variable_elimination_tautology_kept_count() {
+        // Without eliminating anything, the dropped tautology (x1 OR -x1) leaves x1 and x3 pure
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(1)],
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(2), Variable::Positive(3)],
+        ]
+        .into();
+        let elimination = VariableElimination { max_occurrences: 0 };
+        preprocess(&mut formula, &[&elimination]);
+        let expected: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(2), Variable::Positive(3)],
+        ]
+        .into();
+        assert_eq!(formula, expected);
+        assert_eq!(
+            formula.pure_literals(),
+            [Variable::Positive(1), Variable::Positive(3)]
+        );
+    }
+}
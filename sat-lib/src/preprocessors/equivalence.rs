@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+
+use crate::types::{Clause, Formula, Literal, Variable};
+
+use super::{Preprocessor, Reconstruction};
+
+/// Equivalent literal substitution finds literals that imply each other through binary clauses,
+/// and replaces each of them with a single representative throughout the formula.
+///
+/// The binary clause `(a OR b)` gives the implications `-a -> b` and `-b -> a`,
+/// and the literals in a strongly connected component of these implications are all equivalent.
+///
+/// ## Examples
+/// ```plaintext
+/// (-x1 OR x2) AND (-x2 OR x1) AND (x2 OR x3)
+/// x1 <-> x2
+/// (x1 OR x3)
+/// ```
+pub struct EquivalentLiterals;
+
+impl Preprocessor for EquivalentLiterals {
+    fn preprocess(&self, formula: &mut Formula, reconstruction: &mut Reconstruction) {
+        let Some(substitution) = equivalences(formula) else {
+            // A literal equivalent to its own negation
+            *formula = Formula::from(vec![Clause(Vec::new())]);
+            return;
+        };
+        if substitution.is_empty() {
+            return;
+        }
+        let replace = |variable: &Variable| match substitution.get(&variable.id()) {
+            Some(representative) if variable.is_positive() => *representative,
+            Some(representative) => representative.negated(),
+            None => *variable,
+        };
+        let mut clauses = Vec::new();
+        'clauses: for clause in formula.0.drain(..) {
+            let mut variables = Vec::new();
+            for variable in clause.iter().map(replace) {
+                if variables.contains(&variable.negated()) {
+                    continue 'clauses;
+                }
+                if !variables.contains(&variable) {
+                    variables.push(variable);
+                }
+            }
+            clauses.push(Clause(variables));
+        }
+        *formula = Formula::from(clauses);
+        // Both directions of each equivalence, so the substituted variable copies its representative
+        let mut substituted = substitution.into_iter().collect::<Vec<_>>();
+        substituted.sort_by_key(|(id, _)| *id);
+        for (id, representative) in substituted {
+            reconstruction.push(
+                Variable::Negative(id),
+                Clause(vec![Variable::Negative(id), representative]),
+            );
+            reconstruction.push(
+                Variable::Positive(id),
+                Clause(vec![Variable::Positive(id), representative.negated()]),
+            );
+        }
+    }
+}
+
+/// Find the representative literal equal to each variable that can be substituted.
+/// Returns `None` if a variable is equivalent to its own negation, making the formula unsatisfiable.
+fn equivalences(formula: &Formula) -> Option<HashMap<Literal, Variable>> {
+    let domain = formula.domain();
+    let index = |variable: Variable| {
+        2 * domain.index(variable.id()).unwrap() + usize::from(!variable.is_positive())
+    };
+    let variable = |index: usize| {
+        let id = domain.id(index / 2);
+        if index.is_multiple_of(2) {
+            Variable::Positive(id)
+        } else {
+            Variable::Negative(id)
+        }
+    };
+    let size = 2 * domain.len();
+    let mut edges = vec![Vec::new(); size];
+    for clause in formula.iter() {
+        if let [a, b] = clause.0[..] {
+            edges[index(a.negated())].push(index(b));
+            edges[index(b.negated())].push(index(a));
+        }
+    }
+    let mut substitution = HashMap::new();
+    for component in components(&edges) {
+        if component.iter().any(|i| component.contains(&(i ^ 1))) {
+            return None;
+        }
+        // The component of the negated literals has the negated representative, giving the same substitution
+        let representative = component
+            .iter()
+            .map(|i| variable(*i))
+            .min_by_key(Variable::id)
+            .unwrap();
+        for other in component.iter().map(|i| variable(*i)) {
+            if other.id() == representative.id() {
+                continue;
+            }
+            let equal = if other.is_positive() {
+                representative
+            } else {
+                representative.negated()
+            };
+            substitution.insert(other.id(), equal);
+        }
+    }
+    Some(substitution)
+}
+
+/// Find the strongly connected components with more than one node using Tarjan's algorithm.
+fn components(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Search<'a> {
+        edges: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        stack: Vec<usize>,
+        on_stack: Vec<bool>,
+        next: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Search<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next);
+            self.low[node] = self.next;
+            self.next += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+            for &next in &self.edges[node] {
+                match self.index[next] {
+                    None => {
+                        self.visit(next);
+                        self.low[node] = self.low[node].min(self.low[next]);
+                    }
+                    Some(index) if self.on_stack[next] => {
+                        self.low[node] = self.low[node].min(index);
+                    }
+                    Some(_) => (),
+                }
+            }
+            if Some(self.low[node]) == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(other) = self.stack.pop() {
+                    self.on_stack[other] = false;
+                    component.push(other);
+                    if other == node {
+                        break;
+                    }
+                }
+                if component.len() > 1 {
+                    self.components.push(component);
+                }
+            }
+        }
+    }
+
+    let mut search = Search {
+        edges,
+        index: vec![None; edges.len()],
+        low: vec![0; edges.len()],
+        stack: Vec::new(),
+        on_stack: vec![false; edges.len()],
+        next: 0,
+        components: Vec::new(),
+    };
+    for node in 0..edges.len() {
+        if search.index[node].is_none() {
+            search.visit(node);
+        }
+    }
+    search.components
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{preprocessors::preprocess, solver::solve_all, solvers::Dfs};
+
+    use super::*;
+
+    #[test]
+    fn test_equivalent_literals() {
+        // (-x1 OR x2) AND (-x2 OR x1) AND (x2 OR x3) AND (x1 OR x4) AND (-x3 OR -x4)
+        let formula: Formula = vec![
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Negative(2), Variable::Positive(1)],
+            vec![Variable::Positive(2), Variable::Positive(3)],
+            vec![Variable::Positive(1), Variable::Positive(4)],
+            vec![Variable::Negative(3), Variable::Negative(4)],
+        ]
+        .into();
+        let mut substituted = formula.clone();
+        let reconstruction = preprocess(&mut substituted, &[&EquivalentLiterals]);
+        assert_eq!(substituted.literals(), [1, 3, 4]);
+        let solutions = solve_all(&substituted, &Dfs::default());
+        assert_eq!(solutions.len(), solve_all(&formula, &Dfs::default()).len());
+        for mut solution in solutions {
+            reconstruction.extend(&mut solution);
+            assert!(solution.satisfy(&formula));
+        }
+    }
+
+    #[test]
+    fn test_equivalent_literals_unsat() {
+        // (-x1 OR x2) AND (-x2 OR -x1) AND (x1 OR x2) AND (x1 OR -x2)
+        let mut formula: Formula = vec![
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Negative(2), Variable::Negative(1)],
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Positive(1), Variable::Negative(2)],
+        ]
+        .into();
+        preprocess(&mut formula, &[&EquivalentLiterals]);
+        assert!(formula.iter().any(Clause::is_empty));
+    }
+}
@@ -0,0 +1,83 @@
+use crate::types::{Clause, Formula, Solution, Variable};
+
+mod elimination;
+mod equivalence;
+mod subsumption;
+mod symmetry;
+mod vivification;
+pub use elimination::VariableElimination;
+pub use equivalence::EquivalentLiterals;
+pub use subsumption::Subsumption;
+pub use symmetry::{symmetries, SymmetryBreaking};
+pub use vivification::{vivify_clause, Vivification};
+
+/// A preprocessor transforms a formula before it is handed to a solver.
+/// - The transformed formula must be satisfiable if and only if the original formula is.
+/// - Some preprocessors only keep a subset of the models of the original formula.
+/// - Preprocessors that remove variables record how to assign them in the `reconstruction`.
+pub trait Preprocessor {
+    fn preprocess(&self, formula: &mut Formula, reconstruction: &mut Reconstruction);
+}
+
+/// Run a sequence of preprocessors on a formula.
+/// Returns the reconstruction needed to turn solutions of the preprocessed formula into solutions of the original.
+pub fn preprocess(formula: &mut Formula, preprocessors: &[&dyn Preprocessor]) -> Reconstruction {
+    let mut reconstruction = Reconstruction::new();
+    for preprocessor in preprocessors {
+        preprocessor.preprocess(formula, &mut reconstruction);
+    }
+    reconstruction
+}
+
+/// A reconstruction stack of removed clauses, each paired with a witness literal.
+/// When a solution leaves a removed clause unsatisfied, setting its witness to true repairs it.
+///
+/// ## Examples
+/// ```plaintext
+/// eliminate x1 from (x1 OR x2) AND (-x1 OR x3)
+/// push x1 (x1 OR x2)
+/// push -x1 (-x1 OR x3)
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Reconstruction(Vec<(Variable, Clause)>);
+
+impl Reconstruction {
+    /// Create a new empty reconstruction stack.
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Record a removed clause and the literal that can be set to satisfy it.
+    pub fn push(&mut self, witness: Variable, clause: Clause) {
+        self.0.push((witness, clause));
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Extend a solution of the preprocessed formula to a solution of the original formula.
+    /// Variables missing from the solution start out as `false`,
+    /// then removed clauses are repaired in the reverse order they were removed.
+    pub fn extend(&self, solution: &mut Solution) {
+        for (_, clause) in &self.0 {
+            for id in clause.literals() {
+                if !solution.0.contains_key(&id) {
+                    solution.set(id, false);
+                }
+            }
+        }
+        for (witness, clause) in self.0.iter().rev() {
+            let satisfied = clause
+                .iter()
+                .any(|variable| solution.get(variable.id()) == variable.is_positive());
+            if !satisfied {
+                solution.set(witness.id(), witness.is_positive());
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashSet;
+
+use crate::types::{Clause, Formula, Variable};
+
+use super::{Preprocessor, Reconstruction};
+
+impl Formula {
+    /// Remove subsumed clauses and strengthen clauses by self-subsuming resolution, until nothing changes.
+    /// - A clause `C` subsumes `D` if every literal of `C` is in `D`, making `D` redundant.
+    /// - If `C` subsumes `D` except for a literal `l` that appears negated in `D`,
+    ///   then resolving on `l` yields `D` without `-l`, so `-l` can be removed from `D`.
+    ///
+    /// The simplified formula is logically equivalent to the original,
+    /// but variables that only occurred in removed clauses are no longer part of it.
+    pub fn simplify_subsumption(&mut self) {
+        let mut clauses = self
+            .0
+            .iter()
+            .map(|clause| clause.iter().copied().collect::<HashSet<_>>())
+            .collect::<Vec<_>>();
+        let mut removed = vec![false; clauses.len()];
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for c in 0..clauses.len() {
+                if removed[c] || is_tautology(&clauses[c]) {
+                    continue;
+                }
+                for d in 0..clauses.len() {
+                    if c == d || removed[d] || clauses[c].len() > clauses[d].len() {
+                        continue;
+                    }
+                    if clauses[c].is_subset(&clauses[d]) {
+                        removed[d] = true;
+                        changed = true;
+                    } else if let Some(pivot) = self_subsuming_pivot(&clauses[c], &clauses[d]) {
+                        clauses[d].remove(&pivot.negated());
+                        changed = true;
+                    }
+                }
+            }
+        }
+        // Keep the remaining literals in their original order
+        let mut simplified = Vec::new();
+        for (clause, (literals, removed)) in self.0.iter().zip(clauses.iter().zip(removed)) {
+            if removed {
+                continue;
+            }
+            let mut variables = Vec::new();
+            for variable in clause.iter() {
+                if literals.contains(variable) && !variables.contains(variable) {
+                    variables.push(*variable);
+                }
+            }
+            simplified.push(Clause(variables));
+        }
+        *self = Formula::from(simplified);
+    }
+}
+
+fn is_tautology(clause: &HashSet<Variable>) -> bool {
+    clause
+        .iter()
+        .any(|variable| clause.contains(&variable.negated()))
+}
+
+/// Find a literal `l` in `c` such that `-l` is in `d` and all other literals of `c` are in `d`.
+fn self_subsuming_pivot(c: &HashSet<Variable>, d: &HashSet<Variable>) -> Option<Variable> {
+    let mut pivot = None;
+    for variable in c {
+        if d.contains(variable) {
+            continue;
+        }
+        if pivot.is_some() || !d.contains(&variable.negated()) {
+            return None;
+        }
+        pivot = Some(*variable);
+    }
+    pivot
+}
+
+/// Subsumption removes redundant clauses and literals, see [`Formula::simplify_subsumption`].
+pub struct Subsumption;
+
+impl Preprocessor for Subsumption {
+    fn preprocess(&self, formula: &mut Formula, _reconstruction: &mut Reconstruction) {
+        formula.simplify_subsumption();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simplify_subsumption() {
+        // (x1 OR x2) AND (x1 OR x2 OR x3) AND (-x1 OR x2 OR x4) AND (x1 OR -x2)
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![
+                Variable::Positive(1),
+                Variable::Positive(2),
+                Variable::Positive(3),
+            ],
+            vec![
+                Variable::Negative(1),
+                Variable::Positive(2),
+                Variable::Positive(4),
+            ],
+            vec![Variable::Positive(1), Variable::Negative(2)],
+        ]
+        .into();
+        formula.simplify_subsumption();
+        // (x1 OR x2) subsumes (x1 OR x2 OR x3) and strengthens the other two clauses
+        // into (x2 OR x4) and (x1), which in turn subsumes (x1 OR x2)
+        let expected: Formula = vec![
+            vec![Variable::Positive(2), Variable::Positive(4)],
+            vec![Variable::Positive(1)],
+        ]
+        .into();
+        assert_eq!(formula, expected);
+    }
+}
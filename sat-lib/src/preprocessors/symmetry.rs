@@ -0,0 +1,333 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use crate::types::{Clause, Formula, Literal, Variable};
+
+use super::{Preprocessor, Reconstruction};
+
+/// The clause-variable incidence graph of a formula.
+/// Variables and clauses are nodes, and every occurrence of a variable in a clause is an edge labeled by its sign.
+struct Graph {
+    literals: Vec<Literal>,
+    variables: Vec<Vec<(bool, usize)>>,
+    clauses: Vec<Vec<(bool, usize)>>,
+}
+
+impl Graph {
+    fn new(formula: &Formula) -> Self {
+        let literals = formula.literals();
+        let index = literals
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, i))
+            .collect::<HashMap<_, _>>();
+        let mut variables = vec![Vec::new(); literals.len()];
+        let mut clauses = Vec::new();
+        for (c, clause) in formula.iter().enumerate() {
+            let mut edges = Vec::new();
+            for variable in clause.iter() {
+                let v = index[&variable.id()];
+                edges.push((variable.is_positive(), v));
+                variables[v].push((variable.is_positive(), c));
+            }
+            clauses.push(edges);
+        }
+        Self {
+            literals,
+            variables,
+            clauses,
+        }
+    }
+
+    /// Check that a permutation of the variables maps the set of clauses onto itself.
+    fn is_automorphism(&self, permutation: &[usize]) -> bool {
+        let canonical = |map: &dyn Fn(usize) -> usize| {
+            let mut clauses = self
+                .clauses
+                .iter()
+                .map(|edges| {
+                    let mut clause = edges
+                        .iter()
+                        .map(|(sign, v)| (map(*v), *sign))
+                        .collect::<Vec<_>>();
+                    clause.sort();
+                    clause
+                })
+                .collect::<Vec<_>>();
+            clauses.sort();
+            clauses
+        };
+        canonical(&|v| v) == canonical(&|v| permutation[v])
+    }
+}
+
+/// A coloring of the nodes in the incidence graph.
+/// Colors are canonical, so two colorings of the same graph can be compared directly.
+#[derive(Clone)]
+struct Coloring {
+    variables: Vec<usize>,
+    clauses: Vec<usize>,
+}
+
+impl Coloring {
+    fn new(graph: &Graph) -> Self {
+        let mut coloring = Self {
+            variables: vec![0; graph.variables.len()],
+            clauses: vec![0; graph.clauses.len()],
+        };
+        coloring.refine(graph);
+        coloring
+    }
+
+    /// Refine the coloring until it is equitable, meaning that all nodes of the same color
+    /// have the same number of neighbors of each color.
+    fn refine(&mut self, graph: &Graph) {
+        let count = |colors: &[usize]| colors.iter().collect::<HashSet<_>>().len();
+        loop {
+            let before = count(&self.variables) + count(&self.clauses);
+            self.clauses = relabel(&self.clauses, &graph.clauses, &self.variables);
+            self.variables = relabel(&self.variables, &graph.variables, &self.clauses);
+            if count(&self.variables) + count(&self.clauses) == before {
+                break;
+            }
+        }
+    }
+
+    /// Give a variable a color of its own and refine the coloring.
+    fn individualize(&self, graph: &Graph, v: usize) -> Self {
+        let mut coloring = self.clone();
+        coloring.variables[v] = self.variables.iter().max().map_or(0, |max| max + 1);
+        coloring.refine(graph);
+        coloring
+    }
+
+    /// Get the members of the first variable color shared by more than one variable.
+    fn first_cell(&self) -> Option<Vec<usize>> {
+        let mut cells = BTreeMap::<usize, Vec<usize>>::new();
+        for (v, color) in self.variables.iter().enumerate() {
+            cells.entry(*color).or_default().push(v);
+        }
+        cells.into_values().find(|cell| cell.len() > 1)
+    }
+
+    /// Check whether two colorings have the same number of nodes of each color.
+    fn same_shape(&self, other: &Self) -> bool {
+        let histogram = |colors: &[usize]| {
+            let mut colors = colors.to_vec();
+            colors.sort();
+            colors
+        };
+        histogram(&self.variables) == histogram(&other.variables)
+            && histogram(&self.clauses) == histogram(&other.clauses)
+    }
+}
+
+/// Assign new canonical colors to nodes based on their current color and the colors of their neighbors.
+fn relabel(colors: &[usize], edges: &[Vec<(bool, usize)>], neighbors: &[usize]) -> Vec<usize> {
+    let signatures = colors
+        .iter()
+        .zip(edges)
+        .map(|(color, edges)| {
+            let mut signature = edges
+                .iter()
+                .map(|(sign, n)| (*sign, neighbors[*n]))
+                .collect::<Vec<_>>();
+            signature.sort();
+            (*color, signature)
+        })
+        .collect::<Vec<_>>();
+    let labels = signatures
+        .iter()
+        .cloned()
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .enumerate()
+        .map(|(label, signature)| (signature, label))
+        .collect::<BTreeMap<_, _>>();
+    signatures
+        .iter()
+        .map(|signature| labels[signature])
+        .collect()
+}
+
+/// Try to find an automorphism mapping variable `v` to `w` while fixing the variables individualized in `base`.
+/// Follows a single path of individualization and refinement, so it may miss some automorphisms.
+fn find_automorphism(graph: &Graph, base: &Coloring, v: usize, w: usize) -> Option<Vec<usize>> {
+    let mut a = base.individualize(graph, v);
+    let mut b = base.individualize(graph, w);
+    loop {
+        if !a.same_shape(&b) {
+            return None;
+        }
+        let Some(cell) = a.first_cell() else {
+            break;
+        };
+        let color = a.variables[cell[0]];
+        let other = b.variables.iter().position(|c| *c == color)?;
+        a = a.individualize(graph, cell[0]);
+        b = b.individualize(graph, other);
+    }
+    let permutation = a
+        .variables
+        .iter()
+        .map(|color| b.variables.iter().position(|c| c == color))
+        .collect::<Option<Vec<_>>>()?;
+    graph.is_automorphism(&permutation).then_some(permutation)
+}
+
+/// Check whether `w` is reachable from `v` by applying the permutations.
+fn in_orbit(generators: &[Vec<usize>], v: usize, w: usize) -> bool {
+    let mut orbit = HashSet::from([v]);
+    let mut queue = vec![v];
+    while let Some(u) = queue.pop() {
+        for generator in generators {
+            if orbit.insert(generator[u]) {
+                queue.push(generator[u]);
+            }
+        }
+    }
+    orbit.contains(&w)
+}
+
+/// Find symmetries of a formula, permutations of its variables that map the set of clauses onto itself.
+/// Each symmetry only contains the variables it moves.
+///
+/// ## Note
+/// The search does not backtrack, so the symmetries found may not generate the full symmetry group.
+pub fn symmetries(formula: &Formula) -> Vec<HashMap<Literal, Literal>> {
+    let graph = Graph::new(formula);
+    let mut base = Coloring::new(&graph);
+    let mut generators = Vec::new();
+    while let Some(cell) = base.first_cell() {
+        let v = cell[0];
+        for &w in &cell[1..] {
+            if in_orbit(&generators, v, w) {
+                continue;
+            }
+            if let Some(permutation) = find_automorphism(&graph, &base, v, w) {
+                generators.push(permutation);
+            }
+        }
+        base = base.individualize(&graph, v);
+    }
+    generators
+        .iter()
+        .map(|permutation| {
+            permutation
+                .iter()
+                .enumerate()
+                .filter(|(v, w)| v != *w)
+                .map(|(v, w)| (graph.literals[v], graph.literals[*w]))
+                .collect()
+        })
+        .collect()
+}
+
+/// Symmetry breaking adds lex-leader constraints for every symmetry found in the formula,
+/// so that only the lexicographically smallest of each set of symmetric models remains.
+///
+/// The constraints cover the first `depth` variables moved by each symmetry,
+/// each position requiring up to `2^depth` clauses.
+pub struct SymmetryBreaking {
+    pub depth: usize,
+}
+
+impl Default for SymmetryBreaking {
+    fn default() -> Self {
+        Self { depth: 3 }
+    }
+}
+
+impl Preprocessor for SymmetryBreaking {
+    fn preprocess(&self, formula: &mut Formula, _reconstruction: &mut Reconstruction) {
+        for symmetry in symmetries(formula) {
+            let mut support = symmetry.keys().copied().collect::<Vec<_>>();
+            support.sort();
+            support.truncate(self.depth);
+            for (i, x) in support.iter().enumerate() {
+                // If no earlier position has x < y, then x must not be greater than y
+                let mut prefixes: Vec<Vec<Variable>> = vec![Vec::new()];
+                for earlier in &support[..i] {
+                    prefixes = prefixes
+                        .into_iter()
+                        .flat_map(|prefix| {
+                            [
+                                Variable::Negative(*earlier),
+                                Variable::Positive(symmetry[earlier]),
+                            ]
+                            .map(|variable| {
+                                let mut prefix = prefix.clone();
+                                prefix.push(variable);
+                                prefix
+                            })
+                        })
+                        .collect();
+                }
+                for mut variables in prefixes {
+                    variables.push(Variable::Negative(*x));
+                    variables.push(Variable::Positive(symmetry[x]));
+                    variables.sort_by_key(|variable| (variable.id(), variable.is_positive()));
+                    variables.dedup();
+                    if variables
+                        .windows(2)
+                        .any(|pair| pair[0].id() == pair[1].id())
+                    {
+                        continue;
+                    }
+                    formula.add(Clause(variables));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{preprocessors::preprocess, solver::solve_all, solvers::Dfs};
+
+    use super::*;
+
+    /// Every pigeon sits in a hole, and no two pigeons share a hole.
+    fn pigeonhole(pigeons: u32, holes: u32) -> Formula {
+        let var = |p: u32, h: u32| p * holes + h + 1;
+        let mut clauses = Vec::new();
+        for p in 0..pigeons {
+            clauses.push((0..holes).map(|h| Variable::Positive(var(p, h))).collect());
+        }
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    clauses.push(vec![
+                        Variable::Negative(var(p, h)),
+                        Variable::Negative(var(q, h)),
+                    ]);
+                }
+            }
+        }
+        clauses.into()
+    }
+
+    #[test]
+    fn test_symmetries() {
+        // (x1 OR x2) AND (x3)
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Positive(3)],
+        ]
+        .into();
+        assert_eq!(symmetries(&formula), vec![HashMap::from([(1, 2), (2, 1)])]);
+        assert!(!symmetries(&pigeonhole(3, 2)).is_empty());
+    }
+
+    #[test]
+    fn test_symmetry_breaking() {
+        let mut formula = pigeonhole(3, 3);
+        let models = solve_all(&formula, &Dfs::default()).len();
+        preprocess(&mut formula, &[&SymmetryBreaking::default()]);
+        let remaining = solve_all(&formula, &Dfs::default()).len();
+        assert!(remaining > 0 && remaining < models);
+
+        let mut formula = pigeonhole(3, 2);
+        preprocess(&mut formula, &[&SymmetryBreaking::default()]);
+        assert!(solve_all(&formula, &Dfs::default()).is_empty());
+    }
+}
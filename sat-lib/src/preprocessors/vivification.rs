@@ -0,0 +1,106 @@
+use crate::{
+    trail::Trail,
+    types::{Clause, Formula},
+};
+
+use super::{Preprocessor, Reconstruction};
+
+/// Shorten a clause implied by a formula by assuming its literals false one at a time and propagating.
+/// - If propagation makes a literal true, the literals after it are redundant.
+/// - If propagation makes a literal false, that literal is redundant.
+/// - If propagation fails, the remaining literals are redundant.
+///
+/// The clause must not be part of the formula itself, as it would just propagate its own last literal.
+/// This makes it usable on both original clauses (removed from the formula first) and learned clauses.
+pub fn vivify_clause(formula: &Formula, clause: &Clause) -> Clause {
+    let mut trail = Trail::new();
+    let mut kept = Vec::new();
+    if trail.propagate(formula).is_err() {
+        return Clause(kept);
+    }
+    for variable in clause.iter() {
+        match trail.value(variable.id()) {
+            Some(value) if value == variable.is_positive() => {
+                kept.push(*variable);
+                break;
+            }
+            Some(_) => continue,
+            None => (),
+        }
+        kept.push(*variable);
+        trail.decide(variable.negated());
+        if trail.propagate(formula).is_err() {
+            break;
+        }
+    }
+    Clause(kept)
+}
+
+/// Vivification shortens every clause of the formula with [`vivify_clause`], using all other clauses.
+pub struct Vivification;
+
+impl Preprocessor for Vivification {
+    fn preprocess(&self, formula: &mut Formula, _reconstruction: &mut Reconstruction) {
+        for i in 0..formula.len() {
+            let mut others = formula.0.clone();
+            let clause = others.remove(i);
+            formula.0[i] = vivify_clause(&Formula::from(others), &clause);
+        }
+        formula.recount();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{preprocessors::preprocess, types::Variable};
+
+    use super::*;
+
+    #[test]
+    fn test_vivify_clause() {
+        // (-x1 OR x2) AND (-x2 OR x3)
+        let formula: Formula = vec![
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Negative(2), Variable::Positive(3)],
+        ]
+        .into();
+        // Assuming -x3 propagates -x2 and then -x1, so x4 is redundant after x1
+        let clause = Clause(vec![
+            Variable::Positive(3),
+            Variable::Negative(1),
+            Variable::Positive(4),
+        ]);
+        assert_eq!(
+            vivify_clause(&formula, &clause),
+            Clause(vec![Variable::Positive(3), Variable::Negative(1)])
+        );
+        let clause = Clause(vec![Variable::Positive(2), Variable::Positive(3)]);
+        assert_eq!(
+            vivify_clause(&formula, &clause),
+            Clause(vec![Variable::Positive(2), Variable::Positive(3)])
+        );
+    }
+
+    #[test]
+    fn test_vivification() {
+        // (-x1 OR x2) AND (-x2 OR x3) AND (-x1 OR x3 OR x4)
+        let mut formula: Formula = vec![
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Negative(2), Variable::Positive(3)],
+            vec![
+                Variable::Negative(1),
+                Variable::Positive(3),
+                Variable::Positive(4),
+            ],
+        ]
+        .into();
+        preprocess(&mut formula, &[&Vivification]);
+        let expected: Formula = vec![
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Negative(2), Variable::Positive(3)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+        ]
+        .into();
+        assert_eq!(formula, expected);
+    }
+}
@@ -1,49 +1,196 @@
-use crate::types::{Clause, Formula, Literal, Solution, Variable};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::{self, IsTerminal, Write},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        OnceLock,
+    },
+};
+
+use crate::types::{Clause, Cube, Dnf, Formula, Literal, Solution, VarPool, Variable};
 use crossterm::{
     style::{Attribute, Color, SetAttribute, SetForegroundColor},
-    ExecutableCommand,
+    terminal, ExecutableCommand, QueueableCommand,
 };
 
+/// When to color the output with ANSI escape codes, see [`set_color_choice`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ColorChoice {
+    /// Color the output when stdout is a terminal, unless the `NO_COLOR` environment variable is set.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "Unknown color choice {}, expected auto, always or never",
+                s
+            )),
+        }
+    }
+}
+
+static COLOR_CHOICE: AtomicU8 = AtomicU8::new(ColorChoice::Auto as u8);
+
+/// Choose when all printing colors the output, which is automatic by default.
+pub fn set_color_choice(choice: ColorChoice) {
+    COLOR_CHOICE.store(choice as u8, Ordering::Relaxed);
+}
+
+/// Check if printing colors the output, by the choice of [`set_color_choice`].
+pub fn color_enabled() -> bool {
+    static AUTO: OnceLock<bool> = OnceLock::new();
+    match COLOR_CHOICE.load(Ordering::Relaxed) {
+        choice if choice == ColorChoice::Always as u8 => true,
+        choice if choice == ColorChoice::Never as u8 => false,
+        _ => *AUTO.get_or_init(|| {
+            let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+            !no_color && std::io::stdout().is_terminal()
+        }),
+    }
+}
+
+/// Set the color of the following output, if it is colored.
+pub fn set_foreground(color: Color) {
+    if color_enabled() {
+        std::io::stdout()
+            .execute(SetForegroundColor(color))
+            .unwrap();
+    }
+}
+
+/// Set an attribute like bold for the following output, if it is colored.
+pub fn set_attribute(attribute: Attribute) {
+    if color_enabled() {
+        std::io::stdout().execute(SetAttribute(attribute)).unwrap();
+    }
+}
+
+/// The most variables a truth table is meant for, with `2^12` rows.
+pub const MAX_TRUTH_TABLE_VARIABLES: usize = 12;
+
+/// The indentation of the lines a wrapped formula continues on.
+const HANGING_INDENT: usize = 4;
+
+/// The most variables a Karnaugh map is drawn for, in a grid of 4 by 4 cells.
+pub const MAX_KARNAUGH_MAP_VARIABLES: usize = 4;
+
+/// The symbols and colors of printed formulas and solutions, given by a [`PrintStyle`] or customized.
+///
+/// ## Examples
+/// ```rust,ignore
+/// let config = PrinterConfig {
+///     and: "&&".into(),
+///     or: "||".into(),
+///     ..PrintStyle::Programmatic.config().clone()
+/// };
+/// PrintStyle::Programmatic.printer().with_config(&config).print_formula(&formula);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrinterConfig {
+    /// Written before negative literals.
+    pub negation: Cow<'static, str>,
+    pub and: Cow<'static, str>,
+    pub or: Cow<'static, str>,
+    pub true_value: Cow<'static, str>,
+    pub false_value: Cow<'static, str>,
+    /// The color of positive literals and true values.
+    pub positive: Color,
+    /// The color of negative literals and false values.
+    pub negative: Color,
+    /// The color of `and` and `or`.
+    pub operator: Color,
+    /// The color of parentheses, separators and dimmed text.
+    pub punctuation: Color,
+}
+
+static NORMAL: PrinterConfig = PrinterConfig::symbols("-", "and", "or", "T", "F");
+static PROGRAMMATIC: PrinterConfig = PrinterConfig::symbols("!", "&", "|", "1", "0");
+static MATHEMATICAL: PrinterConfig = PrinterConfig::symbols("¬", "∧", "∨", "⊤", "⊥");
+static MARKDOWN: PrinterConfig =
+    PrinterConfig::symbols("\\lnot ", "\\land", "\\lor", "\\top", "\\bot");
+
+impl PrinterConfig {
+    /// Get a configuration with the given symbols and the default colors.
+    const fn symbols(
+        negation: &'static str,
+        and: &'static str,
+        or: &'static str,
+        true_value: &'static str,
+        false_value: &'static str,
+    ) -> Self {
+        Self {
+            negation: Cow::Borrowed(negation),
+            and: Cow::Borrowed(and),
+            or: Cow::Borrowed(or),
+            true_value: Cow::Borrowed(true_value),
+            false_value: Cow::Borrowed(false_value),
+            positive: Color::Green,
+            negative: Color::Red,
+            operator: Color::Yellow,
+            punctuation: Color::DarkGrey,
+        }
+    }
+
+    /// Set a symbol or color by its key, like from a configuration file.
+    /// The symbol keys are `not`, `and`, `or`, `true` and `false`, and the color keys are `positive`, `negative`,
+    /// `operator` and `punctuation` with color names like `green` or `dark_grey`.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let color = || Color::try_from(value).map_err(|_| format!("Unknown color {}", value));
+        match key {
+            "not" => self.negation = value.to_string().into(),
+            "and" => self.and = value.to_string().into(),
+            "or" => self.or = value.to_string().into(),
+            "true" => self.true_value = value.to_string().into(),
+            "false" => self.false_value = value.to_string().into(),
+            "positive" => self.positive = color()?,
+            "negative" => self.negative = color()?,
+            "operator" => self.operator = color()?,
+            "punctuation" => self.punctuation = color()?,
+            _ => return Err(format!("Unknown printer setting {}", key)),
+        }
+        Ok(())
+    }
+
+    fn bool_value(&self, value: bool) -> &str {
+        if value {
+            &self.true_value
+        } else {
+            &self.false_value
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum PrintStyle {
     #[default]
     Normal,
     Programmatic,
     Mathematical,
+    /// Formulas as inline LaTeX math like `$(x_{1} \lor \lnot x_{2})$` and solutions as Markdown tables,
+    /// for pasting into GitHub issues and notebooks. It is never colored.
+    Markdown,
 }
 
 impl PrintStyle {
-    fn neg_sign(&self) -> &'static str {
+    /// Get the symbols and colors of the style, which can be changed by [`Printer::with_config`].
+    pub fn config(self) -> &'static PrinterConfig {
         match self {
-            PrintStyle::Normal => "-",
-            PrintStyle::Programmatic => "!",
-            PrintStyle::Mathematical => "¬",
-        }
-    }
-
-    fn bool_style(&self, value: bool) -> &'static str {
-        match self {
-            PrintStyle::Programmatic => {
-                if value {
-                    "1"
-                } else {
-                    "0"
-                }
-            }
-            PrintStyle::Normal => {
-                if value {
-                    "T"
-                } else {
-                    "F"
-                }
-            }
-            PrintStyle::Mathematical => {
-                if value {
-                    "⊤"
-                } else {
-                    "⊥"
-                }
-            }
+            PrintStyle::Normal => &NORMAL,
+            PrintStyle::Programmatic => &PROGRAMMATIC,
+            PrintStyle::Mathematical => &MATHEMATICAL,
+            PrintStyle::Markdown => &MARKDOWN,
         }
     }
 
@@ -58,101 +205,926 @@ impl PrintStyle {
                         .collect::<String>()
                 )
             }
+            PrintStyle::Markdown => format!("x_{{{}}}", id),
             _ => format!("X{}", id),
         }
     }
 
-    fn and_style(&self) -> &'static str {
-        match self {
-            PrintStyle::Mathematical => "∧",
-            PrintStyle::Normal => "and",
-            PrintStyle::Programmatic => "&",
+    /// Get a printer using the style, which shows the names of named variables.
+    pub fn with_names(self, names: &VarPool) -> Printer<'_> {
+        Printer {
+            names: Some(names),
+            ..self.printer()
         }
     }
 
-    fn or_style(&self) -> &'static str {
-        match self {
-            PrintStyle::Mathematical => "∨",
-            PrintStyle::Normal => "or",
-            PrintStyle::Programmatic => "|",
+    /// Get a printer using the style, which shows the variables of the mapping by their names.
+    pub fn with_mapping(self, mapping: &HashMap<Literal, String>) -> Printer<'_> {
+        Printer {
+            mapping: Some(mapping),
+            ..self.printer()
+        }
+    }
+
+    /// Get a printer using the style, showing every variable by its number.
+    pub fn printer(self) -> Printer<'static> {
+        Printer {
+            style: self,
+            config: self.config(),
+            names: None,
+            mapping: None,
+            max_variable: None,
+            color: color_enabled(),
+            compact: false,
+            only: None,
+            previous: None,
+            diff: false,
+            width: None,
+            clauses_per_line: None,
         }
     }
 
     pub fn print_variable(&self, variable: &Variable) {
-        let mut stdout = std::io::stdout();
+        self.printer().print_variable(variable);
+    }
+
+    pub fn print_clause(&self, clause: &Clause) {
+        self.printer().print_clause(clause);
+    }
+
+    pub fn print_formula(&self, formula: &Formula) {
+        self.printer().print_formula(formula);
+    }
+
+    pub fn print_cube(&self, cube: &Cube) {
+        self.printer().print_cube(cube);
+    }
+
+    pub fn print_solution(&self, solution: &Solution) {
+        self.printer().print_solution(solution);
+    }
+
+    pub fn print_solutions(&self, solutions: &[Solution]) {
+        self.printer().print_solutions(solutions);
+    }
+
+    pub fn print_dnf(&self, dnf: &Dnf) {
+        self.printer().print_dnf(dnf);
+    }
+
+    pub fn print_truth_table(&self, formula: &Formula) -> io::Result<()> {
+        self.printer().print_truth_table(formula)
+    }
+
+    pub fn print_karnaugh_map(&self, formula: &Formula) -> io::Result<()> {
+        self.printer().print_karnaugh_map(formula)
+    }
+
+    /// Format a variable as plain text, see [`Printer::with_color`] for colored strings.
+    pub fn format_variable(&self, variable: &Variable) -> String {
+        self.plain().format_variable(variable)
+    }
+
+    pub fn format_clause(&self, clause: &Clause) -> String {
+        self.plain().format_clause(clause)
+    }
+
+    pub fn format_formula(&self, formula: &Formula) -> String {
+        self.plain().format_formula(formula)
+    }
+
+    pub fn format_cube(&self, cube: &Cube) -> String {
+        self.plain().format_cube(cube)
+    }
+
+    pub fn format_solution(&self, solution: &Solution) -> String {
+        self.plain().format_solution(solution)
+    }
+
+    pub fn format_solutions(&self, solutions: &[Solution]) -> String {
+        self.plain().format_solutions(solutions)
+    }
+
+    pub fn format_dnf(&self, dnf: &Dnf) -> String {
+        self.plain().format_dnf(dnf)
+    }
+
+    pub fn format_truth_table(&self, formula: &Formula) -> io::Result<String> {
+        self.plain().format_truth_table(formula)
+    }
+
+    pub fn format_karnaugh_map(&self, formula: &Formula) -> io::Result<String> {
+        self.plain().format_karnaugh_map(formula)
+    }
+
+    fn plain(self) -> Printer<'static> {
+        self.printer().with_color(false)
+    }
+}
+
+/// A printer prints formulas and solutions in a style,
+/// showing variables by their names when they have one.
+/// Everything can be written to any [`Write`] by the `write_*` methods, while the `print_*` methods write to stdout.
+#[derive(Debug, Clone, Copy)]
+pub struct Printer<'a> {
+    pub style: PrintStyle,
+    /// The symbols and colors, by default those of the style.
+    pub config: &'a PrinterConfig,
+    /// The names of variables, where the auxiliary variables of the pool are hidden from solutions.
+    pub names: Option<&'a VarPool>,
+    /// Names of variables that are shown instead of those of the pool, like from another symbol table.
+    pub mapping: Option<&'a HashMap<Literal, String>>,
+    /// Hide variables of solutions above this identifier, like auxiliary variables added after the others.
+    pub max_variable: Option<Literal>,
+    /// Whether to color the output with ANSI escape codes, by default if [`color_enabled`].
+    pub color: bool,
+    /// Show only the true variables of solutions, or the cube of the variables that matter for partial models.
+    pub compact: bool,
+    /// Show only these variables of solutions, like the few of interest among dozens.
+    pub only: Option<&'a [Literal]>,
+    /// The solution before, whose unchanged values are dimmed so the flipped variables stand out.
+    pub previous: Option<&'a Solution>,
+    /// Compare each solution written by [`Printer::write_solutions`] to the one before it.
+    pub diff: bool,
+    /// Wrap formulas into lines of at most this many columns, by default the width of the terminal
+    /// when printing to one.
+    pub width: Option<usize>,
+    /// Wrap formulas after this many clauses on a line.
+    pub clauses_per_line: Option<usize>,
+}
+
+impl<'a> Printer<'a> {
+    /// Hide variables of solutions above an identifier.
+    pub fn hide_above(self, id: Literal) -> Self {
+        Self {
+            max_variable: Some(id),
+            ..self
+        }
+    }
+
+    /// Color the output or not, like to write plain text to a file.
+    pub fn with_color(self, color: bool) -> Self {
+        Self { color, ..self }
+    }
+
+    /// Show solutions compactly by their true variables, like `{X3, X40}`,
+    /// or partial models with don't-care variables as a cube like `X1 and -X3`.
+    pub fn with_compact(self, compact: bool) -> Self {
+        Self { compact, ..self }
+    }
+
+    /// Use other symbols and colors than those of the style.
+    pub fn with_config(self, config: &'a PrinterConfig) -> Self {
+        Self { config, ..self }
+    }
+
+    /// Show the variables of a mapping by their names, before the names of the pool.
+    pub fn with_mapping(self, mapping: &'a HashMap<Literal, String>) -> Self {
+        Self {
+            mapping: Some(mapping),
+            ..self
+        }
+    }
+
+    /// Wrap formulas into lines of at most a number of columns, unless a clause is longer.
+    pub fn with_width(self, width: usize) -> Self {
+        Self {
+            width: Some(width),
+            ..self
+        }
+    }
+
+    /// Wrap formulas after a number of clauses on a line.
+    pub fn with_clauses_per_line(self, clauses: usize) -> Self {
+        Self {
+            clauses_per_line: Some(clauses.max(1)),
+            ..self
+        }
+    }
+
+    /// Dim the values of solutions that are the same as in the previous solution.
+    pub fn compared_to(self, previous: &'a Solution) -> Self {
+        Self {
+            previous: Some(previous),
+            ..self
+        }
+    }
+
+    /// Compare consecutive solutions, like when enumerating all of them, see [`Printer::compared_to`].
+    pub fn with_diff(self, diff: bool) -> Self {
+        Self { diff, ..self }
+    }
+
+    /// Check if a variable has the same value, or is don't-care, in the previous solution.
+    fn unchanged(&self, solution: &Solution, id: Literal) -> bool {
+        self.previous.is_some_and(|previous| {
+            previous.0.get(&id) == solution.0.get(&id)
+                && previous.is_dont_care(id) == solution.is_dont_care(id)
+        })
+    }
+
+    /// Show only the given variables of solutions.
+    pub fn only(self, variables: &'a [Literal]) -> Self {
+        Self {
+            only: Some(variables),
+            ..self
+        }
+    }
+
+    fn lit_style(&self, id: Literal) -> String {
+        let name = self
+            .mapping
+            .and_then(|mapping| mapping.get(&id).map(String::as_str))
+            .or_else(|| self.names.and_then(|names| names.name(id)));
+        match name {
+            Some(name) if self.style == PrintStyle::Markdown => {
+                format!("\\text{{{}}}", name.replace('_', "\\_"))
+            }
+            Some(name) => name.to_string(),
+            None => self.style.lit_style(id),
+        }
+    }
+
+    fn markdown(&self) -> bool {
+        self.style == PrintStyle::Markdown
+    }
+
+    /// Start or end inline math around a formula in the Markdown style.
+    fn math<W: Write + ?Sized>(&self, out: &mut W) -> io::Result<()> {
+        if self.markdown() {
+            write!(out, "$")?;
+        }
+        Ok(())
+    }
+
+    fn foreground<W: Write + ?Sized>(&self, out: &mut W, color: Color) -> io::Result<()> {
+        if self.color && !self.markdown() {
+            out.queue(SetForegroundColor(color))?;
+        }
+        Ok(())
+    }
+
+    fn attribute<W: Write + ?Sized>(&self, out: &mut W, attribute: Attribute) -> io::Result<()> {
+        if self.color && !self.markdown() {
+            out.queue(SetAttribute(attribute))?;
+        }
+        Ok(())
+    }
+
+    pub fn write_variable<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        variable: &Variable,
+    ) -> io::Result<()> {
         let (id, positive) = match variable {
             Variable::Positive(id) => {
-                stdout.execute(SetForegroundColor(Color::Green)).unwrap();
+                self.foreground(out, self.config.positive)?;
                 (id, true)
             }
             Variable::Negative(id) => {
-                stdout.execute(SetForegroundColor(Color::Red)).unwrap();
+                self.foreground(out, self.config.negative)?;
                 (id, false)
             }
         };
         if !positive {
-            print!("{}", self.neg_sign());
+            write!(out, "{}", self.config.negation)?;
         }
-        print!("{}", self.lit_style(*id));
-        stdout.execute(SetForegroundColor(Color::Reset)).unwrap();
+        write!(out, "{}", self.lit_style(*id))?;
+        self.foreground(out, Color::Reset)
     }
 
-    pub fn print_clause(&self, clause: &Clause) {
-        let mut stdout = std::io::stdout();
-        stdout.execute(SetForegroundColor(Color::DarkGrey)).unwrap();
-        print!("(");
+    pub fn write_clause<W: Write + ?Sized>(&self, out: &mut W, clause: &Clause) -> io::Result<()> {
+        self.foreground(out, self.config.punctuation)?;
+        write!(out, "(")?;
         for (i, variable) in clause.iter().enumerate() {
-            self.print_variable(variable);
+            self.write_variable(out, variable)?;
             if i < clause.len() - 1 {
-                stdout.execute(SetForegroundColor(Color::Yellow)).unwrap();
-                print!(" {} ", self.or_style());
+                self.foreground(out, self.config.operator)?;
+                write!(out, " {} ", self.config.or)?;
             }
         }
-        stdout.execute(SetForegroundColor(Color::DarkGrey)).unwrap();
-        print!(")");
-        stdout.execute(SetForegroundColor(Color::Reset)).unwrap();
+        self.foreground(out, self.config.punctuation)?;
+        write!(out, ")")?;
+        self.foreground(out, Color::Reset)
     }
 
-    pub fn print_formula(&self, formula: &Formula) {
-        let mut stdout = std::io::stdout();
+    /// Write the clauses of a formula on one line, or wrapped at clause boundaries into lines of at most
+    /// [`Printer::with_width`] columns and [`Printer::with_clauses_per_line`] clauses,
+    /// with the following lines indented.
+    pub fn write_formula<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        formula: &Formula,
+    ) -> io::Result<()> {
+        self.math(out)?;
+        let and = format!(" {}", self.config.and);
+        // Math in Markdown can't be broken over lines
+        let wrap = !self.markdown() && (self.width.is_some() || self.clauses_per_line.is_some());
+        let (mut column, mut on_line) = (0, 0);
         for (i, clause) in formula.iter().enumerate() {
-            self.print_clause(clause);
-            if i < formula.len() - 1 {
-                stdout.execute(SetForegroundColor(Color::Yellow)).unwrap();
-                print!(" {} ", self.and_style());
+            let last = i == formula.len() - 1;
+            let length = if wrap {
+                self.with_color(false).format_clause(clause).chars().count()
+            } else {
+                0
+            };
+            if i > 0 {
+                let needed = 1 + length + if last { 0 } else { and.chars().count() };
+                let full = self.clauses_per_line.is_some_and(|max| on_line >= max)
+                    || self.width.is_some_and(|width| column + needed > width);
+                if wrap && full {
+                    writeln!(out)?;
+                    write!(out, "{}", " ".repeat(HANGING_INDENT))?;
+                    (column, on_line) = (HANGING_INDENT, 0);
+                } else {
+                    write!(out, " ")?;
+                    column += 1;
+                }
+            }
+            self.write_clause(out, clause)?;
+            column += length;
+            on_line += 1;
+            if !last {
+                self.foreground(out, self.config.operator)?;
+                write!(out, "{}", and)?;
+                column += and.chars().count();
             }
         }
-        stdout.execute(SetForegroundColor(Color::Reset)).unwrap();
-        println!();
+        self.foreground(out, Color::Reset)?;
+        self.math(out)?;
+        writeln!(out)
     }
 
-    pub fn print_solution(&self, solution: &Solution) {
-        let mut stdout = std::io::stdout();
-        let literals = solution.literals();
+    pub fn write_cube<W: Write + ?Sized>(&self, out: &mut W, cube: &Cube) -> io::Result<()> {
+        self.math(out)?;
+        if cube.is_empty() {
+            self.foreground(out, self.config.positive)?;
+            write!(out, "{}", self.config.bool_value(true))?;
+        }
+        for (i, variable) in cube.iter().enumerate() {
+            self.write_variable(out, variable)?;
+            if i < cube.len() - 1 {
+                self.foreground(out, self.config.operator)?;
+                write!(out, " {} ", self.config.and)?;
+            }
+        }
+        self.foreground(out, Color::Reset)?;
+        self.math(out)?;
+        writeln!(out)
+    }
+
+    pub fn write_dnf<W: Write + ?Sized>(&self, out: &mut W, dnf: &Dnf) -> io::Result<()> {
+        self.math(out)?;
+        if dnf.is_empty() {
+            self.foreground(out, self.config.negative)?;
+            write!(out, "{}", self.config.bool_value(false))?;
+        }
+        for (i, cube) in dnf.iter().enumerate() {
+            self.foreground(out, self.config.punctuation)?;
+            write!(out, "(")?;
+            for (j, variable) in cube.iter().enumerate() {
+                self.write_variable(out, variable)?;
+                if j < cube.len() - 1 {
+                    self.foreground(out, self.config.operator)?;
+                    write!(out, " {} ", self.config.and)?;
+                }
+            }
+            if cube.is_empty() {
+                self.foreground(out, self.config.positive)?;
+                write!(out, "{}", self.config.bool_value(true))?;
+            }
+            self.foreground(out, self.config.punctuation)?;
+            write!(out, ")")?;
+            if i < dnf.len() - 1 {
+                self.foreground(out, self.config.operator)?;
+                write!(out, " {} ", self.config.or)?;
+            }
+        }
+        self.foreground(out, Color::Reset)?;
+        self.math(out)?;
+        writeln!(out)
+    }
+
+    /// Write the values of the variables of a solution on one line, with don't-care variables as `*`.
+    /// The Markdown style writes a table with a column for each variable instead.
+    pub fn write_solution<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        solution: &Solution,
+    ) -> io::Result<()> {
+        if self.compact {
+            return self.write_compact_solution(out, solution);
+        }
+        if self.markdown() {
+            return self.write_solutions(out, std::slice::from_ref(solution));
+        }
+        let literals = self.shown(solution.literals());
         for i in 0..literals.len() {
             let id = literals[i];
-            stdout.execute(SetForegroundColor(Color::Reset)).unwrap();
-            stdout.execute(SetAttribute(Attribute::Bold)).unwrap();
-            print!("{}", self.lit_style(id));
-            stdout.execute(SetAttribute(Attribute::Reset)).unwrap();
-            stdout.execute(SetForegroundColor(Color::DarkGrey)).unwrap();
-            print!(" = ");
-            stdout
-                .execute(SetForegroundColor(if solution.get(id) {
-                    Color::Green
+            if self.unchanged(solution, id) {
+                self.foreground(out, self.config.punctuation)?;
+                write!(out, "{}", self.lit_style(id))?;
+            } else {
+                self.foreground(out, Color::Reset)?;
+                self.attribute(out, Attribute::Bold)?;
+                write!(out, "{}", self.lit_style(id))?;
+                self.attribute(out, Attribute::Reset)?;
+            }
+            self.foreground(out, self.config.punctuation)?;
+            write!(out, " = ")?;
+            if solution.is_dont_care(id) {
+                write!(out, "*")?;
+            } else if self.unchanged(solution, id) {
+                write!(out, "{}", self.config.bool_value(solution.get(id)))?;
+            } else {
+                let color = if solution.get(id) {
+                    self.config.positive
                 } else {
-                    Color::Red
-                }))
-                .unwrap();
-            print!("{}", self.bool_style(solution.get(id)));
+                    self.config.negative
+                };
+                self.foreground(out, color)?;
+                write!(out, "{}", self.config.bool_value(solution.get(id)))?;
+            }
             if i < literals.len() - 1 {
-                stdout.execute(SetForegroundColor(Color::DarkGrey)).unwrap();
-                print!(", ");
+                self.foreground(out, self.config.punctuation)?;
+                write!(out, ", ")?;
+            }
+        }
+        self.foreground(out, Color::Reset)?;
+        writeln!(out)
+    }
+
+    fn write_compact_solution<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        solution: &Solution,
+    ) -> io::Result<()> {
+        let literals = self.shown(solution.literals());
+        if literals.iter().any(|id| solution.is_dont_care(*id)) {
+            let cube = solution
+                .to_cube()
+                .iter()
+                .filter(|variable| literals.contains(&variable.id()))
+                .copied()
+                .collect();
+            return self.write_cube(out, &Cube(cube));
+        }
+        let (open, close) = if self.markdown() {
+            ("\\{", "\\}")
+        } else {
+            ("{", "}")
+        };
+        self.math(out)?;
+        self.foreground(out, self.config.punctuation)?;
+        write!(out, "{}", open)?;
+        let true_literals = literals.iter().filter(|id| solution.get(**id));
+        for (i, id) in true_literals.enumerate() {
+            if i > 0 {
+                self.foreground(out, self.config.punctuation)?;
+                write!(out, ", ")?;
+            }
+            if self.unchanged(solution, *id) {
+                self.foreground(out, self.config.punctuation)?;
+                write!(out, "{}", self.lit_style(*id))?;
+            } else {
+                self.write_variable(out, &Variable::Positive(*id))?;
+            }
+        }
+        self.foreground(out, self.config.punctuation)?;
+        write!(out, "{}", close)?;
+        self.foreground(out, Color::Reset)?;
+        self.math(out)?;
+        writeln!(out)
+    }
+
+    /// Write solutions one per line, or as one Markdown table with a row for each solution in the Markdown style,
+    /// where the variables a solution doesn't assign are left empty.
+    /// With [`Printer::with_diff`], the values that are the same as in the solution before are dimmed,
+    /// and the flipped values of Markdown tables are bold.
+    pub fn write_solutions<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        solutions: &[Solution],
+    ) -> io::Result<()> {
+        let previous = |i: usize| match i.checked_sub(1) {
+            Some(before) if self.diff => Printer {
+                previous: Some(&solutions[before]),
+                ..*self
+            },
+            Some(_) => Printer {
+                previous: None,
+                ..*self
+            },
+            None => *self,
+        };
+        if !self.markdown() || self.compact {
+            for (i, solution) in solutions.iter().enumerate() {
+                previous(i).write_solution(out, solution)?;
+            }
+            return Ok(());
+        }
+        let mut literals = self.shown(solutions.iter().flat_map(Solution::literals).collect());
+        literals.sort();
+        literals.dedup();
+        let rows = solutions
+            .iter()
+            .enumerate()
+            .map(|(i, solution)| {
+                let printer = previous(i);
+                let assigned = solution.literals();
+                literals
+                    .iter()
+                    .map(|id| {
+                        let value = match solution.is_dont_care(*id) {
+                            _ if !assigned.contains(id) => "",
+                            true => "\\*",
+                            false if solution.get(*id) => "true",
+                            false => "false",
+                        };
+                        if printer.previous.is_none() || printer.unchanged(solution, *id) {
+                            value.to_string()
+                        } else {
+                            format!("**{}**", value)
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        self.write_markdown_table(out, &literals, &rows)
+    }
+
+    /// Keep the variables shown in solutions, hiding auxiliary variables, those above [`Printer::hide_above`]
+    /// and those not chosen by [`Printer::only`].
+    fn shown(&self, literals: Vec<Literal>) -> Vec<Literal> {
+        literals
+            .into_iter()
+            .filter(|id| self.max_variable.is_none_or(|max| *id <= max))
+            .filter(|id| self.only.is_none_or(|only| only.contains(id)))
+            .filter(|id| !self.names.is_some_and(|names| names.is_auxiliary(*id)))
+            .collect()
+    }
+
+    /// Write the value of the formula for every assignment to its variables, with the satisfying rows in bold.
+    /// The rows are in counting order with the first variable as the most significant bit,
+    /// so formulas with more than [`MAX_TRUTH_TABLE_VARIABLES`] variables fail with an `InvalidInput` error.
+    /// The Markdown style writes a table with the satisfying rows in bold instead.
+    pub fn write_truth_table<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        formula: &Formula,
+    ) -> io::Result<()> {
+        check_variables(formula, MAX_TRUTH_TABLE_VARIABLES, "a truth table")?;
+        let variables = formula.literals();
+        if self.markdown() {
+            let rows = truth_table_rows(formula, &variables)
+                .into_iter()
+                .map(|(values, satisfied)| {
+                    let mut values = values
+                        .into_iter()
+                        .map(|value| value.to_string())
+                        .collect::<Vec<_>>();
+                    values.push(if satisfied { "**true**" } else { "false" }.to_string());
+                    values
+                })
+                .collect::<Vec<_>>();
+            return self.write_markdown_table(out, &variables, &rows);
+        }
+        let names = variables
+            .iter()
+            .map(|id| self.lit_style(*id))
+            .collect::<Vec<_>>();
+        self.attribute(out, Attribute::Bold)?;
+        write!(out, "  ")?;
+        for name in &names {
+            write!(out, "{} ", name)?;
+        }
+        self.attribute(out, Attribute::Reset)?;
+        self.foreground(out, self.config.punctuation)?;
+        writeln!(out, "|")?;
+        let rows = truth_table_rows(formula, &variables);
+        for (values, satisfied) in &rows {
+            self.foreground(out, Color::Reset)?;
+            if *satisfied {
+                self.attribute(out, Attribute::Bold)?;
+            }
+            write!(out, "  ")?;
+            for (value, name) in values.iter().zip(&names) {
+                write!(
+                    out,
+                    "{:<width$} ",
+                    self.config.bool_value(*value),
+                    width = name.chars().count()
+                )?;
             }
+            self.foreground(out, self.config.punctuation)?;
+            write!(out, "| ")?;
+            let color = if *satisfied {
+                self.config.positive
+            } else {
+                self.config.negative
+            };
+            self.foreground(out, color)?;
+            writeln!(out, "{}", self.config.bool_value(*satisfied))?;
+            self.attribute(out, Attribute::Reset)?;
         }
-        println!();
-        stdout.execute(SetForegroundColor(Color::Reset)).unwrap();
+        self.foreground(out, Color::Reset)?;
+        let count = rows.iter().filter(|(_, satisfied)| *satisfied).count();
+        writeln!(
+            out,
+            "  {} of {} rows satisfy the formula",
+            count,
+            rows.len()
+        )
+    }
+
+    /// Write the Karnaugh map of the formula, with the satisfying cells highlighted.
+    /// The first half of the variables label the rows and the others the columns, both in Gray code order
+    /// so neighboring cells differ in one variable.
+    /// Formulas with more than [`MAX_KARNAUGH_MAP_VARIABLES`] variables fail with an `InvalidInput` error.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    ///   X1 X2 \ X3 X4 | 00 01 11 10
+    ///   --------------+------------
+    ///   00            | T  F  F  T
+    ///   01            | F  F  F  F
+    ///   11            | F  T  T  F
+    ///   10            | T  F  F  T
+    /// ```
+    pub fn write_karnaugh_map<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        formula: &Formula,
+    ) -> io::Result<()> {
+        check_variables(formula, MAX_KARNAUGH_MAP_VARIABLES, "a Karnaugh map")?;
+        let variables = formula.literals();
+        let (row_variables, column_variables) = variables.split_at(variables.len() / 2);
+        let values = truth_table_rows(formula, &variables);
+        let value = |row: usize, column: usize| values[row << column_variables.len() | column].1;
+        let label = |code: usize, bits: usize| {
+            (0..bits)
+                .rev()
+                .map(|i| if code >> i & 1 == 1 { '1' } else { '0' })
+                .collect::<String>()
+        };
+        let names = |ids: &[Literal]| {
+            let names = ids.iter().map(|id| self.lit_style(*id)).collect::<Vec<_>>();
+            match (self.markdown(), names.is_empty()) {
+                (true, false) => format!("${}$", names.join(" ")),
+                _ => names.join(" "),
+            }
+        };
+        let (rows, columns) = (
+            gray_code(row_variables.len()),
+            gray_code(column_variables.len()),
+        );
+        if self.markdown() {
+            write!(
+                out,
+                "| {} \\\\ {} |",
+                names(row_variables),
+                names(column_variables)
+            )?;
+            for column in &columns {
+                write!(out, " {} |", label(*column, column_variables.len()))?;
+            }
+            writeln!(out)?;
+            writeln!(out, "|{}", " --- |".repeat(columns.len() + 1))?;
+            for row in &rows {
+                write!(out, "| {} |", label(*row, row_variables.len()))?;
+                for column in &columns {
+                    let cell = if value(*row, *column) {
+                        "**true**"
+                    } else {
+                        "false"
+                    };
+                    write!(out, " {} |", cell)?;
+                }
+                writeln!(out)?;
+            }
+            return Ok(());
+        }
+
+        let corner = format!("{} \\ {}", names(row_variables), names(column_variables));
+        let width = corner.chars().count();
+        let cell = column_variables.len().max(1);
+        self.attribute(out, Attribute::Bold)?;
+        write!(out, "  {} ", corner)?;
+        self.foreground(out, self.config.punctuation)?;
+        write!(out, "|")?;
+        self.foreground(out, Color::Reset)?;
+        for column in &columns {
+            write!(out, " {}", label(*column, column_variables.len()))?;
+        }
+        self.attribute(out, Attribute::Reset)?;
+        writeln!(out)?;
+        self.foreground(out, self.config.punctuation)?;
+        writeln!(
+            out,
+            "  {}+{}",
+            "-".repeat(width + 1),
+            "-".repeat(columns.len() * (cell + 1))
+        )?;
+        for row in &rows {
+            self.foreground(out, Color::Reset)?;
+            self.attribute(out, Attribute::Bold)?;
+            write!(
+                out,
+                "  {:<width$} ",
+                label(*row, row_variables.len()),
+                width = width
+            )?;
+            self.attribute(out, Attribute::Reset)?;
+            self.foreground(out, self.config.punctuation)?;
+            write!(out, "|")?;
+            for (i, column) in columns.iter().enumerate() {
+                let satisfied = value(*row, *column);
+                write!(out, " ")?;
+                if satisfied {
+                    self.foreground(out, self.config.positive)?;
+                    self.attribute(out, Attribute::Bold)?;
+                } else {
+                    self.foreground(out, self.config.negative)?;
+                }
+                let text = self.config.bool_value(satisfied);
+                write!(out, "{}", text)?;
+                self.attribute(out, Attribute::Reset)?;
+                if i < columns.len() - 1 {
+                    write!(
+                        out,
+                        "{}",
+                        " ".repeat(cell.saturating_sub(text.chars().count()))
+                    )?;
+                }
+            }
+            self.foreground(out, Color::Reset)?;
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Write a Markdown table with a column for each variable, and a last column for the value of
+    /// the formula if the rows are longer.
+    fn write_markdown_table<W: Write + ?Sized>(
+        &self,
+        out: &mut W,
+        variables: &[Literal],
+        rows: &[Vec<String>],
+    ) -> io::Result<()> {
+        write!(out, "|")?;
+        for id in variables {
+            write!(out, " ${}$ |", self.lit_style(*id))?;
+        }
+        let value = rows.first().is_some_and(|row| row.len() > variables.len());
+        if value {
+            write!(out, " value |")?;
+        }
+        writeln!(out)?;
+        write!(out, "|")?;
+        for _ in 0..variables.len() + usize::from(value) {
+            write!(out, " --- |")?;
+        }
+        writeln!(out)?;
+        for row in rows {
+            write!(out, "|")?;
+            for cell in row {
+                write!(out, " {} |", cell)?;
+            }
+            writeln!(out)?;
+        }
+        Ok(())
+    }
+
+    /// Get what a `write_*` method writes as a string, without the final line break.
+    fn format(&self, write: impl FnOnce(&mut Vec<u8>) -> io::Result<()>) -> String {
+        let mut out = Vec::new();
+        write(&mut out).expect("Writing to a vector never fails");
+        let mut string = String::from_utf8(out).expect("Printers write UTF-8");
+        if string.ends_with('\n') {
+            string.pop();
+        }
+        string
+    }
+
+    pub fn format_variable(&self, variable: &Variable) -> String {
+        self.format(|out| self.write_variable(out, variable))
+    }
+
+    pub fn format_clause(&self, clause: &Clause) -> String {
+        self.format(|out| self.write_clause(out, clause))
+    }
+
+    pub fn format_formula(&self, formula: &Formula) -> String {
+        self.format(|out| self.write_formula(out, formula))
+    }
+
+    pub fn format_cube(&self, cube: &Cube) -> String {
+        self.format(|out| self.write_cube(out, cube))
+    }
+
+    pub fn format_dnf(&self, dnf: &Dnf) -> String {
+        self.format(|out| self.write_dnf(out, dnf))
+    }
+
+    pub fn format_solution(&self, solution: &Solution) -> String {
+        self.format(|out| self.write_solution(out, solution))
+    }
+
+    pub fn format_solutions(&self, solutions: &[Solution]) -> String {
+        self.format(|out| self.write_solutions(out, solutions))
+    }
+
+    /// Format a truth table, failing like [`write_truth_table`](Self::write_truth_table) for too many variables.
+    pub fn format_truth_table(&self, formula: &Formula) -> io::Result<String> {
+        check_variables(formula, MAX_TRUTH_TABLE_VARIABLES, "a truth table")?;
+        Ok(self.format(|out| self.write_truth_table(out, formula)))
+    }
+
+    /// Format a Karnaugh map, failing like [`write_karnaugh_map`](Self::write_karnaugh_map) for too many variables.
+    pub fn format_karnaugh_map(&self, formula: &Formula) -> io::Result<String> {
+        check_variables(formula, MAX_KARNAUGH_MAP_VARIABLES, "a Karnaugh map")?;
+        Ok(self.format(|out| self.write_karnaugh_map(out, formula)))
+    }
+
+    pub fn print_variable(&self, variable: &Variable) {
+        self.write_variable(&mut io::stdout(), variable).unwrap();
+    }
+
+    pub fn print_clause(&self, clause: &Clause) {
+        self.write_clause(&mut io::stdout(), clause).unwrap();
+    }
+
+    /// Print a formula, wrapped to the width of the terminal unless another width is given.
+    pub fn print_formula(&self, formula: &Formula) {
+        let printer = match (self.width, terminal::size()) {
+            (None, Ok((columns, _))) if columns > 0 && io::stdout().is_terminal() => {
+                self.with_width(columns as usize)
+            }
+            _ => *self,
+        };
+        printer.write_formula(&mut io::stdout(), formula).unwrap();
+    }
+
+    pub fn print_cube(&self, cube: &Cube) {
+        self.write_cube(&mut io::stdout(), cube).unwrap();
+    }
+
+    pub fn print_dnf(&self, dnf: &Dnf) {
+        self.write_dnf(&mut io::stdout(), dnf).unwrap();
     }
+
+    pub fn print_solution(&self, solution: &Solution) {
+        self.write_solution(&mut io::stdout(), solution).unwrap();
+    }
+
+    pub fn print_solutions(&self, solutions: &[Solution]) {
+        self.write_solutions(&mut io::stdout(), solutions).unwrap();
+    }
+
+    pub fn print_truth_table(&self, formula: &Formula) -> io::Result<()> {
+        self.write_truth_table(&mut io::stdout(), formula)
+    }
+
+    pub fn print_karnaugh_map(&self, formula: &Formula) -> io::Result<()> {
+        self.write_karnaugh_map(&mut io::stdout(), formula)
+    }
+}
+
+/// Get the numbers of a number of bits in Gray code order, where consecutive numbers differ in one bit.
+fn gray_code(bits: usize) -> Vec<usize> {
+    (0..1usize << bits).map(|i| i ^ (i >> 1)).collect()
+}
+
+/// Fail with an `InvalidInput` error if the formula has more variables than a table of the given kind is drawn for.
+fn check_variables(formula: &Formula, max: usize, kind: &str) -> io::Result<()> {
+    let variables = formula.literals().len();
+    if variables > max {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "Too many variables for {}, {} of at most {}",
+                kind, variables, max
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Evaluate the formula for every assignment to the variables, in counting order.
+/// The callers check the variables with [`check_variables`] first, as there are `2^n` rows.
+fn truth_table_rows(formula: &Formula, variables: &[Literal]) -> Vec<(Vec<bool>, bool)> {
+    let mut solution = Solution::new();
+    (0..1usize << variables.len())
+        .map(|row| {
+            let values = (0..variables.len())
+                .map(|i| row >> (variables.len() - 1 - i) & 1 == 1)
+                .collect::<Vec<_>>();
+            for (id, value) in variables.iter().zip(&values) {
+                solution.set(*id, *value);
+            }
+            (values, solution.satisfy(formula))
+        })
+        .collect()
 }
 
 #[cfg(test)]
@@ -172,6 +1144,317 @@ mod tests {
         assert_eq!(PrintStyle::Mathematical.lit_style(100), "𝑋₁₀₀");
     }
 
+    #[test]
+    fn test_named_lit_style() {
+        let mut names = VarPool::new();
+        let rain = names.id("rain");
+        let printer = PrintStyle::Mathematical.with_names(&names);
+        assert_eq!(printer.lit_style(rain), "rain");
+        assert_eq!(printer.lit_style(rain + 1), "𝑋₂");
+    }
+
+    #[test]
+    fn test_mapping() {
+        let mut names = VarPool::new();
+        let rain = names.id("rain");
+        let mapping = HashMap::from([(7, "wet_grass".to_string()), (rain, "storm".to_string())]);
+        let clause = Clause(vec![Variable::Positive(rain), Variable::Negative(7)]);
+        let printer = PrintStyle::Normal.with_mapping(&mapping).with_color(false);
+        assert_eq!(printer.format_clause(&clause), "(storm or -wet_grass)");
+        let printer = PrintStyle::Programmatic
+            .with_names(&names)
+            .with_color(false);
+        assert_eq!(printer.format_clause(&clause), "(rain | !X7)");
+        assert_eq!(
+            printer.with_mapping(&mapping).format_clause(&clause),
+            "(storm | !wet_grass)"
+        );
+        let clause = Clause(vec![Variable::Negative(7), Variable::Positive(8)]);
+        assert_eq!(
+            PrintStyle::Mathematical
+                .with_mapping(&mapping)
+                .format_clause(&clause),
+            "(¬wet_grass ∨ 𝑋₈)"
+        );
+        assert_eq!(
+            PrintStyle::Markdown
+                .with_mapping(&mapping)
+                .format_clause(&clause),
+            "(\\lnot \\text{wet\\_grass} \\lor x_{8})"
+        );
+    }
+
+    #[test]
+    fn test_printer_config() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![Variable::Positive(2)],
+        ]
+        .into();
+        let mut config = PrintStyle::Programmatic.config().clone();
+        config.set("and", "&&").unwrap();
+        config.set("or", "||").unwrap();
+        config.set("not", "~").unwrap();
+        config.set("true", "yes").unwrap();
+        config.set("positive", "blue").unwrap();
+        assert_eq!(config.positive, Color::Blue);
+        assert!(config.set("negative", "plaid").is_err());
+        assert!(config.set("xor", "^").is_err());
+        let printer = PrintStyle::Programmatic.printer().with_config(&config);
+        assert_eq!(
+            printer.with_color(false).format_formula(&formula),
+            "(X1 || ~X2) && (X2)"
+        );
+        let solution: Solution = [(1, true), (2, false)][..].into();
+        assert_eq!(
+            printer.with_color(false).format_solution(&solution),
+            "X1 = yes, X2 = 0"
+        );
+        let colored = printer.with_color(true).format_formula(&formula);
+        assert!(colored.contains("\x1b[38;5;12mX1"));
+        assert_eq!(PrintStyle::Mathematical.config().and, "∧");
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert_eq!("never".parse(), Ok(ColorChoice::Never));
+        assert!("sometimes".parse::<ColorChoice>().is_err());
+        set_color_choice(ColorChoice::Always);
+        assert!(color_enabled());
+        set_color_choice(ColorChoice::Never);
+        assert!(!color_enabled());
+        set_color_choice(ColorChoice::Auto);
+    }
+
+    #[test]
+    fn test_write_plain() {
+        let mut names = VarPool::new();
+        names.reserve(1);
+        let rain = names.id("rain");
+        let printer = PrintStyle::Normal.with_names(&names).with_color(false);
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(rain)],
+            vec![Variable::Positive(rain)],
+        ]
+        .into();
+        let mut out = Vec::new();
+        printer.write_formula(&mut out, &formula).unwrap();
+        let solution: Solution = [(1, true), (rain, false)][..].into();
+        PrintStyle::Mathematical
+            .printer()
+            .with_color(false)
+            .write_solution(&mut out, &solution)
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "(X1 or -rain) and (rain)\n𝑋₁ = ⊤, 𝑋₂ = ⊥\n"
+        );
+        assert_eq!(
+            PrintStyle::Programmatic.format_formula(&formula),
+            "(X1 | !X2) & (X2)"
+        );
+        assert_eq!(PrintStyle::Normal.format_dnf(&Dnf(Vec::new())), "F");
+        assert_eq!(
+            printer.format_truth_table(&formula).unwrap(),
+            "  X1 rain |\n  F  F    | F\n  F  T    | F\n  T  F    | F\n  T  T    | T\n  \
+             1 of 4 rows satisfy the formula"
+        );
+        let colored = printer.with_color(true).format_solution(&solution);
+        assert!(colored.contains("\x1b[1mX1\x1b[0m"));
+        assert!(colored.ends_with("\x1b[39m"));
+        let mut colored = Vec::new();
+        let printer = printer.with_color(true);
+        printer
+            .write_variable(&mut colored, &Variable::Negative(1))
+            .unwrap();
+        assert!(colored.starts_with(b"\x1b["));
+    }
+
+    #[test]
+    fn test_markdown() {
+        let mut names = VarPool::new();
+        let rain = names.id("is_raining");
+        let printer = PrintStyle::Markdown.with_names(&names).with_color(true);
+        let formula: Formula = vec![
+            vec![Variable::Positive(rain), Variable::Negative(2)],
+            vec![Variable::Positive(2)],
+        ]
+        .into();
+        assert_eq!(
+            printer.format_formula(&formula),
+            "$(\\text{is\\_raining} \\lor \\lnot x_{2}) \\land (x_{2})$"
+        );
+        let mut solution: Solution = [(rain, true), (2, true)][..].into();
+        solution.shrink(&vec![vec![Variable::Positive(rain)]].into());
+        assert_eq!(
+            printer.format_solution(&solution),
+            "| $\\text{is\\_raining}$ | $x_{2}$ |\n| --- | --- |\n| true | \\* |"
+        );
+        assert_eq!(
+            PrintStyle::Markdown
+                .format_truth_table(&vec![vec![Variable::Negative(1)]].into())
+                .unwrap(),
+            "| $x_{1}$ | value |\n| --- | --- |\n| false | **true** |\n| true | false |"
+        );
+        assert_eq!(PrintStyle::Markdown.format_dnf(&Dnf(Vec::new())), "$\\bot$");
+        let solutions: Vec<Solution> =
+            vec![[(1, true)][..].into(), [(1, false), (2, true)][..].into()];
+        assert_eq!(
+            PrintStyle::Markdown.format_solutions(&solutions),
+            "| $x_{1}$ | $x_{2}$ |\n| --- | --- |\n| true |  |\n| false | true |"
+        );
+    }
+
+    #[test]
+    fn test_compact() {
+        let printer = PrintStyle::Normal
+            .printer()
+            .with_color(false)
+            .with_compact(true);
+        let mut solution: Solution = [(1, false), (2, true), (3, false), (40, true)][..].into();
+        assert_eq!(printer.format_solution(&solution), "{X2, X40}");
+        let only = [1, 3];
+        assert_eq!(printer.only(&only).format_solution(&solution), "{}");
+        assert_eq!(
+            printer
+                .with_compact(false)
+                .only(&only)
+                .format_solution(&solution),
+            "X1 = F, X3 = F"
+        );
+        // A partial model as the cube of the variables that matter
+        solution.shrink(&vec![vec![Variable::Positive(2)], vec![Variable::Negative(3)]].into());
+        assert_eq!(printer.format_solution(&solution), "X2 and -X3");
+        assert_eq!(
+            PrintStyle::Markdown
+                .printer()
+                .with_compact(true)
+                .format_solutions(&[[(1, true), (2, false)][..].into()]),
+            "$\\{x_{1}\\}$"
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let solutions: Vec<Solution> = vec![
+            [(1, true), (2, false)][..].into(),
+            [(1, true), (2, true)][..].into(),
+        ];
+        let printer = PrintStyle::Normal.printer().with_color(true);
+        let colored = printer
+            .compared_to(&solutions[0])
+            .format_solution(&solutions[1]);
+        assert!(!colored.contains("\x1b[1mX1") && colored.contains("\x1b[1mX2"));
+        assert_eq!(
+            printer
+                .with_color(false)
+                .with_diff(true)
+                .format_solutions(&solutions),
+            "X1 = T, X2 = F\nX1 = T, X2 = T"
+        );
+        assert_eq!(
+            PrintStyle::Markdown
+                .printer()
+                .with_diff(true)
+                .format_solutions(&solutions),
+            "| $x_{1}$ | $x_{2}$ |\n| --- | --- |\n| true | false |\n| true | **true** |"
+        );
+    }
+
+    #[test]
+    fn test_karnaugh_map() {
+        assert_eq!(gray_code(2), [0, 1, 3, 2]);
+        // X1 xor X2
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Negative(2)],
+        ]
+        .into();
+        assert_eq!(
+            PrintStyle::Normal.format_karnaugh_map(&formula).unwrap(),
+            "  X1 \\ X2 | 0 1\n  --------+----\n  0       | F T\n  1       | T F"
+        );
+        assert_eq!(
+            PrintStyle::Markdown.format_karnaugh_map(&formula).unwrap(),
+            "| $x_{1}$ \\\\ $x_{2}$ | 0 | 1 |\n| --- | --- | --- |\n\
+             | 0 | false | **true** |\n| 1 | **true** | false |"
+        );
+        // The columns of X2 X3 in Gray code order, where only X1 and X3 is satisfied
+        let formula: Formula = vec![
+            vec![Variable::Positive(1)],
+            vec![Variable::Positive(2), Variable::Negative(2)],
+            vec![Variable::Positive(3)],
+        ]
+        .into();
+        let map = PrintStyle::Programmatic
+            .format_karnaugh_map(&formula)
+            .unwrap();
+        assert!(map.ends_with("  1          | 0  1  1  0"));
+    }
+
+    #[test]
+    fn test_table_limits() {
+        // A clause of n variables, with 70 overflowing the row numbers
+        let clause = |n| Formula::from(vec![(1..=n).map(Variable::Positive).collect::<Vec<_>>()]);
+        assert!(PrintStyle::Normal.format_truth_table(&clause(12)).is_ok());
+        assert!(PrintStyle::Normal.format_karnaugh_map(&clause(4)).is_ok());
+        for n in [13, 70] {
+            let error = PrintStyle::Normal
+                .format_truth_table(&clause(n))
+                .unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+            let mut out = Vec::new();
+            let printer = PrintStyle::Markdown.printer();
+            assert!(printer.write_truth_table(&mut out, &clause(n)).is_err());
+            assert!(out.is_empty());
+        }
+        let error = PrintStyle::Normal
+            .format_karnaugh_map(&clause(5))
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Too many variables for a Karnaugh map, 5 of at most 4"
+        );
+    }
+
+    #[test]
+    fn test_wrap_formula() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1)],
+            vec![Variable::Positive(3), Variable::Negative(2)],
+            vec![Variable::Positive(4)],
+        ]
+        .into();
+        let printer = PrintStyle::Normal.printer().with_color(false);
+        assert_eq!(
+            printer.format_formula(&formula),
+            "(X1 or X2) and (-X1) and (X3 or -X2) and (X4)"
+        );
+        assert_eq!(
+            printer.with_width(30).format_formula(&formula),
+            "(X1 or X2) and (-X1) and\n    (X3 or -X2) and (X4)"
+        );
+        assert_eq!(
+            printer.with_clauses_per_line(1).format_formula(&formula),
+            "(X1 or X2) and\n    (-X1) and\n    (X3 or -X2) and\n    (X4)"
+        );
+        // A clause longer than the width is on a line of its own
+        assert_eq!(
+            printer
+                .with_width(5)
+                .format_formula(&formula)
+                .lines()
+                .count(),
+            4
+        );
+        assert!(!PrintStyle::Markdown
+            .printer()
+            .with_width(5)
+            .format_formula(&formula)
+            .contains('\n'));
+    }
+
     #[test]
     fn test_print_variable() {
         let variable = Variable::Positive(1);
@@ -202,4 +1485,52 @@ mod tests {
         PrintStyle::Programmatic.print_solution(&solution);
         PrintStyle::Mathematical.print_solution(&solution);
     }
+
+    #[test]
+    fn test_print_dnf() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(3)],
+        ]
+        .into();
+        let dnf = formula.to_dnf(10).unwrap();
+        print!("DNF: ");
+        PrintStyle::Normal.print_dnf(&dnf);
+        PrintStyle::Programmatic.print_dnf(&dnf);
+        PrintStyle::Mathematical.print_dnf(&dnf);
+    }
+
+    #[test]
+    fn test_print_hide_above() {
+        let mut names = VarPool::new();
+        names.reserve(3);
+        names.fresh();
+        let solution: Solution = ([(1, true), (2, false), (3, true), (4, false)][..]).into();
+        let printed = PrintStyle::Normal
+            .with_names(&names)
+            .hide_above(2)
+            .with_color(false)
+            .format_solution(&solution);
+        assert_eq!(printed, "X1 = T, X2 = F");
+    }
+
+    #[test]
+    fn test_truth_table_rows() {
+        let formula: Formula = vec![vec![Variable::Positive(1), Variable::Negative(2)]].into();
+        let rows = truth_table_rows(&formula, &formula.literals());
+        assert_eq!(
+            rows,
+            [
+                (vec![false, false], true),
+                (vec![false, true], false),
+                (vec![true, false], true),
+                (vec![true, true], true),
+            ]
+        );
+        print!("Truth table: ");
+        PrintStyle::Normal.print_truth_table(&formula).unwrap();
+        PrintStyle::Mathematical
+            .print_truth_table(&formula)
+            .unwrap();
+    }
 }
@@ -0,0 +1,134 @@
+use crate::{
+    solver::solve_assuming,
+    solvers::Solver,
+    types::{Certificate, Clause, Formula, Literal, PartialAssignment, Solution, Variable},
+};
+
+/// A quantified boolean formula with a single quantifier alternation (2QBF).
+/// The matrix is a formula in CNF, and variables that are not universal are existential.
+///
+/// ## Examples
+/// ```plaintext
+/// forall x1 x2 exists x3: (x1 OR x3) AND (x2 OR -x3)
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Qbf {
+    pub universal: Vec<Literal>,
+    pub existential: Vec<Literal>,
+    pub matrix: Formula,
+}
+
+/// The truth value of a quantified boolean formula.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum QbfCertificate {
+    True,
+    /// An assignment to the universal variables that no assignment to the existential variables satisfies.
+    False(Solution),
+    /// The solver gave up before the formula was decided.
+    Indeterminate,
+}
+
+impl Qbf {
+    /// Decide the formula `forall X exists Y: matrix` by counterexample-guided abstraction refinement.
+    ///
+    /// Two problems are solved in turns with the given solver:
+    /// 1. The abstraction looks for a candidate assignment to `X` that falsifies the matrix
+    ///    for every assignment to `Y` seen so far. If there is none, the formula is true.
+    /// 2. The matrix is checked under the candidate. If it is unsatisfiable, the candidate is a counterexample.
+    ///    Otherwise the assignment found to `Y` is used to refine the abstraction.
+    pub fn solve<S: Solver + ?Sized>(&self, solver: &S) -> QbfCertificate {
+        let mut next = self
+            .matrix
+            .literals()
+            .into_iter()
+            .chain(self.universal.iter().copied())
+            .chain(self.existential.iter().copied())
+            .max()
+            .unwrap_or(0);
+        let mut abstraction = Formula::new();
+        loop {
+            let mut variables = abstraction.literals();
+            variables.extend(&self.universal);
+            variables.sort();
+            variables.dedup();
+            let mut candidate = Solution::new();
+            let candidate = match solver.solve(&mut abstraction.clone(), &variables, &mut candidate)
+            {
+                Certificate::Satisfiable(candidate) => candidate,
+                Certificate::Unsatisfiable => return QbfCertificate::True,
+                Certificate::Indeterminate => return QbfCertificate::Indeterminate,
+            };
+            let mut universal = Solution::new();
+            let mut assumptions = Vec::new();
+            for id in &self.universal {
+                universal.set(*id, candidate.get(*id));
+                assumptions.push(if candidate.get(*id) {
+                    Variable::Positive(*id)
+                } else {
+                    Variable::Negative(*id)
+                });
+            }
+            let model = match solve_assuming(&self.matrix, &assumptions, solver) {
+                Certificate::Satisfiable(model) => model,
+                Certificate::Unsatisfiable => return QbfCertificate::False(universal),
+                Certificate::Indeterminate => return QbfCertificate::Indeterminate,
+            };
+            // The matrix under the existential assignment, as clauses over the universal variables
+            let mut existential = PartialAssignment::from(&model);
+            for id in &self.universal {
+                existential.unset(*id);
+            }
+            let residual = self.matrix.assign(&existential);
+            // Require some residual clause to be falsified, with an auxiliary variable per clause
+            let mut falsified = Vec::new();
+            for clause in residual.iter() {
+                next += 1;
+                for variable in clause.iter() {
+                    abstraction.add(Clause(vec![Variable::Negative(next), variable.negated()]));
+                }
+                falsified.push(Variable::Positive(next));
+            }
+            abstraction.add(Clause(falsified));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::solvers::Dfs;
+
+    use super::*;
+
+    #[test]
+    fn test_qbf_true() {
+        // forall x1 exists x2: (x1 OR x2) AND (-x1 OR -x2)
+        let qbf = Qbf {
+            universal: vec![1],
+            existential: vec![2],
+            matrix: vec![
+                vec![Variable::Positive(1), Variable::Positive(2)],
+                vec![Variable::Negative(1), Variable::Negative(2)],
+            ]
+            .into(),
+        };
+        assert_eq!(qbf.solve(&Dfs::default()), QbfCertificate::True);
+    }
+
+    #[test]
+    fn test_qbf_false() {
+        // forall x1 x2 exists x3: (x1 OR x3) AND (x2 OR -x3)
+        let qbf = Qbf {
+            universal: vec![1, 2],
+            existential: vec![3],
+            matrix: vec![
+                vec![Variable::Positive(1), Variable::Positive(3)],
+                vec![Variable::Positive(2), Variable::Negative(3)],
+            ]
+            .into(),
+        };
+        assert_eq!(
+            qbf.solve(&Dfs::default()),
+            QbfCertificate::False([(1, false), (2, false)][..].into())
+        );
+    }
+}
@@ -0,0 +1,183 @@
+use std::fmt::Write;
+
+use crate::{
+    printer::PrintStyle,
+    solvers::Stats,
+    types::{Formula, Solution, VarPool},
+};
+
+/// The results of solving a formula, written as a standalone HTML page by [`Report::to_html`].
+#[derive(Debug, Clone, Copy)]
+pub struct Report<'a> {
+    pub formula: &'a Formula,
+    /// The names of the variables, where auxiliary variables are left out of the models.
+    pub names: &'a VarPool,
+    pub solutions: &'a [Solution],
+    /// Whether the search was complete, so no solutions means the formula is unsatisfiable.
+    pub complete: bool,
+    pub stats: &'a Stats,
+}
+
+impl Report<'_> {
+    /// Write the report as an HTML page without external resources, with the formula, the solver statistics
+    /// and the models in a table that is sorted by clicking a column header.
+    pub fn to_html(&self) -> String {
+        let status = match (self.solutions.len(), self.complete) {
+            (0, true) => "Unsatisfiable".to_string(),
+            (0, false) => "Unknown".to_string(),
+            (1, _) => "Satisfiable with 1 model".to_string(),
+            (count, _) => format!("Satisfiable with {} models", count),
+        };
+        let formula = PrintStyle::Normal
+            .with_names(self.names)
+            .with_color(false)
+            .format_formula(self.formula);
+        let mut html = String::from(HEADER);
+        writeln!(html, "<h1>{}</h1>", status).unwrap();
+        writeln!(
+            html,
+            "<h2>Formula</h2>\n<p>{} variables and {} clauses</p>\n<pre>{}</pre>",
+            self.formula.literals().len(),
+            self.formula.len(),
+            escape(&formula)
+        )
+        .unwrap();
+
+        html.push_str("<h2>Statistics</h2>\n<table>\n");
+        let stats = [
+            ("Decisions", self.stats.decisions.to_string()),
+            ("Propagations", self.stats.propagations.to_string()),
+            ("Conflicts", self.stats.conflicts.to_string()),
+            ("Restarts", self.stats.restarts.to_string()),
+            ("Learned clauses", self.stats.learned_clauses.to_string()),
+            ("Peak memory", format!("{} bytes", self.stats.peak_memory)),
+            ("Time", format!("{:?}", self.stats.time)),
+        ];
+        for (name, value) in stats {
+            writeln!(html, "<tr><th>{}</th><td>{}</td></tr>", name, value).unwrap();
+        }
+        html.push_str("</table>\n");
+
+        if !self.solutions.is_empty() {
+            let variables = self
+                .formula
+                .literals()
+                .into_iter()
+                .filter(|id| !self.names.is_auxiliary(*id))
+                .collect::<Vec<_>>();
+            html.push_str("<h2>Models</h2>\n<table class=\"models\">\n<tr><th>#</th>");
+            for id in &variables {
+                let name = self
+                    .names
+                    .name(*id)
+                    .map_or(format!("x{}", id), str::to_string);
+                write!(html, "<th>{}</th>", escape(&name)).unwrap();
+            }
+            html.push_str("</tr>\n");
+            for (i, solution) in self.solutions.iter().enumerate() {
+                write!(html, "<tr><td>{}</td>", i + 1).unwrap();
+                for id in &variables {
+                    let value = match (solution.is_dont_care(*id), solution.get(*id)) {
+                        (true, _) => "<td>*</td>",
+                        (false, true) => "<td class=\"true\">T</td>",
+                        (false, false) => "<td class=\"false\">F</td>",
+                    };
+                    html.push_str(value);
+                }
+                html.push_str("</tr>\n");
+            }
+            html.push_str("</table>\n");
+        }
+        html.push_str(FOOTER);
+        html
+    }
+}
+
+/// Escape the characters with a meaning in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const HEADER: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>SAT Solver Report</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+pre { white-space: pre-wrap; background: #f4f4f4; padding: 1em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+.models th { cursor: pointer; background: #eee; }
+.true { color: #080; }
+.false { color: #c00; }
+</style>
+</head>
+<body>
+"#;
+
+/// Sorts the models by the clicked column, toggling between ascending and descending order.
+const FOOTER: &str = r#"<script>
+document.querySelectorAll(".models th").forEach((header, column) => {
+  header.addEventListener("click", () => {
+    const table = header.closest("table");
+    const rows = Array.from(table.rows).slice(1);
+    const ascending = header.dataset.order !== "asc";
+    header.dataset.order = ascending ? "asc" : "desc";
+    const key = (row) => {
+      const text = row.cells[column].textContent;
+      return column === 0 ? Number(text) : text;
+    };
+    rows.sort((a, b) => (key(a) > key(b) ? 1 : key(a) < key(b) ? -1 : 0) * (ascending ? 1 : -1));
+    rows.forEach((row) => table.tBodies[0].appendChild(row));
+  });
+});
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Variable;
+
+    use super::*;
+
+    #[test]
+    fn test_report_html() {
+        let mut names = VarPool::new();
+        let rain = names.id("rain");
+        let auxiliary = names.fresh();
+        let formula: Formula = vec![
+            vec![Variable::Positive(rain), Variable::Positive(auxiliary)],
+            vec![Variable::Negative(auxiliary)],
+        ]
+        .into();
+        let solutions: Vec<Solution> = vec![[(rain, true), (auxiliary, false)][..].into()];
+        let stats = Stats::new();
+        let report = Report {
+            formula: &formula,
+            names: &names,
+            solutions: &solutions,
+            complete: true,
+            stats: &stats,
+        };
+        let html = report.to_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<h1>Satisfiable with 1 model</h1>"));
+        assert!(html.contains("<pre>(rain or X2) and (-X2)</pre>"));
+        assert!(html.contains(
+            "<tr><th>#</th><th>rain</th></tr>\n<tr><td>1</td><td class=\"true\">T</td></tr>"
+        ));
+        let unsatisfiable = Report {
+            solutions: &[],
+            ..report
+        };
+        assert!(unsatisfiable.to_html().contains("<h1>Unsatisfiable</h1>"));
+        assert!(!unsatisfiable.to_html().contains("Models"));
+        assert_eq!(escape("a<b & \"c\""), "a&lt;b &amp; &quot;c&quot;");
+    }
+}
@@ -0,0 +1,403 @@
+use crate::{expr::Expr, parser::ParseError, types::VarPool};
+
+/// What is expected where a term is missing or invalid.
+const TERM: &[&str] = &["a term"];
+
+/// Parse an SMT-LIB 2 script in the propositional subset into the conjunction of its assertions.
+/// Constants are declared with `declare-const` or `declare-fun` of sort `Bool`, and added to the symbol table.
+/// Terms use `true`, `false`, `not`, `and`, `or`, `xor`, `=>`, `=`, `distinct` and `ite`,
+/// with `let` bindings, `define-fun` definitions without parameters and `!` annotations.
+/// Commands like `set-logic` and `check-sat` that don't change the formula are ignored.
+///
+/// ## Examples
+/// ```plaintext
+/// (set-logic QF_UF)
+/// (declare-const rain Bool)
+/// (declare-const wet Bool)
+/// (assert (=> rain wet))
+/// (check-sat)
+/// ```
+pub fn parse_smtlib(input: &str, pool: &mut VarPool) -> Result<Expr, ParseError> {
+    let mut script = Script {
+        pool,
+        bindings: Vec::new(),
+    };
+    let mut assertions = Vec::new();
+    for command in read(input)? {
+        let Sexp::List(start, items, end) = &command else {
+            let (offset, token) = command.token();
+            return Err(ParseError::new(offset, token, &["'('"]));
+        };
+        let Some(Sexp::Atom(offset, name)) = items.first() else {
+            let (offset, token) = items.first().map_or((*start, "("), Sexp::token);
+            return Err(ParseError::new(offset, token, &["a command"]));
+        };
+        let arguments = &items[1..];
+        match *name {
+            "assert" => {
+                let [term] = arguments else {
+                    return Err(arity(arguments, 1, *end));
+                };
+                assertions.push(script.term(term)?);
+            }
+            "declare-const" => {
+                let [Sexp::Atom(_, symbol), sort] = arguments else {
+                    return Err(arity(arguments, 2, *end));
+                };
+                boolean(sort)?;
+                let id = script.pool.id(unquote(symbol));
+                script.bindings.push((unquote(symbol), Expr::var(id)));
+            }
+            "declare-fun" => {
+                let [Sexp::Atom(_, symbol), Sexp::List(_, parameters, _), sort] = arguments else {
+                    return Err(arity(arguments, 3, *end));
+                };
+                no_parameters(parameters)?;
+                boolean(sort)?;
+                let id = script.pool.id(unquote(symbol));
+                script.bindings.push((unquote(symbol), Expr::var(id)));
+            }
+            "define-fun" => {
+                let [Sexp::Atom(_, symbol), Sexp::List(_, parameters, _), sort, term] = arguments
+                else {
+                    return Err(arity(arguments, 4, *end));
+                };
+                no_parameters(parameters)?;
+                boolean(sort)?;
+                let expr = script.term(term)?;
+                script.bindings.push((unquote(symbol), expr));
+            }
+            "set-logic" | "set-info" | "set-option" | "check-sat" | "get-model" | "get-value"
+            | "get-info" | "echo" | "exit" => (),
+            _ => {
+                return Err(ParseError::new(
+                    *offset,
+                    name,
+                    &["declare-const", "declare-fun", "define-fun", "assert"],
+                ))
+            }
+        }
+    }
+    Ok(Expr::all(assertions))
+}
+
+/// An S-expression with the byte offsets of its atom or parentheses.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Sexp<'a> {
+    Atom(usize, &'a str),
+    List(usize, Vec<Sexp<'a>>, usize),
+}
+
+impl<'a> Sexp<'a> {
+    /// Get the offset and text of the token starting the expression.
+    fn token(&self) -> (usize, &'a str) {
+        match self {
+            Sexp::Atom(offset, atom) => (*offset, atom),
+            Sexp::List(offset, _, _) => (*offset, "("),
+        }
+    }
+}
+
+/// Read the S-expressions of the input, skipping comments from `;` to the end of the line.
+/// Symbols in `|` and strings in `"` are read as single atoms.
+fn read(input: &str) -> Result<Vec<Sexp<'_>>, ParseError> {
+    // The lists being read, with their start, innermost last
+    let mut open: Vec<(usize, Vec<Sexp>)> = vec![(0, Vec::new())];
+    let mut chars = input.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            ';' => while chars.next_if(|(_, c)| *c != '\n').is_some() {},
+            '(' => open.push((offset, Vec::new())),
+            ')' => {
+                if open.len() == 1 {
+                    return Err(ParseError::new(offset, ")", &["'('"]));
+                }
+                let (start, items) = open.pop().unwrap();
+                let list = Sexp::List(start, items, offset);
+                open.last_mut().unwrap().1.push(list);
+            }
+            '|' | '"' => {
+                let Some(end) = input[offset + 1..].find(c) else {
+                    return Err(ParseError::new(input.len(), "", &[&format!("'{}'", c)]));
+                };
+                let end = offset + 1 + end;
+                while chars.next_if(|(i, _)| *i <= end).is_some() {}
+                let atom = Sexp::Atom(offset, &input[offset..=end]);
+                open.last_mut().unwrap().1.push(atom);
+            }
+            c if c.is_whitespace() => (),
+            _ => {
+                let mut end = offset + c.len_utf8();
+                while let Some((i, c)) =
+                    chars.next_if(|(_, c)| !c.is_whitespace() && !"();|\"".contains(*c))
+                {
+                    end = i + c.len_utf8();
+                }
+                open.last_mut()
+                    .unwrap()
+                    .1
+                    .push(Sexp::Atom(offset, &input[offset..end]));
+            }
+        }
+    }
+    if open.len() > 1 {
+        return Err(ParseError::new(input.len(), "", &["')'"]));
+    }
+    Ok(open.pop().unwrap().1)
+}
+
+/// Get the name of a symbol, without the `|` of a quoted symbol.
+fn unquote(symbol: &str) -> &str {
+    symbol
+        .strip_prefix('|')
+        .and_then(|symbol| symbol.strip_suffix('|'))
+        .unwrap_or(symbol)
+}
+
+/// Check that a sort is `Bool`, the only sort of the propositional subset.
+fn boolean(sort: &Sexp) -> Result<(), ParseError> {
+    match sort {
+        Sexp::Atom(_, "Bool") => Ok(()),
+        sort => {
+            let (offset, token) = sort.token();
+            Err(ParseError::new(offset, token, &["Bool"]))
+        }
+    }
+}
+
+/// Check that a function has no parameters, so it is a constant.
+fn no_parameters(parameters: &[Sexp]) -> Result<(), ParseError> {
+    match parameters.first() {
+        Some(parameter) => {
+            let (offset, token) = parameter.token();
+            Err(ParseError::new(offset, token, &["')'"]))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Get the error for a list with the wrong number of arguments, ending at the given offset.
+fn arity(arguments: &[Sexp], expected: usize, end: usize) -> ParseError {
+    match arguments.get(expected) {
+        Some(argument) => {
+            let (offset, token) = argument.token();
+            ParseError::new(offset, token, &["')'"])
+        }
+        None => ParseError::new(end, "", &[&format!("{} arguments", expected)]),
+    }
+}
+
+/// Get the conjunction of the expressions, or the expression itself if there is only one.
+fn conjunction(exprs: impl IntoIterator<Item = Expr>) -> Expr {
+    let mut exprs = exprs.into_iter().collect::<Vec<_>>();
+    if exprs.len() == 1 {
+        exprs.pop().unwrap()
+    } else {
+        Expr::all(exprs)
+    }
+}
+
+/// The state of a script, with the constants and definitions in scope.
+struct Script<'p, 'a> {
+    pool: &'p mut VarPool,
+    /// The expression of each symbol, where later bindings shadow earlier ones.
+    bindings: Vec<(&'a str, Expr)>,
+}
+
+impl<'a> Script<'_, 'a> {
+    /// Convert a term to an expression.
+    fn term(&mut self, term: &Sexp<'a>) -> Result<Expr, ParseError> {
+        let (start, items, end) = match term {
+            Sexp::Atom(_, "true") => return Ok(Expr::constant(true)),
+            Sexp::Atom(_, "false") => return Ok(Expr::constant(false)),
+            Sexp::Atom(offset, symbol) => {
+                return match self
+                    .bindings
+                    .iter()
+                    .rev()
+                    .find(|(s, _)| *s == unquote(symbol))
+                {
+                    Some((_, expr)) => Ok(expr.clone()),
+                    None => Err(ParseError::new(*offset, symbol, &["a declared constant"])),
+                }
+            }
+            Sexp::List(start, items, end) => (*start, items, *end),
+        };
+        let Some(Sexp::Atom(offset, operator)) = items.first() else {
+            let (offset, token) = items.first().map_or((start, ")"), Sexp::token);
+            return Err(ParseError::new(offset, token, &["an operator"]));
+        };
+        let arguments = &items[1..];
+        match *operator {
+            "let" => return self.let_term(arguments, end),
+            // Annotations only name terms, for things like unsat cores
+            "!" => {
+                let Some(term) = arguments.first() else {
+                    return Err(ParseError::new(end, "", TERM));
+                };
+                return self.term(term);
+            }
+            _ => (),
+        }
+        let operands = arguments
+            .iter()
+            .map(|argument| self.term(argument))
+            .collect::<Result<Vec<_>, _>>()?;
+        // The least number of operands of each operator, and the most if limited
+        let (least, most) = match *operator {
+            "not" => (1, Some(1)),
+            "ite" => (3, Some(3)),
+            "and" | "or" => (0, None),
+            "xor" | "=>" | "=" | "distinct" => (2, None),
+            _ => {
+                let expected = &["not", "and", "or", "xor", "=>", "=", "distinct", "ite"];
+                return Err(ParseError::new(*offset, operator, expected));
+            }
+        };
+        if operands.len() < least {
+            let missing = format!("{} more terms", least - operands.len());
+            return Err(ParseError::new(end, "", &[&missing]));
+        }
+        if let Some(argument) = most.and_then(|most| arguments.get(most)) {
+            let (offset, token) = argument.token();
+            return Err(ParseError::new(offset, token, &["')'"]));
+        }
+        let mut operands = operands.into_iter();
+        let first = operands.next();
+        Ok(match *operator {
+            "not" => !first.unwrap(),
+            "and" => Expr::all(first.into_iter().chain(operands)),
+            "or" => Expr::any(first.into_iter().chain(operands)),
+            "xor" => operands.fold(first.unwrap(), Expr::xor),
+            // Implication groups to the right
+            "=>" => {
+                let mut operands = first.into_iter().chain(operands).rev();
+                let last = operands.next().unwrap();
+                operands.fold(last, |consequent, premise| premise.implies(consequent))
+            }
+            // Chained equalities hold between each adjacent pair
+            "=" => {
+                let operands = first.into_iter().chain(operands).collect::<Vec<_>>();
+                conjunction(
+                    operands
+                        .windows(2)
+                        .map(|pair| pair[0].clone().iff(pair[1].clone())),
+                )
+            }
+            // Distinct Booleans differ in each pair
+            "distinct" => {
+                let operands = first.into_iter().chain(operands).collect::<Vec<_>>();
+                conjunction(
+                    operands.iter().enumerate().flat_map(|(i, a)| {
+                        operands[i + 1..].iter().map(|b| a.clone().xor(b.clone()))
+                    }),
+                )
+            }
+            _ => {
+                let condition = first.unwrap();
+                let (then, otherwise) = (operands.next().unwrap(), operands.next().unwrap());
+                condition.clone().implies(then) & (!condition).implies(otherwise)
+            }
+        })
+    }
+
+    /// Convert a `let` term binding each symbol to a term, in scope only for its body.
+    fn let_term(&mut self, arguments: &[Sexp<'a>], end: usize) -> Result<Expr, ParseError> {
+        let [Sexp::List(_, bindings, _), body] = arguments else {
+            return Err(arity(arguments, 2, end));
+        };
+        // The bound terms are converted before any of the bindings are in scope
+        let mut bound = Vec::new();
+        for binding in bindings {
+            let Sexp::List(_, pair, pair_end) = binding else {
+                let (offset, token) = binding.token();
+                return Err(ParseError::new(offset, token, &["'('"]));
+            };
+            let [Sexp::Atom(_, symbol), term] = pair.as_slice() else {
+                return Err(arity(pair, 2, *pair_end));
+            };
+            bound.push((unquote(symbol), self.term(term)?));
+        }
+        let scope = self.bindings.len();
+        self.bindings.extend(bound);
+        let expr = self.term(body);
+        self.bindings.truncate(scope);
+        expr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Solution;
+
+    use super::*;
+
+    #[test]
+    fn test_parse_smtlib() {
+        let mut pool = VarPool::new();
+        let script = "
+            ; A sprinkler or rain makes the grass wet
+            (set-logic QF_UF)
+            (declare-const rain Bool)
+            (declare-fun |sprinkler on| () Bool)
+            (declare-const wet Bool)
+            (define-fun cause () Bool (or rain |sprinkler on|))
+            (assert (=> cause wet))
+            (assert (! (not wet) :named dry))
+            (assert (let ((a rain) (b true)) (xor a b (= wet true))))
+            (check-sat)
+        ";
+        let expr = parse_smtlib(script, &mut pool).unwrap();
+        assert_eq!(pool.name(2), Some("sprinkler on"));
+        assert_eq!(
+            expr.to_string(),
+            "((X1 or X2) -> X3) and -X3 and ((X1 xor T) xor (X3 <-> T))"
+        );
+        let models = (0..8u32)
+            .map(|bits| {
+                Solution::from(&[(1, bits & 1 != 0), (2, bits & 2 != 0), (3, bits & 4 != 0)][..])
+            })
+            .filter(|solution| expr.eval(solution))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            models,
+            [Solution::from(&[(1, false), (2, false), (3, false)][..])]
+        );
+    }
+
+    #[test]
+    fn test_smtlib_operators() {
+        let mut pool = VarPool::new();
+        let script = "(declare-const a Bool) (declare-const b Bool) (declare-const c Bool)
+            (assert (=> a b c)) (assert (= a b c)) (assert (distinct a b c)) (assert (ite a b c))";
+        let expr = parse_smtlib(script, &mut pool).unwrap();
+        assert_eq!(
+            expr.to_string(),
+            "(X1 -> (X2 -> X3)) and (X1 <-> X2) and (X2 <-> X3) and (X1 xor X2) and (X1 xor X3) \
+             and (X2 xor X3) and (X1 -> X2) and (-X1 -> X3)"
+        );
+    }
+
+    #[test]
+    fn test_smtlib_errors() {
+        let mut pool = VarPool::new();
+        let error = parse_smtlib("(declare-const x Int)", &mut pool).unwrap_err();
+        assert_eq!(error.span, 17..20);
+        assert_eq!(error.to_string(), "Unexpected Int, expected Bool");
+        let error = parse_smtlib("(assert y)", &mut pool).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected y, expected a declared constant"
+        );
+        let error = parse_smtlib("(assert (not true false))", &mut pool).unwrap_err();
+        assert_eq!(error.span, 18..23);
+        let error = parse_smtlib("(assert (xor true))", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "Missing 1 more terms");
+        let error = parse_smtlib("(assert (bvand true))", &mut pool).unwrap_err();
+        assert_eq!(error.token, "bvand");
+        let error = parse_smtlib("(assert true", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "Missing ')'");
+        let error = parse_smtlib("(push 1)", &mut pool).unwrap_err();
+        assert_eq!(error.span, 1..5);
+    }
+}
@@ -1,38 +1,624 @@
+use std::{collections::HashMap, time::Instant};
+
 use crate::{
-    solvers::Solver,
-    types::{Certificate, Formula, Solution},
+    solvers::{Budget, Solver, Stats},
+    trail::Trail,
+    types::{Certificate, Clause, Cube, Formula, Literal, PartialAssignment, Solution, Variable},
 };
 
+/// Solve a SAT problem under a set of assumptions using a given solver.
+/// Each assumption is added as a unit clause to a copy of the formula, leaving the original untouched.
+pub fn solve_assuming<S: Solver + ?Sized>(
+    formula: &Formula,
+    assumptions: &[Variable],
+    solver: &S,
+) -> Certificate {
+    let mut formula = formula.clone();
+    for assumption in assumptions {
+        formula.add(Clause(vec![*assumption]));
+    }
+    let variables = formula.literals();
+    let mut solution = Solution::new();
+    solver.solve(&mut formula, &variables, &mut solution)
+}
+
+/// Solve a SAT problem after propagating its unit clauses, so the solver only searches
+/// the variables not implied by them. Brute-force solvers can then handle large structured
+/// problems like puzzles, where most variables follow from the given clues.
+pub fn solve_propagated<S: Solver + ?Sized>(formula: &Formula, solver: &S) -> Certificate {
+    let mut trail = Trail::new();
+    if trail.propagate(formula).is_err() {
+        return Certificate::Unsatisfiable;
+    }
+    let mut assignment = PartialAssignment::new();
+    for implied in trail.iter() {
+        assignment.set(implied.variable.id(), implied.variable.is_positive());
+    }
+    let mut residual = formula.assign(&assignment);
+    let variables = residual.literals();
+    let mut solution = Solution::new();
+    match solver.solve(&mut residual, &variables, &mut solution) {
+        Certificate::Satisfiable(mut model) => {
+            // Variables only in clauses satisfied by propagation can have any value
+            for id in formula.literals() {
+                if let Some(value) = assignment.get(id) {
+                    model.set(id, value);
+                } else if variables.binary_search(&id).is_err() {
+                    model.set(id, false);
+                }
+            }
+            Certificate::Satisfiable(model)
+        }
+        certificate => certificate,
+    }
+}
+
 /// Find all solutions to a SAT problem using a given solver.
-/// Uses an exhaustive search with restart backtracking.
+/// The solutions are found as cubes by [`solve_all_cubes`], and then expanded into full assignments.
 ///
 /// ## Note
-/// This is a naive implementation that may not be efficient for large formulas with many variables and possible solutions.
-pub fn solve_all<S: Solver>(formula: &Formula, solver: &S) -> Vec<Solution> {
+/// The number of solutions grows exponentially with the number of unconstrained variables.
+pub fn solve_all<S: Solver + ?Sized>(formula: &Formula, solver: &S) -> Vec<Solution> {
+    let variables = formula.literals();
+    solve_all_cubes(formula, solver)
+        .iter()
+        .flat_map(|cube| cube.expand(&variables))
+        .collect()
+}
+
+/// Find the first `n` solutions [`solve_all`] would find, and whether there are more,
+/// without enumerating the rest.
+///
+/// ## Examples
+/// ```plaintext
+/// x1 OR x2 OR x3 with n = 2
+/// x1 = F, x2 = F, x3 = T
+/// x1 = F, x2 = T, x3 = F
+/// and more
+/// ```
+pub fn solve_n<S: Solver + ?Sized>(
+    formula: &Formula,
+    n: usize,
+    solver: &S,
+) -> (Vec<Solution>, bool) {
     let mut formula = formula.clone();
     let mut solutions = Vec::new();
     let variables = formula.literals();
     let mut solution = Solution::new();
 
+    while let Certificate::Satisfiable(model) = {
+        solution.reset();
+        solver.solve(&mut formula, &variables, &mut solution)
+    } {
+        let cube = model.implicant(&formula);
+        formula.add(cube.negative_clause());
+        let mut expansions = cube.expansions(&variables);
+        solutions.extend(expansions.by_ref().take(n - solutions.len()));
+        if solutions.len() == n {
+            let more = expansions.next().is_some() || {
+                solution.reset();
+                let certificate = solver.solve(&mut formula, &variables, &mut solution);
+                matches!(certificate, Certificate::Satisfiable(_))
+            };
+            return (solutions, more);
+        }
+    }
+    (solutions, false)
+}
+
+/// Find disjoint cubes covering all solutions to a SAT problem using a given solver.
+/// Each solution found is shrunk to an implicant of the formula, which is then blocked as a whole,
+/// so variables that don't matter are never enumerated one assignment at a time.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 OR x2) AND x3
+/// x1 AND x3
+/// -x1 AND x2 AND x3
+/// ```
+pub fn solve_all_cubes<S: Solver + ?Sized>(formula: &Formula, solver: &S) -> Vec<Cube> {
+    let mut formula = formula.clone();
+    let mut cubes = Vec::new();
+    let variables = formula.literals();
+    let mut solution = Solution::new();
+
     while let Certificate::Satisfiable(solution) = {
         solution.reset();
         solver.solve(&mut formula, &variables, &mut solution)
     } {
-        solutions.push(solution.clone());
-        // Remove that exact solution from the formula
-        formula.add(solution.negative_clause());
+        // Shrinking against the blocking clauses too keeps the cubes disjoint
+        let cube = solution.implicant(&formula);
+        formula.add(cube.negative_clause());
+        cubes.push(cube);
+    }
+    cubes
+}
+
+/// Find all distinct assignments to the projection variables that can be extended to a solution of the formula.
+/// The other variables are treated as existentially quantified, and are left out of the solutions.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 OR x2) AND (-x1 OR x3) projected onto x1
+/// x1 = F
+/// x1 = T
+/// ```
+pub fn solve_all_projected<S: Solver + ?Sized>(
+    formula: &Formula,
+    projection: &[Literal],
+    solver: &S,
+) -> Vec<Solution> {
+    let mut formula = formula.clone();
+    let mut solutions = Vec::new();
+    let mut variables = formula.literals();
+    variables.extend(projection);
+    variables.sort();
+    variables.dedup();
+    let mut solution = Solution::new();
+
+    while let Certificate::Satisfiable(model) = {
+        solution.reset();
+        solver.solve(&mut formula, &variables, &mut solution)
+    } {
+        let mut projected = Solution::new();
+        for id in projection {
+            projected.set(*id, model.get(*id));
+        }
+        // Only block the assignment to the projection variables
+        formula.add(projected.negative_clause());
+        solutions.push(projected);
+        if projection.is_empty() {
+            break;
+        }
     }
     solutions
 }
 
+/// Find a solution that is minimal with respect to the set of variables assigned `true`,
+/// meaning no variable assigned `true` can be made `false` without falsifying the formula.
+///
+/// A first solution is shrunk by repeatedly solving the formula with the `false` variables kept `false`,
+/// and at least one of the `true` variables made `false`, until that is unsatisfiable.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 OR x2) AND (-x1 OR x3)
+/// x1 = F, x2 = T, x3 = F
+/// ```
+pub fn solve_minimal<S: Solver + ?Sized>(formula: &Formula, solver: &S) -> Certificate {
+    let variables = formula.literals();
+    let mut solution = Solution::new();
+    match solver.solve(&mut formula.clone(), &variables, &mut solution) {
+        Certificate::Satisfiable(model) => minimize(formula, &variables, model, solver),
+        certificate => certificate,
+    }
+}
+
+/// Find all solutions that are minimal with respect to the set of variables assigned `true`.
+/// After each minimal solution is found, all solutions with a superset of its `true` variables are blocked.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 OR x2) AND (-x1 OR x3)
+/// x1 = F, x2 = T, x3 = F
+/// x1 = T, x2 = F, x3 = T
+/// ```
+pub fn solve_all_minimal<S: Solver + ?Sized>(formula: &Formula, solver: &S) -> Vec<Solution> {
+    let mut formula = formula.clone();
+    let mut solutions = Vec::new();
+    let variables = formula.literals();
+    let mut solution = Solution::new();
+
+    while let Certificate::Satisfiable(model) = {
+        solution.reset();
+        solver.solve(&mut formula, &variables, &mut solution)
+    } {
+        // A solution minimal under the blocking clauses is also minimal in the original formula,
+        // as any smaller solution would contain an earlier minimal one and be blocked too
+        let Certificate::Satisfiable(minimal) = minimize(&formula, &variables, model, solver)
+        else {
+            break;
+        };
+        let superset = variables
+            .iter()
+            .filter(|id| minimal.get(**id))
+            .map(|id| Variable::Negative(*id))
+            .collect::<Vec<_>>();
+        solutions.push(minimal);
+        if superset.is_empty() {
+            break;
+        }
+        formula.add(Clause(superset));
+    }
+    solutions
+}
+
+/// Shrink a solution until none of its `true` variables can be made `false`.
+fn minimize<S: Solver + ?Sized>(
+    formula: &Formula,
+    variables: &[Literal],
+    mut model: Solution,
+    solver: &S,
+) -> Certificate {
+    loop {
+        let mut smaller = formula.clone();
+        let mut fewer = Vec::new();
+        for id in variables {
+            if model.get(*id) {
+                fewer.push(Variable::Negative(*id));
+            } else {
+                smaller.add(Clause(vec![Variable::Negative(*id)]));
+            }
+        }
+        if fewer.is_empty() {
+            return Certificate::Satisfiable(model);
+        }
+        smaller.add(Clause(fewer));
+        let mut solution = Solution::new();
+        match solver.solve(&mut smaller, variables, &mut solution) {
+            Certificate::Satisfiable(other) => model = other,
+            Certificate::Unsatisfiable => return Certificate::Satisfiable(model),
+            Certificate::Indeterminate => return Certificate::Indeterminate,
+        }
+    }
+}
+
+/// Find a solution minimizing a weighted sum of the variables assigned `true`,
+/// returning it together with its cost, or `None` if the formula is unsatisfiable.
+/// To maximize the objective instead, negate the weights.
+///
+/// Uses branch-and-bound over the variables, pruning partial assignments that falsify a clause
+/// or cannot improve on the best solution found so far.
+/// Costs saturate at the bounds of `i64` instead of overflowing.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 OR x2) AND (x2 OR x3)
+/// minimize 2*x1 + 3*x2 + 2*x3
+/// x1 = T, x2 = F, x3 = T with cost 4
+/// ```
+pub fn solve_optimize(formula: &Formula, objective: &[(Literal, i64)]) -> Option<(Solution, i64)> {
+    solve_optimize_limited(formula, objective, &Budget::default())
+}
+
+/// Minimize an objective like [`solve_optimize`], giving up when the budget runs out,
+/// counting the search nodes as decisions.
+/// The best solution found by then is returned, which may not be optimal,
+/// and `None` if none was found, so callers check their budget to tell these apart.
+pub fn solve_optimize_limited(
+    formula: &Formula,
+    objective: &[(Literal, i64)],
+    budget: &Budget,
+) -> Option<(Solution, i64)> {
+    // The search only checks the clauses of assigned variables, which never includes an empty clause
+    if formula.iter().any(|clause| clause.0.is_empty()) {
+        return None;
+    }
+    let mut variables = formula.literals();
+    variables.extend(objective.iter().map(|(id, _)| *id));
+    variables.sort();
+    variables.dedup();
+    let mut weights = vec![0i64; variables.len()];
+    for (id, weight) in objective {
+        let index = variables.binary_search(id).unwrap();
+        weights[index] = weights[index].saturating_add(*weight);
+    }
+    let mut search = BranchAndBound {
+        clauses: formula
+            .iter()
+            .map(|clause| {
+                clause
+                    .iter()
+                    .map(|variable| {
+                        let index = variables.binary_search(&variable.id()).unwrap();
+                        (index, variable.is_positive())
+                    })
+                    .collect()
+            })
+            .collect(),
+        occurrences: vec![Vec::new(); variables.len()],
+        // The most the remaining variables can lower the cost, as a suffix sum of negative weights
+        optimistic: vec![0; variables.len() + 1],
+        weights,
+        assignment: vec![None; variables.len()],
+        best: None,
+        budget,
+        stats: Stats::new(),
+        started: Instant::now(),
+    };
+    for (c, clause) in search.clauses.iter().enumerate() {
+        for (index, _) in clause {
+            search.occurrences[*index].push(c);
+        }
+    }
+    for i in (0..variables.len()).rev() {
+        search.optimistic[i] = search.optimistic[i + 1].saturating_add(search.weights[i].min(0));
+    }
+    search.search(0, 0);
+    let (assignment, cost) = search.best?;
+    let mut solution = Solution::new();
+    for (id, value) in variables.iter().zip(assignment) {
+        solution.set(*id, value);
+    }
+    Some((solution, cost))
+}
+
+struct BranchAndBound<'a> {
+    clauses: Vec<Vec<(usize, bool)>>,
+    occurrences: Vec<Vec<usize>>,
+    weights: Vec<i64>,
+    optimistic: Vec<i64>,
+    assignment: Vec<Option<bool>>,
+    best: Option<(Vec<bool>, i64)>,
+    budget: &'a Budget,
+    stats: Stats,
+    started: Instant,
+}
+
+impl BranchAndBound<'_> {
+    fn search(&mut self, index: usize, cost: i64) {
+        if self.budget.exceeded(&self.stats, self.started) {
+            return;
+        }
+        self.stats.decisions += 1;
+        if let Some((_, best)) = &self.best {
+            if cost.saturating_add(self.optimistic[index]) >= *best {
+                return;
+            }
+        }
+        if index == self.assignment.len() {
+            let assignment = self.assignment.iter().map(|value| value.unwrap()).collect();
+            self.best = Some((assignment, cost));
+            return;
+        }
+        // Try the cheaper value first
+        let weight = self.weights[index];
+        for value in [weight < 0, weight >= 0] {
+            self.assignment[index] = Some(value);
+            if !self.falsified(index) {
+                let cost = if value {
+                    cost.saturating_add(weight)
+                } else {
+                    cost
+                };
+                self.search(index + 1, cost);
+            }
+        }
+        self.assignment[index] = None;
+    }
+
+    /// Check if assigning a variable falsified any of the clauses it occurs in.
+    fn falsified(&self, index: usize) -> bool {
+        self.occurrences[index].iter().any(|c| {
+            self.clauses[*c]
+                .iter()
+                .all(|(index, positive)| self.assignment[*index] == Some(!positive))
+        })
+    }
+}
+
+/// Count the models of a formula over its variables, see [`count_models_projected`].
+pub fn count_models(formula: &Formula) -> u128 {
+    count_models_projected(formula, &formula.literals())
+}
+
+/// Count the distinct assignments to the projection variables that can be extended to a model of the formula,
+/// without enumerating them like [`solve_all_projected`].
+/// Projection variables that are not in the formula can have either value.
+///
+/// Counts by a search that propagates unit clauses and splits the clauses into components without shared variables,
+/// whose counts are multiplied, remembering the count of every set of clauses it has seen.
+/// Once a component has no projection variables left, it only matters whether it is satisfiable.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 OR x2) AND (-x1 OR x3)
+/// 4 models, or 2 projected onto x1
+/// ```
+pub fn count_models_projected(formula: &Formula, projection: &[Literal]) -> u128 {
+    count_models_limited(formula, projection, &Budget::default())
+        .expect("The default budget never runs out")
+}
+
+/// Count the models of a formula projected onto some variables like [`count_models_projected`],
+/// giving up with `None` when the budget runs out, counting the search nodes as decisions.
+pub fn count_models_limited(
+    formula: &Formula,
+    projection: &[Literal],
+    budget: &Budget,
+) -> Option<u128> {
+    let mut projection = projection.to_vec();
+    projection.sort();
+    projection.dedup();
+    let mut clauses = Vec::new();
+    for clause in formula.iter() {
+        let mut literals = clause
+            .iter()
+            .map(|variable| (variable.id(), variable.is_positive()))
+            .collect::<Vec<_>>();
+        literals.sort();
+        literals.dedup();
+        // Tautologies are satisfied by every assignment
+        if !literals.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            clauses.push(literals);
+        }
+    }
+    clauses.sort();
+    clauses.dedup();
+    let mut counter = ModelCounter {
+        projection: &projection,
+        cache: HashMap::new(),
+        budget,
+        stats: Stats::new(),
+        started: Instant::now(),
+        stopped: false,
+    };
+    let free = projection.len() - counter.projected(&clauses).len();
+    let count = counter
+        .count(clauses)
+        .saturating_mul(2u128.saturating_pow(free as u32));
+    (!counter.stopped).then_some(count)
+}
+
+/// Clauses of variables with their signs, sorted so equal sets of clauses are equal.
+type Clauses = Vec<Vec<(Literal, bool)>>;
+
+struct ModelCounter<'a> {
+    /// The projection variables, sorted.
+    projection: &'a [Literal],
+    cache: HashMap<Clauses, u128>,
+    budget: &'a Budget,
+    stats: Stats,
+    started: Instant,
+    /// Whether the budget ran out, leaving the counts made since wrong.
+    stopped: bool,
+}
+
+impl ModelCounter<'_> {
+    /// Count the assignments to the projection variables occurring in the clauses that extend to a model of them.
+    fn count(&mut self, clauses: Clauses) -> u128 {
+        if let Some(count) = self.cache.get(&clauses) {
+            return *count;
+        }
+        let count = self.search(&clauses);
+        self.cache.insert(clauses, count);
+        count
+    }
+
+    fn search(&mut self, clauses: &Clauses) -> u128 {
+        if self.stopped || self.budget.exceeded(&self.stats, self.started) {
+            self.stopped = true;
+            return 0;
+        }
+        self.stats.decisions += 1;
+        if clauses.is_empty() {
+            return 1;
+        }
+        if clauses.iter().any(|clause| clause.is_empty()) {
+            return 0;
+        }
+        if let Some(unit) = clauses.iter().find(|clause| clause.len() == 1) {
+            return self.assign(clauses, unit[0]);
+        }
+        let components = components(clauses);
+        if components.len() > 1 {
+            let mut count = 1u128;
+            for component in components {
+                count = count.saturating_mul(self.count(component));
+                if count == 0 {
+                    break;
+                }
+            }
+            return count;
+        }
+        // Branch on the most frequent projection variable
+        let mut occurrences = HashMap::new();
+        for (id, _) in clauses.iter().flatten() {
+            if self.is_projected(*id) {
+                *occurrences.entry(*id).or_insert(0) += 1;
+            }
+        }
+        match occurrences
+            .into_iter()
+            .max_by_key(|(id, count)| (*count, *id))
+        {
+            Some((id, _)) => self
+                .assign(clauses, (id, true))
+                .saturating_add(self.assign(clauses, (id, false))),
+            None => {
+                let id = clauses[0][0].0;
+                if self.assign(clauses, (id, true)) > 0 {
+                    1
+                } else {
+                    self.assign(clauses, (id, false))
+                }
+            }
+        }
+    }
+
+    /// Count the assignments where a variable has the given value, where the projection variables
+    /// that no longer occur in the simplified clauses can have either value.
+    fn assign(&mut self, clauses: &Clauses, (id, value): (Literal, bool)) -> u128 {
+        let mut assigned = clauses
+            .iter()
+            .filter(|clause| !clause.contains(&(id, value)))
+            .map(|clause| {
+                clause
+                    .iter()
+                    .copied()
+                    .filter(|(x, _)| *x != id)
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Clauses>();
+        assigned.sort();
+        assigned.dedup();
+        let remaining = self.projected(clauses).len() - usize::from(self.is_projected(id));
+        let free = remaining - self.projected(&assigned).len();
+        self.count(assigned)
+            .saturating_mul(2u128.saturating_pow(free as u32))
+    }
+
+    fn is_projected(&self, id: Literal) -> bool {
+        self.projection.binary_search(&id).is_ok()
+    }
+
+    /// Get the projection variables occurring in the clauses.
+    fn projected(&self, clauses: &Clauses) -> Vec<Literal> {
+        let mut variables = clauses
+            .iter()
+            .flatten()
+            .map(|(id, _)| *id)
+            .filter(|id| self.is_projected(*id))
+            .collect::<Vec<_>>();
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+}
+
+/// Split the clauses into groups that share no variables with each other.
+fn components(clauses: &Clauses) -> Vec<Clauses> {
+    let mut occurrences: HashMap<Literal, Vec<usize>> = HashMap::new();
+    for (c, clause) in clauses.iter().enumerate() {
+        for (id, _) in clause {
+            occurrences.entry(*id).or_default().push(c);
+        }
+    }
+    let mut seen = vec![false; clauses.len()];
+    let mut components = Vec::new();
+    for start in 0..clauses.len() {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut component = vec![start];
+        let mut next = 0;
+        while let Some(c) = component.get(next).copied() {
+            next += 1;
+            for (id, _) in &clauses[c] {
+                for other in &occurrences[id] {
+                    if !seen[*other] {
+                        seen[*other] = true;
+                        component.push(*other);
+                    }
+                }
+            }
+        }
+        // Keep the clauses in order, so equal components are equal
+        component.sort();
+        components.push(component.into_iter().map(|c| clauses[c].clone()).collect());
+    }
+    components
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{printer::PrintStyle, solvers, types::Variable};
+    use crate::{printer::PrintStyle, solvers};
 
     use super::*;
 
     fn solvers() -> Vec<impl Solver> {
-        vec![solvers::Dfs]
+        vec![solvers::Dfs::default()]
     }
 
     #[test]
@@ -151,4 +737,180 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_solve_all_projected() {
+        // (x1 OR x2) AND (-x1 OR x3)
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+        ]
+        .into();
+        for solver in solvers() {
+            let solutions = solve_all_projected(&formula, &[1], &solver);
+            assert_eq!(solutions.len(), 2);
+            assert!(solutions.contains(&[(1, false)][..].into()));
+            assert!(solutions.contains(&[(1, true)][..].into()));
+            let solutions = solve_all_projected(&formula, &[2, 3], &solver);
+            assert_eq!(solutions.len(), 3);
+            assert!(!solutions.contains(&[(2, false), (3, false)][..].into()));
+        }
+    }
+
+    #[test]
+    fn test_solve_n() {
+        // x1 OR x2 OR x3
+        let formula: Formula = vec![vec![
+            Variable::Positive(1),
+            Variable::Positive(2),
+            Variable::Positive(3),
+        ]]
+        .into();
+        for solver in solvers() {
+            let all = solve_all(&formula, &solver);
+            assert_eq!(all.len(), 7);
+            for n in 0..7 {
+                let (solutions, more) = solve_n(&formula, n, &solver);
+                assert_eq!(solutions, all[..n]);
+                assert!(more);
+            }
+            assert_eq!(solve_n(&formula, 7, &solver), (all.clone(), false));
+            assert_eq!(solve_n(&formula, 10, &solver), (all, false));
+        }
+        let unsatisfiable: Formula =
+            vec![vec![Variable::Positive(1)], vec![Variable::Negative(1)]].into();
+        assert_eq!(
+            solve_n(&unsatisfiable, 0, &solvers::Dfs::default()),
+            (vec![], false)
+        );
+    }
+
+    #[test]
+    fn test_count_models() {
+        // (x1 OR x2) AND (-x1 OR x3)
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+        ]
+        .into();
+        assert_eq!(count_models(&formula), 4);
+        assert_eq!(count_models_projected(&formula, &[1]), 2);
+        assert_eq!(count_models_projected(&formula, &[2, 3]), 3);
+        // x4 is not in the formula
+        assert_eq!(count_models_projected(&formula, &[1, 4]), 4);
+        assert_eq!(count_models(&Formula::new()), 1);
+        let unsatisfiable: Formula =
+            vec![vec![Variable::Positive(1)], vec![Variable::Negative(1)]].into();
+        assert_eq!(count_models(&unsatisfiable), 0);
+        for seed in 0..10 {
+            let formula = crate::generate::random_ksat(8, 12, 3, seed);
+            let models = solve_all(&formula, &solvers::Dfs::default());
+            assert_eq!(count_models(&formula), models.len() as u128);
+            let projected = solve_all_projected(&formula, &[1, 2, 3], &solvers::Dfs::default());
+            assert_eq!(
+                count_models_projected(&formula, &[1, 2, 3]),
+                projected.len() as u128
+            );
+        }
+    }
+
+    #[test]
+    fn test_solve_minimal() {
+        // (x1 OR x2 OR x3) AND (-x1 OR x2) AND (x3 OR x4)
+        let formula: Formula = vec![
+            vec![
+                Variable::Positive(1),
+                Variable::Positive(2),
+                Variable::Positive(3),
+            ],
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Positive(3), Variable::Positive(4)],
+        ]
+        .into();
+        for solver in solvers() {
+            let Certificate::Satisfiable(minimal) = solve_minimal(&formula, &solver) else {
+                panic!("Expected a solution");
+            };
+            assert!(minimal.satisfy(&formula));
+            let solutions = solve_all_minimal(&formula, &solver);
+            assert_eq!(solutions.len(), 2);
+            assert!(solutions.contains(&minimal));
+            assert!(solutions.contains(&[(1, false), (2, false), (3, true), (4, false)][..].into()));
+            assert!(solutions.contains(&[(1, false), (2, true), (3, false), (4, true)][..].into()));
+        }
+    }
+
+    #[test]
+    fn test_solve_all_cubes() {
+        // (x1 OR x2 OR x3 OR x4)
+        let formula: Formula = vec![vec![
+            Variable::Positive(1),
+            Variable::Positive(2),
+            Variable::Positive(3),
+            Variable::Positive(4),
+        ]]
+        .into();
+        for solver in solvers() {
+            let cubes = solve_all_cubes(&formula, &solver);
+            assert!(cubes.len() <= 4);
+            let solutions = solve_all(&formula, &solver);
+            assert_eq!(solutions.len(), 15);
+            for (i, solution) in solutions.iter().enumerate() {
+                assert!(solution.satisfy(&formula));
+                assert!(!solutions[i + 1..].contains(solution));
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_optimize() {
+        // (x1 OR x2) AND (x2 OR x3)
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Positive(2), Variable::Positive(3)],
+        ]
+        .into();
+        let (solution, cost) = solve_optimize(&formula, &[(1, 2), (2, 3), (3, 2)]).unwrap();
+        assert_eq!(cost, 3);
+        assert_eq!(solution, [(1, false), (2, true), (3, false)][..].into());
+        let (solution, cost) = solve_optimize(&formula, &[(1, 2), (2, 5), (3, 2)]).unwrap();
+        assert_eq!(cost, 4);
+        assert_eq!(solution, [(1, true), (2, false), (3, true)][..].into());
+        // Maximize x1 + x2 + x3 + x4
+        let (solution, cost) =
+            solve_optimize(&formula, &[(1, -1), (2, -1), (3, -1), (4, -1)]).unwrap();
+        assert_eq!(cost, -4);
+        assert!(solution.satisfy(&formula));
+        // Weights summing past the bounds of i64 saturate
+        let (_, cost) = solve_optimize(&formula, &[(1, i64::MAX), (2, i64::MAX), (3, 1)]).unwrap();
+        assert_eq!(cost, i64::MAX);
+        let (_, cost) = solve_optimize(&formula, &[(1, i64::MIN), (2, -1), (2, i64::MIN)]).unwrap();
+        assert_eq!(cost, i64::MIN);
+
+        let formula: Formula =
+            vec![vec![Variable::Positive(1)], vec![Variable::Negative(1)]].into();
+        assert_eq!(solve_optimize(&formula, &[(1, 1)]), None);
+    }
+
+    #[test]
+    fn test_limited_optimize_and_count() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Positive(2), Variable::Positive(3)],
+        ]
+        .into();
+        let cancellation = solvers::CancellationToken::new();
+        let budget = Budget {
+            cancellation: Some(cancellation.clone()),
+            ..Default::default()
+        };
+        let (_, cost) =
+            solve_optimize_limited(&formula, &[(1, 2), (2, 3), (3, 2)], &budget).unwrap();
+        assert_eq!(cost, 3);
+        assert_eq!(count_models_limited(&formula, &[1, 2, 3], &budget), Some(5));
+        // A cancelled solve gives up before finding anything
+        cancellation.cancel();
+        assert_eq!(solve_optimize_limited(&formula, &[(1, 2)], &budget), None);
+        assert_eq!(count_models_limited(&formula, &[1, 2, 3], &budget), None);
+    }
 }
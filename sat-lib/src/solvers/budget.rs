@@ -0,0 +1,115 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::Stats;
+
+/// Limits on the work a solver may do before giving up with `Certificate::Indeterminate`.
+/// A limit of `None` means unlimited, so the default budget never runs out.
+/// The budget is checked against the statistics given to the solver,
+/// so reusing the statistics for several solves shares the budget between them.
+///
+/// ## Examples
+/// ```plaintext
+/// Budget { nodes: Some(1000), time: Some(Duration::from_secs(1)), ..Default::default() }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Budget {
+    /// Search nodes visited, or flips made by local search solvers, counted as decisions.
+    pub nodes: Option<u64>,
+    pub conflicts: Option<u64>,
+    /// Variables assigned by unit propagation, which the CDCL solver does.
+    pub propagations: Option<u64>,
+    pub time: Option<Duration>,
+    /// Give up as soon as the token is cancelled, from any thread.
+    pub cancellation: Option<CancellationToken>,
+}
+
+/// A shared flag for aborting a running solve from another thread, like a Ctrl-C handler.
+/// Clones of the token share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every solve using this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Clear the flag, so the token can be used for another solve.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+impl Budget {
+    /// Check whether a solve started at the given time has used up the budget.
+    pub(crate) fn exceeded(&self, stats: &Stats, started: Instant) -> bool {
+        let over = |used: u64, limit: Option<u64>| limit.is_some_and(|limit| used > limit);
+        over(stats.decisions, self.nodes)
+            || over(stats.conflicts, self.conflicts)
+            || over(stats.propagations, self.propagations)
+            || self
+                .time
+                .is_some_and(|time| stats.time + started.elapsed() > time)
+            || self
+                .cancellation
+                .as_ref()
+                .is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use crate::{
+        solvers::{Dfs, Solver},
+        types::{Certificate, Formula, Solution, Variable},
+    };
+
+    use super::*;
+
+    #[test]
+    fn test_cancellation() {
+        // (x1 OR x2 OR ... OR x40) AND -x1 AND ... AND -x40 takes 2^40 nodes to refute
+        let mut clauses = vec![(1..=40).map(Variable::Positive).collect::<Vec<_>>()];
+        clauses.extend((1..=40).map(|id| vec![Variable::Negative(id)]));
+        let mut formula: Formula = clauses.into();
+        let token = CancellationToken::new();
+        let budget = Budget {
+            cancellation: Some(token.clone()),
+            ..Default::default()
+        };
+        let handle = thread::spawn(move || {
+            let variables = formula.literals();
+            let mut solution = Solution::new();
+            Dfs::default().solve_limited(
+                &mut formula,
+                &variables,
+                &mut solution,
+                &budget,
+                &mut Stats::new(),
+                &mut (),
+            )
+        });
+        thread::sleep(Duration::from_millis(50));
+        token.cancel();
+        assert_eq!(handle.join().unwrap(), Certificate::Indeterminate);
+        assert!(token.is_cancelled());
+        token.reset();
+        assert!(!token.is_cancelled());
+    }
+}
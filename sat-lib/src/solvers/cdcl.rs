@@ -0,0 +1,502 @@
+use std::{collections::HashMap, mem::size_of, time::Instant};
+
+use crate::{
+    preprocessors::vivify_clause,
+    rng::Rng,
+    trail::{Assignment, Trail},
+    types::{Certificate, Formula, Literal, Solution, Variable},
+};
+
+use super::{Budget, Heuristic, Observer, Polarity, Restart, Solver, SolverConfig, Stats};
+
+/// A conflict-driven clause learning (CDCL) solver for the SAT problem. \
+/// The solver propagates the unit clauses after each decision, and on a conflict learns a clause
+/// by first-UIP analysis on the [`Trail`], then backjumps to the level where the learned clause is unit.
+/// Variables are decided in the order given by its heuristic, which for LRB changes with every learned clause,
+/// with the value they had last, starting from the value given by its polarity.
+/// With chronological backtracking, long backjumps are replaced by backtracking to the level below the conflict,
+/// keeping the assignments in between, and the learned clause is then asserted out of order on the trail.
+/// With learned vivification, each learned clause is first shortened by [`vivify_clause`] on the clauses so far.
+#[derive(Clone, Debug)]
+pub struct Cdcl {
+    pub heuristic: Heuristic,
+    pub polarity: Polarity,
+    pub restart: Restart,
+    pub chronological: Option<usize>,
+    pub learned_vivification: bool,
+    pub seed: u64,
+}
+
+impl Cdcl {
+    pub fn new(config: &SolverConfig) -> Self {
+        Self {
+            heuristic: config.heuristic,
+            polarity: config.polarity,
+            restart: config.restart,
+            chronological: config.chronological,
+            learned_vivification: config.learned_vivification,
+            seed: config.seed,
+        }
+    }
+}
+
+impl Default for Cdcl {
+    fn default() -> Self {
+        Self::new(&SolverConfig::default())
+    }
+}
+
+impl Solver for Cdcl {
+    fn solve_limited(
+        &self,
+        formula: &mut Formula,
+        variables: &[Literal],
+        solution: &mut Solution,
+        budget: &Budget,
+        stats: &mut Stats,
+        observer: &mut dyn Observer,
+    ) -> Certificate {
+        let started = Instant::now();
+        let certificate = self.search(
+            formula, variables, solution, budget, stats, started, observer,
+        );
+        stats.time += started.elapsed();
+        certificate
+    }
+}
+
+impl Cdcl {
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        formula: &Formula,
+        variables: &[Literal],
+        solution: &mut Solution,
+        budget: &Budget,
+        stats: &mut Stats,
+        started: Instant,
+        observer: &mut dyn Observer,
+    ) -> Certificate {
+        let mut rng = Rng::new(self.seed);
+        let mut order = formula.literals();
+        for id in variables {
+            if !order.contains(id) {
+                order.push(*id);
+            }
+        }
+        self.heuristic.order(formula, &mut order, &mut rng);
+        let phases = self.polarity.phases(formula, &order, &mut rng);
+        let mut phases = order.iter().copied().zip(phases).collect::<HashMap<_, _>>();
+        // The learned clauses are added to a copy, so the antecedents of the trail index into it
+        let mut clauses = formula.clone();
+        let mut trail = Trail::new();
+        let mut lrb = (self.heuristic == Heuristic::Lrb).then(Lrb::new);
+        // The assignments on the trail that LRB has seen made
+        let mut assigned = 0;
+        let mut restarts = 0;
+        let mut conflicts = 0;
+        loop {
+            if budget.exceeded(stats, started) {
+                return Certificate::Indeterminate;
+            }
+            let before = trail.len();
+            let propagated = trail.propagate(&clauses);
+            stats.propagations += (trail.len() - before) as u64;
+            if let Some(lrb) = &mut lrb {
+                lrb.assigned(trail.iter().skip(assigned));
+                assigned = trail.len();
+            }
+            if propagated.is_err() {
+                stats.conflicts += 1;
+                observer.on_conflict();
+                let (learned, resolved) = trail.analyze(&clauses).expect("There is a conflict");
+                observer.on_analyze(&trail, &clauses);
+                if let Some(lrb) = &mut lrb {
+                    lrb.learned(learned.iter().map(Variable::id).chain(resolved));
+                }
+                let learned = if self.learned_vivification {
+                    vivify_clause(&clauses, &learned)
+                } else {
+                    learned
+                };
+                if learned.is_empty() {
+                    return Certificate::Unsatisfiable;
+                }
+                let mut levels = learned
+                    .iter()
+                    .filter_map(|variable| trail.get(variable.id()))
+                    .map(|assignment| assignment.level)
+                    .collect::<Vec<_>>();
+                levels.sort_unstable_by(|a, b| b.cmp(a));
+                // The clause is unit at the level of its second highest literal, unless vivification left
+                // no single literal at the highest level, then it has two unassigned below that level
+                let top = levels[0];
+                let level = match levels[..] {
+                    [_, second, ..] if second == top => top - 1,
+                    [_, second, ..] => second,
+                    _ => 0,
+                };
+                let level = match self.chronological {
+                    Some(threshold) if top - level > threshold => top - 1,
+                    _ => level,
+                };
+                for assignment in trail.iter().filter(|assignment| assignment.level > level) {
+                    phases.insert(assignment.variable.id(), assignment.variable.is_positive());
+                    if let Some(lrb) = &mut lrb {
+                        lrb.unassigned(assignment.variable.id());
+                    }
+                }
+                trail.backtrack(level);
+                assigned = trail.len();
+                clauses.add(learned);
+                stats.learned_clauses += 1;
+                let literals = clauses.iter().map(|clause| clause.len()).sum::<usize>();
+                stats.memory((literals + trail.len()) * size_of::<Variable>());
+                conflicts += 1;
+                if self
+                    .restart
+                    .interval(restarts)
+                    .is_some_and(|interval| conflicts >= interval)
+                {
+                    if let Some(lrb) = &mut lrb {
+                        for assignment in trail.iter().filter(|assignment| assignment.level > 0) {
+                            lrb.unassigned(assignment.variable.id());
+                        }
+                    }
+                    trail.backtrack(0);
+                    assigned = trail.len();
+                    restarts += 1;
+                    conflicts = 0;
+                    stats.restarts += 1;
+                    observer.on_restart();
+                }
+                continue;
+            }
+            let mut unassigned = order
+                .iter()
+                .copied()
+                .filter(|id| trail.value(*id).is_none());
+            let next = match &lrb {
+                Some(lrb) => lrb.best(unassigned),
+                None => unassigned.next(),
+            };
+            let Some(id) = next else {
+                for id in variables {
+                    solution.set(*id, trail.value(*id).unwrap_or_default());
+                }
+                observer.on_solution(solution);
+                return Certificate::Satisfiable(solution.clone());
+            };
+            let variable = if phases[&id] {
+                Variable::Positive(id)
+            } else {
+                Variable::Negative(id)
+            };
+            stats.decisions += 1;
+            observer.on_decision(variable);
+            trail.decide(variable);
+        }
+    }
+}
+
+/// The scores of learning-rate branching, where a variable's learning rate over an assignment is the share of
+/// the clauses learned while it was assigned that it took part in, by being in the clause or resolved away.
+/// Its score is an exponential moving average of its learning rates, whose step size decays with each learned clause.
+struct Lrb {
+    step: f64,
+    learned: usize,
+    scores: HashMap<Literal, f64>,
+    /// The number of learned clauses when each assigned variable was assigned.
+    assigned: HashMap<Literal, usize>,
+    participated: HashMap<Literal, usize>,
+}
+
+impl Lrb {
+    const STEP: f64 = 0.4;
+    const MIN_STEP: f64 = 0.06;
+    const STEP_DECAY: f64 = 1e-6;
+
+    fn new() -> Self {
+        Self {
+            step: Self::STEP,
+            learned: 0,
+            scores: HashMap::new(),
+            assigned: HashMap::new(),
+            participated: HashMap::new(),
+        }
+    }
+
+    fn assigned<'a>(&mut self, assignments: impl Iterator<Item = &'a Assignment>) {
+        for assignment in assignments {
+            let id = assignment.variable.id();
+            self.assigned.insert(id, self.learned);
+            self.participated.insert(id, 0);
+        }
+    }
+
+    fn learned(&mut self, participants: impl Iterator<Item = Literal>) {
+        for id in participants {
+            *self.participated.entry(id).or_default() += 1;
+        }
+        self.learned += 1;
+        self.step = (self.step - Self::STEP_DECAY).max(Self::MIN_STEP);
+    }
+
+    fn unassigned(&mut self, id: Literal) {
+        let Some(assigned) = self.assigned.remove(&id) else {
+            return;
+        };
+        let interval = self.learned - assigned;
+        if interval > 0 {
+            let rate = self.participated[&id] as f64 / interval as f64;
+            let score = self.scores.entry(id).or_default();
+            *score = (1.0 - self.step) * *score + self.step * rate;
+        }
+    }
+
+    /// Get the variable with the highest score, the first one on ties.
+    fn best(&self, variables: impl Iterator<Item = Literal>) -> Option<Literal> {
+        let mut best: Option<(Literal, f64)> = None;
+        for id in variables {
+            let score = self.scores.get(&id).copied().unwrap_or_default();
+            if best.is_none_or(|(_, highest)| score > highest) {
+                best = Some((id, score));
+            }
+        }
+        best.map(|(id, _)| id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{generate::random_ksat, solver::solve_all, solvers::Dfs, types::Clause};
+
+    use super::*;
+
+    #[test]
+    fn test_cdcl() {
+        // (x1 OR x2) AND (-x1 OR x2) AND (-x2 OR x3) AND (-x3 OR -x1)
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Negative(2), Variable::Positive(3)],
+            vec![Variable::Negative(3), Variable::Negative(1)],
+        ]
+        .into();
+        let Certificate::Satisfiable(solution) =
+            Cdcl::default().solve(&mut formula, &[1, 2, 3], &mut Solution::new())
+        else {
+            panic!("Expected a solution");
+        };
+        assert!(solution.satisfy(&formula));
+        formula.add(Clause(vec![Variable::Positive(1)]));
+        assert_eq!(
+            Cdcl::default().solve(&mut formula, &[1, 2, 3], &mut Solution::new()),
+            Certificate::Unsatisfiable
+        );
+    }
+
+    #[test]
+    fn test_cdcl_learns() {
+        // Every assignment of x1, x2 and x3 falsifies one of the 8 clauses
+        let mut clauses = Vec::new();
+        for signs in 0..8 {
+            clauses.push(
+                (1..=3)
+                    .map(|id| {
+                        if signs >> (id - 1) & 1 == 1 {
+                            Variable::Positive(id)
+                        } else {
+                            Variable::Negative(id)
+                        }
+                    })
+                    .collect::<Vec<_>>(),
+            );
+        }
+        let mut formula: Formula = clauses.into();
+        let mut stats = Stats::new();
+        let certificate = Cdcl::default().solve_limited(
+            &mut formula,
+            &[1, 2, 3],
+            &mut Solution::new(),
+            &Budget::default(),
+            &mut stats,
+            &mut (),
+        );
+        assert_eq!(certificate, Certificate::Unsatisfiable);
+        assert!(stats.learned_clauses > 0);
+        assert_eq!(stats.learned_clauses, stats.conflicts - 1);
+        assert!(stats.propagations > 0);
+        let budget = Budget {
+            propagations: Some(0),
+            ..Default::default()
+        };
+        let certificate = Cdcl::default().solve_limited(
+            &mut formula,
+            &[1, 2, 3],
+            &mut Solution::new(),
+            &budget,
+            &mut Stats::new(),
+            &mut (),
+        );
+        assert_eq!(certificate, Certificate::Indeterminate);
+    }
+
+    #[test]
+    fn test_cdcl_agrees_with_dfs() {
+        for seed in 0..40 {
+            let formula = random_ksat(8, 34, 3, seed);
+            let variables = formula.literals();
+            let expected = !solve_all(&formula, &Dfs::default()).is_empty();
+            for learned_vivification in [false, true] {
+                let cdcl = Cdcl::new(&SolverConfig {
+                    restart: Restart::Fixed(3),
+                    learned_vivification,
+                    ..Default::default()
+                });
+                match cdcl.solve(&mut formula.clone(), &variables, &mut Solution::new()) {
+                    Certificate::Satisfiable(solution) => {
+                        assert!(expected);
+                        assert!(solution.satisfy(&formula));
+                    }
+                    certificate => {
+                        assert_eq!(certificate, Certificate::Unsatisfiable);
+                        assert!(!expected);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cdcl_chronological() {
+        // Backtracking chronologically, always or only past a threshold, agrees with backjumping
+        for seed in 0..40 {
+            let formula = random_ksat(10, 43, 3, seed);
+            let variables = formula.literals();
+            let solve = |chronological| {
+                let cdcl = Cdcl::new(&SolverConfig {
+                    chronological,
+                    restart: Restart::Fixed(5),
+                    ..Default::default()
+                });
+                match cdcl.solve(&mut formula.clone(), &variables, &mut Solution::new()) {
+                    Certificate::Satisfiable(solution) => {
+                        assert!(solution.satisfy(&formula));
+                        true
+                    }
+                    certificate => {
+                        assert_eq!(certificate, Certificate::Unsatisfiable);
+                        false
+                    }
+                }
+            };
+            let expected = solve(None);
+            assert_eq!(solve(Some(0)), expected);
+            assert_eq!(solve(Some(1)), expected);
+        }
+    }
+
+    #[test]
+    fn test_cdcl_lrb() {
+        // On random 3-SAT at the threshold, LRB agrees with the input order and needs fewer conflicts
+        let solve = |formula: &Formula, heuristic| {
+            let cdcl = Cdcl::new(&SolverConfig {
+                heuristic,
+                restart: Restart::Luby(50),
+                ..Default::default()
+            });
+            let mut stats = Stats::new();
+            let certificate = cdcl.solve_limited(
+                &mut formula.clone(),
+                &formula.literals(),
+                &mut Solution::new(),
+                &Budget::default(),
+                &mut stats,
+                &mut (),
+            );
+            if let Certificate::Satisfiable(solution) = &certificate {
+                assert!(solution.satisfy(formula));
+            }
+            (certificate != Certificate::Unsatisfiable, stats.conflicts)
+        };
+        let mut conflicts = (0, 0);
+        for seed in 0..20 {
+            let formula = random_ksat(24, 102, 3, seed);
+            let (input, input_conflicts) = solve(&formula, Heuristic::Input);
+            let (lrb, lrb_conflicts) = solve(&formula, Heuristic::Lrb);
+            assert_eq!(lrb, input);
+            conflicts.0 += input_conflicts;
+            conflicts.1 += lrb_conflicts;
+        }
+        assert!(conflicts.1 < conflicts.0);
+    }
+
+    #[test]
+    fn test_cdcl_learned_vivification() {
+        // Deciding -x1 and -x2 conflicts on x3 and learns (x2 OR x1), but (x2 OR -x1) makes x1 redundant,
+        // and then the second conflict on x4 and x5 is analyzed with the learned clause
+        let formula: Formula = vec![
+            vec![Variable::Positive(2), Variable::Negative(1)],
+            vec![
+                Variable::Positive(1),
+                Variable::Positive(2),
+                Variable::Positive(3),
+            ],
+            vec![
+                Variable::Positive(1),
+                Variable::Positive(2),
+                Variable::Negative(3),
+            ],
+            vec![
+                Variable::Negative(2),
+                Variable::Positive(4),
+                Variable::Positive(5),
+            ],
+            vec![
+                Variable::Negative(2),
+                Variable::Positive(4),
+                Variable::Negative(5),
+            ],
+            vec![
+                Variable::Negative(2),
+                Variable::Negative(4),
+                Variable::Positive(5),
+            ],
+            vec![
+                Variable::Negative(2),
+                Variable::Negative(4),
+                Variable::Negative(5),
+            ],
+        ]
+        .into();
+        struct Learned(Vec<Clause>);
+        impl Observer for Learned {
+            fn on_analyze(&mut self, _trail: &Trail, clauses: &Formula) {
+                self.0 = clauses.iter().skip(7).cloned().collect();
+            }
+        }
+        let learned = |learned_vivification| {
+            let cdcl = Cdcl::new(&SolverConfig {
+                learned_vivification,
+                ..Default::default()
+            });
+            let mut observer = Learned(Vec::new());
+            let certificate = cdcl.solve_limited(
+                &mut formula.clone(),
+                &formula.literals(),
+                &mut Solution::new(),
+                &Budget::default(),
+                &mut Stats::new(),
+                &mut observer,
+            );
+            assert_eq!(certificate, Certificate::Unsatisfiable);
+            observer.0
+        };
+        assert_eq!(
+            learned(false)[0],
+            Clause(vec![Variable::Positive(2), Variable::Positive(1)])
+        );
+        assert_eq!(learned(true)[0], Clause(vec![Variable::Positive(2)]));
+    }
+}
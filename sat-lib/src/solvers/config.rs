@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+
+use crate::{
+    preprocessors::{
+        EquivalentLiterals, Preprocessor, Subsumption, SymmetryBreaking, VariableElimination,
+        Vivification,
+    },
+    rng::Rng,
+    types::{Formula, Literal},
+};
+
+/// Settings shared by all solvers, passed to their constructors.
+/// Solvers ignore the settings that don't apply to their algorithm.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SolverConfig {
+    /// The seed for solvers using randomness, the same seed and formula always give the same run.
+    pub seed: u64,
+    pub restart: Restart,
+    pub heuristic: Heuristic,
+    pub polarity: Polarity,
+    /// Backtrack chronologically, to the level below a conflict, when backjumping would undo more than this many levels.
+    /// `Some(0)` always backtracks chronologically, and `None` always backjumps.
+    pub chronological: Option<usize>,
+    /// The number of flips before local search solvers give up.
+    pub max_flips: usize,
+    pub equivalences: bool,
+    pub subsumption: bool,
+    pub vivification: bool,
+    /// Shorten each learned clause by [`vivify_clause`](crate::preprocessors::vivify_clause) before adding it.
+    pub learned_vivification: bool,
+    pub elimination: bool,
+    pub symmetry_breaking: bool,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            seed: 1,
+            restart: Restart::Never,
+            heuristic: Heuristic::Input,
+            polarity: Polarity::False,
+            chronological: None,
+            max_flips: 100_000,
+            equivalences: false,
+            subsumption: false,
+            vivification: false,
+            learned_vivification: false,
+            elimination: false,
+            symmetry_breaking: false,
+        }
+    }
+}
+
+impl SolverConfig {
+    /// Get the enabled preprocessors, in the order they should run.
+    pub fn preprocessors(&self) -> Vec<Box<dyn Preprocessor>> {
+        let mut preprocessors: Vec<Box<dyn Preprocessor>> = Vec::new();
+        if self.equivalences {
+            preprocessors.push(Box::new(EquivalentLiterals));
+        }
+        if self.subsumption {
+            preprocessors.push(Box::new(Subsumption));
+        }
+        if self.vivification {
+            preprocessors.push(Box::new(Vivification));
+        }
+        if self.elimination {
+            preprocessors.push(Box::new(VariableElimination::default()));
+        }
+        if self.symmetry_breaking {
+            preprocessors.push(Box::new(SymmetryBreaking::default()));
+        }
+        preprocessors
+    }
+}
+
+/// When a solver should give up its current search and start over.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Restart {
+    Never,
+    /// Restart every `n` steps.
+    Fixed(usize),
+    /// Restart after `n` times the terms of the Luby sequence 1, 1, 2, 1, 1, 2, 4, ... steps.
+    Luby(usize),
+}
+
+impl Restart {
+    /// Get the number of steps before the restart following the given number of restarts,
+    /// or `None` if the solver should never restart.
+    pub fn interval(&self, restarts: usize) -> Option<usize> {
+        match self {
+            Restart::Never => None,
+            Restart::Fixed(n) => Some(*n),
+            Restart::Luby(n) => Some(n * luby(restarts + 1)),
+        }
+    }
+}
+
+/// Get the i:th term of the Luby sequence, starting from 1.
+fn luby(mut i: usize) -> usize {
+    loop {
+        // Find the smallest k such that i <= 2^k - 1
+        let mut k = 1;
+        while (1 << k) - 1 < i {
+            k += 1;
+        }
+        if i == (1 << k) - 1 {
+            return 1 << (k - 1);
+        }
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+
+/// The order in which a solver decides variables.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Heuristic {
+    /// The order the variables are given in.
+    Input,
+    /// Variables occurring in the most clauses first.
+    Occurrences,
+    /// A random order from the seed.
+    Random,
+    /// Learning-rate branching, deciding the variable that took part in the most learned clauses while it was assigned,
+    /// as a moving average over its assignments.
+    /// Variables are first decided by occurrences, which solvers that learn no clauses keep to,
+    /// so the DFS solver decides by [`Heuristic::Occurrences`] instead.
+    Lrb,
+}
+
+impl Heuristic {
+    /// Sort variables in the order they should be decided.
+    pub(crate) fn order(&self, formula: &Formula, variables: &mut [Literal], rng: &mut Rng) {
+        match self {
+            Heuristic::Input => (),
+            Heuristic::Occurrences | Heuristic::Lrb => variables.sort_by_key(|id| {
+                std::cmp::Reverse(
+                    formula
+                        .iter()
+                        .filter(|clause| clause.iter().any(|variable| variable.id() == *id))
+                        .count(),
+                )
+            }),
+            Heuristic::Random => {
+                // Fisher-Yates shuffle
+                for i in (1..variables.len()).rev() {
+                    variables.swap(i, rng.below(i + 1));
+                }
+            }
+        }
+    }
+}
+
+/// The value a solver tries first when deciding a variable.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Polarity {
+    False,
+    True,
+    /// A random value from the seed.
+    Random,
+    /// The value satisfying the most clauses, weighing shorter clauses exponentially higher.
+    JeroslowWang,
+}
+
+impl Polarity {
+    /// Get the value to try first for each variable.
+    pub(crate) fn phases(
+        &self,
+        formula: &Formula,
+        variables: &[Literal],
+        rng: &mut Rng,
+    ) -> Vec<bool> {
+        match self {
+            Polarity::False => vec![false; variables.len()],
+            Polarity::True => vec![true; variables.len()],
+            Polarity::Random => variables.iter().map(|_| rng.bool()).collect(),
+            Polarity::JeroslowWang => {
+                // J(l) is the sum of 2^-|c| over the clauses c containing l
+                let mut scores = HashMap::new();
+                for clause in formula.iter() {
+                    let weight = 0.5f64.powi(clause.len() as i32);
+                    for variable in clause.iter() {
+                        let score = scores.entry(variable.id()).or_insert((0.0, 0.0));
+                        if variable.is_positive() {
+                            score.0 += weight;
+                        } else {
+                            score.1 += weight;
+                        }
+                    }
+                }
+                variables
+                    .iter()
+                    .map(|id| {
+                        scores
+                            .get(id)
+                            .is_some_and(|(positive, negative)| positive > negative)
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_luby() {
+        let sequence = (1..=15).map(luby).collect::<Vec<_>>();
+        assert_eq!(sequence, [1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+        assert_eq!(Restart::Luby(10).interval(2), Some(20));
+    }
+}
@@ -1,40 +1,296 @@
-use crate::types::{Certificate, Formula, Literal, Solution};
+use std::{mem::size_of, time::Instant};
 
-use super::Solver;
+use crate::{
+    rng::Rng,
+    types::{Certificate, Domain, Formula, Lit, Literal, Solution, Variable},
+};
 
-pub fn brute_force(formula: &Formula, literals: &[Literal], solution: &mut Solution) -> bool {
-    if literals.is_empty() {
-        return solution.satisfy(formula);
+use super::{Budget, Heuristic, Observer, Polarity, Solver, SolverConfig, Stats};
+
+/// Try both values of each literal in order, starting with the given literal.
+/// The literals, values and clauses are packed by the position of each variable in its [`Domain`],
+/// and each literal is given with the identifier of its variable for the observer.
+/// Returns `None` if the budget ran out before the search was done.
+pub fn brute_force(
+    clauses: &[Vec<Lit>],
+    literals: &[(Literal, Lit)],
+    values: &mut [bool],
+    budget: &Budget,
+    stats: &mut Stats,
+    started: Instant,
+    observer: &mut dyn Observer,
+) -> Option<bool> {
+    if budget.exceeded(stats, started) {
+        return None;
     }
-    let lit = literals[0];
-    // First, set the literal to false
-    solution.set(lit, false);
-    if brute_force(formula, &literals[1..], solution) {
-        return true;
+    if literals.is_empty() {
+        if clauses
+            .iter()
+            .all(|clause| clause.iter().any(|lit| lit.eval(values)))
+        {
+            return Some(true);
+        }
+        stats.conflicts += 1;
+        observer.on_conflict();
+        return Some(false);
     }
-    // Then, set the literal to true
-    solution.set(lit, true);
-    if brute_force(formula, &literals[1..], solution) {
-        return true;
+    let (id, lit) = literals[0];
+    // First, set the literal to its preferred value, then to the opposite
+    for value in [lit.is_positive(), !lit.is_positive()] {
+        values[lit.var() as usize] = value;
+        stats.decisions += 1;
+        observer.on_decision(if value {
+            Variable::Positive(id)
+        } else {
+            Variable::Negative(id)
+        });
+        if brute_force(
+            clauses,
+            &literals[1..],
+            values,
+            budget,
+            stats,
+            started,
+            observer,
+        )? {
+            return Some(true);
+        }
     }
-    false
+    Some(false)
 }
 
 /// A depth-first search (DFS) solver for the SAT problem. \
-/// The solver uses brute force to find a solution.
-pub struct Dfs;
+/// The solver uses brute force to find a solution, deciding variables in the order given by its heuristic,
+/// trying the value given by its polarity first.
+/// It learns no clauses, so [`Heuristic::Lrb`] decides by occurrences like [`Heuristic::Occurrences`].
+#[derive(Clone, Debug)]
+pub struct Dfs {
+    pub heuristic: Heuristic,
+    pub polarity: Polarity,
+    pub seed: u64,
+}
+
+impl Dfs {
+    pub fn new(config: &SolverConfig) -> Self {
+        Self {
+            heuristic: config.heuristic,
+            polarity: config.polarity,
+            seed: config.seed,
+        }
+    }
+}
+
+impl Default for Dfs {
+    fn default() -> Self {
+        Self::new(&SolverConfig::default())
+    }
+}
 
 impl Solver for Dfs {
-    fn solve(
+    fn solve_limited(
         &self,
         formula: &mut Formula,
         variables: &[Literal],
         solution: &mut Solution,
+        budget: &Budget,
+        stats: &mut Stats,
+        observer: &mut dyn Observer,
     ) -> Certificate {
-        if brute_force(formula, variables, solution) {
-            Certificate::Satisfiable(solution.clone())
-        } else {
-            Certificate::Unsatisfiable
+        let started = Instant::now();
+        let mut rng = Rng::new(self.seed);
+        let mut variables = variables.to_vec();
+        self.heuristic.order(formula, &mut variables, &mut rng);
+        let phases = self.polarity.phases(formula, &variables, &mut rng);
+        let mut ids = formula.literals();
+        ids.extend(&variables);
+        let domain = Domain::new(&ids);
+        let variables = variables
+            .into_iter()
+            .zip(phases)
+            .map(|(id, first)| (id, Lit::new(domain.index(id).unwrap() as Literal, first)))
+            .collect::<Vec<_>>();
+        let clauses = domain.lits(formula);
+        let size = domain.len();
+        let mut values = vec![false; size];
+        // The clauses, the search order, and the assignment with a stack frame per variable
+        stats.memory(
+            clauses.iter().map(Vec::len).sum::<usize>() * size_of::<Lit>()
+                + variables.len() * (size_of::<Literal>() + size_of::<Lit>() + 64)
+                + size * size_of::<bool>(),
+        );
+        let result = brute_force(
+            &clauses,
+            &variables,
+            &mut values,
+            budget,
+            stats,
+            started,
+            observer,
+        );
+        stats.time += started.elapsed();
+        match result {
+            Some(true) => {
+                for (id, lit) in &variables {
+                    solution.set(*id, values[lit.var() as usize]);
+                }
+                observer.on_solution(solution);
+                Certificate::Satisfiable(solution.clone())
+            }
+            Some(false) => Certificate::Unsatisfiable,
+            None => Certificate::Indeterminate,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn test_dfs_budget() {
+        // (x1 OR x2) AND (x3 OR x4) AND -x1 AND -x3
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Positive(3), Variable::Positive(4)],
+            vec![Variable::Negative(1)],
+            vec![Variable::Negative(3)],
+        ]
+        .into();
+        let variables = formula.literals();
+        let budget = Budget {
+            nodes: Some(2),
+            ..Default::default()
+        };
+        let mut solution = Solution::new();
+        assert_eq!(
+            Dfs::default().solve_limited(
+                &mut formula,
+                &variables,
+                &mut solution,
+                &budget,
+                &mut Stats::new(),
+                &mut (),
+            ),
+            Certificate::Indeterminate
+        );
+        let budget = Budget {
+            conflicts: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            Dfs::default().solve_limited(
+                &mut formula,
+                &variables,
+                &mut solution,
+                &budget,
+                &mut Stats::new(),
+                &mut (),
+            ),
+            Certificate::Indeterminate
+        );
+        let budget = Budget {
+            nodes: Some(20),
+            time: Some(Duration::from_secs(60)),
+            ..Default::default()
+        };
+        assert!(matches!(
+            Dfs::default().solve_limited(
+                &mut formula,
+                &variables,
+                &mut solution,
+                &budget,
+                &mut Stats::new(),
+                &mut (),
+            ),
+            Certificate::Satisfiable(_)
+        ));
+    }
+
+    #[test]
+    fn test_dfs_polarity() {
+        // x1 OR x2 OR -x3
+        let mut formula: Formula = vec![vec![
+            Variable::Positive(1),
+            Variable::Positive(2),
+            Variable::Negative(3),
+        ]]
+        .into();
+        let mut solve = |polarity| {
+            let dfs = Dfs::new(&SolverConfig {
+                polarity,
+                ..Default::default()
+            });
+            dfs.solve(&mut formula, &[1, 2, 3], &mut Solution::new())
+        };
+        assert_eq!(
+            solve(Polarity::False),
+            Certificate::Satisfiable([(1, false), (2, false), (3, false)][..].into())
+        );
+        assert_eq!(
+            solve(Polarity::True),
+            Certificate::Satisfiable([(1, true), (2, true), (3, true)][..].into())
+        );
+        assert_eq!(
+            solve(Polarity::JeroslowWang),
+            Certificate::Satisfiable([(1, true), (2, true), (3, false)][..].into())
+        );
+    }
+
+    #[test]
+    fn test_dfs_stats() {
+        // (x1 OR x2) AND -x1
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1)],
+        ]
+        .into();
+        let mut stats = Stats::new();
+        let mut solution = Solution::new();
+        Dfs::default().solve_limited(
+            &mut formula,
+            &[1, 2],
+            &mut solution,
+            &Budget::default(),
+            &mut stats,
+            &mut (),
+        );
+        // x1 = F, x2 = F fails, x1 = F, x2 = T succeeds
+        assert_eq!(stats.decisions, 3);
+        assert_eq!(stats.conflicts, 1);
+        assert!(stats.peak_memory > 0);
+    }
+
+    #[test]
+    fn test_dfs_gap_variables() {
+        // Only x1 and x100000000 exist, so the assignment has two values
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(100_000_000)],
+            vec![Variable::Negative(1)],
+        ]
+        .into();
+        let variables = formula.literals();
+        let mut stats = Stats::new();
+        let mut solution = Solution::new();
+        let Certificate::Satisfiable(solution) = Dfs::default().solve_limited(
+            &mut formula,
+            &variables,
+            &mut solution,
+            &Budget::default(),
+            &mut stats,
+            &mut (),
+        ) else {
+            panic!("Expected a solution");
+        };
+        assert!(solution.get(100_000_000));
+        assert_eq!(solution.literals(), [1, 100_000_000]);
+        assert!(stats.peak_memory < 1024);
+        // Identifiers too large for a packed literal are packed by their position instead
+        let mut formula: Formula = vec![vec![Variable::Positive(Literal::MAX)]].into();
+        assert_eq!(
+            Dfs::default().solve(&mut formula, &[Literal::MAX], &mut Solution::new()),
+            Certificate::Satisfiable([(Literal::MAX, true)][..].into())
+        );
+    }
+}
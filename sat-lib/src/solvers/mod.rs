@@ -1,16 +1,92 @@
+use std::cell::RefCell;
+
 use crate::types::{Certificate, Formula, Literal, Solution};
 
+mod budget;
+mod cdcl;
+mod config;
 mod dfs;
+mod observer;
+mod stats;
+mod tabu;
+pub use budget::{Budget, CancellationToken};
+pub use cdcl::Cdcl;
+pub use config::{Heuristic, Polarity, Restart, SolverConfig};
 pub use dfs::Dfs;
+pub use observer::Observer;
+pub use stats::Stats;
+pub use tabu::Tabu;
 
 /// A SAT solver is a program that determines whether a given boolean formula is satisfiable.
 /// - If the formula is satisfiable, the solver returns `Some(solution)`.
 /// - If the formula is unsatisfiable, the solver returns `None`.
+/// - Incomplete solvers, and solvers running out of their budget, return `Certificate::Indeterminate`.
 pub trait Solver {
-    fn solve(
+    /// Solve the formula, giving up when the budget is exceeded.
+    /// The work done is added to the statistics, and reported to the observer as it happens.
+    fn solve_limited(
         &self,
         formula: &mut Formula,
         variables: &[Literal],
         solution: &mut Solution,
+        budget: &Budget,
+        stats: &mut Stats,
+        observer: &mut dyn Observer,
     ) -> Certificate;
+
+    /// Solve the formula without any limits.
+    fn solve(
+        &self,
+        formula: &mut Formula,
+        variables: &[Literal],
+        solution: &mut Solution,
+    ) -> Certificate {
+        self.solve_limited(
+            formula,
+            variables,
+            solution,
+            &Budget::default(),
+            &mut Stats::new(),
+            &mut (),
+        )
+    }
+
+    /// Whether the solver always decides the formula, so running out of solutions means it is unsatisfiable.
+    fn is_complete(&self) -> bool {
+        true
+    }
+}
+
+/// A solver that always uses the given budget, and sums the statistics of every solve.
+/// This way functions like `solve_all` share one budget over all solves they make.
+/// The budget and statistics passed to `solve_limited` are replaced by its own, while the observer is kept.
+pub struct Limited<'a, S: Solver + ?Sized> {
+    pub solver: &'a S,
+    pub budget: Budget,
+    pub stats: RefCell<Stats>,
+}
+
+impl<S: Solver + ?Sized> Solver for Limited<'_, S> {
+    fn solve_limited(
+        &self,
+        formula: &mut Formula,
+        variables: &[Literal],
+        solution: &mut Solution,
+        _budget: &Budget,
+        _stats: &mut Stats,
+        observer: &mut dyn Observer,
+    ) -> Certificate {
+        self.solver.solve_limited(
+            formula,
+            variables,
+            solution,
+            &self.budget,
+            &mut self.stats.borrow_mut(),
+            observer,
+        )
+    }
+
+    fn is_complete(&self) -> bool {
+        self.solver.is_complete()
+    }
 }
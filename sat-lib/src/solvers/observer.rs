@@ -0,0 +1,85 @@
+use crate::{
+    trail::Trail,
+    types::{Formula, Solution, Variable},
+};
+
+/// Callbacks made by a solver while it is running, for displaying progress or collecting telemetry.
+/// All methods do nothing by default, so an observer only implements the events it cares about.
+///
+/// ## Examples
+/// ```plaintext
+/// struct Progress(u64);
+///
+/// impl Observer for Progress {
+///     fn on_conflict(&mut self) {
+///         self.0 += 1;
+///     }
+/// }
+/// ```
+pub trait Observer {
+    /// A variable was assigned by a decision, or flipped by a local search solver.
+    fn on_decision(&mut self, _variable: Variable) {}
+
+    /// An assignment was found to falsify the formula.
+    fn on_conflict(&mut self) {}
+
+    /// A conflict was analyzed by a clause learning solver, with the trail still at the conflict
+    /// and the clauses before the learned one is added, so [`Trail::to_dot`] shows the implication graph.
+    fn on_analyze(&mut self, _trail: &Trail, _clauses: &Formula) {}
+
+    fn on_restart(&mut self) {}
+
+    fn on_solution(&mut self, _solution: &Solution) {}
+}
+
+/// The empty observer ignores all events.
+impl Observer for () {}
+
+#[cfg(test)]
+mod tests {
+    use crate::solvers::{Budget, Dfs, Solver, Stats};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl Observer for Events {
+        fn on_decision(&mut self, variable: Variable) {
+            self.0.push(format!("{:?}", variable));
+        }
+
+        fn on_conflict(&mut self) {
+            self.0.push("conflict".to_string());
+        }
+
+        fn on_solution(&mut self, _solution: &Solution) {
+            self.0.push("solution".to_string());
+        }
+    }
+
+    #[test]
+    fn test_observer() {
+        // x1 OR x2
+        let mut formula: Formula = vec![vec![Variable::Positive(1), Variable::Positive(2)]].into();
+        let mut events = Events::default();
+        Dfs::default().solve_limited(
+            &mut formula,
+            &[1, 2],
+            &mut Solution::new(),
+            &Budget::default(),
+            &mut Stats::new(),
+            &mut events,
+        );
+        assert_eq!(
+            events.0,
+            [
+                "Negative(1)",
+                "Negative(2)",
+                "conflict",
+                "Positive(2)",
+                "solution"
+            ]
+        );
+    }
+}
@@ -0,0 +1,68 @@
+use std::{ops::AddAssign, time::Duration};
+
+/// Statistics about the work done by a solver.
+/// Solvers add to the statistics they are given, so they can be summed over several solves.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Stats {
+    /// Variables assigned by a decision, or flipped by local search solvers.
+    pub decisions: u64,
+    /// Variables assigned by unit propagation.
+    pub propagations: u64,
+    /// Assignments found to falsify the formula.
+    pub conflicts: u64,
+    pub restarts: u64,
+    pub learned_clauses: u64,
+    /// The estimated peak number of bytes used by the solver's own data structures.
+    pub peak_memory: usize,
+    pub time: Duration,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record the memory currently used, keeping the peak.
+    pub fn memory(&mut self, bytes: usize) {
+        self.peak_memory = self.peak_memory.max(bytes);
+    }
+}
+
+/// Sum the statistics of another solve, keeping the highest peak memory.
+impl AddAssign<&Stats> for Stats {
+    fn add_assign(&mut self, other: &Stats) {
+        self.decisions += other.decisions;
+        self.propagations += other.propagations;
+        self.conflicts += other.conflicts;
+        self.restarts += other.restarts;
+        self.learned_clauses += other.learned_clauses;
+        self.memory(other.peak_memory);
+        self.time += other.time;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_add_stats() {
+        let mut total = Stats {
+            decisions: 3,
+            peak_memory: 100,
+            time: Duration::from_millis(5),
+            ..Default::default()
+        };
+        total += &Stats {
+            decisions: 2,
+            conflicts: 1,
+            peak_memory: 40,
+            time: Duration::from_millis(10),
+            ..Default::default()
+        };
+        assert_eq!(total.decisions, 5);
+        assert_eq!(total.conflicts, 1);
+        assert_eq!(total.peak_memory, 100);
+        assert_eq!(total.time, Duration::from_millis(15));
+    }
+}
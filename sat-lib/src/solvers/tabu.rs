@@ -0,0 +1,291 @@
+use std::{collections::HashMap, mem::size_of, time::Instant};
+
+use crate::{
+    rng::Rng,
+    types::{Certificate, Domain, Formula, Lit, Literal, Solution, Variable},
+};
+
+use super::{Budget, Observer, Restart, Solver, SolverConfig, Stats};
+
+/// A tabu search solver for the SAT problem. \
+/// Starting from a random assignment, the solver repeatedly flips the variable that leaves the fewest clauses unsatisfied.
+/// Recently flipped variables are tabu for a number of steps, so the search does not cycle back into the same local minimum.
+///
+/// ## Note
+/// The solver is incomplete, it returns `Certificate::Indeterminate` if no solution is found within `max_flips` flips.
+#[derive(Clone, Debug)]
+pub struct Tabu {
+    /// The number of flips a flipped variable stays tabu for.
+    pub tenure: usize,
+    /// The number of flips before giving up.
+    pub max_flips: usize,
+    /// When to start over from a new random assignment.
+    pub restart: Restart,
+    /// Allow flipping a tabu variable if it leads to fewer unsatisfied clauses than ever seen before.
+    pub aspiration: bool,
+    pub seed: u64,
+}
+
+impl Tabu {
+    pub fn new(config: &SolverConfig) -> Self {
+        Self {
+            tenure: 10,
+            max_flips: config.max_flips,
+            restart: config.restart,
+            aspiration: true,
+            seed: config.seed,
+        }
+    }
+}
+
+impl Default for Tabu {
+    fn default() -> Self {
+        Self::new(&SolverConfig::default())
+    }
+}
+
+/// Count the clauses not satisfied by an assignment.
+fn unsatisfied(clauses: &[Vec<Lit>], values: &[bool]) -> usize {
+    clauses
+        .iter()
+        .filter(|clause| !clause.iter().any(|lit| lit.eval(values)))
+        .count()
+}
+
+impl Solver for Tabu {
+    fn solve_limited(
+        &self,
+        formula: &mut Formula,
+        variables: &[Literal],
+        solution: &mut Solution,
+        budget: &Budget,
+        stats: &mut Stats,
+        observer: &mut dyn Observer,
+    ) -> Certificate {
+        let started = Instant::now();
+        let certificate = self.search(
+            formula, variables, solution, budget, stats, started, observer,
+        );
+        stats.time += started.elapsed();
+        certificate
+    }
+
+    fn is_complete(&self) -> bool {
+        false
+    }
+}
+
+impl Tabu {
+    #[allow(clippy::too_many_arguments)]
+    fn search(
+        &self,
+        formula: &Formula,
+        variables: &[Literal],
+        solution: &mut Solution,
+        budget: &Budget,
+        stats: &mut Stats,
+        started: Instant,
+        observer: &mut dyn Observer,
+    ) -> Certificate {
+        let mut rng = Rng::new(self.seed);
+        let mut ids = formula.literals();
+        ids.extend(variables);
+        let domain = Domain::new(&ids);
+        let clauses = domain.lits(formula);
+        let size = domain.len();
+        let mut values = vec![false; size];
+        // The position of each variable in the values
+        let variables = variables
+            .iter()
+            .map(|id| (*id, domain.index(*id).unwrap()))
+            .collect::<Vec<_>>();
+        for (_, index) in &variables {
+            values[*index] = rng.bool();
+        }
+        let mut best = unsatisfied(&clauses, &values);
+        // The flip after which each variable is no longer tabu
+        let mut tabu: HashMap<Literal, usize> = HashMap::new();
+        let mut restarts = 0;
+        let mut next_restart = self.restart.interval(restarts);
+        for flip in 0..self.max_flips {
+            if unsatisfied(&clauses, &values) == 0 {
+                break;
+            }
+            if budget.exceeded(stats, started) {
+                return Certificate::Indeterminate;
+            }
+            if next_restart == Some(flip) {
+                for (_, index) in &variables {
+                    values[*index] = rng.bool();
+                }
+                tabu.clear();
+                best = unsatisfied(&clauses, &values);
+                restarts += 1;
+                stats.restarts += 1;
+                observer.on_restart();
+                next_restart = self.restart.interval(restarts).map(|n| flip + n);
+            }
+            let mut candidates = Vec::new();
+            let mut lowest = usize::MAX;
+            for (id, index) in &variables {
+                values[*index] = !values[*index];
+                let score = unsatisfied(&clauses, &values);
+                values[*index] = !values[*index];
+                let allowed = tabu.get(id).is_none_or(|until| *until <= flip)
+                    || (self.aspiration && score < best);
+                if !allowed || score > lowest {
+                    continue;
+                }
+                if score < lowest {
+                    lowest = score;
+                    candidates.clear();
+                }
+                candidates.push((*id, *index));
+            }
+            if candidates.is_empty() {
+                // Every variable is tabu, wait for the tenure to run out
+                continue;
+            }
+            let (id, index) = candidates[rng.below(candidates.len())];
+            values[index] = !values[index];
+            tabu.insert(id, flip + 1 + self.tenure);
+            stats.decisions += 1;
+            observer.on_decision(if values[index] {
+                Variable::Positive(id)
+            } else {
+                Variable::Negative(id)
+            });
+            stats.memory(
+                clauses.iter().map(Vec::len).sum::<usize>() * size_of::<Lit>()
+                    + size * size_of::<bool>()
+                    + tabu.len() * (size_of::<Literal>() + size_of::<usize>()),
+            );
+            best = best.min(lowest);
+        }
+        if unsatisfied(&clauses, &values) == 0 {
+            for (id, index) in &variables {
+                solution.set(*id, values[*index]);
+            }
+            observer.on_solution(solution);
+            Certificate::Satisfiable(solution.clone())
+        } else {
+            Certificate::Indeterminate
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::types::Variable;
+
+    use super::*;
+
+    #[test]
+    fn test_tabu_satisfiable() {
+        // (x1 OR x2) AND (-x1 OR x3) AND (-x2 OR -x3) AND (x2 OR x3)
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+            vec![Variable::Negative(2), Variable::Negative(3)],
+            vec![Variable::Positive(2), Variable::Positive(3)],
+        ]
+        .into();
+        let variables = formula.literals();
+        let mut solution = Solution::new();
+        let Certificate::Satisfiable(solution) =
+            Tabu::default().solve(&mut formula, &variables, &mut solution)
+        else {
+            panic!("Expected a solution");
+        };
+        assert!(solution.satisfy(&formula));
+    }
+
+    #[test]
+    fn test_tabu_restarts() {
+        // (x1 OR x2) AND (-x1 OR x2) AND (x1 OR -x2)
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Positive(1), Variable::Negative(2)],
+        ]
+        .into();
+        let tabu = Tabu::new(&SolverConfig {
+            restart: Restart::Luby(2),
+            seed: 7,
+            ..Default::default()
+        });
+        let mut solution = Solution::new();
+        assert_eq!(
+            tabu.solve(&mut formula, &[1, 2], &mut solution),
+            Certificate::Satisfiable([(1, true), (2, true)][..].into())
+        );
+    }
+
+    #[test]
+    fn test_tabu_reproducible() {
+        // (x1 OR x2 OR x3) AND (-x1 OR -x2) AND (x4 OR -x3)
+        let mut formula: Formula = vec![
+            vec![
+                Variable::Positive(1),
+                Variable::Positive(2),
+                Variable::Positive(3),
+            ],
+            vec![Variable::Negative(1), Variable::Negative(2)],
+            vec![Variable::Positive(4), Variable::Negative(3)],
+        ]
+        .into();
+        let variables = formula.literals();
+        for seed in 0..10 {
+            let tabu = Tabu::new(&SolverConfig {
+                seed,
+                ..Default::default()
+            });
+            let mut run = || {
+                let mut stats = Stats::new();
+                let certificate = tabu.solve_limited(
+                    &mut formula,
+                    &variables,
+                    &mut Solution::new(),
+                    &Budget::default(),
+                    &mut stats,
+                    &mut (),
+                );
+                (certificate, stats.decisions)
+            };
+            assert_eq!(run(), run());
+        }
+    }
+
+    #[test]
+    fn test_tabu_indeterminate() {
+        // x1 AND -x1
+        let mut formula: Formula =
+            vec![vec![Variable::Positive(1)], vec![Variable::Negative(1)]].into();
+        let tabu = Tabu {
+            max_flips: 100,
+            ..Default::default()
+        };
+        let mut solution = Solution::new();
+        assert_eq!(
+            tabu.solve(&mut formula, &[1], &mut solution),
+            Certificate::Indeterminate
+        );
+    }
+
+    #[test]
+    fn test_tabu_gap_variables() {
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(3), Variable::Positive(100_000_000)],
+            vec![Variable::Negative(3)],
+        ]
+        .into();
+        let variables = formula.literals();
+        let mut solution = Solution::new();
+        let Certificate::Satisfiable(solution) =
+            Tabu::default().solve(&mut formula, &variables, &mut solution)
+        else {
+            panic!("Expected a solution");
+        };
+        assert_eq!(solution.to_string(), "X3 = F, X100000000 = T");
+    }
+}
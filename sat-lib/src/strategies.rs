@@ -0,0 +1,156 @@
+#[cfg(feature = "proptest")]
+use proptest::{
+    arbitrary::Arbitrary,
+    collection::vec,
+    prelude::{any, BoxedStrategy, Strategy},
+};
+
+use crate::types::{Clause, Formula, Literal, Variable};
+
+/// The largest variable generated for the `Arbitrary` implementations,
+/// small enough that brute-force solvers can check the formulas.
+pub const MAX_VARIABLE: Literal = 8;
+/// The most clauses of a formula generated for the `Arbitrary` implementations.
+pub const MAX_CLAUSES: usize = 12;
+/// The most variables of a clause generated for the `Arbitrary` implementations.
+pub const MAX_CLAUSE_LEN: usize = 4;
+
+/// Generate variables of `x1..=xmax`, shrinking towards `-x1`.
+#[cfg(feature = "proptest")]
+pub fn variables(max: Literal) -> impl Strategy<Value = Variable> + Clone {
+    (1..=max, any::<bool>()).prop_map(|(id, positive)| {
+        if positive {
+            Variable::Positive(id)
+        } else {
+            Variable::Negative(id)
+        }
+    })
+}
+
+/// Generate non-empty clauses of at most `max_len` variables of `x1..=max_variable`,
+/// shrinking by removing variables.
+#[cfg(feature = "proptest")]
+pub fn clauses(max_variable: Literal, max_len: usize) -> impl Strategy<Value = Clause> + Clone {
+    vec(variables(max_variable), 1..=max_len.max(1)).prop_map(Clause)
+}
+
+/// Generate formulas of at most `max_clauses` clauses over `x1..=max_variable`,
+/// shrinking by removing clauses and then variables of the remaining clauses.
+///
+/// ## Examples
+/// ```rust,ignore
+/// proptest! {
+///     #[test]
+///     fn test_encoding(formula in formulas(6, 10)) {
+///         // ...
+///     }
+/// }
+/// ```
+#[cfg(feature = "proptest")]
+pub fn formulas(max_variable: Literal, max_clauses: usize) -> impl Strategy<Value = Formula> {
+    vec(clauses(max_variable, MAX_CLAUSE_LEN), 0..=max_clauses).prop_map(Formula::from)
+}
+
+#[cfg(feature = "proptest")]
+impl Arbitrary for Variable {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        variables(MAX_VARIABLE).boxed()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl Arbitrary for Clause {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        clauses(MAX_VARIABLE, MAX_CLAUSE_LEN).boxed()
+    }
+}
+
+#[cfg(feature = "proptest")]
+impl Arbitrary for Formula {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        formulas(MAX_VARIABLE, MAX_CLAUSES).boxed()
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Variable {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let id = u.int_in_range(1..=MAX_VARIABLE)?;
+        Ok(if u.arbitrary()? {
+            Variable::Positive(id)
+        } else {
+            Variable::Negative(id)
+        })
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Clause {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let len = u.int_in_range(1..=MAX_CLAUSE_LEN)?;
+        (0..len)
+            .map(|_| u.arbitrary())
+            .collect::<arbitrary::Result<_>>()
+            .map(Clause)
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for Formula {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        let len = u.int_in_range(0..=MAX_CLAUSES)?;
+        (0..len)
+            .map(|_| u.arbitrary())
+            .collect::<arbitrary::Result<Vec<Clause>>>()
+            .map(Formula::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "proptest")]
+    use proptest::{prop_assert, prop_assert_eq, prop_assume, proptest};
+
+    #[cfg(feature = "proptest")]
+    use crate::{parser::parse, solver::solve_all, solvers::Dfs};
+
+    #[allow(unused_imports)]
+    use super::*;
+
+    #[cfg(feature = "proptest")]
+    proptest! {
+        #[test]
+        fn test_formulas_round_trip(formula in formulas(6, 8)) {
+            prop_assume!(!formula.is_empty());
+            prop_assert_eq!(parse(&formula.to_string()).unwrap(), formula);
+        }
+
+        #[test]
+        fn test_formulas_solutions(formula: Formula) {
+            for solution in solve_all(&formula, &Dfs::default()) {
+                prop_assert!(solution.satisfy(&formula));
+            }
+        }
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_formula() {
+        let bytes = (0..=255).collect::<Vec<u8>>();
+        let formula: Formula = arbitrary::Unstructured::new(&bytes).arbitrary().unwrap();
+        assert!(formula.len() <= MAX_CLAUSES);
+        for clause in formula.iter() {
+            assert!((1..=MAX_CLAUSE_LEN).contains(&clause.len()));
+            assert!(clause.literals().iter().all(|id| *id <= MAX_VARIABLE));
+        }
+    }
+}
@@ -0,0 +1,275 @@
+use std::fmt::Write;
+
+use crate::{
+    parser::{numbered, ParseError},
+    types::{Clause, Formula, VarPool, Variable},
+};
+
+/// Parse a problem in the CNF form of the TPTP library, restricted to propositional atoms.
+/// Every clause is an annotated formula `cnf(name, role, clause).` of literals separated by `|`,
+/// where atoms are lower case words or in single quotes, negated by `~`.
+/// The clause `$false` is empty, and clauses with `$true` are left out.
+/// Atoms like `x3` are numbered variables, and other atoms are added to the symbol table.
+///
+/// ## Examples
+/// ```plaintext
+/// % The grass is wet if it rains
+/// cnf(rain_wets, axiom, ~rain | wet).
+/// cnf(dry, negated_conjecture, ( ~wet )).
+/// ```
+pub fn parse_tptp(input: &str, pool: &mut VarPool) -> Result<Formula, ParseError> {
+    let tokens = tokenize(input)?;
+    // Reserve the numbered variables first, so names are given identifiers after them
+    for (_, token) in &tokens {
+        if let Some(id) = numbered(token) {
+            pool.reserve(id);
+        }
+    }
+    let mut tokens = Tokens {
+        tokens: tokens.into_iter().peekable(),
+        end: input.len(),
+    };
+    let mut formula = Formula::new();
+    while tokens.tokens.peek().is_some() {
+        tokens.expect("cnf")?;
+        tokens.expect("(")?;
+        tokens.word(&["a name"])?;
+        tokens.expect(",")?;
+        tokens.word(&["a role"])?;
+        tokens.expect(",")?;
+        let parenthesized = tokens.next_if("(");
+        let mut clause = Some(Vec::new());
+        loop {
+            let negated = tokens.next_if("~");
+            let (offset, atom) = tokens.word(&["an atom", "'~'"])?;
+            let id = match atom {
+                // A true literal makes the clause always true, and a false one is left out
+                "$true" | "$false" => {
+                    if (atom == "$true") != negated {
+                        clause = None;
+                    }
+                    None
+                }
+                atom if atom.starts_with(|c: char| c.is_ascii_lowercase()) => {
+                    Some(numbered(atom).unwrap_or_else(|| pool.id(atom)))
+                }
+                atom if atom.starts_with('\'') => {
+                    let name = atom[1..atom.len() - 1]
+                        .replace("\\'", "'")
+                        .replace("\\\\", "\\");
+                    Some(pool.id(&name))
+                }
+                atom => return Err(ParseError::new(offset, atom, &["a propositional atom"])),
+            };
+            if let (Some(id), Some(clause)) = (id, &mut clause) {
+                clause.push(if negated {
+                    Variable::Negative(id)
+                } else {
+                    Variable::Positive(id)
+                });
+            }
+            if !tokens.next_if("|") {
+                break;
+            }
+        }
+        if parenthesized {
+            tokens.expect(")")?;
+        }
+        // Optional source and useful info, which don't change the clause
+        if tokens.next_if(",") {
+            tokens.skip_annotations()?;
+        }
+        tokens.expect(")")?;
+        tokens.expect(".")?;
+        if let Some(clause) = clause {
+            formula.add(Clause(clause));
+        }
+    }
+    Ok(formula)
+}
+
+/// Write a formula as a TPTP problem of clauses named `c1`, `c2`, ... with the role `axiom`.
+/// Named variables keep their names, quoted if they are not lower case words,
+/// and the other variables are written as `x1`, `x2`, ...
+pub fn write_tptp(formula: &Formula, pool: &VarPool) -> String {
+    let mut output = String::new();
+    for (i, clause) in formula.iter().enumerate() {
+        let literals = clause
+            .iter()
+            .map(|variable| {
+                let sign = if variable.is_positive() { "" } else { "~" };
+                let atom = match pool.name(variable.id()) {
+                    Some(name) if is_lower_word(name) => name.to_string(),
+                    Some(name) => {
+                        format!("'{}'", name.replace('\\', "\\\\").replace('\'', "\\'"))
+                    }
+                    None => format!("x{}", variable.id()),
+                };
+                format!("{}{}", sign, atom)
+            })
+            .collect::<Vec<_>>();
+        let literals = if literals.is_empty() {
+            "$false".to_string()
+        } else {
+            literals.join(" | ")
+        };
+        writeln!(output, "cnf(c{}, axiom, ({})).", i + 1, literals).unwrap();
+    }
+    output
+}
+
+/// Check if a name is a lower case word, which can be written as an atom without quotes.
+fn is_lower_word(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_lowercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Split a problem into tokens with their byte offsets, skipping whitespace and comments.
+/// Words may start with `$`, and quoted atoms keep their quotes.
+fn tokenize(input: &str) -> Result<Vec<(usize, &str)>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while let Some(c) = input[start..].chars().next() {
+        let rest = &input[start..];
+        // Whitespace and comments are skipped, without making a token
+        let skipped = if c.is_whitespace() {
+            Some(c.len_utf8())
+        } else if c == '%' {
+            Some(rest.find('\n').unwrap_or(rest.len()))
+        } else if rest.starts_with("/*") {
+            match rest.find("*/") {
+                Some(end) => Some(end + 2),
+                None => return Err(ParseError::new(input.len(), "", &["'*/'"])),
+            }
+        } else {
+            None
+        };
+        if let Some(length) = skipped {
+            start += length;
+            continue;
+        }
+        let length = if c == '\'' {
+            // Quotes and backslashes in quoted atoms are escaped by a backslash
+            let mut escaped = false;
+            let end = rest[1..].find(|c| {
+                let end = c == '\'' && !escaped;
+                escaped = c == '\\' && !escaped;
+                end
+            });
+            match end {
+                Some(end) => end + 2,
+                None => return Err(ParseError::new(input.len(), "", &["'''"])),
+            }
+        } else if c.is_ascii_alphanumeric() || c == '$' || c == '_' {
+            rest[1..]
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .map_or(rest.len(), |end| end + 1)
+        } else {
+            c.len_utf8()
+        };
+        tokens.push((start, &rest[..length]));
+        start += length;
+    }
+    Ok(tokens)
+}
+
+/// The tokens of a problem being parsed.
+struct Tokens<'a> {
+    tokens: std::iter::Peekable<std::vec::IntoIter<(usize, &'a str)>>,
+    /// The byte offset of the end of the input, where missing tokens are reported.
+    end: usize,
+}
+
+impl<'a> Tokens<'a> {
+    /// Take the next token, failing if it is not the expected one.
+    fn expect(&mut self, expected: &str) -> Result<(), ParseError> {
+        match self.tokens.next() {
+            Some((_, token)) if token == expected => Ok(()),
+            Some((offset, token)) => Err(ParseError::new(offset, token, &[expected])),
+            None => Err(ParseError::new(self.end, "", &[expected])),
+        }
+    }
+
+    /// Take the next token if it is the given one.
+    fn next_if(&mut self, token: &str) -> bool {
+        self.tokens.next_if(|(_, next)| *next == token).is_some()
+    }
+
+    /// Take the next token if it is a word or quoted atom.
+    fn word(&mut self, expected: &[&str]) -> Result<(usize, &'a str), ParseError> {
+        match self.tokens.next() {
+            Some((offset, token))
+                if token.starts_with(|c: char| c.is_ascii_alphanumeric() || "$_'".contains(c)) =>
+            {
+                Ok((offset, token))
+            }
+            Some((offset, token)) => Err(ParseError::new(offset, token, expected)),
+            None => Err(ParseError::new(self.end, "", expected)),
+        }
+    }
+
+    /// Skip the annotations of a clause up to its closing parenthesis, balancing nested brackets.
+    fn skip_annotations(&mut self) -> Result<(), ParseError> {
+        let mut depth = 0;
+        while let Some((_, token)) = self.tokens.peek() {
+            match *token {
+                ")" | "]" if depth == 0 => return Ok(()),
+                "(" | "[" => depth += 1,
+                ")" | "]" => depth -= 1,
+                _ => (),
+            }
+            self.tokens.next();
+        }
+        Err(ParseError::new(self.end, "", &["')'"]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_tptp() {
+        let mut pool = VarPool::new();
+        let input = "
+            % The grass is wet if it rains
+            cnf(rain_wets, axiom, ~rain | wet).
+            /* Numbered and quoted atoms */
+            cnf(c2, hypothesis, ( x1 | 'Sprinkler\\'s on' ), file('wet.p', c2), [useful]).
+            cnf(dry, negated_conjecture, ~wet | $false).
+            cnf(trivial, axiom, rain | $true).
+            cnf(negated, axiom, ~$false | wet).
+            cnf(kept, axiom, ~$true | wet).
+            cnf(empty, axiom, $false).
+        ";
+        let formula = parse_tptp(input, &mut pool).unwrap();
+        assert_eq!(
+            formula.to_string(),
+            "(-X2 or X3) and (X1 or X4) and (-X3) and (X3) and ()"
+        );
+        assert_eq!(pool.name(4), Some("Sprinkler's on"));
+        let output = write_tptp(&formula, &pool);
+        assert_eq!(
+            output,
+            "cnf(c1, axiom, (~rain | wet)).\ncnf(c2, axiom, (x1 | 'Sprinkler\\'s on')).\n\
+             cnf(c3, axiom, (~wet)).\ncnf(c4, axiom, (wet)).\ncnf(c5, axiom, ($false)).\n"
+        );
+        assert_eq!(parse_tptp(&output, &mut pool), Ok(formula));
+    }
+
+    #[test]
+    fn test_tptp_errors() {
+        let mut pool = VarPool::new();
+        let error = parse_tptp("cnf(c1, axiom, p(X) | q).", &mut pool).unwrap_err();
+        assert_eq!((error.span.start, error.token.as_str()), (16, "("));
+        let error = parse_tptp("cnf(c1, axiom, X | q).", &mut pool).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unexpected X, expected a propositional atom"
+        );
+        let error = parse_tptp("fof(f1, axiom, p).", &mut pool).unwrap_err();
+        assert_eq!(error.span, 0..3);
+        let error = parse_tptp("cnf(c1, axiom, p)", &mut pool).unwrap_err();
+        assert_eq!(error.to_string(), "Missing .");
+    }
+}
@@ -0,0 +1,451 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use crate::types::{Clause, Formula, Literal, Variable};
+
+/// An assignment on the trail, made either by a decision or implied by a clause.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Assignment {
+    /// The variable made true by the assignment.
+    pub variable: Variable,
+    /// The number of decisions made before and including this assignment.
+    pub level: usize,
+    /// The index of the clause that implied the assignment, or `None` for decisions.
+    pub antecedent: Option<usize>,
+}
+
+/// The trail records variable assignments in the order they were made, with their decision level and antecedent clause.
+/// Together with the formula it forms the implication graph, where each implied variable has an edge from
+/// every other variable of its antecedent clause.
+///
+/// ## Examples
+/// ```plaintext
+/// (-x1 OR x2) AND (-x2 OR x3)
+/// decide x1     level 1
+/// propagate x2  level 1 from clause 0
+/// propagate x3  level 1 from clause 1
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Trail {
+    assignments: Vec<Assignment>,
+    positions: HashMap<Literal, usize>,
+    level: usize,
+    conflict: Option<usize>,
+}
+
+impl Trail {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a new decision level by assigning a variable.
+    pub fn decide(&mut self, variable: Variable) {
+        self.level += 1;
+        self.assign(variable, None);
+    }
+
+    /// Assign a variable at the current decision level.
+    pub fn assign(&mut self, variable: Variable, antecedent: Option<usize>) {
+        self.positions.insert(variable.id(), self.assignments.len());
+        self.assignments.push(Assignment {
+            variable,
+            level: self.level,
+            antecedent,
+        });
+    }
+
+    /// Repeatedly assign the only unassigned variable of clauses where all other variables are false,
+    /// at the highest level of the others, which is below the current level after chronological backtracking.
+    /// Returns the index of the first clause found with all its variables false, which is also kept as the conflict.
+    pub fn propagate(&mut self, formula: &Formula) -> Result<(), usize> {
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for (i, clause) in formula.iter().enumerate() {
+                let mut unassigned = Vec::new();
+                let mut satisfied = false;
+                for variable in clause.iter() {
+                    match self.value(variable.id()) {
+                        Some(value) if value == variable.is_positive() => {
+                            satisfied = true;
+                            break;
+                        }
+                        Some(_) => (),
+                        None => unassigned.push(*variable),
+                    }
+                }
+                if satisfied {
+                    continue;
+                }
+                match unassigned[..] {
+                    [] => {
+                        self.conflict = Some(i);
+                        return Err(i);
+                    }
+                    [variable] if self.value(variable.id()).is_none() => {
+                        let level = clause
+                            .iter()
+                            .filter_map(|other| self.get(other.id()))
+                            .map(|assignment| assignment.level)
+                            .max()
+                            .unwrap_or(0);
+                        self.positions.insert(variable.id(), self.assignments.len());
+                        self.assignments.push(Assignment {
+                            variable,
+                            level,
+                            antecedent: Some(i),
+                        });
+                        changed = true;
+                    }
+                    _ => (),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Undo all assignments above a decision level, and forget any conflict.
+    /// After chronological backtracking the levels on the trail are out of order,
+    /// so the assignments kept below the undone ones are moved down.
+    pub fn backtrack(&mut self, level: usize) {
+        let first = self
+            .assignments
+            .iter()
+            .position(|assignment| assignment.level > level)
+            .unwrap_or(self.assignments.len());
+        for assignment in self.assignments.drain(first..).collect::<Vec<_>>() {
+            if assignment.level <= level {
+                self.positions
+                    .insert(assignment.variable.id(), self.assignments.len());
+                self.assignments.push(assignment);
+            } else {
+                self.positions.remove(&assignment.variable.id());
+            }
+        }
+        self.level = self.level.min(level);
+        self.conflict = None;
+    }
+
+    /// Get the value assigned to a variable, if any.
+    pub fn value(&self, id: Literal) -> Option<bool> {
+        self.get(id)
+            .map(|assignment| assignment.variable.is_positive())
+    }
+
+    pub fn get(&self, id: Literal) -> Option<&Assignment> {
+        self.positions.get(&id).map(|i| &self.assignments[*i])
+    }
+
+    pub fn decision_level(&self) -> usize {
+        self.level
+    }
+
+    /// Get the clause found falsified by the last propagation.
+    pub fn conflict(&self) -> Option<usize> {
+        self.conflict
+    }
+
+    pub fn len(&self) -> usize {
+        self.assignments.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assignments.is_empty()
+    }
+
+    /// Iterate over the assignments in the order they were made.
+    pub fn iter(&self) -> std::slice::Iter<'_, Assignment> {
+        self.assignments.iter()
+    }
+
+    /// Get the true variables that implied an assignment through its antecedent clause.
+    /// Decisions and unassigned variables have no reasons.
+    pub fn reasons(&self, formula: &Formula, id: Literal) -> Vec<Variable> {
+        let Some(antecedent) = self.get(id).and_then(|assignment| assignment.antecedent) else {
+            return Vec::new();
+        };
+        formula.0[antecedent]
+            .iter()
+            .filter(|variable| variable.id() != id)
+            .map(Variable::negated)
+            .collect()
+    }
+
+    /// Get the edges of the implication graph, from each reason to the variable it implied.
+    pub fn implication_graph(&self, formula: &Formula) -> Vec<(Variable, Variable)> {
+        let mut edges = Vec::new();
+        for assignment in &self.assignments {
+            for reason in self.reasons(formula, assignment.variable.id()) {
+                edges.push((reason, assignment.variable));
+            }
+        }
+        edges
+    }
+
+    /// Learn a clause from the conflict by resolving it with the antecedents of the conflict's decision level,
+    /// back to the first unique implication point, the assignment closest to the conflict on every path to it.
+    /// The learned clause is falsified by the trail, and asserts the negated implication point after backtracking.
+    /// The conflict's level is the highest level of its clause, which is below the current level
+    /// when a decision was made without propagating first.
+    /// Returns `None` without a conflict, and the empty clause for a conflict at level 0.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (-x1 OR x2) AND (-x2 OR -x3 OR x4) AND (-x4 OR -x3)
+    /// decide x1, decide x3, conflict in clause 2
+    /// learn (-x3 OR -x2)
+    /// ```
+    pub fn learned_clause(&self, formula: &Formula) -> Option<Clause> {
+        self.analyze(formula).map(|(learned, _)| learned)
+    }
+
+    /// Find the learned clause and the assignments of the conflict's level on the conflict side of its cut,
+    /// which are resolved away.
+    pub(crate) fn analyze(&self, formula: &Formula) -> Option<(Clause, Vec<Literal>)> {
+        let conflict = self.conflict?;
+        let level = self.level_of(&formula.0[conflict]);
+        if level == 0 {
+            return Some((Clause(Vec::new()), Vec::new()));
+        }
+        let mut learned = Vec::new();
+        let mut resolved = Vec::new();
+        let mut seen = HashSet::new();
+        // The false variables of the clause being resolved, and the current level ones left to resolve
+        let mut reasons = formula.0[conflict].0.clone();
+        let mut pending = 0;
+        let mut index = self.assignments.len();
+        loop {
+            for variable in reasons {
+                let assignment = self.get(variable.id()).expect("Reasons are assigned");
+                if assignment.level == 0 || !seen.insert(variable.id()) {
+                    continue;
+                }
+                if assignment.level == level {
+                    pending += 1;
+                } else {
+                    learned.push(variable);
+                }
+            }
+            index -= 1;
+            // Lower levels can be above the conflict's level on the trail after chronological backtracking
+            while self.assignments[index].level != level
+                || !seen.contains(&self.assignments[index].variable.id())
+            {
+                index -= 1;
+            }
+            let assignment = self.assignments[index];
+            pending -= 1;
+            if pending == 0 {
+                learned.insert(0, assignment.variable.negated());
+                return Some((Clause(learned), resolved));
+            }
+            resolved.push(assignment.variable.id());
+            let antecedent = assignment
+                .antecedent
+                .expect("Only the first decision of a level has no antecedent");
+            reasons = formula.0[antecedent]
+                .iter()
+                .filter(|variable| variable.id() != assignment.variable.id())
+                .copied()
+                .collect();
+        }
+    }
+
+    /// Get the highest decision level of the variables of a clause, which must all be assigned.
+    pub fn level_of(&self, clause: &Clause) -> usize {
+        clause
+            .iter()
+            .map(|variable| {
+                self.get(variable.id())
+                    .expect("Variables are assigned")
+                    .level
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Get the implication graph in the Graphviz DOT language, for explaining conflict analysis.
+    /// Decisions are boxes, implied assignments have an edge from each of their reasons labeled by the antecedent clause,
+    /// and a conflict is a node of its own.
+    /// After a conflict, the conflict side of the cut of the learned clause is red, and the learned clause is the graph label.
+    pub fn to_dot(&self, formula: &Formula) -> String {
+        let analysis = self.analyze(formula);
+        let conflict_side = analysis
+            .as_ref()
+            .map(|(_, resolved)| resolved.clone())
+            .unwrap_or_default();
+        let mut dot = String::from("digraph implication {\n  rankdir=LR;\n");
+        if let Some((learned, _)) = &analysis {
+            writeln!(dot, "  label=\"learned {}\";", learned).unwrap();
+        }
+        for assignment in &self.assignments {
+            let variable = assignment.variable;
+            let sign = if variable.is_positive() { "" } else { "-" };
+            let shape = if assignment.antecedent.is_none() {
+                ", shape=box"
+            } else {
+                ""
+            };
+            let color = if conflict_side.contains(&variable.id()) {
+                ", color=red"
+            } else {
+                ""
+            };
+            writeln!(
+                dot,
+                "  x{} [label=\"{}x{}@{}\"{}{}];",
+                variable.id(),
+                sign,
+                variable.id(),
+                assignment.level,
+                shape,
+                color
+            )
+            .unwrap();
+        }
+        for assignment in &self.assignments {
+            let Some(antecedent) = assignment.antecedent else {
+                continue;
+            };
+            for reason in self.reasons(formula, assignment.variable.id()) {
+                writeln!(
+                    dot,
+                    "  x{} -> x{} [label=\"c{}\"];",
+                    reason.id(),
+                    assignment.variable.id(),
+                    antecedent + 1
+                )
+                .unwrap();
+            }
+        }
+        if let Some(conflict) = self.conflict {
+            dot.push_str("  conflict [shape=octagon, color=red];\n");
+            for variable in formula.0[conflict].iter() {
+                writeln!(
+                    dot,
+                    "  x{} -> conflict [label=\"c{}\"];",
+                    variable.id(),
+                    conflict + 1
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trail() {
+        // (-x1 OR x2) AND (-x2 OR -x3 OR x4) AND (-x4 OR -x3)
+        let formula: Formula = vec![
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![
+                Variable::Negative(2),
+                Variable::Negative(3),
+                Variable::Positive(4),
+            ],
+            vec![Variable::Negative(4), Variable::Negative(3)],
+        ]
+        .into();
+        let mut trail = Trail::new();
+        trail.decide(Variable::Positive(1));
+        assert_eq!(trail.propagate(&formula), Ok(()));
+        assert_eq!(trail.value(2), Some(true));
+        assert_eq!(trail.get(2).unwrap().antecedent, Some(0));
+        trail.decide(Variable::Positive(3));
+        // x4 is implied by clause 1, and then clause 2 is falsified
+        assert_eq!(trail.propagate(&formula), Err(2));
+        assert_eq!(trail.decision_level(), 2);
+        assert_eq!(trail.get(4).unwrap().level, 2);
+        assert_eq!(
+            trail.implication_graph(&formula),
+            [
+                (Variable::Positive(1), Variable::Positive(2)),
+                (Variable::Positive(2), Variable::Positive(4)),
+                (Variable::Positive(3), Variable::Positive(4)),
+            ]
+        );
+        trail.backtrack(1);
+        assert_eq!(trail.len(), 2);
+        assert_eq!(trail.value(3), None);
+        assert_eq!(trail.conflict(), None);
+    }
+
+    #[test]
+    fn test_learned_clause() {
+        // x1 -> x2, x3 -> x5, (x2 AND x5) -> x4 and (x4 AND x5) is a conflict, where x5 is the first UIP
+        let formula: Formula = vec![
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Negative(3), Variable::Positive(5)],
+            vec![
+                Variable::Negative(2),
+                Variable::Negative(5),
+                Variable::Positive(4),
+            ],
+            vec![Variable::Negative(4), Variable::Negative(5)],
+        ]
+        .into();
+        let mut trail = Trail::new();
+        assert_eq!(trail.learned_clause(&formula), None);
+        trail.decide(Variable::Positive(1));
+        assert_eq!(trail.propagate(&formula), Ok(()));
+        trail.decide(Variable::Positive(3));
+        assert_eq!(trail.propagate(&formula), Err(3));
+        let learned = trail.learned_clause(&formula).unwrap();
+        assert_eq!(learned.to_string(), "(-X5 or -X2)");
+        let dot = trail.to_dot(&formula);
+        assert!(dot.contains("label=\"learned (-X5 or -X2)\""));
+        assert!(dot.contains("  x3 [label=\"x3@2\", shape=box];\n"));
+        assert!(dot.contains("  x4 [label=\"x4@2\", color=red];\n"));
+        assert!(dot.contains("  x5 [label=\"x5@2\"];\n"));
+        assert!(dot.contains("  x5 -> conflict [label=\"c4\"];\n"));
+    }
+
+    #[test]
+    fn test_learned_clause_below_current_level() {
+        // -x1 AND (x2 OR x3), where deciding x2 before propagating leaves the conflict at level 1
+        let formula: Formula = vec![
+            vec![Variable::Negative(1)],
+            vec![Variable::Positive(2), Variable::Positive(3)],
+        ]
+        .into();
+        let mut trail = Trail::new();
+        trail.decide(Variable::Positive(1));
+        trail.decide(Variable::Positive(2));
+        assert_eq!(trail.propagate(&formula), Err(0));
+        assert_eq!(trail.level_of(&formula.0[0]), 1);
+        let learned = trail.learned_clause(&formula).unwrap();
+        assert_eq!(learned.to_string(), "(-X1)");
+    }
+
+    #[test]
+    fn test_backtrack_out_of_order() {
+        let mut formula: Formula = vec![vec![Variable::Positive(2), Variable::Positive(3)]].into();
+        let mut trail = Trail::new();
+        trail.decide(Variable::Positive(1));
+        trail.decide(Variable::Negative(2));
+        assert_eq!(trail.propagate(&formula), Ok(()));
+        // A clause learned after backtracking chronologically implies x4 at level 1, above the assignments of level 2
+        formula.add(Clause(vec![Variable::Negative(1), Variable::Positive(4)]));
+        assert_eq!(trail.propagate(&formula), Ok(()));
+        assert_eq!(trail.get(3).unwrap().level, 2);
+        assert_eq!(trail.get(4).unwrap().level, 1);
+        trail.backtrack(1);
+        assert_eq!(
+            trail
+                .iter()
+                .map(|assignment| assignment.variable)
+                .collect::<Vec<_>>(),
+            [Variable::Positive(1), Variable::Positive(4)]
+        );
+        assert_eq!(trail.get(4).unwrap().antecedent, Some(1));
+        assert_eq!(trail.value(3), None);
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::HashSet;
+
+use crate::types::{Clause, Formula, Literal, Variable};
+
+/// A Boolean function given by its value for every assignment to its variables.
+/// The rows are in counting order, with the first variable as the most significant bit,
+/// so the first row has all variables false and the last row all variables true.
+///
+/// ## Examples
+/// ```plaintext
+/// table x1 x2 : 0110
+/// x1 XOR x2
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TruthTable {
+    pub variables: Vec<Literal>,
+    /// The value of the function in each row, `2^n` values for `n` variables.
+    pub values: Vec<bool>,
+}
+
+/// A set of rows given by the values of the bits outside the mask, which can have any value.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct Term {
+    bits: usize,
+    mask: usize,
+}
+
+impl Term {
+    fn covers(&self, row: usize) -> bool {
+        row & !self.mask == self.bits
+    }
+}
+
+impl TruthTable {
+    /// Get the canonical CNF of the function, with a clause excluding each row where it is false.
+    pub fn to_cnf(&self) -> Formula {
+        self.false_rows()
+            .map(|row| self.clause(Term { bits: row, mask: 0 }))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Get a smaller CNF of the function by the Quine-McCluskey method, with a clause for each prime implicate
+    /// in a cover of the rows where it is false. Essential prime implicates are chosen first,
+    /// and then the ones excluding the most remaining rows, which is not always the smallest cover.
+    pub fn to_cnf_minimized(&self) -> Formula {
+        let rows = self.false_rows().collect::<Vec<_>>();
+        let primes = prime_terms(&rows);
+        let mut uncovered = rows.iter().copied().collect::<HashSet<_>>();
+        let mut cover = Vec::new();
+        for row in &rows {
+            let mut covering = primes.iter().filter(|term| term.covers(*row));
+            if let (Some(term), None) = (covering.next(), covering.next()) {
+                if !cover.contains(term) {
+                    cover.push(*term);
+                }
+            }
+        }
+        uncovered.retain(|row| !cover.iter().any(|term| term.covers(*row)));
+        while !uncovered.is_empty() {
+            let best = *primes
+                .iter()
+                .max_by_key(|term| uncovered.iter().filter(|row| term.covers(**row)).count())
+                .unwrap();
+            uncovered.retain(|row| !best.covers(*row));
+            cover.push(best);
+        }
+        cover
+            .into_iter()
+            .map(|term| self.clause(term))
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    fn false_rows(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.values.len()).filter(|row| !self.values[*row])
+    }
+
+    /// Get the clause that is false exactly in the rows of the term.
+    fn clause(&self, term: Term) -> Clause {
+        let width = self.variables.len();
+        let variables = self
+            .variables
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| term.mask >> (width - 1 - i) & 1 == 0)
+            .map(|(i, id)| {
+                if term.bits >> (width - 1 - i) & 1 == 1 {
+                    Variable::Negative(*id)
+                } else {
+                    Variable::Positive(*id)
+                }
+            })
+            .collect();
+        Clause(variables)
+    }
+}
+
+/// Find the terms that can't be merged into a larger one, by merging terms differing in one bit until none are left.
+fn prime_terms(rows: &[usize]) -> Vec<Term> {
+    let mut primes = Vec::new();
+    let mut terms = rows
+        .iter()
+        .map(|row| Term {
+            bits: *row,
+            mask: 0,
+        })
+        .collect::<Vec<_>>();
+    while !terms.is_empty() {
+        let mut merged = Vec::new();
+        let mut used = vec![false; terms.len()];
+        for i in 0..terms.len() {
+            for j in i + 1..terms.len() {
+                let (a, b) = (terms[i], terms[j]);
+                let difference = a.bits ^ b.bits;
+                if a.mask == b.mask && difference.count_ones() == 1 {
+                    merged.push(Term {
+                        bits: a.bits & !difference,
+                        mask: a.mask | difference,
+                    });
+                    used[i] = true;
+                    used[j] = true;
+                }
+            }
+        }
+        primes.extend(
+            terms
+                .iter()
+                .zip(&used)
+                .filter(|(_, used)| !**used)
+                .map(|(term, _)| *term),
+        );
+        let mut seen = HashSet::new();
+        merged.retain(|term| seen.insert(*term));
+        terms = merged;
+    }
+    primes
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{solver::solve_all, solvers::Dfs};
+
+    use super::*;
+
+    #[test]
+    fn test_truth_table_cnf() {
+        // x1 x2 x3 : 10110100
+        let table = TruthTable {
+            variables: vec![1, 2, 3],
+            values: [1, 0, 1, 1, 0, 1, 0, 0].map(|value| value == 1).to_vec(),
+        };
+        let canonical = table.to_cnf();
+        assert_eq!(canonical.len(), 4);
+        let minimized = table.to_cnf_minimized();
+        assert_eq!(
+            minimized.to_string(),
+            "(X1 or X2 or -X3) and (-X1 or X3) and (-X1 or -X2)"
+        );
+        for formula in [canonical, minimized] {
+            let mut rows = solve_all(&formula, &Dfs::default())
+                .iter()
+                .map(|solution| (1..=3).fold(0, |row, id| 2 * row + usize::from(solution.get(id))))
+                .collect::<Vec<_>>();
+            rows.sort();
+            assert_eq!(rows, [0, 2, 3, 5]);
+        }
+    }
+
+    #[test]
+    fn test_truth_table_constants() {
+        let table = TruthTable {
+            variables: vec![1],
+            values: vec![true, true],
+        };
+        assert!(table.to_cnf_minimized().is_empty());
+        let table = TruthTable {
+            variables: vec![1],
+            values: vec![false, false],
+        };
+        assert_eq!(table.to_cnf_minimized().to_string(), "()");
+        assert_eq!(table.to_cnf().len(), 2);
+    }
+}
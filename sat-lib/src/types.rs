@@ -1,8 +1,107 @@
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::{self, Display, Formatter, Write},
+    hash::{Hash, Hasher},
+    ops::Not,
+};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{expr::Expr, solver::solve_assuming, solvers::Solver};
 
 /// A literal is a identifier of a variable.
 pub type Literal = u32;
 
+/// The largest identifier of a variable, so every variable fits in a [`Lit`].
+pub const MAX_LITERAL: Literal = i32::MAX as Literal - 1;
+
+/// A symbol table mapping variable names to literal identifiers.
+/// Numbered variables like `x3` can be mixed with named ones,
+/// as names are given identifiers above every reserved number.
+///
+/// ## Examples
+/// ```plaintext
+/// (rain OR sprinkler) AND -wet
+/// rain = 1, sprinkler = 2, wet = 3
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VarPool {
+    ids: HashMap<String, Literal>,
+    names: HashMap<Literal, String>,
+    /// The variables introduced by encodings rather than by the user.
+    auxiliary: HashSet<Literal>,
+    last: Literal,
+}
+
+impl VarPool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get the identifier of a named variable, creating a new one if the name is unknown.
+    pub fn id(&mut self, name: &str) -> Literal {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+        self.last += 1;
+        self.ids.insert(name.to_string(), self.last);
+        self.names.insert(self.last, name.to_string());
+        self.last
+    }
+
+    /// Get the identifier of a named variable, if the name is known.
+    pub fn get(&self, name: &str) -> Option<Literal> {
+        self.ids.get(name).copied()
+    }
+
+    /// Get the name of a variable, if it has one.
+    pub fn name(&self, id: Literal) -> Option<&str> {
+        self.names.get(&id).map(String::as_str)
+    }
+
+    /// Reserve an identifier for a numbered variable, so new names are given higher identifiers.
+    /// Returns `false` if the identifier already belongs to a name.
+    /// An auxiliary variable of an earlier encoding that is reserved is no longer auxiliary,
+    /// as it's now used by the user. Identifiers above [`MAX_LITERAL`] are not reserved, returning `false`.
+    pub fn reserve(&mut self, id: Literal) -> bool {
+        if id > MAX_LITERAL {
+            return false;
+        }
+        self.last = self.last.max(id);
+        self.auxiliary.remove(&id);
+        !self.names.contains_key(&id)
+    }
+
+    /// Create an unnamed auxiliary variable above every identifier in use.
+    pub fn fresh(&mut self) -> Literal {
+        self.last += 1;
+        self.auxiliary.insert(self.last);
+        self.last
+    }
+
+    /// Check if a variable was created by [`VarPool::fresh`], so it can be projected out of solutions.
+    pub fn is_auxiliary(&self, id: Literal) -> bool {
+        self.auxiliary.contains(&id)
+    }
+
+    /// Get the names of the variables, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        let mut names = self.ids.keys().map(String::as_str).collect::<Vec<_>>();
+        names.sort();
+        names
+    }
+
+    /// Get the number of named variables.
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
 /// A variable is a symbol that can be assigned a truth value.
 /// It can be either positive or negative.
 ///
@@ -11,12 +110,104 @@ pub type Literal = u32;
 /// x1
 /// -x2
 /// ```
+/// Serialized as `{"Positive": 1}` and `{"Negative": 2}` with the `serde` feature.
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub enum Variable {
     Positive(Literal),
     Negative(Literal),
 }
 
+impl Variable {
+    /// Get the literal identifier of the variable.
+    pub fn id(&self) -> Literal {
+        match self {
+            Variable::Positive(id) | Variable::Negative(id) => *id,
+        }
+    }
+
+    pub fn is_positive(&self) -> bool {
+        matches!(self, Variable::Positive(_))
+    }
+
+    /// Get the variable with the opposite sign.
+    pub fn negated(&self) -> Variable {
+        match self {
+            Variable::Positive(id) => Variable::Negative(*id),
+            Variable::Negative(id) => Variable::Positive(*id),
+        }
+    }
+}
+
+/// Plain text in the normal print style, like `-X1`.
+impl Display for Variable {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Variable::Positive(id) => write!(f, "X{}", id),
+            Variable::Negative(id) => write!(f, "-X{}", id),
+        }
+    }
+}
+
+/// A literal packed into a signed integer: the variable identifier plus one, negated for negative literals.
+/// It takes half the memory of a [`Variable`], so solvers use it for clauses they evaluate many times,
+/// while [`Variable`] remains the type used by formulas and the rest of the API.
+///
+/// ## Examples
+/// ```plaintext
+///  x1 = Lit(2)
+/// -x1 = Lit(-2)
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Lit(i32);
+
+impl Lit {
+    /// ## Panics
+    /// If the identifier is above [`MAX_LITERAL`].
+    pub fn new(id: Literal, positive: bool) -> Self {
+        let packed = i32::try_from(id + 1).expect("Variable identifier too large for a Lit");
+        Self(if positive { packed } else { -packed })
+    }
+
+    /// Get the literal identifier of the variable.
+    pub fn var(self) -> Literal {
+        self.0.unsigned_abs() - 1
+    }
+
+    pub fn is_positive(self) -> bool {
+        self.0 > 0
+    }
+
+    /// Check if the literal is true under an assignment indexed by variable identifier.
+    pub fn eval(self, values: &[bool]) -> bool {
+        values[self.var() as usize] == self.is_positive()
+    }
+}
+
+impl Not for Lit {
+    type Output = Lit;
+
+    fn not(self) -> Lit {
+        Lit(-self.0)
+    }
+}
+
+impl From<Variable> for Lit {
+    fn from(variable: Variable) -> Self {
+        Lit::new(variable.id(), variable.is_positive())
+    }
+}
+
+impl From<Lit> for Variable {
+    fn from(lit: Lit) -> Self {
+        if lit.is_positive() {
+            Variable::Positive(lit.var())
+        } else {
+            Variable::Negative(lit.var())
+        }
+    }
+}
+
 /// A clause is a disjunction of variables.
 ///
 /// ## Examples
@@ -24,7 +215,9 @@ pub enum Variable {
 ///  x1 OR x2 OR  x3
 /// -x1 OR x2 OR -x3
 /// ```
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// Serialized as a list of variables with the `serde` feature.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
 pub struct Clause(pub(crate) Vec<Variable>);
 
 impl Clause {
@@ -47,9 +240,41 @@ impl Clause {
         self.0.len()
     }
 
-    pub fn iter(&self) -> std::slice::Iter<Variable> {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Variable> {
         self.0.iter()
     }
+
+    /// Check if the clause contains both a variable and its negation, so every assignment satisfies it.
+    pub fn is_tautology(&self) -> bool {
+        self.0
+            .iter()
+            .any(|variable| self.0.contains(&variable.negated()))
+    }
+
+    /// Remove repeated variables and sort the rest by identifier, with the positive variable first.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x3 OR -x1 OR x3) => (-x1 OR x3)
+    /// ```
+    pub fn normalize(&mut self) {
+        self.0
+            .sort_by_key(|variable| (variable.id(), !variable.is_positive()));
+        self.0.dedup();
+    }
+}
+
+/// Plain text in the normal print style, like `(X1 or -X2)`.
+impl Display for Clause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "(")?;
+        join(f, &self.0, " or ")?;
+        write!(f, ")")
+    }
 }
 
 /// A boolean propositional formula in conjunctive normal form (CNF).
@@ -59,20 +284,158 @@ impl Clause {
 /// ( x1 OR -x2) AND x3
 /// (-x1 OR  x2) AND (x1 OR -x2) AND (-x3 OR x1)
 /// ```
+/// Serialized as a list of clauses with the `serde` feature.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Formula(pub(crate) Vec<Clause>);
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(from = "Vec<Clause>", into = "Vec<Clause>")
+)]
+pub struct Formula(pub(crate) Vec<Clause>, Occurrences);
+
+/// The number of occurrences of each literal and the unit clauses of a formula,
+/// updated as clauses are added so they don't need a scan over all clauses.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub(crate) struct Occurrences {
+    counts: HashMap<Variable, usize>,
+    units: Vec<Variable>,
+}
+
+impl Occurrences {
+    fn add(&mut self, clause: &Clause) {
+        for variable in clause.iter() {
+            *self.counts.entry(*variable).or_default() += 1;
+        }
+        if let [unit] = clause.0[..] {
+            if !self.units.contains(&unit) {
+                self.units.push(unit);
+            }
+        }
+    }
+}
 
 impl Formula {
     /// Create a new empty formula.
     pub fn new() -> Self {
-        Self(Vec::new())
+        Self(Vec::new(), Occurrences::default())
     }
 
     /// Add a new clause to the formula.
     pub fn add(&mut self, clause: Clause) {
+        self.1.add(&clause);
         self.0.push(clause);
     }
 
+    /// Count the occurrences again after the clauses were changed in place.
+    pub(crate) fn recount(&mut self) {
+        self.1 = Occurrences::default();
+        for clause in &self.0 {
+            self.1.add(clause);
+        }
+    }
+
+    /// Get the literals of the unit clauses, in the order they were added.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// x1 AND (x2 OR x3) AND -x4
+    /// x1, -x4
+    /// ```
+    pub fn unit_clauses(&self) -> &[Variable] {
+        &self.1.units
+    }
+
+    /// Get the pure literals, whose negation doesn't occur in the formula, ordered by variable.
+    /// Setting a pure literal true satisfies its clauses without falsifying any others.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR -x2) AND (-x1 OR -x2 OR x3)
+    /// -x2, x3
+    /// ```
+    pub fn pure_literals(&self) -> Vec<Variable> {
+        let mut pure = self
+            .1
+            .counts
+            .keys()
+            .filter(|variable| !self.1.counts.contains_key(&variable.negated()))
+            .copied()
+            .collect::<Vec<_>>();
+        pure.sort_by_key(|variable| variable.id());
+        pure
+    }
+
+    /// Add a clause that can be removed again, guarded by a new activation variable from the pool.
+    /// The clause only constrains solutions while the activation variable is assumed,
+    /// so solve with [`ClauseId::assumption`] of every active handle, see [`solve_assuming`].
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// x1 OR x2 with activation variable x3
+    /// (x1 OR x2 OR -x3)
+    /// ```
+    pub fn add_removable(&mut self, clause: Clause, pool: &mut VarPool) -> ClauseId {
+        self.reserve(pool);
+        for id in clause.literals() {
+            pool.reserve(id);
+        }
+        let id = ClauseId(pool.fresh());
+        self.add_to(id, clause);
+        id
+    }
+
+    /// Add a clause to the group of a removable clause, so they are assumed and removed together.
+    pub fn add_to(&mut self, id: ClauseId, mut clause: Clause) {
+        clause.0.push(id.assumption().negated());
+        self.add(clause);
+    }
+
+    /// Remove the clauses added with a handle.
+    pub fn remove(&mut self, id: ClauseId) {
+        let guard = id.assumption().negated();
+        self.0.retain(|clause| !clause.0.contains(&guard));
+        self.recount();
+    }
+
+    /// Add a new clause to the formula after normalizing it, unless it is a tautology.
+    pub fn add_normalized(&mut self, mut clause: Clause) {
+        if !clause.is_tautology() {
+            clause.normalize();
+            self.add(clause);
+        }
+    }
+
+    /// Normalize every clause and drop the tautologies, which only waste the work of solvers.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR x1 OR -x1) AND (x2 OR x2) => (x2)
+    /// ```
+    pub fn normalize(&mut self) {
+        self.0.retain(|clause| !clause.is_tautology());
+        self.0.iter_mut().for_each(Clause::normalize);
+        self.recount();
+    }
+
+    /// Normalize the formula and sort its clauses, removing repeated ones, so formulas with the same clauses
+    /// in any order and with any repetitions get the same canonical form.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x3 OR -x1) AND x2 AND (x2 OR x2) => (-x1 OR x3) AND x2
+    /// ```
+    pub fn canonicalize(&mut self) {
+        self.normalize();
+        self.0.sort_by_cached_key(|clause| {
+            clause
+                .iter()
+                .map(|variable| (variable.id(), !variable.is_positive()))
+                .collect::<Vec<_>>()
+        });
+        self.0.dedup();
+        self.recount();
+    }
+
     /// Get all literal variables in the formula.
     pub fn literals(&self) -> Vec<Literal> {
         let mut variables = Vec::new();
@@ -88,134 +451,1854 @@ impl Formula {
         self.0.len()
     }
 
-    pub fn iter(&self) -> std::slice::Iter<Clause> {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Clause> {
         self.0.iter()
     }
-}
 
-impl From<Vec<Vec<Variable>>> for Formula {
-    fn from(clauses: Vec<Vec<Variable>>) -> Self {
-        let mut formula = Formula::new();
-        for clause in clauses {
-            formula.add(Clause(clause));
-        }
-        formula
+    /// Get the variables in use, to index assignments densely by, see [`Domain::lits`].
+    pub fn domain(&self) -> Domain {
+        Domain::from(self)
     }
-}
 
-/// A solution is a mapping of variables to truth values.
-///
-/// ## Examples
-/// ```plaintext
-/// x1 = true, x2 = false, x3 = true
-/// ```
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Solution(pub(crate) HashMap<Literal, bool>);
+    /// Convert the formula to an equivalent formula in DNF by distributing the clauses over each other,
+    /// dropping contradictory cubes and cubes containing another cube.
+    ///
+    /// The number of cubes can grow exponentially with the number of clauses,
+    /// so the conversion gives up and returns `None` if there are ever more than `limit` cubes.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR x2) AND (-x1 OR x3)
+    /// (x1 AND x3) OR (x2 AND -x1) OR (x2 AND x3)
+    /// ```
+    pub fn to_dnf(&self, limit: usize) -> Option<Dnf> {
+        let mut cubes: Vec<Vec<Variable>> = vec![Vec::new()];
+        for clause in &self.0 {
+            let mut next: Vec<Vec<Variable>> = Vec::new();
+            for cube in &cubes {
+                for variable in clause.iter() {
+                    if cube.contains(&variable.negated()) {
+                        continue;
+                    }
+                    let mut cube = cube.clone();
+                    if !cube.contains(variable) {
+                        cube.push(*variable);
+                    }
+                    next.push(cube);
+                }
+            }
+            // Absorption: a cube implied by a smaller cube adds no solutions
+            next.sort_by_key(Vec::len);
+            let mut absorbed: Vec<Vec<Variable>> = Vec::new();
+            for cube in next {
+                if !absorbed
+                    .iter()
+                    .any(|other| other.iter().all(|variable| cube.contains(variable)))
+                {
+                    absorbed.push(cube);
+                }
+            }
+            if absorbed.len() > limit {
+                return None;
+            }
+            cubes = absorbed;
+        }
+        Some(Dnf(cubes.into_iter().map(Cube).collect()))
+    }
 
-impl Solution {
-    /// Create a new empty solution.
-    pub fn new() -> Self {
-        Self(HashMap::new())
+    /// Renumber the variables densely from `x1`, keeping their order,
+    /// and get the map to translate between the old and new identifiers.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x3 OR -x10) AND x42
+    /// (x1 OR -x2) AND x3
+    /// ```
+    pub fn compact(&self) -> (Formula, VarMap) {
+        let map = VarMap::new(&self.literals());
+        (self.rename(&map), map)
     }
 
-    /// Check if a variable is assigned a truth value.
-    pub fn get(&self, id: Literal) -> bool {
-        if let Some(value) = self.0.get(&id) {
-            *value
-        } else {
-            panic!("Variable x{} is not assigned a value", id);
-        }
+    /// Rename the variables in the map, keeping the others.
+    /// Clauses are kept as they are, even if renaming two variables to the same one repeats it,
+    /// see [`Formula::normalize`].
+    pub fn rename(&self, map: &VarMap) -> Formula {
+        self.0
+            .iter()
+            .map(|clause| Clause(clause.0.iter().map(|v| map.rename(*v)).collect()))
+            .collect::<Vec<_>>()
+            .into()
     }
 
-    /// Assign a truth value to a variable.
-    pub fn set(&mut self, id: Literal, value: bool) {
-        self.0.insert(id, value);
+    /// Replace a variable by an expression, like a literal, a constant or another variable,
+    /// and simplify the clauses it occurs in. The other clauses are kept as they are.
+    ///
+    /// Each clause with the variable is converted to CNF by [`Expr::to_cnf_exact`] after the substitution,
+    /// so the number of clauses can grow exponentially with the size of a larger expression.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR x2) AND (-x1 OR x3) with x1 = -x3
+    /// (x2 OR -x3) AND x3
+    /// ```
+    pub fn substitute(&self, id: Literal, expr: &Expr) -> Formula {
+        let mut formula = Formula::new();
+        for clause in &self.0 {
+            if !clause.0.iter().any(|variable| variable.id() == id) {
+                formula.add(clause.clone());
+                continue;
+            }
+            let substituted = Expr::any(clause.0.iter().map(|variable| match variable {
+                Variable::Positive(x) if *x == id => expr.clone(),
+                Variable::Negative(x) if *x == id => !expr.clone(),
+                variable => Expr::from(*variable),
+            }));
+            for clause in substituted.to_cnf_exact().0 {
+                formula.add(clause);
+            }
+        }
+        formula
     }
 
-    pub fn literals(&self) -> Vec<Literal> {
-        let mut variables = self.0.keys().copied().collect::<Vec<_>>();
-        variables.sort();
-        variables
+    /// Simplify the formula under a partial assignment, removing the satisfied clauses
+    /// and the falsified variables from the others. Unassigned variables are kept.
+    ///
+    /// The residual formula is empty if the assignment satisfies the formula,
+    /// and a single empty clause if it falsifies a clause.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR x2) AND (-x1 OR x3) AND (x2 OR -x3) under x1 = T
+    /// x3 AND (x2 OR -x3)
+    /// ```
+    pub fn assign(&self, assignment: &PartialAssignment) -> Formula {
+        let mut residual = Formula::new();
+        for clause in &self.0 {
+            let mut remaining = Vec::new();
+            let mut satisfied = false;
+            for variable in &clause.0 {
+                match assignment.value(*variable) {
+                    Some(true) => {
+                        satisfied = true;
+                        break;
+                    }
+                    Some(false) => (),
+                    None => remaining.push(*variable),
+                }
+            }
+            if satisfied {
+                continue;
+            }
+            if remaining.is_empty() {
+                return Formula::from(vec![Clause(Vec::new())]);
+            }
+            residual.add(Clause(remaining));
+        }
+        residual
     }
 
-    /// Set all variables to `false`.
-    pub fn reset(&mut self) {
-        self.0.iter_mut().for_each(|(_, value)| *value = false);
+    /// Get the conjunction of the formulas, with the clauses of both.
+    pub fn and(&self, other: &Formula) -> Formula {
+        self.0
+            .iter()
+            .chain(&other.0)
+            .cloned()
+            .collect::<Vec<_>>()
+            .into()
     }
 
-    pub fn satisfy(&self, formula: &Formula) -> bool {
-        for clause in &formula.0 {
-            if !clause.0.iter().any(|variable| match variable {
-                Variable::Positive(id) => self.get(*id),
-                Variable::Negative(id) => !self.get(*id),
-            }) {
-                return false;
+    /// Get the disjunction of the formulas in CNF, using an auxiliary selector variable from the pool.
+    /// Its models projected onto the variables of the formulas are the models of either formula.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR x2) or (-x1 AND x3)
+    /// (x4 OR x1 OR x2) AND (-x4 OR -x1) AND (-x4 OR x3)
+    /// ```
+    pub fn or(&self, other: &Formula, pool: &mut VarPool) -> Formula {
+        self.reserve(pool);
+        other.reserve(pool);
+        let selector = pool.fresh();
+        let mut formula = Formula::new();
+        for (clauses, selector) in [
+            (self, Variable::Positive(selector)),
+            (other, Variable::Negative(selector)),
+        ] {
+            for clause in clauses.iter() {
+                let mut clause = clause.clone();
+                clause.0.insert(0, selector);
+                formula.add(clause);
             }
         }
-        true
+        formula
     }
 
-    /// When a new solution is found, Add a new clause that forbids that exact solution. \
-    /// Done using **De Morgan's Laws**:
+    /// Get the negation of the formula in CNF, using an auxiliary variable from the pool per clause
+    /// that implies the clause is falsified, and requiring one of them to be true.
+    /// Its models projected onto the variables of the formula are the assignments that falsify it.
+    ///
+    /// ## Examples
     /// ```plaintext
-    /// -(x1 AND x2 ... AND xN)  =>  (-x1 OR -x2 OR ... OR -xN)
+    /// not (x1 OR x2) AND x3
+    /// (-x4 OR -x1) AND (-x4 OR -x2) AND (-x5 OR -x3) AND (x4 OR x5)
     /// ```
-    pub fn negative_clause(&self) -> Clause {
-        let mut clause = Vec::new();
-        for (id, value) in &self.0 {
-            // if !value {
-            //     clause.push(Variable::Positive(*id));
-            // }
-            if *value {
-                clause.push(Variable::Negative(*id));
-            } else {
-                clause.push(Variable::Positive(*id));
+    pub fn not(&self, pool: &mut VarPool) -> Formula {
+        self.reserve(pool);
+        let mut formula = Formula::new();
+        let mut falsified = Vec::new();
+        for clause in &self.0 {
+            let y = pool.fresh();
+            for variable in clause.iter() {
+                formula.add(Clause(vec![Variable::Negative(y), variable.negated()]));
             }
+            falsified.push(Variable::Positive(y));
         }
-        Clause(clause)
+        formula.add(Clause(falsified));
+        formula
     }
-}
 
-/// Create a new solution from a list of pairs of literals and truth values.
-impl From<&[(Literal, bool)]> for Solution {
-    fn from(pairs: &[(Literal, bool)]) -> Self {
-        let mut solution = Solution::new();
-        for (id, value) in pairs {
-            solution.set(*id, *value);
+    /// Get the implication from the formula to the other formula in CNF, as the disjunction
+    /// of the negation of the formula and the other formula.
+    pub fn implies(&self, other: &Formula, pool: &mut VarPool) -> Formula {
+        self.not(pool).or(other, pool)
+    }
+
+    /// Reserve the variables of the formula in the pool, so auxiliary variables are above them.
+    fn reserve(&self, pool: &mut VarPool) {
+        for id in self.literals() {
+            pool.reserve(id);
         }
-        solution
     }
-}
 
-/// A certificate is a proof that a formula is satisfiable or unsatisfiable.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub enum Certificate {
-    Unsatisfiable,
-    Satisfiable(Solution),
-}
+    /// Check if every model of the formula is a model of the other formula,
+    /// by checking that the formula and the negation of the other formula has no model.
+    ///
+    /// Returns `false` if the solver gives up before deciding it.
+    pub fn entails<S: Solver + ?Sized>(&self, other: &Formula, solver: &S) -> bool {
+        let mut pool = VarPool::new();
+        self.reserve(&mut pool);
+        let miter = self.and(&other.not(&mut pool));
+        solve_assuming(&miter, &[], solver) == Certificate::Unsatisfiable
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    /// Get the clauses added and removed going from the formula to the other formula.
+    /// Both are canonicalized first, so reordered clauses and variables, repetitions
+    /// and tautologies are not differences, see [`Formula::canonicalize`].
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR x2) AND x3 to x3 AND (x2 OR x1) AND -x4
+    /// + (-X4)
+    /// ```
+    pub fn diff(&self, other: &Formula) -> FormulaDiff {
+        let (mut before, mut after) = (self.clone(), other.clone());
+        before.canonicalize();
+        after.canonicalize();
+        let missing = |clauses: &Formula, from: &Formula| {
+            let present = from.0.iter().collect::<HashSet<_>>();
+            clauses
+                .0
+                .iter()
+                .filter(|clause| !present.contains(clause))
+                .cloned()
+                .collect()
+        };
+        FormulaDiff {
+            added: missing(&after, &before),
+            removed: missing(&before, &after),
+        }
+    }
 
-    #[test]
-    fn test_formula_get_literals() {
-        let formula = Formula(vec![
-            Clause(vec![Variable::Positive(1), Variable::Negative(2)]),
-            Clause(vec![Variable::Positive(2), Variable::Negative(3)]),
-        ]);
-        assert_eq!(formula.literals(), vec![1, 2, 3]);
+    /// Check if the formulas have the same models, by checking that each entails the other.
+    ///
+    /// Returns `false` if the solver gives up before deciding it.
+    pub fn equivalent<S: Solver + ?Sized>(&self, other: &Formula, solver: &S) -> bool {
+        self.entails(other, solver) && other.entails(self, solver)
     }
 
-    #[test]
-    fn test_solution_satisfy() {
-        // (x1 OR -x2) AND (x2 OR -x3)
-        let formula = vec![
-            vec![Variable::Positive(1), Variable::Negative(2)],
-            vec![Variable::Positive(2), Variable::Negative(3)],
+    /// Get the variables whose value never affects whether the formula is satisfied,
+    /// where the formula with the variable false is equivalent to the formula with it true.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR x2) AND (x1 OR -x2) AND (x3 OR -x3)
+    /// x2, x3
+    /// ```
+    pub fn dont_cares<S: Solver + ?Sized>(&self, solver: &S) -> Vec<Literal> {
+        self.literals()
+            .into_iter()
+            .filter(|id| {
+                let unset = self.substitute(*id, &Expr::constant(false));
+                unset.equivalent(&self.substitute(*id, &Expr::constant(true)), solver)
+            })
+            .collect()
+    }
+
+    /// Get an independent support of the formula, a set of variables whose values in a model
+    /// determine the values of all other variables. Enumerating or counting the models projected
+    /// onto the support gives one projected model per model, often with far fewer variables.
+    ///
+    /// The support is found greedily by Padoa's method: a variable is dropped if two models
+    /// agreeing on the rest of the support can't disagree on it.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (-x3 OR x1) AND (-x3 OR x2) AND (x3 OR -x1 OR -x2)
+    /// x1, x2
+    /// ```
+    pub fn independent_support<S: Solver + ?Sized>(&self, solver: &S) -> Vec<Literal> {
+        let variables = self.literals();
+        let mut pool = VarPool::new();
+        self.reserve(&mut pool);
+        let mut copy = VarMap::default();
+        for id in &variables {
+            copy.insert(*id, pool.fresh());
+        }
+        let both = self.and(&self.rename(&copy));
+        let mut support = variables.clone();
+        for id in &variables {
+            let mut padoa = both.clone();
+            for other in support.iter().filter(|other| *other != id) {
+                let (a, b) = (*other, copy.get(*other).unwrap());
+                padoa.add(Clause(vec![Variable::Negative(a), Variable::Positive(b)]));
+                padoa.add(Clause(vec![Variable::Positive(a), Variable::Negative(b)]));
+            }
+            let disagree = [
+                Variable::Positive(*id),
+                Variable::Negative(copy.get(*id).unwrap()),
+            ];
+            if solve_assuming(&padoa, &disagree, solver) == Certificate::Unsatisfiable {
+                support.retain(|other| other != id);
+            }
+        }
+        support
+    }
+
+    /// Get the backbone of the formula, the variables that are assigned the same value in every model.
+    /// Each candidate is tested by assuming its negation, and every new model found along the way
+    /// rules out the candidates it disagrees with.
+    ///
+    /// Returns an empty list if the formula is unsatisfiable.
+    pub fn backbone<S: Solver + ?Sized>(&self, solver: &S) -> Vec<Variable> {
+        let Certificate::Satisfiable(model) = solve_assuming(self, &[], solver) else {
+            return Vec::new();
+        };
+        let mut candidates = model
+            .literals()
+            .into_iter()
+            .map(|id| {
+                if model.get(id) {
+                    Variable::Positive(id)
+                } else {
+                    Variable::Negative(id)
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut backbone = Vec::new();
+        while let Some(candidate) = candidates.pop() {
+            match solve_assuming(self, &[candidate.negated()], solver) {
+                Certificate::Unsatisfiable => backbone.push(candidate),
+                Certificate::Satisfiable(other) => {
+                    candidates.retain(|variable| other.get(variable.id()) == variable.is_positive())
+                }
+                // Only literals proven to be fixed are part of the backbone
+                Certificate::Indeterminate => (),
+            }
+        }
+        backbone.sort_by_key(Variable::id);
+        backbone
+    }
+
+    /// Get the clause-variable incidence graph in the Graphviz DOT language, for rendering with `dot` or `neato`.
+    /// Every variable `xN` and clause `cN` is a node, and each clause is connected to its variables,
+    /// by a solid edge for a positive literal and a dashed edge for a negative one.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR -x2)
+    /// graph incidence {
+    ///   node [shape=circle];
+    ///   x1; x2;
+    ///   node [shape=box];
+    ///   c1 -- x1;
+    ///   c1 -- x2 [style=dashed];
+    /// }
+    /// ```
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("graph incidence {\n  node [shape=circle];\n ");
+        for id in self.literals() {
+            write!(dot, " x{};", id).unwrap();
+        }
+        dot.push_str("\n  node [shape=box];\n");
+        for (i, clause) in self.0.iter().enumerate() {
+            if clause.is_empty() {
+                writeln!(dot, "  c{};", i + 1).unwrap();
+            }
+            for variable in clause.iter() {
+                let style = if variable.is_positive() {
+                    ""
+                } else {
+                    " [style=dashed]"
+                };
+                writeln!(dot, "  c{} -- x{}{};", i + 1, variable.id(), style).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Get the variable interaction graph in the Graphviz DOT language,
+    /// with an edge between every two variables occurring together in some clause.
+    pub fn to_interaction_dot(&self) -> String {
+        let mut dot = String::from("graph interaction {\n  node [shape=circle];\n ");
+        for id in self.literals() {
+            write!(dot, " x{};", id).unwrap();
+        }
+        dot.push('\n');
+        let mut edges = BTreeSet::new();
+        for clause in &self.0 {
+            let ids = clause.literals();
+            for (i, a) in ids.iter().enumerate() {
+                for b in &ids[i + 1..] {
+                    edges.insert((*a, *b));
+                }
+            }
+        }
+        for (a, b) in edges {
+            writeln!(dot, "  x{} -- x{};", a, b).unwrap();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// A handle to removable clauses, named by the activation variable guarding them.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ClauseId(Literal);
+
+impl ClauseId {
+    /// Get the assumption that activates the clauses of the handle.
+    pub fn assumption(&self) -> Variable {
+        Variable::Positive(self.0)
+    }
+}
+
+/// A formula in canonical form, see [`Formula::canonicalize`], for use as a key of hash maps and sets.
+/// Formulas with the same clauses in any order and with any repetitions or tautologies are equal and hash the same.
+///
+/// ## Examples
+/// ```plaintext
+/// (x3 OR -x1) AND x2 AND (x2 OR x2) == x2 AND (-x1 OR x3)
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CanonicalFormula(Formula);
+
+impl CanonicalFormula {
+    pub fn formula(&self) -> &Formula {
+        &self.0
+    }
+
+    pub fn into_formula(self) -> Formula {
+        self.0
+    }
+}
+
+impl From<Formula> for CanonicalFormula {
+    fn from(mut formula: Formula) -> Self {
+        formula.canonicalize();
+        Self(formula)
+    }
+}
+
+/// Hashes the clauses, which are in canonical order, leaving out the occurrences that follow from them.
+impl Hash for CanonicalFormula {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0 .0.hash(state);
+    }
+}
+
+impl Default for Formula {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Vec<Clause>> for Formula {
+    fn from(clauses: Vec<Clause>) -> Self {
+        let mut formula = Formula::new();
+        for clause in clauses {
+            formula.add(clause);
+        }
+        formula
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Formula> for Vec<Clause> {
+    fn from(formula: Formula) -> Self {
+        formula.0
+    }
+}
+
+impl From<Vec<Vec<Variable>>> for Formula {
+    fn from(clauses: Vec<Vec<Variable>>) -> Self {
+        let mut formula = Formula::new();
+        for clause in clauses {
+            formula.add(Clause(clause));
+        }
+        formula
+    }
+}
+
+/// Plain text in the normal print style, like `(X1 or -X2) and (X3)`.
+impl Display for Formula {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        join(f, &self.0, " and ")
+    }
+}
+
+/// A solution is a mapping of variables to truth values.
+/// Variables whose value doesn't affect satisfaction can be marked as don't-care.
+///
+/// ## Examples
+/// ```plaintext
+/// x1 = true, x2 = false, x3 = true
+/// x1 = true, x2 = false, x3 = *
+/// ```
+/// Serialized as `{"values": {"1": true, "2": false}, "dont_care": [3]}` with the `serde` feature,
+/// where `dont_care` is left out when empty.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize, Serialize),
+    serde(from = "SolutionData", into = "SolutionData")
+)]
+pub struct Solution(
+    pub(crate) HashMap<Literal, bool>,
+    pub(crate) HashSet<Literal>,
+);
+
+/// The serialized form of a solution, with the variables sorted so the output is stable.
+#[cfg(feature = "serde")]
+#[derive(Deserialize, Serialize)]
+struct SolutionData {
+    values: BTreeMap<Literal, bool>,
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    dont_care: BTreeSet<Literal>,
+}
+
+#[cfg(feature = "serde")]
+impl From<Solution> for SolutionData {
+    fn from(solution: Solution) -> Self {
+        Self {
+            values: solution.0.into_iter().collect(),
+            dont_care: solution.1.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<SolutionData> for Solution {
+    fn from(data: SolutionData) -> Self {
+        Self(
+            data.values.into_iter().collect(),
+            data.dont_care.into_iter().collect(),
+        )
+    }
+}
+
+impl Solution {
+    /// Create a new empty solution.
+    pub fn new() -> Self {
+        Self(HashMap::new(), HashSet::new())
+    }
+
+    /// Check if a variable is assigned a truth value.
+    pub fn get(&self, id: Literal) -> bool {
+        if let Some(value) = self.0.get(&id) {
+            *value
+        } else {
+            panic!("Variable x{} is not assigned a value", id);
+        }
+    }
+
+    /// Assign a truth value to a variable, which is then no longer don't-care.
+    pub fn set(&mut self, id: Literal, value: bool) {
+        self.0.insert(id, value);
+        self.1.remove(&id);
+    }
+
+    /// Keep only the variables in the projection, like to drop auxiliary variables before comparing solutions.
+    /// Variables of the projection not in the solution are left out.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// x1 = T, x2 = F, x3 = T projected onto x1, x3, x4
+    /// x1 = T, x3 = T
+    /// ```
+    pub fn project(&self, projection: &[Literal]) -> Solution {
+        let mut projected = Solution::new();
+        for id in projection {
+            if let Some(value) = self.0.get(id) {
+                projected.0.insert(*id, *value);
+                if self.1.contains(id) {
+                    projected.1.insert(*id);
+                }
+            }
+        }
+        projected
+    }
+
+    /// Keep only the variables of a formula, dropping those added by encodings or Tseitin transformation.
+    pub fn restrict_to(&self, formula: &Formula) -> Solution {
+        self.project(&formula.literals())
+    }
+
+    /// Check if the value of a variable doesn't affect whether the solution satisfies the formula.
+    /// Don't-care variables keep their assigned value, so the solution can still be used as a full assignment.
+    pub fn is_dont_care(&self, id: Literal) -> bool {
+        self.1.contains(&id)
+    }
+
+    /// Mark the variables not in the implicant of the solution as don't-care.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// (x1 OR x2) AND -x3
+    /// x1 = T, x2 = T, x3 = F
+    /// x1 = T, x2 = *, x3 = F
+    /// ```
+    pub fn shrink(&mut self, formula: &Formula) {
+        self.1.clear();
+        let relevant = self.implicant(formula).literals();
+        for id in self.literals() {
+            if !relevant.contains(&id) {
+                self.1.insert(id);
+            }
+        }
+    }
+
+    pub fn literals(&self) -> Vec<Literal> {
+        let mut variables = self.0.keys().copied().collect::<Vec<_>>();
+        variables.sort();
+        variables
+    }
+
+    /// Get the cube of the variables that are not don't-care, describing the partial model
+    /// of every solution agreeing with it.
+    ///
+    /// ## Examples
+    /// ```plaintext
+    /// x1 = T, x2 = *, x3 = F
+    /// x1 AND -x3
+    /// ```
+    pub fn to_cube(&self) -> Cube {
+        Cube(
+            self.literals()
+                .into_iter()
+                .filter(|id| !self.is_dont_care(*id))
+                .map(|id| {
+                    if self.get(id) {
+                        Variable::Positive(id)
+                    } else {
+                        Variable::Negative(id)
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Set all variables to `false`.
+    pub fn reset(&mut self) {
+        self.0.iter_mut().for_each(|(_, value)| *value = false);
+    }
+
+    pub fn satisfy(&self, formula: &Formula) -> bool {
+        for clause in &formula.0 {
+            if !clause.0.iter().any(|variable| match variable {
+                Variable::Positive(id) => self.get(*id),
+                Variable::Negative(id) => !self.get(*id),
+            }) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// When a new solution is found, Add a new clause that forbids that exact solution. \
+    /// Done using **De Morgan's Laws**:
+    /// ```plaintext
+    /// -(x1 AND x2 ... AND xN)  =>  (-x1 OR -x2 OR ... OR -xN)
+    /// ```
+    pub fn negative_clause(&self) -> Clause {
+        let mut clause = Vec::new();
+        for (id, value) in &self.0 {
+            // if !value {
+            //     clause.push(Variable::Positive(*id));
+            // }
+            if *value {
+                clause.push(Variable::Negative(*id));
+            } else {
+                clause.push(Variable::Positive(*id));
+            }
+        }
+        Clause(clause)
+    }
+
+    /// Shrink the solution to a cube that still satisfies every clause of the formula,
+    /// by greedily dropping variables that no clause depends on.
+    /// A clause depends on a variable when it is its only true one, however often it is repeated in the clause.
+    /// Every solution that agrees with the cube satisfies the formula.
+    pub fn implicant(&self, formula: &Formula) -> Cube {
+        let is_true = |variable: &Variable| self.get(variable.id()) == variable.is_positive();
+        // Count the number of distinct true variables in each clause
+        let mut counts = formula
+            .iter()
+            .map(|clause| {
+                let mut true_variables = clause.iter().filter(|v| is_true(v)).collect::<Vec<_>>();
+                true_variables.sort_by_key(|variable| variable.id());
+                true_variables.dedup();
+                true_variables.len()
+            })
+            .collect::<Vec<_>>();
+        let mut cube = Vec::new();
+        for id in self.literals().into_iter().rev() {
+            let variable = if self.get(id) {
+                Variable::Positive(id)
+            } else {
+                Variable::Negative(id)
+            };
+            let supported = formula
+                .iter()
+                .enumerate()
+                .filter(|(_, clause)| clause.iter().any(|v| *v == variable))
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            if supported.iter().all(|i| counts[*i] > 1) {
+                supported.iter().for_each(|i| counts[*i] -= 1);
+            } else {
+                cube.push(variable);
+            }
+        }
+        cube.reverse();
+        Cube(cube)
+    }
+}
+
+impl Default for Solution {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Create a new solution from a list of pairs of literals and truth values.
+impl From<&[(Literal, bool)]> for Solution {
+    fn from(pairs: &[(Literal, bool)]) -> Self {
+        let mut solution = Solution::new();
+        for (id, value) in pairs {
+            solution.set(*id, *value);
+        }
+        solution
+    }
+}
+
+/// Plain text in the normal print style, like `X1 = T, X2 = F, X3 = *`.
+impl Display for Solution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (i, id) in self.literals().into_iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            let value = if self.is_dont_care(id) {
+                "*"
+            } else if self.get(id) {
+                "T"
+            } else {
+                "F"
+            };
+            write!(f, "X{} = {}", id, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A map renaming variables, like the dense renumbering given by [`Formula::compact`],
+/// that translates solutions back to the original identifiers.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct VarMap {
+    renamed: HashMap<Literal, Literal>,
+    original: HashMap<Literal, Literal>,
+}
+
+impl VarMap {
+    /// Create a map giving the variables dense identifiers from `x1` in the order they are listed.
+    pub fn new(variables: &[Literal]) -> Self {
+        let mut map = Self::default();
+        for id in variables {
+            if !map.renamed.contains_key(id) {
+                map.insert(*id, map.len() as Literal + 1);
+            }
+        }
+        map
+    }
+
+    /// Rename a variable, replacing any earlier renaming of it.
+    pub fn insert(&mut self, id: Literal, renamed: Literal) {
+        if let Some(previous) = self.renamed.insert(id, renamed) {
+            self.original.remove(&previous);
+        }
+        self.original.insert(renamed, id);
+    }
+
+    /// Get the new identifier of an original variable.
+    pub fn get(&self, id: Literal) -> Option<Literal> {
+        self.renamed.get(&id).copied()
+    }
+
+    /// Get the original identifier of a renamed variable.
+    pub fn original(&self, id: Literal) -> Option<Literal> {
+        self.original.get(&id).copied()
+    }
+
+    /// Give a variable its new identifier, keeping its sign.
+    /// Variables not in the map are kept as they are.
+    pub fn rename(&self, variable: Variable) -> Variable {
+        let id = self.get(variable.id()).unwrap_or(variable.id());
+        if variable.is_positive() {
+            Variable::Positive(id)
+        } else {
+            Variable::Negative(id)
+        }
+    }
+
+    /// Map a solution of the renamed formula back to the original variables.
+    pub fn solution(&self, solution: &Solution) -> Solution {
+        let mut original = Solution::new();
+        for (id, value) in &solution.0 {
+            let original_id = self.original(*id).unwrap_or(*id);
+            original.0.insert(original_id, *value);
+            if solution.is_dont_care(*id) {
+                original.1.insert(original_id);
+            }
+        }
+        original
+    }
+
+    pub fn len(&self) -> usize {
+        self.renamed.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.renamed.is_empty()
+    }
+}
+
+/// The variables in use, like those of a formula, numbered densely from 0 in order of their identifiers.
+/// Solvers index their assignments by position in the domain instead of by identifier,
+/// so gaps between identifiers like in `x1 AND x100` take no space and are never iterated over.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 OR x100) AND -x7
+/// x1 = 0, x7 = 1, x100 = 2
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Domain(Vec<Literal>);
+
+impl Domain {
+    pub fn new(ids: &[Literal]) -> Self {
+        let mut ids = ids.to_vec();
+        ids.sort();
+        ids.dedup();
+        Self(ids)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Get the identifiers in order.
+    pub fn ids(&self) -> &[Literal] {
+        &self.0
+    }
+
+    pub fn contains(&self, id: Literal) -> bool {
+        self.index(id).is_some()
+    }
+
+    /// Get the position of a variable in the domain, or `None` if it's not in use.
+    pub fn index(&self, id: Literal) -> Option<usize> {
+        self.0.binary_search(&id).ok()
+    }
+
+    /// Get the identifier of the variable at a position.
+    pub fn id(&self, index: usize) -> Literal {
+        self.0[index]
+    }
+
+    /// Get the clauses of a formula as packed literals of positions, see [`Lit::eval`].
+    ///
+    /// ## Panics
+    /// If a variable of the formula is not in the domain.
+    pub fn lits(&self, formula: &Formula) -> Vec<Vec<Lit>> {
+        let index = |id: Literal| {
+            self.index(id)
+                .unwrap_or_else(|| panic!("Variable x{} is not in the domain", id))
+        };
+        formula
+            .iter()
+            .map(|clause| {
+                clause
+                    .iter()
+                    .map(|variable| {
+                        Lit::new(index(variable.id()) as Literal, variable.is_positive())
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl From<&Formula> for Domain {
+    fn from(formula: &Formula) -> Self {
+        Self(formula.literals())
+    }
+}
+
+/// A partial assignment gives some variables a truth value and leaves the others unassigned.
+/// Unlike a [`Solution`], looking up an unassigned variable is not an error.
+///
+/// ## Examples
+/// ```plaintext
+/// x1 = true, x3 = false
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PartialAssignment(BTreeMap<Literal, bool>);
+
+impl PartialAssignment {
+    pub fn new() -> Self {
+        Self(BTreeMap::new())
+    }
+
+    /// Get the truth value of a variable, or `None` if it is unassigned.
+    pub fn get(&self, id: Literal) -> Option<bool> {
+        self.0.get(&id).copied()
+    }
+
+    pub fn set(&mut self, id: Literal, value: bool) {
+        self.0.insert(id, value);
+    }
+
+    pub fn unset(&mut self, id: Literal) {
+        self.0.remove(&id);
+    }
+
+    /// Get the truth value of a positive or negative variable, or `None` if it is unassigned.
+    pub fn value(&self, variable: Variable) -> Option<bool> {
+        self.get(variable.id())
+            .map(|value| value == variable.is_positive())
+    }
+
+    /// Get the assigned variables in order.
+    pub fn literals(&self) -> Vec<Literal> {
+        self.0.keys().copied().collect()
+    }
+
+    /// Get a solution over the given variables, failing with the unassigned ones if there are any.
+    pub fn complete(&self, variables: &[Literal]) -> Result<Solution, Unassigned> {
+        let unassigned = variables
+            .iter()
+            .copied()
+            .filter(|id| self.get(*id).is_none())
+            .collect::<Vec<_>>();
+        if !unassigned.is_empty() {
+            return Err(Unassigned(unassigned));
+        }
+        let mut solution = Solution::new();
+        for id in variables {
+            solution.set(*id, self.get(*id).unwrap());
+        }
+        Ok(solution)
+    }
+}
+
+impl From<&Solution> for PartialAssignment {
+    fn from(solution: &Solution) -> Self {
+        let mut assignment = PartialAssignment::new();
+        for (id, value) in &solution.0 {
+            assignment.set(*id, *value);
+        }
+        assignment
+    }
+}
+
+/// Create a partial assignment making every variable of the list true, like assumptions.
+impl From<&[Variable]> for PartialAssignment {
+    fn from(variables: &[Variable]) -> Self {
+        let mut assignment = PartialAssignment::new();
+        for variable in variables {
+            assignment.set(variable.id(), variable.is_positive());
+        }
+        assignment
+    }
+}
+
+impl From<&[(Literal, bool)]> for PartialAssignment {
+    fn from(pairs: &[(Literal, bool)]) -> Self {
+        let mut assignment = PartialAssignment::new();
+        for (id, value) in pairs {
+            assignment.set(*id, *value);
+        }
+        assignment
+    }
+}
+
+/// The variables a partial assignment was missing to be a complete solution.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Unassigned(pub Vec<Literal>);
+
+impl Display for Unassigned {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "Unassigned variables: ")?;
+        let variables = self
+            .0
+            .iter()
+            .map(|id| format!("X{}", id))
+            .collect::<Vec<_>>();
+        join(f, &variables, ", ")
+    }
+}
+
+impl std::error::Error for Unassigned {}
+
+/// A cube is a conjunction of variables.
+/// It describes all solutions that agree with it, leaving the variables not in the cube free.
+///
+/// ## Examples
+/// ```plaintext
+///  x1 AND -x3
+/// -x1 AND  x2 AND x3
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Cube(pub(crate) Vec<Variable>);
+
+impl Cube {
+    /// Get all literal variables in the cube.
+    pub fn literals(&self) -> Vec<Literal> {
+        let mut variables = self.0.iter().map(Variable::id).collect::<Vec<_>>();
+        variables.sort();
+        variables.dedup();
+        variables
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Variable> {
+        self.0.iter()
+    }
+
+    /// Get the clause that forbids every solution agreeing with the cube.
+    pub fn negative_clause(&self) -> Clause {
+        Clause(self.0.iter().map(Variable::negated).collect())
+    }
+
+    /// Get a solution over the given variables that agrees with the cube,
+    /// where the free variables are don't-care and assigned `false`.
+    pub fn assign(&self, variables: &[Literal]) -> Solution {
+        let mut solution = Solution::new();
+        for id in variables {
+            solution.set(*id, false);
+            solution.1.insert(*id);
+        }
+        for variable in &self.0 {
+            solution.set(variable.id(), variable.is_positive());
+        }
+        solution
+    }
+
+    /// Expand the cube into all solutions over the given variables that agree with it.
+    /// The free variables are enumerated in order, starting from all `false`.
+    pub fn expand(&self, variables: &[Literal]) -> Vec<Solution> {
+        self.expansions(variables).collect()
+    }
+
+    /// Expand the cube into the solutions of [`Cube::expand`] one at a time,
+    /// so only as many as are needed are made.
+    pub fn expansions(&self, variables: &[Literal]) -> impl Iterator<Item = Solution> {
+        let mut base = Solution::new();
+        for variable in &self.0 {
+            base.set(variable.id(), variable.is_positive());
+        }
+        let free = variables
+            .iter()
+            .copied()
+            .filter(|id| !base.0.contains_key(id))
+            .collect::<Vec<_>>();
+        // The solution at an index has the bits of the index as the values of the free variables,
+        // with the first one as the most significant bit, and there is no end with 64 or more of them
+        let end = u32::try_from(free.len())
+            .ok()
+            .and_then(|bits| 1usize.checked_shl(bits));
+        (0..)
+            .take_while(move |index| Some(*index) != end)
+            .map(move |index: usize| {
+                let mut solution = base.clone();
+                for (i, id) in free.iter().enumerate() {
+                    let bit = index.checked_shr((free.len() - 1 - i) as u32).unwrap_or(0) & 1;
+                    solution.set(*id, bit == 1);
+                }
+                solution
+            })
+    }
+}
+
+/// Plain text in the normal print style, like `X1 and -X3`, or `T` for the empty cube.
+impl Display for Cube {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "T");
+        }
+        join(f, &self.0, " and ")
+    }
+}
+
+/// A boolean propositional formula in disjunctive normal form (DNF), a disjunction of cubes.
+///
+/// ## Examples
+/// ```plaintext
+/// (x1 AND -x2) OR (x2 AND x3)
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dnf(pub(crate) Vec<Cube>);
+
+impl Dnf {
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, Cube> {
+        self.0.iter()
+    }
+}
+
+/// Plain text in the normal print style, like `(X1 and -X2) or (X3)`, or `F` for no cubes.
+impl Display for Dnf {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return write!(f, "F");
+        }
+        let cubes = self
+            .0
+            .iter()
+            .map(|cube| format!("({})", cube))
+            .collect::<Vec<_>>();
+        join(f, &cubes, " or ")
+    }
+}
+
+/// The clauses added and removed between two formulas, in canonical form, see [`Formula::diff`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct FormulaDiff {
+    pub added: Vec<Clause>,
+    pub removed: Vec<Clause>,
+}
+
+impl FormulaDiff {
+    /// Check if the formulas have the same clauses.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A line per clause, with `-` for removed and `+` for added clauses, like `- (X1 or X2)`.
+impl Display for FormulaDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let removed = self.removed.iter().map(|clause| format!("- {}", clause));
+        let added = self.added.iter().map(|clause| format!("+ {}", clause));
+        join(f, &removed.chain(added).collect::<Vec<_>>(), "\n")
+    }
+}
+
+/// Write the items separated by the separator.
+fn join<T: Display>(f: &mut Formatter<'_>, items: &[T], separator: &str) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, "{}", separator)?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+/// A certificate is a proof that a formula is satisfiable or unsatisfiable.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Certificate {
+    Unsatisfiable,
+    Satisfiable(Solution),
+    /// The solver gave up without deciding the formula.
+    Indeterminate,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_formula_get_literals() {
+        let formula = Formula::from(vec![
+            Clause(vec![Variable::Positive(1), Variable::Negative(2)]),
+            Clause(vec![Variable::Positive(2), Variable::Negative(3)]),
+        ]);
+        assert_eq!(formula.literals(), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_solution_satisfy() {
+        // (x1 OR -x2) AND (x2 OR -x3)
+        let formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![Variable::Positive(2), Variable::Negative(3)],
         ]
         .into();
         let solution: Solution = ([(1, true), (2, false), (3, false)][..]).into();
         assert!(solution.satisfy(&formula));
     }
+
+    #[test]
+    fn test_formula_backbone() {
+        // (x1 OR x2) AND (-x1 OR x2) AND (x3 OR -x2) AND (x4 OR -x4)
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(2)],
+            vec![Variable::Positive(3), Variable::Negative(2)],
+            vec![Variable::Positive(4), Variable::Negative(4)],
+        ]
+        .into();
+        assert_eq!(
+            formula.backbone(&crate::solvers::Dfs::default()),
+            vec![Variable::Positive(2), Variable::Positive(3)]
+        );
+
+        // x1 AND -x1
+        let formula: Formula =
+            vec![vec![Variable::Positive(1)], vec![Variable::Negative(1)]].into();
+        assert!(formula.backbone(&crate::solvers::Dfs::default()).is_empty());
+    }
+
+    #[test]
+    fn test_solution_implicant() {
+        // (x1 OR x2) AND (-x1 OR x3)
+        let formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+        ]
+        .into();
+        let solution: Solution = ([(1, true), (2, true), (3, true)][..]).into();
+        let cube = solution.implicant(&formula);
+        assert_eq!(
+            cube,
+            Cube(vec![Variable::Positive(1), Variable::Positive(3)])
+        );
+        let solutions = cube.expand(&formula.literals());
+        assert_eq!(solutions.len(), 2);
+        assert!(solutions.iter().all(|solution| solution.satisfy(&formula)));
+        let cube = Cube(vec![Variable::Negative(2)]);
+        let solutions = cube.expand(&[1, 2, 3]);
+        assert_eq!(
+            solutions,
+            [
+                [(1, false), (3, false)],
+                [(1, false), (3, true)],
+                [(1, true), (3, false)],
+                [(1, true), (3, true)]
+            ]
+            .map(|values| Solution::from(&[values[0], (2, false), values[1]][..]))
+        );
+        let variables = (1..=100).collect::<Vec<_>>();
+        assert!(cube.expansions(&variables).nth(5).unwrap().get(100));
+        // A repeated variable is still the only one satisfying its clause
+        let formula = vec![vec![Variable::Positive(1), Variable::Positive(1)]].into();
+        let solution: Solution = ([(1, true)][..]).into();
+        assert_eq!(
+            solution.implicant(&formula),
+            Cube(vec![Variable::Positive(1)])
+        );
+    }
+
+    #[test]
+    fn test_solution_shrink() {
+        // (x1 OR x2) AND -x3
+        let formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(3)],
+        ]
+        .into();
+        let mut solution: Solution = ([(1, true), (2, true), (3, false)][..]).into();
+        solution.shrink(&formula);
+        assert!(!solution.is_dont_care(1));
+        assert!(solution.is_dont_care(2));
+        assert!(!solution.is_dont_care(3));
+        assert!(solution.get(2));
+        assert!(solution.satisfy(&formula));
+        assert_eq!(solution.to_cube().to_string(), "X1 and -X3");
+        solution.set(2, false);
+        assert!(!solution.is_dont_care(2));
+        let cube = Cube(vec![Variable::Negative(3)]);
+        let solution = cube.assign(&[1, 2, 3]);
+        assert!(solution.is_dont_care(1) && solution.is_dont_care(2));
+        assert!(!solution.is_dont_care(3));
+        assert_eq!(solution.to_cube(), cube);
+    }
+
+    #[test]
+    fn test_var_pool() {
+        let mut pool = VarPool::new();
+        assert!(pool.reserve(2));
+        let rain = pool.id("rain");
+        assert_eq!(rain, 3);
+        assert_eq!(pool.id("rain"), rain);
+        assert_eq!(pool.id("wet"), 4);
+        assert_eq!(pool.get("sun"), None);
+        assert_eq!(pool.name(rain), Some("rain"));
+        assert_eq!(pool.name(2), None);
+        assert!(!pool.reserve(rain));
+        assert!(!pool.reserve(MAX_LITERAL + 1));
+        assert_eq!(pool.len(), 2);
+        assert_eq!(pool.names(), ["rain", "wet"]);
+        let auxiliary = pool.fresh();
+        assert!(pool.is_auxiliary(auxiliary));
+        assert!(pool.reserve(auxiliary));
+        assert!(!pool.is_auxiliary(auxiliary));
+        let auxiliary = pool.fresh();
+        assert!(auxiliary > rain && pool.is_auxiliary(auxiliary));
+    }
+
+    #[test]
+    fn test_lit() {
+        let lit = Lit::new(0, true);
+        assert_eq!(lit.var(), 0);
+        assert!(lit.is_positive());
+        assert!(!(!lit).is_positive());
+        assert_eq!(!!lit, lit);
+        assert_eq!(Variable::from(!Lit::new(3, true)), Variable::Negative(3));
+        assert_eq!(Lit::from(Variable::Positive(3)), Lit::new(3, true));
+        assert_eq!(
+            std::mem::size_of::<Lit>() * 2,
+            std::mem::size_of::<Variable>()
+        );
+        let values = [false, true];
+        assert!(Lit::new(1, true).eval(&values));
+        assert!(Lit::new(0, false).eval(&values));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn test_serde() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![Variable::Positive(3)],
+        ]
+        .into();
+        let json = serde_json::to_string(&formula).unwrap();
+        assert_eq!(
+            json,
+            r#"[[{"Positive":1},{"Negative":2}],[{"Positive":3}]]"#
+        );
+        assert_eq!(serde_json::from_str::<Formula>(&json).unwrap(), formula);
+
+        let mut solution: Solution = [(2, false), (1, true)][..].into();
+        let json = serde_json::to_string(&solution).unwrap();
+        assert_eq!(json, r#"{"values":{"1":true,"2":false}}"#);
+        assert_eq!(serde_json::from_str::<Solution>(&json).unwrap(), solution);
+        solution.1.insert(2);
+        let json = serde_json::to_string(&solution).unwrap();
+        assert_eq!(json, r#"{"values":{"1":true,"2":false},"dont_care":[2]}"#);
+        assert_eq!(serde_json::from_str::<Solution>(&json).unwrap(), solution);
+    }
+
+    #[test]
+    fn test_display() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![Variable::Positive(3)],
+        ]
+        .into();
+        assert_eq!(formula.to_string(), "(X1 or -X2) and (X3)");
+        assert_eq!(crate::parser::parse(&formula.to_string()), Ok(formula));
+        let mut solution: Solution = [(2, false), (1, true), (3, true)][..].into();
+        solution.1.insert(3);
+        assert_eq!(solution.to_string(), "X1 = T, X2 = F, X3 = *");
+        assert_eq!(Cube(vec![]).to_string(), "T");
+        assert_eq!(Cube(vec![Variable::Negative(4)]).to_string(), "-X4");
+    }
+
+    #[test]
+    fn test_formula_assign() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+            vec![Variable::Positive(2), Variable::Negative(3)],
+        ]
+        .into();
+        let residual = formula.assign(&[(1, true)][..].into());
+        let expected: Formula = vec![
+            vec![Variable::Positive(3)],
+            vec![Variable::Positive(2), Variable::Negative(3)],
+        ]
+        .into();
+        assert_eq!(residual, expected);
+        assert!(formula
+            .assign(&[(1, true), (2, true), (3, true)][..].into())
+            .is_empty());
+        let falsified = formula.assign(&[(1, false), (2, false)][..].into());
+        assert_eq!(falsified, vec![vec![]].into());
+    }
+
+    #[test]
+    fn test_formula_equivalent() {
+        let solver = crate::solvers::Dfs::default();
+        // (x1 OR x2) AND (x1 OR -x2) is equivalent to x1
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Positive(1), Variable::Negative(2)],
+        ]
+        .into();
+        let x1: Formula = vec![vec![Variable::Positive(1)]].into();
+        let x1_or_x2: Formula = vec![vec![Variable::Positive(1), Variable::Positive(2)]].into();
+        assert!(formula.equivalent(&x1, &solver));
+        assert!(x1.entails(&x1_or_x2, &solver));
+        assert!(!x1.equivalent(&x1_or_x2, &solver));
+        assert!(formula.entails(&Formula::new(), &solver));
+    }
+
+    #[test]
+    fn test_formula_normalize() {
+        let mut formula: Formula = vec![
+            vec![
+                Variable::Positive(1),
+                Variable::Positive(1),
+                Variable::Negative(1),
+            ],
+            vec![
+                Variable::Positive(3),
+                Variable::Negative(2),
+                Variable::Positive(3),
+            ],
+        ]
+        .into();
+        formula.normalize();
+        let expected: Formula = vec![vec![Variable::Negative(2), Variable::Positive(3)]].into();
+        assert_eq!(formula, expected);
+        let mut formula = Formula::new();
+        formula.add_normalized(Clause(vec![Variable::Negative(1), Variable::Positive(1)]));
+        formula.add_normalized(Clause(vec![Variable::Negative(1), Variable::Positive(0)]));
+        let expected: Formula = vec![vec![Variable::Positive(0), Variable::Negative(1)]].into();
+        assert_eq!(formula, expected);
+    }
+
+    #[test]
+    fn test_partial_assignment() {
+        let mut assignment: PartialAssignment =
+            [Variable::Positive(1), Variable::Negative(3)][..].into();
+        assert_eq!(assignment.get(1), Some(true));
+        assert_eq!(assignment.get(2), None);
+        assert_eq!(assignment.get(10), None);
+        assert_eq!(assignment.value(Variable::Negative(3)), Some(true));
+        assert_eq!(assignment.literals(), [1, 3]);
+        let error = assignment.complete(&[1, 2, 3, 4]).unwrap_err();
+        assert_eq!(error, Unassigned(vec![2, 4]));
+        assert_eq!(error.to_string(), "Unassigned variables: X2, X4");
+        assignment.unset(3);
+        assert_eq!(assignment.complete(&[1]), Ok([(1, true)][..].into()));
+        let mut assignment = PartialAssignment::new();
+        assignment.set(u32::MAX - 1, true);
+        assert_eq!(assignment.literals(), [u32::MAX - 1]);
+    }
+
+    #[test]
+    fn test_formula_compact() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(3), Variable::Negative(10)],
+            vec![Variable::Positive(42)],
+        ]
+        .into();
+        let (compact, map) = formula.compact();
+        let expected: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![Variable::Positive(3)],
+        ]
+        .into();
+        assert_eq!(compact, expected);
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.original(2), Some(10));
+        assert_eq!(map.get(42), Some(3));
+        let mut solution: Solution = [(1, true), (2, false), (3, true)][..].into();
+        solution.1.insert(3);
+        let mut expected: Solution = [(3, true), (10, false), (42, true)][..].into();
+        expected.1.insert(42);
+        assert_eq!(map.solution(&solution), expected);
+    }
+
+    #[test]
+    fn test_formula_to_dnf() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+        ]
+        .into();
+        let dnf = formula.to_dnf(10).unwrap();
+        assert_eq!(
+            dnf.to_string(),
+            "(X1 and X3) or (X2 and -X1) or (X2 and X3)"
+        );
+        for bits in 0..8u32 {
+            let solution: Solution =
+                [(1, bits & 1 != 0), (2, bits & 2 != 0), (3, bits & 4 != 0)][..].into();
+            let satisfied = dnf
+                .iter()
+                .any(|cube| cube.iter().all(|v| solution.get(v.id()) == v.is_positive()));
+            assert_eq!(satisfied, solution.satisfy(&formula));
+        }
+        assert_eq!(formula.to_dnf(1), None);
+        assert_eq!(Formula::new().to_dnf(1).unwrap().to_string(), "(T)");
+        let unsat: Formula = vec![vec![Variable::Positive(1)], vec![Variable::Negative(1)]].into();
+        assert!(unsat.to_dnf(1).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_formula_to_dot() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![
+                Variable::Positive(2),
+                Variable::Positive(3),
+                Variable::Negative(1),
+            ],
+        ]
+        .into();
+        assert_eq!(
+            formula.to_dot(),
+            "graph incidence {\n  node [shape=circle];\n  x1; x2; x3;\n  node [shape=box];\n\
+             \x20 c1 -- x1;\n  c1 -- x2 [style=dashed];\n\
+             \x20 c2 -- x2;\n  c2 -- x3;\n  c2 -- x1 [style=dashed];\n}\n"
+        );
+        assert_eq!(
+            formula.to_interaction_dot(),
+            "graph interaction {\n  node [shape=circle];\n  x1; x2; x3;\n\
+             \x20 x1 -- x2;\n  x1 -- x3;\n  x2 -- x3;\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_formula_combinators() {
+        let f: Formula = vec![vec![Variable::Positive(1), Variable::Positive(2)]].into();
+        let g: Formula = vec![vec![Variable::Negative(1)], vec![Variable::Positive(3)]].into();
+        let solver = crate::solvers::Dfs::default();
+        let mut pool = VarPool::new();
+        let models = |formula: &Formula| {
+            let mut models = crate::solver::solve_all_projected(formula, &[1, 2, 3], &solver);
+            models.sort_by_key(|solution| solution.to_string());
+            models
+        };
+        let all = (0..8u32)
+            .map(|bits| [(1, bits & 1 != 0), (2, bits & 2 != 0), (3, bits & 4 != 0)][..].into())
+            .collect::<Vec<Solution>>();
+        let expected = |predicate: &dyn Fn(&Solution) -> bool| {
+            let mut models = all
+                .iter()
+                .filter(|s| predicate(s))
+                .cloned()
+                .collect::<Vec<_>>();
+            models.sort_by_key(|solution| solution.to_string());
+            models
+        };
+        assert_eq!(
+            models(&f.and(&g)),
+            expected(&|s| s.satisfy(&f) && s.satisfy(&g))
+        );
+        assert_eq!(
+            models(&f.or(&g, &mut pool)),
+            expected(&|s| s.satisfy(&f) || s.satisfy(&g))
+        );
+        assert_eq!(
+            models(
+                &f.not(&mut pool)
+                    .and(&vec![vec![Variable::Positive(3), Variable::Negative(3)]].into())
+            ),
+            expected(&|s| !s.satisfy(&f))
+        );
+        assert_eq!(
+            models(&f.implies(&g, &mut pool)),
+            expected(&|s| !s.satisfy(&f) || s.satisfy(&g))
+        );
+    }
+
+    #[test]
+    fn test_formula_substitute() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+        ]
+        .into();
+        let expected: Formula = vec![
+            vec![Variable::Positive(2), Variable::Negative(3)],
+            vec![Variable::Positive(3)],
+        ]
+        .into();
+        assert_eq!(formula.substitute(1, &!Expr::var(3)), expected);
+        let expected: Formula = vec![vec![Variable::Positive(3)]].into();
+        assert_eq!(formula.substitute(1, &Expr::constant(true)), expected);
+        // The second clause becomes a tautology
+        let expected: Formula = vec![
+            vec![Variable::Positive(2)],
+            vec![Variable::Positive(2), Variable::Positive(3)],
+        ]
+        .into();
+        assert_eq!(
+            formula.substitute(1, &(Expr::var(2) & Expr::var(3))),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_formula_rename() {
+        let formula: Formula = vec![vec![Variable::Positive(1), Variable::Negative(2)]].into();
+        let mut map = VarMap::default();
+        map.insert(2, 5);
+        let expected: Formula = vec![vec![Variable::Positive(1), Variable::Negative(5)]].into();
+        assert_eq!(formula.rename(&map), expected);
+        assert_eq!(
+            map.solution(&[(1, true), (5, false)][..].into()),
+            [(1, true), (2, false)][..].into()
+        );
+    }
+
+    #[test]
+    fn test_formula_dont_cares() {
+        let solver = crate::solvers::Dfs::default();
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Positive(1), Variable::Negative(2)],
+            vec![Variable::Positive(3), Variable::Negative(3)],
+        ]
+        .into();
+        assert_eq!(formula.dont_cares(&solver), [2, 3]);
+    }
+
+    #[test]
+    fn test_formula_independent_support() {
+        let solver = crate::solvers::Dfs::default();
+        // x3 <-> (x1 AND x2)
+        let formula: Formula = vec![
+            vec![Variable::Negative(3), Variable::Positive(1)],
+            vec![Variable::Negative(3), Variable::Positive(2)],
+            vec![
+                Variable::Positive(3),
+                Variable::Negative(1),
+                Variable::Negative(2),
+            ],
+        ]
+        .into();
+        let support = formula.independent_support(&solver);
+        assert_eq!(support, [1, 2]);
+        let projected = crate::solver::solve_all_projected(&formula, &support, &solver);
+        assert_eq!(
+            projected.len(),
+            crate::solver::solve_all(&formula, &solver).len()
+        );
+    }
+
+    #[test]
+    fn test_formula_removable() {
+        let solver = crate::solvers::Dfs::default();
+        let mut pool = VarPool::new();
+        let mut formula: Formula = vec![vec![Variable::Positive(1)]].into();
+        let id = formula.add_removable(Clause(vec![Variable::Negative(1)]), &mut pool);
+        formula.add_to(id, Clause(vec![Variable::Positive(2)]));
+        assert!(pool.is_auxiliary(id.assumption().id()));
+        assert_eq!(
+            solve_assuming(&formula, &[id.assumption()], &solver),
+            Certificate::Unsatisfiable
+        );
+        assert!(matches!(
+            solve_assuming(&formula, &[], &solver),
+            Certificate::Satisfiable(_)
+        ));
+        formula.remove(id);
+        assert_eq!(formula, vec![vec![Variable::Positive(1)]].into());
+    }
+
+    #[test]
+    fn test_formula_units_and_pure() {
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(1)],
+            vec![Variable::Positive(2), Variable::Positive(3)],
+            vec![Variable::Negative(4)],
+            vec![Variable::Positive(1)],
+        ]
+        .into();
+        assert_eq!(
+            formula.unit_clauses(),
+            [Variable::Positive(1), Variable::Negative(4)]
+        );
+        assert_eq!(
+            formula.pure_literals(),
+            [
+                Variable::Positive(1),
+                Variable::Positive(2),
+                Variable::Positive(3),
+                Variable::Negative(4)
+            ]
+        );
+        formula.add(Clause(vec![Variable::Negative(2), Variable::Positive(4)]));
+        formula.add(Clause(vec![Variable::Positive(3)]));
+        assert_eq!(formula.unit_clauses().len(), 3);
+        assert_eq!(
+            formula.pure_literals(),
+            [Variable::Positive(1), Variable::Positive(3)]
+        );
+        // In place changes count the occurrences again
+        formula.normalize();
+        assert_eq!(formula.unit_clauses().len(), 3);
+        let id = formula.add_removable(Clause(vec![Variable::Negative(1)]), &mut VarPool::new());
+        // The activation variable only occurs negated
+        assert_eq!(
+            formula.pure_literals(),
+            [Variable::Positive(3), Variable::Negative(5)]
+        );
+        formula.remove(id);
+        assert_eq!(
+            formula.pure_literals(),
+            [Variable::Positive(1), Variable::Positive(3)]
+        );
+    }
+
+    #[test]
+    fn test_formula_canonicalize() {
+        let mut formula: Formula = vec![
+            vec![Variable::Positive(3), Variable::Negative(1)],
+            vec![Variable::Positive(2)],
+            vec![Variable::Positive(2), Variable::Positive(2)],
+            vec![Variable::Positive(1), Variable::Negative(1)],
+        ]
+        .into();
+        let other: Formula = vec![
+            vec![Variable::Positive(2)],
+            vec![Variable::Negative(1), Variable::Positive(3)],
+        ]
+        .into();
+        let hash = |formula: &CanonicalFormula| {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            formula.hash(&mut hasher);
+            hasher.finish()
+        };
+        assert_ne!(formula, other);
+        let canonical = CanonicalFormula::from(formula.clone());
+        let other = CanonicalFormula::from(other);
+        assert_eq!(canonical, other);
+        assert_eq!(hash(&canonical), hash(&other));
+        let cache = HashSet::from([canonical]);
+        assert!(cache.contains(&other));
+        formula.canonicalize();
+        assert_eq!(formula.to_string(), "(-X1 or X3) and (X2)");
+        assert_eq!(&formula, other.formula());
+    }
+
+    #[test]
+    fn test_solution_project() {
+        let mut solution: Solution = ([(1, true), (2, false), (3, true)][..]).into();
+        solution.1.insert(3);
+        let projected = solution.project(&[1, 3, 4]);
+        assert_eq!(projected.to_string(), "X1 = T, X3 = *");
+        let formula: Formula = vec![vec![Variable::Positive(2), Variable::Negative(5)]].into();
+        assert_eq!(solution.restrict_to(&formula).to_string(), "X2 = F");
+    }
+
+    #[test]
+    fn test_formula_diff() {
+        let before: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(2)],
+            vec![Variable::Positive(3)],
+            vec![Variable::Negative(5)],
+        ]
+        .into();
+        let after: Formula = vec![
+            vec![Variable::Positive(3)],
+            vec![
+                Variable::Positive(2),
+                Variable::Positive(1),
+                Variable::Positive(2),
+            ],
+            vec![Variable::Negative(4)],
+            vec![Variable::Positive(3)],
+        ]
+        .into();
+        let diff = before.diff(&after);
+        assert_eq!(diff.added, [Clause(vec![Variable::Negative(4)])]);
+        assert_eq!(diff.removed, [Clause(vec![Variable::Negative(5)])]);
+        assert_eq!(diff.to_string(), "- (-X5)\n+ (-X4)");
+        assert!(before.diff(&before).is_empty());
+    }
+
+    #[test]
+    fn test_domain() {
+        let formula: Formula = vec![
+            vec![Variable::Positive(1), Variable::Positive(100)],
+            vec![Variable::Negative(7)],
+        ]
+        .into();
+        let domain = formula.domain();
+        assert_eq!(domain.ids(), [1, 7, 100]);
+        assert_eq!(domain.index(100), Some(2));
+        assert_eq!(domain.index(50), None);
+        assert_eq!(domain.id(1), 7);
+        assert_eq!(
+            domain.lits(&formula),
+            [
+                vec![Lit::new(0, true), Lit::new(2, true)],
+                vec![Lit::new(1, false)]
+            ]
+        );
+        // The positions are contiguous, and map back to the identifiers they were packed from
+        let packed = domain
+            .lits(&formula)
+            .into_iter()
+            .flatten()
+            .map(|lit| lit.var() as usize)
+            .collect::<std::collections::BTreeSet<_>>();
+        assert!(packed.into_iter().eq(0..domain.len()));
+        for (index, id) in domain.ids().iter().enumerate() {
+            assert_eq!(domain.index(*id), Some(index));
+            assert_eq!(domain.id(index), *id);
+        }
+        // Variables between or past the identifiers are not in the domain
+        for id in [0, 2, 99, 101] {
+            assert!(!domain.contains(id));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "Variable x50 is not in the domain")]
+    fn test_domain_outside() {
+        let domain = Domain::new(&[1, 7, 100]);
+        domain.lits(&vec![vec![Variable::Positive(50)]].into());
+    }
 }
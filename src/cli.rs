@@ -0,0 +1,279 @@
+use std::path::Path;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use sat_lib::{
+    aiger, blif, dimacs, input, json, opb, parser,
+    printer::{ColorChoice, PrintStyle},
+    smtlib, solver,
+    solvers::SolverConfig,
+    tptp,
+    types::{Certificate, Formula, Literal, Solution, VarPool},
+};
+
+use crate::{new_solver, print_file_error, print_solutions, print_status, read_file, Output};
+
+/// A Boolean satisfiability problem (SAT) solver, with an interactive shell when run without a command or file.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// A problem to solve without the shell, like the solve command
+    pub file: Option<String>,
+    /// Print results as JSON for scripts, and leave out the banner and prompts of the shell
+    #[arg(long, global = true, conflicts_with = "competition")]
+    pub json: bool,
+    /// Print results as the s and v lines of the SAT competitions, exiting with the code 10, 20 or 0 of the last result
+    #[arg(long, global = true)]
+    pub competition: bool,
+    /// When to color the output
+    #[arg(long, global = true, value_name = "WHEN", default_value = "auto")]
+    pub color: ColorChoice,
+    /// Write an HTML report of the last formula of the shell when exiting
+    #[arg(long, value_name = "FILE")]
+    pub report: Option<String>,
+}
+
+impl Cli {
+    pub fn output(&self) -> Output {
+        if self.json {
+            Output::Json
+        } else if self.competition {
+            Output::Competition
+        } else {
+            Output::Text
+        }
+    }
+}
+
+/// The problems are read by the extension of the file, see [`read_problem`].
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Solve a problem, exiting with the code 10 if it is satisfiable and 20 if not
+    Solve { file: String },
+    /// Print every solution of a problem
+    Enumerate { file: String },
+    /// Count the solutions of a problem
+    Count {
+        file: String,
+        /// Count the assignments to these variables that extend to solutions, like x1,x2
+        #[arg(long, value_name = "VARIABLES")]
+        project: Option<String>,
+    },
+    /// Convert a problem to CNF in another format, written to stdout
+    Convert {
+        file: String,
+        #[arg(long, value_enum)]
+        to: Format,
+    },
+    /// Start the interactive shell, like running without a command
+    Repl,
+}
+
+/// The formats a problem can be converted to.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum Format {
+    Dimacs,
+    Tptp,
+    Json,
+    /// A formula like the shell prints it
+    Text,
+}
+
+/// Run a command other than the shell, returning the exit code.
+/// Errors are printed on stderr, and `--competition` is only accepted by the solve command.
+pub fn run(command: Command, output: Output) -> i32 {
+    if output == Output::Competition && !matches!(command, Command::Solve { .. }) {
+        eprintln!("The --competition option only applies to the solve command");
+        return 1;
+    }
+    let mut names = VarPool::new();
+    let solver = new_solver("dfs", &SolverConfig::default());
+    match command {
+        Command::Solve { file } => {
+            let Some((formula, variables)) = read_problem(&file, &mut names) else {
+                return 1;
+            };
+            // The auxiliary variables are solved too, and the declared ones in no clause are given values
+            let mut all = formula.literals();
+            all.extend(variables);
+            all.sort();
+            all.dedup();
+            let certificate = solver.solve(&mut formula.clone(), &all, &mut Solution::new());
+            match (output, &certificate) {
+                (Output::Competition, certificate) => {
+                    print!("{}", dimacs::write_result(certificate))
+                }
+                (Output::Json, certificate) => {
+                    println!("{}", json::solution_to_json(certificate, &names))
+                }
+                (Output::Text, Certificate::Satisfiable(solution)) => {
+                    print_status(1, true);
+                    print_solutions(
+                        PrintStyle::Normal.with_names(&names),
+                        std::slice::from_ref(solution),
+                    );
+                }
+                (Output::Text, Certificate::Unsatisfiable) => print_status(0, true),
+                (Output::Text, Certificate::Indeterminate) => print_status(0, false),
+            }
+            dimacs::exit_code(&certificate)
+        }
+        Command::Enumerate { file } => {
+            let Some((formula, variables)) = read_problem(&file, &mut names) else {
+                return 1;
+            };
+            // Solutions differing only in auxiliary variables of the conversion to CNF are the same
+            let solutions = solver::solve_all_projected(&formula, &variables, solver.as_ref());
+            if output == Output::Json {
+                let complete = solver.is_complete();
+                println!("{}", json::solutions_to_json(&solutions, complete, &names));
+            } else {
+                print_status(solutions.len(), solver.is_complete());
+                print_solutions(PrintStyle::Normal.with_names(&names), &solutions);
+            }
+            0
+        }
+        Command::Count { file, project } => {
+            let Some((formula, variables)) = read_problem(&file, &mut names) else {
+                return 1;
+            };
+            let projection =
+                match project.map(|project| parser::parse_variables(&project, &mut names)) {
+                    Some(Ok(projection)) => projection,
+                    Some(Err(error)) => {
+                        eprintln!("--project: {}", error);
+                        return 1;
+                    }
+                    // The variables of the conversion to CNF are not counted, and declared ones in no clause are
+                    None => variables,
+                };
+            let count = solver::count_models_projected(&formula, &projection);
+            if output == Output::Json {
+                println!("{{\"count\":{}}}", count);
+            } else {
+                println!("{}", count);
+            }
+            0
+        }
+        Command::Convert { file, to } => {
+            let Some((formula, _)) = read_problem(&file, &mut names) else {
+                return 1;
+            };
+            match to {
+                Format::Dimacs => print!("{}", dimacs::write_dimacs(&formula)),
+                Format::Tptp => print!("{}", tptp::write_tptp(&formula, &names)),
+                Format::Json => println!("{}", json::to_json(&formula, &names)),
+                Format::Text => println!(
+                    "{}",
+                    PrintStyle::Normal
+                        .with_names(&names)
+                        .format_formula(&formula)
+                ),
+            }
+            0
+        }
+        Command::Repl => unreachable!("The shell is run by main"),
+    }
+}
+
+/// Read a problem in CNF by the extension of the file, which may be compressed by gzip or xz:
+/// DIMACS for `.cnf`, TPTP for `.p`, SMT-LIB for `.smt2`, JSON for `.json`, OPB for `.opb`,
+/// AIGER for `.aag` and `.aig`, BLIF for `.blif`, and otherwise a formula like those of the shell.
+/// The formula is given with its variables other than the auxiliary ones,
+/// which for DIMACS include those declared by the header that are in no clause.
+/// Errors are printed, returning `None`.
+pub fn read_problem(path: &str, names: &mut VarPool) -> Option<(Formula, Vec<Literal>)> {
+    let name = path.trim_end_matches(".gz").trim_end_matches(".xz");
+    let extension = Path::new(name)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("");
+    if extension == "cnf" {
+        let read = input::open(path)
+            .map_err(|error| error.to_string())
+            .and_then(|file| {
+                dimacs::read_dimacs(file, |_, _| ()).map_err(|error| error.to_string())
+            });
+        return match read {
+            Ok(dimacs) => {
+                let variables = dimacs.variables();
+                Some((dimacs.formula, variables))
+            }
+            Err(error) => {
+                eprintln!("{}: {}", path, error);
+                None
+            }
+        };
+    }
+    let formula = read_formula(path, extension, names)?;
+    let variables = formula
+        .literals()
+        .into_iter()
+        .filter(|id| !names.is_auxiliary(*id))
+        .collect();
+    Some((formula, variables))
+}
+
+/// Read a problem in a format other than DIMACS, see [`read_problem`].
+fn read_formula(path: &str, extension: &str, names: &mut VarPool) -> Option<Formula> {
+    if extension == "aag" || extension == "aig" {
+        let input = match input::read(path) {
+            Ok(input) => input,
+            Err(error) => {
+                eprintln!("Could not read {}: {}", path, error);
+                return None;
+            }
+        };
+        return match aiger::parse_aiger(&input, names) {
+            Ok(circuit) => Some(circuit.any_output()),
+            Err(error) => {
+                print_file_error(path, &String::from_utf8_lossy(&input), &error);
+                None
+            }
+        };
+    }
+    let input = read_file(path)?;
+    let parsed = match extension {
+        "p" => tptp::parse_tptp(&input, names),
+        "smt2" => smtlib::parse_smtlib(&input, names).map(|expr| expr.to_cnf(names)),
+        "opb" => opb::parse_opb(&input).map(|opb| opb.to_formula(names)),
+        "blif" => blif::parse_blif(&input, names).map(|circuit| circuit.any_output()),
+        "json" => {
+            return match json::parse_json(&input, names) {
+                Ok(formula) => Some(formula),
+                Err(error) => {
+                    eprintln!("{}: {}", path, error);
+                    None
+                }
+            }
+        }
+        _ => parser::parse_with(&input, names),
+    };
+    match parsed {
+        Ok(formula) => Some(formula),
+        Err(error) => {
+            print_file_error(path, &input, &error);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::*;
+
+    #[test]
+    fn test_count_declared_variables() {
+        // x3, x4 and x5 are declared but in no clause, so each doubles the 3 models of (x1 OR -x2)
+        let path = env::temp_dir().join("sat-solve-test-declared.cnf");
+        fs::write(&path, "p cnf 5 1\n1 -2 0\n").unwrap();
+        let (formula, variables) =
+            read_problem(path.to_str().unwrap(), &mut VarPool::new()).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(variables, [1, 2, 3, 4, 5]);
+        assert_eq!(solver::count_models_projected(&formula, &variables), 24);
+    }
+}
@@ -0,0 +1,148 @@
+use rustyline::{
+    completion::Completer, highlight::Highlighter, hint::Hinter, validate::Validator, Context,
+    Helper,
+};
+
+/// The commands of the shell, completed at the start of a line.
+const COMMANDS: &[&str] = &[
+    "add",
+    "aig",
+    "assume",
+    "assumptions",
+    "backbone",
+    "bench",
+    "blif",
+    "cdcl",
+    "clauses",
+    "cnf",
+    "compact",
+    "competition",
+    "count",
+    "cubes",
+    "dfs",
+    "diff",
+    "dot",
+    "eliminate",
+    "equiv",
+    "exit",
+    "forall",
+    "help",
+    "icnf",
+    "json",
+    "kmap",
+    "let",
+    "limit",
+    "markdown",
+    "math",
+    "max",
+    "merge",
+    "min",
+    "minimal",
+    "normal",
+    "opb",
+    "prog",
+    "random",
+    "report",
+    "retract",
+    "seed",
+    "set",
+    "shrink",
+    "smt",
+    "solve",
+    "stats",
+    "subsume",
+    "sudoku",
+    "support",
+    "symmetry",
+    "table",
+    "tabu",
+    "timeout",
+    "vivify",
+    "wcnf",
+    "wrap",
+];
+
+/// Completes the commands of the shell and the names of the variables seen so far.
+#[derive(Debug, Default)]
+pub struct Completion {
+    /// The named variables, updated before reading each line.
+    pub variables: Vec<String>,
+}
+
+impl Completion {
+    /// Get the candidates for the word ending at `pos`, with the position where the word starts.
+    /// The first word of a line is a command or a variable, and the others are variables.
+    pub fn candidates(&self, line: &str, pos: usize) -> (usize, Vec<String>) {
+        let start = line[..pos]
+            .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .map_or(0, |i| i + line[i..].chars().next().unwrap().len_utf8());
+        let word = &line[start..pos];
+        let first = line[..start].trim().is_empty();
+        let commands = COMMANDS.iter().copied().filter(|_| first);
+        let mut candidates = commands
+            .chain(self.variables.iter().map(String::as_str))
+            .filter(|candidate| candidate.starts_with(word))
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        candidates.sort();
+        candidates.dedup();
+        (start, candidates)
+    }
+}
+
+impl Completer for Completion {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(self.candidates(line, pos))
+    }
+}
+
+impl Hinter for Completion {
+    type Hint = String;
+}
+
+impl Highlighter for Completion {}
+
+impl Validator for Completion {}
+
+impl Helper for Completion {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion() {
+        let completion = Completion {
+            variables: vec!["rain".to_string(), "sprinkler".to_string()],
+        };
+        assert_eq!(
+            completion.candidates("ma", 2),
+            (
+                0,
+                vec![
+                    "markdown".to_string(),
+                    "math".to_string(),
+                    "max".to_string()
+                ]
+            )
+        );
+        assert_eq!(
+            completion.candidates("rain and (-spr", 14),
+            (11, vec!["sprinkler".to_string()])
+        );
+        // Commands are only completed at the start of a line
+        assert_eq!(completion.candidates("x1 or s", 7).1, ["sprinkler"]);
+        assert_eq!(completion.candidates("se", 2).1, ["seed", "set"]);
+        assert_eq!(
+            completion.candidates("table r", 7),
+            (6, vec!["rain".to_string()])
+        );
+    }
+}
@@ -1,106 +1,2016 @@
-use std::io::Write;
+mod cli;
+mod completion;
 
-use crossterm::{
-    style::{Attribute, Color, SetAttribute, SetForegroundColor},
-    ExecutableCommand,
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::Display,
+    io::{IsTerminal, Write},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
 };
+
+use clap::Parser;
+use crossterm::style::{Attribute, Color};
 use crossterm_cursor::{cursor, TerminalCursor};
-use sat_lib::{parser, printer::PrintStyle, solver, solvers, types::Formula};
+use rustyline::{error::ReadlineError, history::DefaultHistory, Editor};
+use sat_lib::{
+    aiger, blif, dimacs,
+    encode::{self, Comparison, PbConstraint},
+    expr::Expr,
+    generate, input, json, opb,
+    parser::{self, ParseError},
+    preprocessors::{self, Preprocessor},
+    printer::{self, PrintStyle, Printer, PrinterConfig},
+    qbf::QbfCertificate,
+    report::Report,
+    smtlib, solver,
+    solvers::{
+        self, Budget, CancellationToken, Heuristic, Limited, Observer, Polarity, Restart, Solver,
+        SolverConfig, Stats,
+    },
+    trail::Trail,
+    types::{Certificate, Formula, Literal, Solution, VarPool, Variable},
+};
+
+use crate::completion::Completion;
 
 fn main() {
-    println!("Welcome to the SAT Solver!");
+    let cli = cli::Cli::parse();
+    printer::set_color_choice(cli.color);
+    let output = cli.output();
+    // A file alone is solved like by the solve command, for scripts and benchmarks
+    let command = match (cli.command, cli.file) {
+        (None, Some(file)) => Some(cli::Command::Solve { file }),
+        (command, _) => command,
+    };
+    match command {
+        None | Some(cli::Command::Repl) => repl(output, cli.report),
+        Some(command) => std::process::exit(cli::run(command, output)),
+    }
+}
+
+/// Run the interactive shell, where the JSON and competition outputs are meant for scripts
+/// and benchmark harnesses reading the results without the banner and prompts meant for people.
+/// A report of the last formula is written to `report_path` when exiting.
+fn repl(mut output: Output, report_path: Option<String>) {
+    let prompt = output == Output::Text;
+    // The exit code of the last result in the competition output
+    let mut exit_code = 0;
+    if prompt {
+        println!("Welcome to the SAT Solver!");
+    }
     let mut cursor = cursor();
-    let mut stdout = std::io::stdout();
-    let mut solver = solvers::Dfs;
+    // Line editing with history and completion, when the input is typed in a terminal
+    let mut editor = Editor::new()
+        .ok()
+        .filter(|_| std::io::stdin().is_terminal())
+        .map(|mut editor: Editor<Completion, DefaultHistory>| {
+            editor.set_helper(Some(Completion::default()));
+            editor
+        });
+    let mut config = SolverConfig::default();
+    let mut algorithm = "dfs";
+    let mut solver = new_solver(algorithm, &config);
     let mut style = PrintStyle::Normal;
+    let mut current: Option<Formula> = None;
+    let mut names = VarPool::new();
+    let mut show_cubes = false;
+    let mut show_stats = false;
+    let mut session = SessionStats::default();
+    let mut minimal = false;
+    let mut shrink = false;
+    let mut view = View::default();
+    // Named formulas by the variable of their name, which is replaced by the formula where it's used
+    let mut bindings: HashMap<String, Expr> = HashMap::new();
+    // The most solutions to enumerate of a formula
+    let mut limit: Option<usize> = None;
+    // The wall-clock time a solve may take before giving up
+    let mut timeout: Option<Duration> = None;
+    // Literals assumed in every solve until they are retracted
+    let mut assumptions: Vec<Variable> = Vec::new();
+    let mut generated = 0;
+    // Ctrl-C cancels a running solve, and exits while waiting for input
+    let cancellation = CancellationToken::new();
+    let solving = Arc::new(AtomicBool::new(false));
+    {
+        let cancellation = cancellation.clone();
+        let solving = solving.clone();
+        ctrlc::set_handler(move || {
+            if solving.load(Ordering::Relaxed) {
+                cancellation.cancel();
+            } else {
+                std::process::exit(130);
+            }
+        })
+        .unwrap();
+    }
     loop {
-        let (input, start) = read_line(&mut cursor);
-        match input.trim() {
+        let (input, start) = read_line(editor.as_mut(), &names, &bindings, &mut cursor, prompt);
+        // Entering a formula solves it, so `solve f1` is the same as `f1`
+        let line = input.trim();
+        match line.strip_prefix("solve ").unwrap_or(line) {
             "" => (),
+            // Comments, like in a replayed script
+            line if line.starts_with('#') || line.starts_with("//") => (),
             "exit" => break,
             "math" => {
                 style = PrintStyle::Mathematical;
+                view.symbols = None;
                 println!("OK");
             }
             "normal" => {
                 style = PrintStyle::Normal;
+                view.symbols = None;
                 println!("OK");
             }
             "prog" => {
                 style = PrintStyle::Programmatic;
+                view.symbols = None;
+                println!("OK");
+            }
+            "markdown" => {
+                style = PrintStyle::Markdown;
+                view.symbols = None;
                 println!("OK");
             }
             "dfs" => {
-                solver = solvers::Dfs;
+                algorithm = "dfs";
+                solver = new_solver(algorithm, &config);
+                println!("OK");
+                print_heuristic_fallback(algorithm, &config);
+            }
+            "tabu" => {
+                algorithm = "tabu";
+                solver = new_solver(algorithm, &config);
+                println!("OK");
+            }
+            "cdcl" => {
+                algorithm = "cdcl";
+                solver = new_solver(algorithm, &config);
+                println!("OK");
+            }
+            "set" => print_config(&config),
+            "seed" => println!("{}", config.seed),
+            command if command.starts_with("seed ") => {
+                if set(&mut config, "seed", command[5..].trim()) {
+                    solver = new_solver(algorithm, &config);
+                    println!("OK");
+                }
+            }
+            command if command.starts_with("set ") => {
+                let mut args = command.split_whitespace().skip(1);
+                let (Some(key), Some(value)) = (args.next(), args.next()) else {
+                    println!("Expected: set <key> <value>");
+                    continue;
+                };
+                let value = [value]
+                    .into_iter()
+                    .chain(args)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                if let Some(key) = key.strip_prefix("print.") {
+                    // Symbols and colors of the printer, starting from those of the style
+                    let symbols = view.symbols.get_or_insert_with(|| style.config().clone());
+                    match symbols.set(key, &value) {
+                        Ok(()) => println!("OK"),
+                        Err(error) => println!("{}", error),
+                    }
+                } else if set(&mut config, key, &value) {
+                    solver = new_solver(algorithm, &config);
+                    println!("OK");
+                    print_heuristic_fallback(algorithm, &config);
+                }
+            }
+            "symmetry" => {
+                config.symmetry_breaking = !config.symmetry_breaking;
+                if config.symmetry_breaking {
+                    println!("Symmetry breaking enabled");
+                } else {
+                    println!("Symmetry breaking disabled");
+                }
+            }
+            "merge" => {
+                config.equivalences = !config.equivalences;
+                if config.equivalences {
+                    println!("Equivalent literal substitution enabled");
+                } else {
+                    println!("Equivalent literal substitution disabled");
+                }
+            }
+            "subsume" => {
+                config.subsumption = !config.subsumption;
+                if config.subsumption {
+                    println!("Subsumption enabled");
+                } else {
+                    println!("Subsumption disabled");
+                }
+            }
+            "eliminate" => {
+                config.elimination = !config.elimination;
+                if config.elimination {
+                    println!("Variable elimination enabled");
+                } else {
+                    println!("Variable elimination disabled");
+                }
+            }
+            "vivify" => {
+                config.vivification = !config.vivification;
+                if config.vivification {
+                    println!("Vivification enabled");
+                } else {
+                    println!("Vivification disabled");
+                }
+            }
+            "cubes" => {
+                show_cubes = !show_cubes;
+                if show_cubes {
+                    println!("Showing solutions as cubes");
+                } else {
+                    println!("Showing solutions as full assignments");
+                }
+            }
+            "shrink" => {
+                shrink = !shrink;
+                if shrink {
+                    println!("Showing variables that don't matter as *");
+                } else {
+                    println!("Showing every variable's value");
+                }
+            }
+            "compact" => {
+                view.compact = !view.compact;
+                view.only = None;
+                if view.compact {
+                    println!("Showing only the true variables of solutions");
+                } else {
+                    println!("Showing every variable's value");
+                }
+            }
+            command if command.starts_with("compact ") => {
+                match parser::parse_variables(&command[8..], &mut names) {
+                    Ok(variables) => {
+                        view.compact = true;
+                        view.only = Some(variables);
+                        println!(
+                            "Showing only the true variables of solutions among the chosen ones"
+                        );
+                    }
+                    Err(error) => print_parse_error(&input, &command[8..], &error),
+                }
+            }
+            "wrap" => {
+                view.clauses_per_line = None;
+                println!("Wrapping formulas at the width of the terminal");
+            }
+            command if command.starts_with("wrap ") => match command[5..].trim().parse() {
+                Ok(clauses) if clauses > 0 => {
+                    view.clauses_per_line = Some(clauses);
+                    println!("Wrapping formulas after {} clauses per line", clauses);
+                }
+                _ => println!("Expected: wrap <clauses per line>"),
+            },
+            "limit" => {
+                limit = None;
+                println!("Showing all solutions");
+            }
+            command if command.starts_with("limit ") => match command[6..].trim().parse() {
+                Ok(solutions) if solutions > 0 => {
+                    limit = Some(solutions);
+                    println!("Showing at most {} solutions", solutions);
+                }
+                _ => println!("Expected: limit <solutions>"),
+            },
+            "timeout" => {
+                timeout = None;
+                println!("Solving without a time limit");
+            }
+            command if command.starts_with("timeout ") => {
+                match parse_duration(command[8..].trim()) {
+                    Some(duration) if !duration.is_zero() => {
+                        timeout = Some(duration);
+                        println!("Giving up solves after {:?}", duration);
+                    }
+                    _ => println!("Expected: timeout <seconds>s, like timeout 5s or 500ms"),
+                }
+            }
+            "diff" => {
+                view.diff = !view.diff;
+                if view.diff {
+                    println!("Dimming the values that are the same as in the solution before");
+                } else {
+                    println!("Showing every value alike");
+                }
+            }
+            "minimal" => {
+                minimal = !minimal;
+                if minimal {
+                    println!("Showing only subset-minimal solutions");
+                } else {
+                    println!("Showing all solutions");
+                }
+            }
+            "json" | "competition" => {
+                let toggled = if input.trim() == "json" {
+                    Output::Json
+                } else {
+                    Output::Competition
+                };
+                output = if output == toggled {
+                    Output::Text
+                } else {
+                    toggled
+                };
+                match output {
+                    Output::Text => println!("Showing results as text"),
+                    Output::Json => println!("Showing results as JSON"),
+                    Output::Competition => println!("Showing results as s and v lines"),
+                }
+            }
+            "stats" => match &session.last {
+                Some(last) => {
+                    println!("Last solve:");
+                    print_stats(last);
+                    let plural = if session.solves == 1 { "" } else { "s" };
+                    println!("Session, {} solve{}:", session.solves, plural);
+                    print_stats(&session.total);
+                }
+                None => println!("No solves yet"),
+            },
+            "stats reset" => {
+                session = SessionStats::default();
+                println!("Reset the solver statistics");
+            }
+            "stats on" => {
+                show_stats = true;
+                println!("Showing solver statistics after each solve");
+            }
+            "stats off" => {
+                show_stats = false;
+                println!("Hiding solver statistics");
+            }
+            command if command.starts_with("let ") => {
+                match parser::parse_binding(&command[4..], &mut names, &bindings) {
+                    Ok((name, expr)) => {
+                        bindings.insert(name.to_string(), expr);
+                        println!("OK");
+                    }
+                    Err(error) => print_parse_error(&input, &command[4..], &error),
+                }
+            }
+            command if command.starts_with("equiv ") => {
+                let [first, second] = command[6..].split_whitespace().collect::<Vec<_>>()[..]
+                else {
+                    println!("Expected: equiv <formula> <formula>");
+                    continue;
+                };
+                let (Some(first), Some(second)) = (bindings.get(first), bindings.get(second))
+                else {
+                    let unknown = if bindings.contains_key(first) {
+                        second
+                    } else {
+                        first
+                    };
+                    println!("Unknown formula: {}", unknown);
+                    continue;
+                };
+                // Auxiliary variables of the Tseitin encoding would differ between the formulas
+                let (first, second) = (
+                    assuming(&first.to_cnf_exact(), &assumptions),
+                    assuming(&second.to_cnf_exact(), &assumptions),
+                );
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let equivalent = interruptible(&solving, &cancellation, timeout, || {
+                    first.equivalent(&second, &solver)
+                });
+                session.record(&solver);
+                if cancellation.is_cancelled() {
+                    continue;
+                }
+                match (output, equivalent) {
+                    (Output::Json, _) => println!("{{\"equivalent\":{}}}", equivalent),
+                    (_, true) => println!("Equivalent"),
+                    (_, false) => println!("Not equivalent"),
+                }
+            }
+            command if command == "count" || command.starts_with("count ") => {
+                // Projection variables come before a colon, like in: count x1 x2 : f1
+                let rest = command[5..].trim_start();
+                let (projection, expr) = match rest.split_once(':') {
+                    Some((variables, expr)) => (Some(variables), expr),
+                    None => (None, rest),
+                };
+                let projection = match projection
+                    .map(|variables| (variables, parser::parse_variables(variables, &mut names)))
+                {
+                    None => None,
+                    Some((_, Ok(projection))) => Some(projection),
+                    Some((variables, Err(error))) => {
+                        print_parse_error(&input, variables, &error);
+                        continue;
+                    }
+                };
+                let (formula, mut variables) = if expr.trim().is_empty() {
+                    let Some(formula) = &current else {
+                        println!("No formula entered yet");
+                        continue;
+                    };
+                    (formula.clone(), formula.literals())
+                } else {
+                    match parser::parse_expr_bound(expr, &mut names, &bindings) {
+                        // Variables simplified away, like in the tautology x1 or -x1, can have either value
+                        Ok(expr) => (expr.to_cnf(&mut names), expr.literals()),
+                        Err(errors) => {
+                            print_parse_errors(&input, expr, &errors);
+                            continue;
+                        }
+                    }
+                };
+                variables.retain(|id| !names.is_auxiliary(*id));
+                let formula = assuming(&formula, &assumptions);
+                let budget = budget(&cancellation, timeout);
+                let count = interruptible(&solving, &cancellation, timeout, || {
+                    solver::count_models_limited(
+                        &formula,
+                        projection.as_deref().unwrap_or(&variables),
+                        &budget,
+                    )
+                });
+                let Some(count) = count else {
+                    continue;
+                };
+                match (output, count) {
+                    (Output::Json, _) => println!("{{\"count\":{}}}", count),
+                    (_, 1) => println!("1 model"),
+                    (_, count) => println!("{} models", count),
+                }
+            }
+            command if command.starts_with("assume ") => {
+                match parser::parse_assumptions(&command[7..], &mut names) {
+                    Ok(assumed) => {
+                        // Assuming a variable again replaces the assumption before
+                        assumptions.retain(|assumption| {
+                            !assumed
+                                .iter()
+                                .any(|variable| variable.id() == assumption.id())
+                        });
+                        assumptions.extend(assumed);
+                        println!("OK");
+                    }
+                    Err(error) => print_parse_error(&input, &command[7..], &error),
+                }
+            }
+            "assumptions" => {
+                if assumptions.is_empty() {
+                    println!("No assumptions");
+                    continue;
+                }
+                for (i, variable) in assumptions.iter().enumerate() {
+                    style.with_names(&names).print_variable(variable);
+                    if i < assumptions.len() - 1 {
+                        print!(", ");
+                    }
+                }
+                println!();
+            }
+            "retract" => {
+                assumptions.clear();
                 println!("OK");
             }
+            command if command.starts_with("retract ") => {
+                match parser::parse_variables(&command[8..], &mut names) {
+                    Ok(retracted) => {
+                        assumptions.retain(|assumption| !retracted.contains(&assumption.id()));
+                        println!("OK");
+                    }
+                    Err(error) => print_parse_error(&input, &command[8..], &error),
+                }
+            }
+            "clauses" => {
+                let Some(formula) = &current else {
+                    println!("No formula entered yet");
+                    continue;
+                };
+                let printer = view.printer(style, &names);
+                let width = formula.len().to_string().len();
+                for (i, clause) in formula.iter().enumerate() {
+                    print!("  {:>width$}: ", i + 1);
+                    printer.print_clause(clause);
+                    println!();
+                }
+            }
+            "backbone" => {
+                let Some(formula) = &current else {
+                    println!("No formula entered yet");
+                    continue;
+                };
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let formula = assuming(formula, &assumptions);
+                let backbone = interruptible(&solving, &cancellation, timeout, || {
+                    formula.backbone(&solver)
+                });
+                session.record(&solver);
+                if backbone.is_empty() {
+                    println!("No backbone literals");
+                    continue;
+                }
+                for (i, variable) in backbone.iter().enumerate() {
+                    style.with_names(&names).print_variable(variable);
+                    if i < backbone.len() - 1 {
+                        print!(", ");
+                    }
+                }
+                println!();
+            }
+            "support" => {
+                let Some(formula) = &current else {
+                    println!("No formula entered yet");
+                    continue;
+                };
+                let formula = assuming(formula, &assumptions);
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let (support, dont_cares) = interruptible(&solving, &cancellation, timeout, || {
+                    (
+                        formula.independent_support(&solver),
+                        formula.dont_cares(&solver),
+                    )
+                });
+                session.record(&solver);
+                print!("Independent support: ");
+                print_variables(&support, &style, &names);
+                print!("Don't-care: ");
+                print_variables(&dont_cares, &style, &names);
+            }
+            command if command.starts_with("min ") || command.starts_with("max ") => {
+                let Some(formula) = &current else {
+                    println!("No formula entered yet");
+                    continue;
+                };
+                let (goal, objective) = command.split_at(3);
+                let mut objective = match parser::parse_objective(objective, &mut names) {
+                    Ok(objective) => objective,
+                    Err(error) => {
+                        print_parse_error(&input, objective, &error);
+                        continue;
+                    }
+                };
+                // Maximizing is minimizing the negated objective
+                let sign = if goal == "max" { -1 } else { 1 };
+                if goal == "max" {
+                    let negated = objective
+                        .iter()
+                        .map(|(id, weight)| weight.checked_neg().map(|weight| (*id, weight)))
+                        .collect::<Option<Vec<_>>>();
+                    let Some(negated) = negated else {
+                        println!("A weight of {} can't be maximized", i64::MIN);
+                        continue;
+                    };
+                    objective = negated;
+                }
+                let formula = assuming(formula, &assumptions);
+                let budget = budget(&cancellation, timeout);
+                let optimum = interruptible(&solving, &cancellation, timeout, || {
+                    solver::solve_optimize_limited(&formula, &objective, &budget)
+                });
+                let complete = !cancellation.is_cancelled();
+                match optimum {
+                    Some((solution, cost)) => {
+                        print_status(1, true);
+                        print_solutions(
+                            view.printer(style, &names),
+                            std::slice::from_ref(&solution),
+                        );
+                        if complete {
+                            println!("  Objective: {}", cost.saturating_mul(sign));
+                        } else {
+                            let cost = cost.saturating_mul(sign);
+                            println!("  Objective: {}, not proven optimal", cost);
+                        }
+                    }
+                    None => print_status(0, complete),
+                }
+            }
+            command if command.starts_with("cnf ") => {
+                let path = command[4..].trim();
+                let file = match input::open(path) {
+                    Ok(file) => file,
+                    Err(error) => {
+                        println!("Could not read {}: {}", path, error);
+                        continue;
+                    }
+                };
+                let read = dimacs::read_dimacs(file, |read, total| {
+                    if output == Output::Text {
+                        print!("\r  Read {} of {} clauses", read, total);
+                        std::io::stdout().flush().unwrap();
+                    }
+                });
+                if output == Output::Text {
+                    println!();
+                }
+                let dimacs = match read {
+                    Ok(dimacs) => dimacs,
+                    Err(error) => {
+                        printer::set_foreground(Color::Red);
+                        println!("{}: {}", path, error);
+                        printer::set_foreground(Color::Reset);
+                        continue;
+                    }
+                };
+                // The declared variables in no clause are given values too
+                let variables = dimacs.variables();
+                let formula = dimacs.formula;
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let certificate = interruptible(&solving, &cancellation, timeout, || {
+                    let mut assumed = assuming(&formula, &assumptions);
+                    solver.solve(&mut assumed, &variables, &mut Solution::new())
+                });
+                session.record(&solver);
+                match (output, certificate) {
+                    (Output::Competition, certificate) => {
+                        print!("{}", dimacs::write_result(&certificate));
+                        exit_code = dimacs::exit_code(&certificate);
+                    }
+                    (Output::Json, Certificate::Satisfiable(solution)) => {
+                        println!("{}", json::solutions_to_json(&[solution], true, &names))
+                    }
+                    (Output::Json, certificate) => {
+                        let complete = certificate == Certificate::Unsatisfiable;
+                        println!("{}", json::solutions_to_json(&[], complete, &names))
+                    }
+                    (Output::Text, Certificate::Satisfiable(solution)) => {
+                        print_status(1, true);
+                        print_solutions(
+                            view.printer(style, &names),
+                            std::slice::from_ref(&solution),
+                        );
+                    }
+                    (Output::Text, Certificate::Unsatisfiable) => print_status(0, true),
+                    (Output::Text, Certificate::Indeterminate) => print_status(0, false),
+                }
+                current = Some(formula);
+            }
+            command if command.starts_with("report ") => {
+                let Some(formula) = &current else {
+                    println!("No formula entered yet");
+                    continue;
+                };
+                let formula = assuming(formula, &assumptions);
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let solutions = interruptible(&solving, &cancellation, timeout, || {
+                    solver::solve_all(&formula, &solver)
+                });
+                session.record(&solver);
+                let complete = solver.is_complete() && !cancellation.is_cancelled();
+                write_report(
+                    command[7..].trim(),
+                    &formula,
+                    &names,
+                    &solutions,
+                    complete,
+                    &solver,
+                );
+            }
+            command if command.starts_with("dot ") => {
+                let Some(formula) = &current else {
+                    println!("No formula entered yet");
+                    continue;
+                };
+                let arguments = command[4..].trim();
+                let (path, dot) = if let Some(path) = arguments.strip_prefix("vig ") {
+                    (path.trim(), formula.to_interaction_dot())
+                } else if let Some(path) = arguments.strip_prefix("conflict ") {
+                    let Some(dot) = first_conflict(formula, &config) else {
+                        println!("The last formula is solved without conflicts");
+                        continue;
+                    };
+                    (path.trim(), dot)
+                } else {
+                    (arguments, formula.to_dot())
+                };
+                match std::fs::write(path, dot) {
+                    Ok(()) => println!("Wrote the graph of the last formula to {}", path),
+                    Err(error) => println!("Could not write {}: {}", path, error),
+                }
+            }
+            command if command.starts_with("wcnf ") => {
+                let path = command[5..].trim();
+                let Some(input) = read_file(path) else {
+                    continue;
+                };
+                let mut wcnf = match dimacs::parse_wcnf(&input) {
+                    Ok(wcnf) => wcnf,
+                    Err(error) => {
+                        print_file_error(path, &input, &error);
+                        continue;
+                    }
+                };
+                wcnf.hard = assuming(&wcnf.hard, &assumptions);
+                let budget = budget(&cancellation, timeout);
+                let optimum = interruptible(&solving, &cancellation, timeout, || {
+                    wcnf.solve_limited(&budget)
+                });
+                let complete = !cancellation.is_cancelled();
+                match optimum {
+                    Ok(Some((solution, cost))) => {
+                        print_status(1, true);
+                        print_solutions(
+                            view.printer(style, &names),
+                            std::slice::from_ref(&solution),
+                        );
+                        print_cost(cost, complete);
+                    }
+                    Ok(None) => print_status(0, complete),
+                    Err(error) => println!("{}: {}", path, error),
+                }
+            }
+            command if command.starts_with("opb ") => {
+                let path = command[4..].trim();
+                let Some(input) = read_file(path) else {
+                    continue;
+                };
+                let mut opb = match opb::parse_opb(&input) {
+                    Ok(opb) => opb,
+                    Err(error) => {
+                        print_file_error(path, &input, &error);
+                        continue;
+                    }
+                };
+                opb.constraints
+                    .extend(assumptions.iter().map(|assumption| PbConstraint {
+                        terms: vec![(1, *assumption)],
+                        comparison: Comparison::AtLeast,
+                        bound: 1,
+                    }));
+                let budget = budget(&cancellation, timeout);
+                let optimum = interruptible(&solving, &cancellation, timeout, || {
+                    opb.solve_limited(&budget)
+                });
+                let complete = !cancellation.is_cancelled();
+                match optimum {
+                    Some((solution, cost)) => {
+                        print_status(1, true);
+                        print_solutions(
+                            view.printer(style, &names),
+                            std::slice::from_ref(&solution),
+                        );
+                        if !opb.objective.is_empty() {
+                            print_cost(cost, complete);
+                        }
+                    }
+                    None => print_status(0, complete),
+                }
+            }
+            command if command.starts_with("icnf ") => {
+                let path = command[5..].trim();
+                let Some(input) = read_file(path) else {
+                    continue;
+                };
+                let steps = match dimacs::parse_icnf(&input) {
+                    Ok(steps) => steps,
+                    Err(error) => {
+                        print_file_error(path, &input, &error);
+                        continue;
+                    }
+                };
+                // Each step is solved under the assumptions of the trace instead
+                print_ignored_assumptions(&assumptions);
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let certificates = interruptible(&solving, &cancellation, timeout, || {
+                    dimacs::replay_icnf(&steps, &solver)
+                });
+                session.record(&solver);
+                for (i, certificate) in certificates.iter().enumerate() {
+                    match certificate {
+                        Certificate::Satisfiable(_) => println!("  {}: Satisfiable", i + 1),
+                        Certificate::Unsatisfiable => println!("  {}: Unsatisfiable", i + 1),
+                        Certificate::Indeterminate => println!("  {}: Unknown", i + 1),
+                    }
+                }
+            }
+            command if command.starts_with("smt ") => {
+                let path = command[4..].trim();
+                let Some(input) = read_file(path) else {
+                    continue;
+                };
+                let formula = match smtlib::parse_smtlib(&input, &mut names) {
+                    Ok(expr) => expr.to_cnf(&mut names),
+                    Err(error) => {
+                        print_file_error(path, &input, &error);
+                        continue;
+                    }
+                };
+                print!("  ");
+                view.printer(style, &names).print_formula(&formula);
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let certificate = interruptible(&solving, &cancellation, timeout, || {
+                    solver::solve_assuming(&formula, &assumptions, &solver)
+                });
+                session.record(&solver);
+                match certificate {
+                    Certificate::Satisfiable(solution) => {
+                        print_status(1, true);
+                        print_solutions(
+                            view.printer(style, &names),
+                            std::slice::from_ref(&solution),
+                        );
+                    }
+                    Certificate::Unsatisfiable => print_status(0, true),
+                    Certificate::Indeterminate => print_status(0, false),
+                }
+                current = Some(formula);
+            }
+            command if command.starts_with("aig ") || command.starts_with("blif ") => {
+                let (format, path) = command.split_once(' ').unwrap();
+                let path = path.trim();
+                let input = match input::read(path) {
+                    Ok(input) => input,
+                    Err(error) => {
+                        println!("Could not read {}: {}", path, error);
+                        continue;
+                    }
+                };
+                let parsed = if format == "aig" {
+                    aiger::parse_aiger(&input, &mut names)
+                } else {
+                    blif::parse_blif(&String::from_utf8_lossy(&input), &mut names)
+                };
+                let circuit = match parsed {
+                    Ok(circuit) => circuit,
+                    Err(error) => {
+                        print_file_error(path, &String::from_utf8_lossy(&input), &error);
+                        continue;
+                    }
+                };
+                let formula = circuit.any_output();
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let certificate = interruptible(&solving, &cancellation, timeout, || {
+                    solver::solve_assuming(&formula, &assumptions, &solver)
+                });
+                session.record(&solver);
+                match certificate {
+                    Certificate::Satisfiable(solution) => {
+                        print_status(1, true);
+                        print_solutions(
+                            view.printer(style, &names),
+                            std::slice::from_ref(&solution),
+                        );
+                    }
+                    Certificate::Unsatisfiable => print_status(0, true),
+                    Certificate::Indeterminate => print_status(0, false),
+                }
+                current = Some(formula);
+            }
+            command if command.starts_with("forall") => {
+                let qbf = match parser::parse_qbf(command, &mut names) {
+                    Ok(qbf) => qbf,
+                    Err(error) => {
+                        print_parse_error(&input, command, &error);
+                        continue;
+                    }
+                };
+                // Assumptions on universal variables would not mean the same as for a formula
+                print_ignored_assumptions(&assumptions);
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let certificate =
+                    interruptible(&solving, &cancellation, timeout, || qbf.solve(&solver));
+                session.record(&solver);
+                match certificate {
+                    QbfCertificate::True => println!("True"),
+                    QbfCertificate::False(counterexample) => {
+                        print!("False, no solution for: ");
+                        print_solutions(
+                            view.printer(style, &names),
+                            std::slice::from_ref(&counterexample),
+                        );
+                    }
+                    QbfCertificate::Indeterminate => println!("Unknown"),
+                }
+            }
+            "table" => {
+                let Some(formula) = &current else {
+                    println!("No formula entered yet");
+                    continue;
+                };
+                print_truth_table(formula, &style, &names);
+            }
+            command if command.starts_with("table ") && !command.contains(':') => {
+                let expr = &command[6..];
+                let formula = match parser::parse_expr(expr, &mut names) {
+                    Ok(expr) => expr.fold_constants().to_cnf_exact(),
+                    Err(error) => {
+                        print_parse_error(&input, expr, &error);
+                        continue;
+                    }
+                };
+                print_truth_table(&formula, &style, &names);
+            }
+            "kmap" => {
+                let Some(formula) = &current else {
+                    println!("No formula entered yet");
+                    continue;
+                };
+                print_karnaugh_map(formula, &style, &names);
+            }
+            command if command.starts_with("kmap ") => {
+                let expr = &command[5..];
+                let formula = match parser::parse_expr(expr, &mut names) {
+                    Ok(expr) => expr.fold_constants().to_cnf_exact(),
+                    Err(error) => {
+                        print_parse_error(&input, expr, &error);
+                        continue;
+                    }
+                };
+                print_karnaugh_map(&formula, &style, &names);
+            }
+            command if command.starts_with("table ") => {
+                let table = &command[6..];
+                let formula = match parser::parse_truth_table(table, &mut names) {
+                    Ok(table) => table.to_cnf_minimized(),
+                    Err(error) => {
+                        print_parse_error(&input, table, &error);
+                        continue;
+                    }
+                };
+                print!("  ");
+                view.printer(style, &names).print_formula(&formula);
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let solutions = interruptible(&solving, &cancellation, timeout, || {
+                    solver::solve_all(&formula, &solver)
+                });
+                session.record(&solver);
+                print_status(
+                    solutions.len(),
+                    solver.is_complete() && !cancellation.is_cancelled(),
+                );
+                print_solutions(view.printer(style, &names), &solutions);
+                current = Some(formula);
+            }
+            command if command.starts_with("sudoku ") => {
+                let puzzle = &command[7..];
+                let grid = match parser::parse_sudoku(puzzle) {
+                    Ok(grid) => grid,
+                    Err(error) => {
+                        print_parse_error(&input, puzzle, &error);
+                        continue;
+                    }
+                };
+                let formula = encode::sudoku(grid);
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let certificate = interruptible(&solving, &cancellation, timeout, || {
+                    solver::solve_propagated(&assuming(&formula, &assumptions), &solver)
+                });
+                session.record(&solver);
+                match certificate {
+                    Certificate::Satisfiable(solution) => {
+                        print_sudoku(&grid, &encode::sudoku_decode(&solution))
+                    }
+                    Certificate::Unsatisfiable => print_status(0, true),
+                    Certificate::Indeterminate => print_status(0, false),
+                }
+            }
+            command if command.starts_with("random ") => {
+                let args = command
+                    .split_whitespace()
+                    .skip(1)
+                    .map(|arg| arg.parse::<usize>())
+                    .collect::<Vec<_>>();
+                let [Ok(vars), Ok(clauses), Ok(k)] = args.as_slice() else {
+                    println!("Expected: random <variables> <clauses> <k>");
+                    continue;
+                };
+                if k > vars {
+                    println!("Expected k to be at most the number of variables");
+                    continue;
+                }
+                // Every formula is different, but the sequence is reproducible from the seed
+                let seed = config.seed.wrapping_add(generated);
+                generated += 1;
+                let formula = generate::random_ksat(*vars as Literal, *clauses, *k, seed);
+                // Generated variables are numbered, so new names must not take their identifiers
+                names.reserve(*vars as Literal);
+                print!("  ");
+                view.printer(style, &names).print_formula(&formula);
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                let certificate = interruptible(&solving, &cancellation, timeout, || {
+                    solver::solve_assuming(&formula, &assumptions, &solver)
+                });
+                session.record(&solver);
+                match certificate {
+                    Certificate::Satisfiable(solution) => {
+                        print_status(1, true);
+                        print_solutions(
+                            view.printer(style, &names),
+                            std::slice::from_ref(&solution),
+                        );
+                    }
+                    Certificate::Unsatisfiable => print_status(0, true),
+                    Certificate::Indeterminate => print_status(0, false),
+                }
+                current = Some(formula);
+            }
+            command if command.split_whitespace().next() == Some("bench") => {
+                let formulas = match command[5..].trim() {
+                    "" => match &current {
+                        Some(formula) => vec![formula.clone()],
+                        None => {
+                            println!("No formula entered yet");
+                            continue;
+                        }
+                    },
+                    instances => match instances.parse::<usize>() {
+                        // Random 3-SAT at the ratio of the hardest instances, half of them satisfiable
+                        Ok(instances) if instances > 0 => (0..instances)
+                            .map(|_| {
+                                let seed = config.seed.wrapping_add(generated);
+                                generated += 1;
+                                generate::random_ksat(12, 51, 3, seed)
+                            })
+                            .collect(),
+                        _ => {
+                            println!("Expected: bench <instances>");
+                            continue;
+                        }
+                    },
+                };
+                // Each solve is limited by the timeout, rather than the whole comparison
+                let formulas = formulas
+                    .iter()
+                    .map(|formula| assuming(formula, &assumptions))
+                    .collect::<Vec<_>>();
+                let results = interruptible(&solving, &cancellation, None, || {
+                    bench(&formulas, &config, &cancellation, timeout)
+                });
+                print_bench(&formulas, &results);
+            }
             "help" => {
                 println!("Commands:");
-                println!("  dfs      Use depth-first search (DFS) brute-force solver (default)");
-                println!("  math     Use mathematical notation");
-                println!("  normal   Use normal notation");
-                println!("  prog     Use programmatic notation");
-                println!("  help     Display this help message");
-                println!("  exit     Exit the program");
+                println!("  dfs       Use depth-first search (DFS) brute-force solver (default)");
+                println!("  tabu      Use tabu search local solver (incomplete)");
+                println!("  cdcl      Use conflict-driven clause learning (CDCL) solver");
+                println!(
+                    "  set       Show the solver settings, change one with: set <key> <value>"
+                );
+                println!(
+                    "            set print.<key> <value> changes the not, and, or, true and false symbols"
+                );
+                println!(
+                    "            or the positive, negative, operator and punctuation colors of the style"
+                );
+                println!("  seed <n>  Set the seed of randomized solvers, for reproducible runs");
+                println!("  math      Use mathematical notation");
+                println!("  normal    Use normal notation");
+                println!("  prog      Use programmatic notation");
+                println!("  markdown  Use Markdown with math formulas and solution tables, for pasting into issues");
+                println!("  symmetry  Toggle symmetry breaking before solving");
+                println!("  merge     Toggle substituting equivalent literals before solving");
+                println!("  subsume   Toggle subsumption simplification before solving");
+                println!("  eliminate Toggle bounded variable elimination before solving");
+                println!("  vivify    Toggle clause vivification before solving");
+                println!("  cubes     Toggle showing solutions as cubes of the relevant literals");
+                println!("  shrink    Toggle showing solutions with don't-care variables as *");
+                println!("  compact   Toggle showing only the true variables of solutions, and partial ones as cubes");
+                println!("            compact <variables> shows only the true ones among the given variables");
+                println!("  wrap <n>  Wrap formulas after n clauses per line, or only at the terminal width by: wrap");
+                println!("  timeout <t> Give up solves after a time like 5s or 500ms, or never by: timeout");
+                println!("  limit <n> Show at most n solutions of a formula, or all by: limit");
+                println!("  diff      Toggle dimming the values that are the same as in the solution before");
+                println!("  minimal   Toggle showing only solutions with a minimal set of true variables");
+                println!("  json      Toggle showing results as JSON for scripts, like the --json option");
+                println!("  competition Toggle showing results as s and v lines, like the --competition option");
+                println!(
+                    "  bench     Compare the results and times of the solvers on the last formula"
+                );
+                println!("            bench <n> compares them on n random 3-SAT formulas, flagging disagreements as bugs");
+                println!("  stats     Show the solver statistics of the last solve and the session, or reset them by: stats reset");
+                println!("            stats on shows them after each solve, until: stats off");
+                println!("  backbone  Show the literals fixed in every model of the last formula");
+                println!("  let f = .. Name a formula, to use in others like: f and -x1");
+                println!("  equiv f g Check if the named formulas f and g have the same models");
+                println!("  add ..    Add clauses like: add (x2 or -x4) to the last formula and solve it");
+                println!("  clauses   List the clauses of the last formula by number");
+                println!("  solve ..  Solve a formula, the same as entering it");
+                println!("  count     Count the models of the last formula, or of one like: count x1 or x2");
+                println!("            count x1 x2 : .. counts the assignments to x1 and x2 that extend to models");
+                println!("  assume .. Solve under assumptions like: assume x3 -x5, until they are retracted");
+                println!("  assumptions Show the assumptions");
+                println!("  retract .. Retract the assumptions on variables like: retract x3, or all by: retract");
+                println!("  support   Show the independent support and don't-care variables of the last formula");
+                println!("  report <file> Write an HTML report of the models of the last formula");
+                println!("  dot <file> Write the clause-variable graph of the last formula in Graphviz DOT");
+                println!("  dot vig <file> Write the variable interaction graph of the last formula instead");
+                println!("  dot conflict <file> Write the implication graph at the first conflict of the CDCL solver on the last formula");
+                println!("  min <obj> Find a model of the last formula minimizing an objective like 2 x1 + x2");
+                println!("  max <obj> Find a model of the last formula maximizing an objective");
+                println!("  cnf <file> Solve a DIMACS CNF file, reading it clause by clause");
+                println!("  wcnf <file> Find a model of the hard clauses of a MaxSAT problem with minimal soft cost");
+                println!("  opb <file> Find a model of the constraints of a pseudo-Boolean problem with minimal cost");
+                println!("  icnf <file> Replay the solves of an incremental problem with its assumptions");
+                println!(
+                    "  smt <file> Solve the assertions of an SMT-LIB script over Bool constants"
+                );
+                println!(
+                    "  aig <file> Find inputs setting some output of an AIGER circuit to true"
+                );
+                println!("  forall .. Decide a formula like: forall x1 x2 exists x3: (x1 or x3)");
+                println!("  random <n> <m> <k> Solve a random formula of m clauses with k of n variables");
+                println!(
+                    "  table ..  Solve the minimized CNF of a truth table like: table x1 x2 : 0110"
+                );
+                println!("  table     Show the truth table of the last formula, or of one like: table x1 -> x2");
+                println!("  kmap      Show the Karnaugh map of the last formula, or of one like: kmap x1 xor x2");
+                println!(
+                    "  sudoku .. Solve a Sudoku of 81 digits row by row, with . for empty cells"
+                );
+                println!("  help      Display this help message");
+                println!("  exit      Exit the program");
             }
             expr => {
-                let Some(formula) = parser::parse(expr) else {
-                    continue;
-                };
-                update_line(&input, start, &formula, &mut cursor, &style);
-                let solutions = solver::solve_all(&formula, &solver);
-                if !solutions.is_empty() {
-                    stdout.execute(SetForegroundColor(Color::DarkGrey)).unwrap();
-                    stdout.execute(SetAttribute(Attribute::Italic)).unwrap();
-                    print!("\n  Satisfiable");
-                    if solutions.len() > 1 {
-                        print!(" ({})", solutions.len());
-                        println!(": ");
-                    } else {
-                        print!(": ");
+                // Clauses added to the last formula are solved together with it
+                let (expr, added_to) = match expr.strip_prefix("add ") {
+                    Some(clauses) => (clauses, current.clone()),
+                    None => (expr, None),
+                };
+                let formula = match parser::parse_expr_bound(expr, &mut names, &bindings) {
+                    Ok(expr) => expr.to_cnf(&mut names),
+                    Err(errors) => {
+                        print_parse_errors(&input, expr, &errors);
+                        continue;
                     }
-                    stdout.execute(SetForegroundColor(Color::Reset)).unwrap();
-                    stdout.execute(SetAttribute(Attribute::Reset)).unwrap();
-                    for solution in &solutions {
-                        if solutions.len() > 1 {
+                };
+                let formula = match added_to {
+                    Some(current) => current.and(&formula),
+                    None => formula,
+                };
+                if let (Output::Text, Some(start)) = (output, start) {
+                    update_line(
+                        &input,
+                        start,
+                        &formula,
+                        &mut cursor,
+                        view.printer(style, &names),
+                    );
+                }
+                let assumed = assuming(&formula, &assumptions);
+                let mut problem = assumed.clone();
+                let preprocessors = config.preprocessors();
+                let passes = preprocessors
+                    .iter()
+                    .map(|preprocessor| preprocessor.as_ref())
+                    .collect::<Vec<&dyn Preprocessor>>();
+                let reconstruction = preprocessors::preprocess(&mut problem, &passes);
+                let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+                if output == Output::Competition {
+                    // A single solution, like SAT competition solvers give
+                    let mut certificate = interruptible(&solving, &cancellation, timeout, || {
+                        solver::solve_assuming(&problem, &[], &solver)
+                    });
+                    if let Certificate::Satisfiable(solution) = &mut certificate {
+                        reconstruction.extend(solution);
+                    }
+                    print!("{}", dimacs::write_result(&certificate));
+                    exit_code = dimacs::exit_code(&certificate);
+                } else if output == Output::Json {
+                    let (mut solutions, more) =
+                        interruptible(&solving, &cancellation, timeout, || {
+                            if minimal {
+                                (solver::solve_all_minimal(&assumed, &solver), false)
+                            } else {
+                                solve_limited(&problem, limit, &solver)
+                            }
+                        });
+                    if !minimal {
+                        for solution in &mut solutions {
+                            reconstruction.extend(solution);
+                        }
+                    }
+                    let complete = solver.is_complete() && !cancellation.is_cancelled();
+                    println!(
+                        "{}",
+                        json::limited_solutions_to_json(&solutions, complete, more, &names)
+                    );
+                } else if minimal {
+                    // Preprocessing doesn't preserve minimality, so the original formula is solved
+                    let solutions = interruptible(&solving, &cancellation, timeout, || {
+                        solver::solve_all_minimal(&assumed, &solver)
+                    });
+                    print_status(
+                        solutions.len(),
+                        solver.is_complete() && !cancellation.is_cancelled(),
+                    );
+                    print_solutions(view.printer(style, &names), &solutions);
+                } else if shrink {
+                    let cubes = interruptible(&solving, &cancellation, timeout, || {
+                        solver::solve_all_cubes(&problem, &solver)
+                    });
+                    print_status(
+                        cubes.len(),
+                        solver.is_complete() && !cancellation.is_cancelled(),
+                    );
+                    let variables = problem.literals();
+                    let solutions = cubes
+                        .iter()
+                        .map(|cube| {
+                            // Eliminated variables are assigned by the reconstruction, so they are never don't-care
+                            let mut solution = cube.assign(&variables);
+                            reconstruction.extend(&mut solution);
+                            solution
+                        })
+                        .collect::<Vec<_>>();
+                    print_solutions(view.printer(style, &names), &solutions);
+                } else if show_cubes {
+                    let mut cubes = interruptible(&solving, &cancellation, timeout, || {
+                        solver::solve_all_cubes(&problem, &solver)
+                    });
+                    print_status(
+                        cubes.len(),
+                        solver.is_complete() && !cancellation.is_cancelled(),
+                    );
+                    if !reconstruction.is_empty() {
+                        // The cubes of the preprocessed formula are extended to implicants of the original one
+                        let variables = problem.literals();
+                        for cube in &mut cubes {
+                            let mut solution = cube.assign(&variables);
+                            reconstruction.extend(&mut solution);
+                            *cube = solution.implicant(&assumed);
+                        }
+                    }
+                    for cube in &cubes {
+                        if cubes.len() > 1 {
                             print!("  ");
                         }
-                        style.print_solution(solution);
+                        style.with_names(&names).print_cube(cube);
                     }
                 } else {
-                    stdout.execute(SetForegroundColor(Color::DarkGrey)).unwrap();
-                    stdout.execute(SetAttribute(Attribute::Italic)).unwrap();
-                    println!("\n  Unsatisfiable");
-                    stdout.execute(SetForegroundColor(Color::Reset)).unwrap();
-                    stdout.execute(SetAttribute(Attribute::Reset)).unwrap();
+                    let (mut solutions, more) =
+                        interruptible(&solving, &cancellation, timeout, || {
+                            solve_limited(&problem, limit, &solver)
+                        });
+                    for solution in &mut solutions {
+                        reconstruction.extend(solution);
+                    }
+                    print_status(
+                        solutions.len(),
+                        solver.is_complete() && !cancellation.is_cancelled(),
+                    );
+                    print_solutions(view.printer(style, &names), &solutions);
+                    if more {
+                        print_more();
+                    }
                 }
+                session.record(&solver);
+                if show_stats && output == Output::Text {
+                    println!();
+                    print_stats(&solver.stats.borrow());
+                }
+                current = Some(formula);
             }
         }
     }
+    if let (Some(path), Some(formula)) = (report_path, &current) {
+        let solver = cancellable(solver.as_ref(), &cancellation, timeout);
+        let solutions = interruptible(&solving, &cancellation, timeout, || {
+            solver::solve_all(formula, &solver)
+        });
+        let complete = solver.is_complete() && !cancellation.is_cancelled();
+        write_report(&path, formula, &names, &solutions, complete, &solver);
+    }
+    if output == Output::Competition {
+        std::process::exit(exit_code);
+    }
 }
 
-fn read_line(cursor: &mut TerminalCursor) -> (String, (u16, u16)) {
+/// The statistics of the last solve and their totals over the session, shown by the `stats` command.
+#[derive(Debug, Default)]
+struct SessionStats {
+    last: Option<Stats>,
+    total: Stats,
+    solves: usize,
+}
+
+impl SessionStats {
+    /// Record the statistics of the solves made by a command.
+    fn record(&mut self, solver: &Limited<dyn Solver + '_>) {
+        let stats = solver.stats.borrow();
+        self.total += &*stats;
+        self.last = Some(stats.clone());
+        self.solves += 1;
+    }
+}
+
+/// Write an HTML report of the models of a formula, found by the solver with its statistics.
+fn write_report(
+    path: &str,
+    formula: &Formula,
+    names: &VarPool,
+    solutions: &[Solution],
+    complete: bool,
+    solver: &Limited<dyn Solver + '_>,
+) {
+    let stats = solver.stats.borrow();
+    let report = Report {
+        formula,
+        names,
+        solutions,
+        complete,
+        stats: &stats,
+    };
+    match std::fs::write(path, report.to_html()) {
+        Ok(()) => println!("Wrote the report of the last formula to {}", path),
+        Err(error) => println!("Could not write {}: {}", path, error),
+    }
+}
+
+/// How formulas and solutions are shown in text, set by the `wrap`, `compact`, `diff` and `set print.<key>` commands.
+#[derive(Clone, Debug, Default)]
+struct View {
+    /// Only the true variables of solutions, and partial ones as cubes.
+    compact: bool,
+    /// The variables to show in compact solutions, or all of them.
+    only: Option<Vec<Literal>>,
+    /// Dim the values that are the same as in the solution before.
+    diff: bool,
+    /// Wrap formulas after this many clauses, besides at the width of the terminal.
+    clauses_per_line: Option<usize>,
+    /// Symbols and colors set by `set print.<key> <value>`, until the style is changed.
+    symbols: Option<PrinterConfig>,
+}
+
+/// How the results of solving are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Output {
+    /// Colored text for people.
+    Text,
+    /// JSON for scripts, see `json::solutions_to_json`.
+    Json,
+    /// The `s` and `v` lines of the SAT competitions, exiting with the code of the last result.
+    Competition,
+}
+
+/// Get the budget of a solve that gives up when the token is cancelled or the time runs out.
+fn budget(cancellation: &CancellationToken, timeout: Option<Duration>) -> Budget {
+    Budget {
+        time: timeout,
+        cancellation: Some(cancellation.clone()),
+        ..Default::default()
+    }
+}
+
+/// Wrap a solver so it gives up when the token is cancelled.
+fn cancellable<'a>(
+    solver: &'a dyn Solver,
+    cancellation: &CancellationToken,
+    timeout: Option<Duration>,
+) -> Limited<'a, dyn Solver + 'a> {
+    Limited {
+        solver,
+        budget: budget(cancellation, timeout),
+        stats: RefCell::new(Stats::new()),
+    }
+}
+
+/// Run a solve that can be cancelled with Ctrl-C or run out of time, telling the user if it was.
+/// Running out of time cancels the token too, so the results are known to be incomplete.
+fn interruptible<T>(
+    solving: &AtomicBool,
+    cancellation: &CancellationToken,
+    timeout: Option<Duration>,
+    solve: impl FnOnce() -> T,
+) -> T {
+    cancellation.reset();
+    solving.store(true, Ordering::Relaxed);
+    let started = Instant::now();
+    let result = solve();
+    solving.store(false, Ordering::Relaxed);
+    // On stderr, so the output of --json and --competition stays parseable
+    if cancellation.is_cancelled() {
+        eprintln!("Interrupted");
+    } else if let Some(timeout) = timeout.filter(|timeout| started.elapsed() > *timeout) {
+        cancellation.cancel();
+        eprintln!("Timed out after {:?}", timeout);
+    }
+    result
+}
+
+/// Parse a duration like `5s`, `500ms`, `1.5m` or `2` seconds.
+fn parse_duration(input: &str) -> Option<Duration> {
+    let (number, unit) = match input.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(i) => input.split_at(i),
+        None => (input, "s"),
+    };
+    let seconds = match unit {
+        "ms" => 0.001,
+        "s" => 1.0,
+        "m" => 60.0,
+        _ => return None,
+    };
+    let number: f64 = number.trim().parse().ok()?;
+    Duration::try_from_secs_f64(number * seconds).ok()
+}
+
+/// The names of the solvers made by [`new_solver`], compared by the `bench` command.
+const SOLVERS: &[&str] = &["dfs", "tabu", "cdcl"];
+
+/// Print that the solver decides by occurrences instead, if it can't use the heuristic of the settings.
+fn print_heuristic_fallback(algorithm: &str, config: &SolverConfig) {
+    if algorithm == "dfs" && config.heuristic == Heuristic::Lrb {
+        println!("The dfs solver learns no clauses, so it decides by occurrences instead of LRB");
+    }
+}
+
+/// Create the solver with the given name using the settings.
+fn new_solver(algorithm: &str, config: &SolverConfig) -> Box<dyn Solver> {
+    match algorithm {
+        "tabu" => Box::new(solvers::Tabu::new(config)),
+        "cdcl" => Box::new(solvers::Cdcl::new(config)),
+        _ => Box::new(solvers::Dfs::new(config)),
+    }
+}
+
+/// Get the implication graph at the first conflict of the CDCL solver on a formula in the DOT language,
+/// with the learned clause, or `None` if it's solved without conflicts.
+fn first_conflict(formula: &Formula, config: &SolverConfig) -> Option<String> {
+    struct FirstConflict(Option<String>);
+
+    impl Observer for FirstConflict {
+        fn on_analyze(&mut self, trail: &Trail, clauses: &Formula) {
+            self.0.get_or_insert_with(|| trail.to_dot(clauses));
+        }
+    }
+
+    let mut first = FirstConflict(None);
+    // Give up right after the first conflict
+    let budget = Budget {
+        conflicts: Some(0),
+        ..Default::default()
+    };
+    solvers::Cdcl::new(config).solve_limited(
+        &mut formula.clone(),
+        &formula.literals(),
+        &mut Solution::new(),
+        &budget,
+        &mut Stats::new(),
+        &mut first,
+    );
+    first.0
+}
+
+/// Solve every formula with each of the [`SOLVERS`], returning their certificates and times by formula.
+/// Stops at the first formula not solved by all of them when cancelled.
+fn bench(
+    formulas: &[Formula],
+    config: &SolverConfig,
+    cancellation: &CancellationToken,
+    timeout: Option<Duration>,
+) -> Vec<Vec<(Certificate, Duration)>> {
+    let solvers = SOLVERS
+        .iter()
+        .map(|name| new_solver(name, config))
+        .collect::<Vec<_>>();
+    let mut results = Vec::new();
+    for formula in formulas {
+        let mut solved = Vec::new();
+        for solver in &solvers {
+            let solver = cancellable(solver.as_ref(), cancellation, timeout);
+            let started = Instant::now();
+            let certificate = solver::solve_assuming(formula, &[], &solver);
+            solved.push((certificate, started.elapsed()));
+        }
+        if cancellation.is_cancelled() {
+            break;
+        }
+        results.push(solved);
+    }
+    results
+}
+
+/// Print a table of the results and total time of each solver from [`bench`],
+/// followed by the formulas where the solvers disagree or a model is wrong, which are bugs.
+fn print_bench(formulas: &[Formula], results: &[Vec<(Certificate, Duration)>]) {
     println!();
-    let mut input = String::new();
-    print!("> ");
-    std::io::stdout().flush().unwrap();
-    let start = cursor.pos().unwrap();
-    std::io::stdin().read_line(&mut input).unwrap();
+    println!("  solver  sat  unsat  unknown  time");
+    for (i, name) in SOLVERS.iter().enumerate() {
+        let mut counts = [0; 3];
+        let mut time = Duration::ZERO;
+        for solved in results {
+            let (certificate, elapsed) = &solved[i];
+            match certificate {
+                Certificate::Satisfiable(_) => counts[0] += 1,
+                Certificate::Unsatisfiable => counts[1] += 1,
+                Certificate::Indeterminate => counts[2] += 1,
+            }
+            time += *elapsed;
+        }
+        println!(
+            "  {:<6} {:>4} {:>6} {:>8}  {:?}",
+            name, counts[0], counts[1], counts[2], time
+        );
+    }
+    let mut bugs = Vec::new();
+    for (formula, (number, solved)) in formulas.iter().zip(results.iter().enumerate()) {
+        let found = |satisfiable: bool| {
+            SOLVERS
+                .iter()
+                .zip(solved)
+                .filter(|(_, (certificate, _))| match certificate {
+                    Certificate::Satisfiable(_) => satisfiable,
+                    Certificate::Unsatisfiable => !satisfiable,
+                    Certificate::Indeterminate => false,
+                })
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+        };
+        let (satisfiable, unsatisfiable) = (found(true), found(false));
+        if !satisfiable.is_empty() && !unsatisfiable.is_empty() {
+            bugs.push(format!(
+                "formula {}: {} found a model, but {} found it unsatisfiable",
+                number + 1,
+                satisfiable.join(", "),
+                unsatisfiable.join(", ")
+            ));
+        }
+        for (name, (certificate, _)) in SOLVERS.iter().zip(solved) {
+            if let Certificate::Satisfiable(solution) = certificate {
+                if !solution.satisfy(formula) {
+                    bugs.push(format!(
+                        "formula {}: {} found a model that does not satisfy it",
+                        number + 1,
+                        name
+                    ));
+                }
+            }
+        }
+    }
+    if bugs.is_empty() {
+        println!("  No disagreements between the solvers");
+        return;
+    }
+    printer::set_foreground(Color::Red);
+    printer::set_attribute(Attribute::Bold);
+    for bug in &bugs {
+        println!("  BUG: {}", bug);
+    }
+    printer::set_foreground(Color::Reset);
+    printer::set_attribute(Attribute::Reset);
+}
+
+fn print_config(config: &SolverConfig) {
+    println!("  seed      {}", config.seed);
+    match config.restart {
+        Restart::Never => println!("  restart   never"),
+        Restart::Fixed(n) => println!("  restart   fixed {}", n),
+        Restart::Luby(n) => println!("  restart   luby {}", n),
+    }
+    match config.heuristic {
+        Heuristic::Input => println!("  heuristic input"),
+        Heuristic::Occurrences => println!("  heuristic occurrences"),
+        Heuristic::Random => println!("  heuristic random"),
+        Heuristic::Lrb => println!("  heuristic lrb"),
+    }
+    match config.polarity {
+        Polarity::False => println!("  polarity  false"),
+        Polarity::True => println!("  polarity  true"),
+        Polarity::Random => println!("  polarity  random"),
+        Polarity::JeroslowWang => println!("  polarity  jw"),
+    }
+    match config.chronological {
+        Some(threshold) => println!("  chrono    {}", threshold),
+        None => println!("  chrono    off"),
+    }
+    println!("  flips     {}", config.max_flips);
+    println!("  merge     {}", on_off(config.equivalences));
+    println!("  subsume   {}", on_off(config.subsumption));
+    println!("  vivify    {}", on_off(config.vivification));
+    println!("  shorten   {}", on_off(config.learned_vivification));
+    println!("  eliminate {}", on_off(config.elimination));
+    println!("  symmetry  {}", on_off(config.symmetry_breaking));
+}
+
+/// Print the truth table of a formula, or why not if it has too many variables for one.
+fn print_truth_table(formula: &Formula, style: &PrintStyle, names: &VarPool) {
+    if let Err(error) = style.with_names(names).print_truth_table(formula) {
+        println!("{}", error);
+    }
+}
+
+fn print_karnaugh_map(formula: &Formula, style: &PrintStyle, names: &VarPool) {
+    if let Err(error) = style.with_names(names).print_karnaugh_map(formula) {
+        println!("{}", error);
+    }
+}
+
+/// Print a list of variables separated by commas, or `none` if it is empty.
+fn print_variables(variables: &[Literal], style: &PrintStyle, names: &VarPool) {
+    if variables.is_empty() {
+        println!("none");
+        return;
+    }
+    for (i, id) in variables.iter().enumerate() {
+        style
+            .with_names(names)
+            .print_variable(&Variable::Positive(*id));
+        if i < variables.len() - 1 {
+            print!(", ");
+        }
+    }
+    println!();
+}
+
+fn print_stats(stats: &Stats) {
+    println!("  decisions    {}", stats.decisions);
+    println!("  propagations {}", stats.propagations);
+    println!("  conflicts    {}", stats.conflicts);
+    println!("  restarts     {}", stats.restarts);
+    println!("  learned      {}", stats.learned_clauses);
+    println!("  peak memory  {} bytes", stats.peak_memory);
+    println!("  time         {:?}", stats.time);
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Change a solver setting from the `set` command, returns `false` if the key or value is invalid.
+fn set(config: &mut SolverConfig, key: &str, value: &str) -> bool {
+    let switch = |value: &str| match value {
+        "on" => Some(true),
+        "off" => Some(false),
+        _ => None,
+    };
+    let valid = match key {
+        "seed" => value.parse().map(|seed| config.seed = seed).is_ok(),
+        "flips" => value.parse().map(|flips| config.max_flips = flips).is_ok(),
+        "restart" => {
+            let mut words = value.split_whitespace();
+            let restart = match (words.next(), words.next().map(str::parse)) {
+                (Some("never"), None) => Some(Restart::Never),
+                (Some("fixed"), Some(Ok(n))) if n > 0 => Some(Restart::Fixed(n)),
+                (Some("luby"), Some(Ok(n))) if n > 0 => Some(Restart::Luby(n)),
+                _ => None,
+            };
+            restart.map(|restart| config.restart = restart).is_some()
+        }
+        "heuristic" => {
+            let heuristic = match value {
+                "input" => Some(Heuristic::Input),
+                "occurrences" => Some(Heuristic::Occurrences),
+                "random" => Some(Heuristic::Random),
+                "lrb" => Some(Heuristic::Lrb),
+                _ => None,
+            };
+            heuristic
+                .map(|heuristic| config.heuristic = heuristic)
+                .is_some()
+        }
+        "polarity" => {
+            let polarity = match value {
+                "false" => Some(Polarity::False),
+                "true" => Some(Polarity::True),
+                "random" => Some(Polarity::Random),
+                "jw" => Some(Polarity::JeroslowWang),
+                _ => None,
+            };
+            polarity
+                .map(|polarity| config.polarity = polarity)
+                .is_some()
+        }
+        "chrono" => match value {
+            "off" => {
+                config.chronological = None;
+                true
+            }
+            _ => value
+                .parse()
+                .map(|threshold| config.chronological = Some(threshold))
+                .is_ok(),
+        },
+        "merge" => switch(value).map(|on| config.equivalences = on).is_some(),
+        "subsume" => switch(value).map(|on| config.subsumption = on).is_some(),
+        "vivify" => switch(value).map(|on| config.vivification = on).is_some(),
+        "shorten" => switch(value)
+            .map(|on| config.learned_vivification = on)
+            .is_some(),
+        "eliminate" => switch(value).map(|on| config.elimination = on).is_some(),
+        "symmetry" => switch(value)
+            .map(|on| config.symmetry_breaking = on)
+            .is_some(),
+        _ => {
+            println!("Unknown setting: {}", key);
+            return false;
+        }
+    };
+    if !valid {
+        println!("Invalid value for {}: {}", key, value);
+    }
+    valid
+}
+
+/// Print a solved Sudoku grid, with the digits not given by the puzzle highlighted.
+fn print_sudoku(puzzle: &encode::Grid, solved: &[[u8; 9]; 9]) {
+    for (row, digits) in solved.iter().enumerate() {
+        if row > 0 && row % 3 == 0 {
+            printer::set_foreground(Color::DarkGrey);
+            println!("  ------+-------+------");
+            printer::set_foreground(Color::Reset);
+        }
+        print!(" ");
+        for (col, digit) in digits.iter().enumerate() {
+            if col > 0 && col % 3 == 0 {
+                printer::set_foreground(Color::DarkGrey);
+                print!(" |");
+                printer::set_foreground(Color::Reset);
+            }
+            if puzzle[row][col].is_none() {
+                printer::set_foreground(Color::Green);
+            }
+            print!(" {}", digit);
+            printer::set_foreground(Color::Reset);
+        }
+        println!();
+    }
+}
+
+impl View {
+    /// Get the printer in a style, showing the names of named variables.
+    fn printer<'a>(&'a self, style: PrintStyle, names: &'a VarPool) -> Printer<'a> {
+        let mut printer = style
+            .with_names(names)
+            .with_compact(self.compact)
+            .with_diff(self.diff);
+        if let Some(clauses) = self.clauses_per_line {
+            printer = printer.with_clauses_per_line(clauses);
+        }
+        if let Some(symbols) = &self.symbols {
+            printer = printer.with_config(symbols);
+        }
+        match &self.only {
+            Some(variables) => printer.only(variables),
+            None => printer,
+        }
+    }
+}
+
+/// Print solutions after their status, indented on their own lines when there are several,
+/// or as a Markdown table starting on a new line.
+fn print_solutions(printer: Printer, solutions: &[Solution]) {
+    if printer.style == PrintStyle::Markdown {
+        // A blank line before the table, where the status of several solutions already ended its line
+        if solutions.len() == 1 {
+            println!();
+        }
+        println!();
+        printer.print_solutions(solutions);
+        return;
+    }
+    for (i, solution) in solutions.iter().enumerate() {
+        if solutions.len() > 1 {
+            print!("  ");
+        }
+        match i.checked_sub(1) {
+            Some(before) if printer.diff => printer
+                .compared_to(&solutions[before])
+                .print_solution(solution),
+            _ => printer.print_solution(solution),
+        }
+    }
+}
+
+/// Print whether a formula is satisfiable, given the number of solutions found.
+/// Incomplete solvers finding no solutions can't tell whether the formula is unsatisfiable.
+fn print_status(count: usize, complete: bool) {
+    printer::set_foreground(Color::DarkGrey);
+    printer::set_attribute(Attribute::Italic);
+    if count == 0 && !complete {
+        println!("\n  Unknown");
+    } else if count == 0 {
+        println!("\n  Unsatisfiable");
+    } else {
+        print!("\n  Satisfiable");
+        if count > 1 {
+            print!(" ({})", count);
+            println!(": ");
+        } else {
+            print!(": ");
+        }
+    }
+    printer::set_foreground(Color::Reset);
+    printer::set_attribute(Attribute::Reset);
+}
+
+/// Print the cost of an optimal solution, or of the best one found before the solve was interrupted.
+fn print_cost(cost: impl Display, complete: bool) {
+    if complete {
+        println!("  Cost: {}", cost);
+    } else {
+        println!("  Cost: {}, not proven optimal", cost);
+    }
+}
+
+/// Print that there are more solutions than the ones shown, because of the limit.
+fn print_more() {
+    printer::set_foreground(Color::DarkGrey);
+    printer::set_attribute(Attribute::Italic);
+    println!("  More solutions are not shown, see: limit");
+    printer::set_foreground(Color::Reset);
+    printer::set_attribute(Attribute::Reset);
+}
+
+/// Read a line of input, with the cursor position after the prompt unless the input is not a terminal.
+/// The line is edited by the editor if there is one, completing the variables of `names`
+/// and the names of the formulas of `bindings`, and the end of the input, like Ctrl-D, exits like the exit command.
+///
+/// Input continues on the next line after a `..>` prompt while it ends with `\` or has unclosed parentheses,
+/// joining the lines without the backslashes.
+fn read_line(
+    mut editor: Option<&mut Editor<Completion, DefaultHistory>>,
+    names: &VarPool,
+    bindings: &HashMap<String, Expr>,
+    cursor: &mut TerminalCursor,
+    prompt: bool,
+) -> (String, Option<(u16, u16)>) {
+    if prompt {
+        println!();
+    }
+    if let Some(completion) = editor.as_mut().and_then(|editor| editor.helper_mut()) {
+        completion.variables = names
+            .names()
+            .into_iter()
+            .chain(bindings.keys().map(String::as_str))
+            .map(str::to_string)
+            .collect();
+    }
+    let prompts = if prompt { ("> ", "..> ") } else { ("", "") };
+    let Some((mut input, mut start)) = read_input_line(editor.as_deref_mut(), cursor, prompts.0)
+    else {
+        return ("exit".to_string(), None);
+    };
+    while continues(&input) {
+        let line = input.trim_end();
+        input = line.strip_suffix('\\').unwrap_or(line).to_string();
+        input.push('\n');
+        // The formula can't be shown in place of the input over several lines
+        start = None;
+        match read_input_line(editor.as_deref_mut(), cursor, prompts.1) {
+            Some((line, _)) => input += &line,
+            None => break,
+        }
+    }
+    if let Some(editor) = editor {
+        let _ = editor.add_history_entry(input.trim_end());
+    }
     (input, start)
 }
 
+/// Read a single line after the prompt, or `None` at the end of the input.
+fn read_input_line(
+    editor: Option<&mut Editor<Completion, DefaultHistory>>,
+    cursor: &mut TerminalCursor,
+    prompt: &str,
+) -> Option<(String, Option<(u16, u16)>)> {
+    if let Some(editor) = editor {
+        let line = match editor.readline(prompt) {
+            Ok(line) => line,
+            // Ctrl-C is read as a key while editing, rather than a signal
+            Err(ReadlineError::Interrupted) => std::process::exit(130),
+            Err(_) => return None,
+        };
+        // The cursor is at the start of the line after the input
+        let start = cursor.pos().ok().map(|(x, y)| (x + prompt.len() as u16, y));
+        return Some((line, start));
+    }
+    print!("{}", prompt);
+    std::io::stdout().flush().unwrap();
+    let start = cursor.pos().ok();
+    let mut line = String::new();
+    match std::io::stdin().read_line(&mut line) {
+        Ok(0) => None,
+        _ => Some((line, start)),
+    }
+}
+
+/// Check if the input continues on the next line, as it ends with `\` or has more `(` than `)` outside comments.
+fn continues(input: &str) -> bool {
+    let depth = input
+        .lines()
+        .map(|line| {
+            let code = line.split('#').next().unwrap().split("//").next().unwrap();
+            code.matches('(').count() as isize - code.matches(')').count() as isize
+        })
+        .sum::<isize>();
+    input.trim_end().ends_with('\\') || depth > 0
+}
+
+/// Print a caret under the part of the input line an error points at, followed by the error.
+/// The parsed text is a slice of the input, which the span of the error is relative to.
+fn print_parse_error(input: &str, parsed: &str, error: &ParseError) {
+    print_parse_errors(input, parsed, std::slice::from_ref(error));
+}
+
+/// Underline every error in the input line above, followed by their messages,
+/// or show the lines with the errors for input over several lines.
+fn print_parse_errors(input: &str, parsed: &str, errors: &[ParseError]) {
+    let offset = parsed.as_ptr() as usize - input.as_ptr() as usize;
+    if input.trim_end().contains('\n') {
+        // Over several lines, show each error under its line
+        printer::set_foreground(Color::Red);
+        for error in errors {
+            let error = ParseError {
+                span: offset + error.span.start..offset + error.span.end,
+                ..error.clone()
+            };
+            print!("{}", error.snippet(input));
+            println!("{}", error);
+        }
+        printer::set_foreground(Color::Reset);
+        return;
+    }
+    let mut carets = String::new();
+    for error in errors {
+        let column = input[..offset + error.span.start].chars().count();
+        let width = input[offset + error.span.start..offset + error.span.end]
+            .chars()
+            .count();
+        let padding = column.saturating_sub(carets.chars().count());
+        carets += &" ".repeat(padding);
+        carets += &"^".repeat(width.max(1));
+    }
+    printer::set_foreground(Color::Red);
+    // Skip past the prompt too
+    println!("  {}", carets);
+    for error in errors {
+        println!("{}", error);
+    }
+    printer::set_foreground(Color::Reset);
+}
+
+/// Add the assumptions to a copy of the formula as unit clauses, like [`solver::solve_assuming`],
+/// so they hold in every solution without changing the formula.
+fn assuming(formula: &Formula, assumptions: &[Variable]) -> Formula {
+    let units = assumptions
+        .iter()
+        .map(|assumption| vec![*assumption])
+        .collect::<Vec<_>>();
+    formula.and(&units.into())
+}
+
+/// Print that the assumptions are not used by a command, if there are any.
+fn print_ignored_assumptions(assumptions: &[Variable]) {
+    if !assumptions.is_empty() {
+        println!("Ignoring the assumptions, which this command doesn't support");
+    }
+}
+
+/// Find all solutions of a formula, or the first ones up to the limit and whether there are more.
+fn solve_limited<S: Solver + ?Sized>(
+    formula: &Formula,
+    limit: Option<usize>,
+    solver: &S,
+) -> (Vec<Solution>, bool) {
+    match limit {
+        Some(limit) => solver::solve_n(formula, limit, solver),
+        None => (solver::solve_all(formula, solver), false),
+    }
+}
+
+/// Read a whole file, showing why if it can't be read.
+fn read_file(path: &str) -> Option<String> {
+    match input::read_to_string(path) {
+        Ok(input) => Some(input),
+        Err(error) => {
+            eprintln!("Could not read {}: {}", path, error);
+            None
+        }
+    }
+}
+
+/// Show an error in a file on stderr, with its position and the underlined line.
+fn print_file_error(path: &str, input: &str, error: &ParseError) {
+    let (line, column) = error.line_column(input);
+    printer::set_foreground(Color::Red);
+    eprintln!("{}:{}:{}: {}", path, line, column, error);
+    eprint!("{}", error.snippet(input));
+    printer::set_foreground(Color::Reset);
+}
+
 fn update_line(
     input: &str,
     start: (u16, u16),
     formula: &Formula,
     cursor: &mut TerminalCursor,
-    style: &PrintStyle,
+    printer: Printer,
 ) {
     cursor.goto(start.0, start.1 - 1).unwrap();
     // Clear the current line with length of the input
     print!("{}", " ".repeat(input.len()));
     std::io::stdout().flush().unwrap();
     cursor.goto(start.0, start.1 - 1).unwrap();
-    style.print_formula(formula);
+    printer.print_formula(formula);
     std::io::stdout().flush().unwrap();
 }